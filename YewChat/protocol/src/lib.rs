@@ -0,0 +1,732 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MsgTypes {
+    Users,
+    Register,
+    Message,
+    /// Like `Register`, but joins read-only: the server drops any messages from this client.
+    Spectate,
+    /// `dataArray` lists the connected spectators; sent alongside `Users`.
+    Spectators,
+    /// `data` is a JSON list of `ChannelInfo`; sent after registering.
+    Channels,
+    /// The server refused a frame; `data` says why. `outgoingId` is the refused message's,
+    /// when it was a posted message that carried one.
+    Error,
+    /// Client toggles its reaction; `data` is a JSON `ReactionData`.
+    React,
+    /// Server sends the full reaction list of one message; `data` is a JSON `MessageReactions`.
+    Reactions,
+    /// Client sets its `Status`; `data` is the JSON-encoded status.
+    Status,
+    /// Client rewrites one of its messages; `data` is a JSON `EditData`.
+    Edit,
+    /// Server sends the edited message, history included; `data` is a JSON `MessageData`. Also
+    /// sent when a checklist item is ticked, without marking the message edited.
+    Edited,
+    /// Moderator acts on a message or user; `data` is a JSON `ModerationAction`.
+    Moderate,
+    /// Server sends moderators audit entries, backlog first; `data` is a JSON `Vec<AuditEntry>`.
+    Audit,
+    /// A moderator removed a message; `data` is its id.
+    Deleted,
+    /// `data` is a JSON list of every pinned message id.
+    Pins,
+    /// Client asks for an invite to the channel named in `data`; the server answers with the
+    /// same type carrying a JSON `InviteInfo`.
+    Invite,
+    /// Client redeems the invite token in `data`; the server answers with `Error` if it is
+    /// unknown.
+    Join,
+    /// Client asks for older messages with a JSON `HistoryQuery`; the server answers with the
+    /// same type carrying a JSON `HistoryPage`.
+    History,
+    /// Client ticks or unticks a checklist item; `data` is a JSON `TaskToggle`.
+    ToggleTask,
+    /// A line drawn on a channel's whiteboard; `data` is a JSON `Stroke`. The server relays it
+    /// to everyone.
+    Stroke,
+    /// Client asks for the whiteboard of the channel named in `data`; the server answers with
+    /// the same type carrying a JSON `Whiteboard`. Also sent to everyone when a board is wiped.
+    Whiteboard,
+    /// Client wipes the whiteboard of the channel named in `data`.
+    ClearWhiteboard,
+    /// Client heartbeat; the server sends the frame straight back to the sender, `data` and
+    /// all, so the client can time the round trip.
+    Ping,
+    /// Client describes what it runs on, e.g. "Firefox on Linux", for its nick's other
+    /// sessions to see.
+    Device,
+    /// Client asks for every session registered under its nick; the server answers with the
+    /// same type carrying a JSON `Vec<SessionInfo>`.
+    Sessions,
+    /// Client ends another session of its nick; `data` is the session id. The server sends
+    /// the same type, without `data`, to that session before closing it.
+    SignOut,
+    /// Client asks to be pushed its mentions while none of its nick's sessions are open;
+    /// `data` is the browser's JSON `PushSubscription`.
+    PushSubscribe,
+    /// Client stops the pushes to the subscription whose endpoint is in `data`.
+    PushUnsubscribe,
+    /// Client is typing in the channel named in `data`, sent every few seconds while it
+    /// keeps on. The server passes it on to everyone else as a JSON `TypingInfo`.
+    Typing,
+    /// Client says what it's up to, e.g. "In a meeting", shown to everyone in the user list;
+    /// empty `data` clears it.
+    Activity,
+    /// Client says which mentions it doesn't want to be pinged by; `data` is a JSON
+    /// `MentionSuppression`. The server skips those when pushing.
+    SuppressMentions,
+    /// Server announces a join, a leave or a channel rename to everyone; `data` is a JSON
+    /// `SystemNotice`.
+    System,
+}
+
+impl MsgTypes {
+    /// Frames that set up the sender's session, replayed after a reconnect.
+    pub fn is_registration(&self) -> bool {
+        matches!(
+            self,
+            MsgTypes::Register
+                | MsgTypes::Spectate
+                | MsgTypes::Status
+                | MsgTypes::Device
+                | MsgTypes::Activity
+                | MsgTypes::SuppressMentions
+        )
+    }
+}
+
+pub const DEFAULT_CHANNEL: &str = "general";
+
+fn default_channel() -> String {
+    DEFAULT_CHANNEL.into()
+}
+
+/// A single frame on the wire. Structured payloads travel JSON-encoded in `data`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebSocketMessage {
+    pub message_type: MsgTypes,
+    pub data_array: Option<Vec<String>>,
+    pub data: Option<String>,
+    /// Channel the frame is addressed to; omitted means `DEFAULT_CHANNEL`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<String>,
+    /// Id of the message a posted message answers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reply_to: Option<u64>,
+    /// Posted alongside the message text.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attachment: Option<Attachment>,
+    /// Lets everyone, not just the author, tick the posted message's checklist items.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub tasks_open: bool,
+    /// The sender's own id for a posted message, handed back if the server refuses it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outgoing_id: Option<u64>,
+    /// Account key sent with a registration, needed for nicks the server gives a role or
+    /// badge to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+}
+
+impl WebSocketMessage {
+    pub fn new(message_type: MsgTypes) -> Self {
+        Self {
+            message_type,
+            data_array: None,
+            data: None,
+            channel: None,
+            reply_to: None,
+            attachment: None,
+            tasks_open: false,
+            outgoing_id: None,
+            key: None,
+        }
+    }
+
+    pub fn with_data(message_type: MsgTypes, data: impl Into<String>) -> Self {
+        Self {
+            data: Some(data.into()),
+            ..Self::new(message_type)
+        }
+    }
+
+    pub fn with_data_array(message_type: MsgTypes, data_array: Vec<String>) -> Self {
+        Self {
+            data_array: Some(data_array),
+            ..Self::new(message_type)
+        }
+    }
+
+    pub fn in_channel(self, channel: impl Into<String>) -> Self {
+        Self {
+            channel: Some(channel.into()),
+            ..self
+        }
+    }
+
+    pub fn replying_to(self, message_id: u64) -> Self {
+        Self {
+            reply_to: Some(message_id),
+            ..self
+        }
+    }
+
+    pub fn with_attachment(self, attachment: Attachment) -> Self {
+        Self {
+            attachment: Some(attachment),
+            ..self
+        }
+    }
+
+    pub fn with_open_tasks(self) -> Self {
+        Self {
+            tasks_open: true,
+            ..self
+        }
+    }
+
+    pub fn with_outgoing_id(self, outgoing_id: u64) -> Self {
+        Self {
+            outgoing_id: Some(outgoing_id),
+            ..self
+        }
+    }
+
+    pub fn with_key(self, key: impl Into<String>) -> Self {
+        Self {
+            key: Some(key.into()),
+            ..self
+        }
+    }
+
+    pub fn channel(&self) -> &str {
+        self.channel.as_deref().unwrap_or(DEFAULT_CHANNEL)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct MessageData {
+    /// Assigned by the server, increasing from 1; 0 for servers that don't number messages.
+    #[serde(default)]
+    pub id: u64,
+    pub from: String,
+    pub message: String,
+    #[serde(default)]
+    pub time: u64,
+    #[serde(default = "default_channel")]
+    pub channel: String,
+    /// When the current text was written by an edit; 0 if never edited.
+    #[serde(default, rename = "editedAt")]
+    pub edited_at: u64,
+    /// Earlier versions of the text, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub history: Vec<Revision>,
+    /// Id of the message this one answers.
+    #[serde(default, rename = "replyTo", skip_serializing_if = "Option::is_none")]
+    pub reply_to: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attachment: Option<Attachment>,
+    /// Anyone may tick the checklist items, not just the author.
+    #[serde(
+        default,
+        rename = "tasksOpen",
+        skip_serializing_if = "std::ops::Not::not"
+    )]
+    pub tasks_open: bool,
+}
+
+/// Media posted with a message. Carried inline in the frame; there is no separate upload.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum Attachment {
+    /// `url` is a `data:` URL; the size is in pixels.
+    Image {
+        url: String,
+        width: u32,
+        height: u32,
+        /// What the image shows, for people who can't see it.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        alt: Option<String>,
+        /// Shown blurred until the viewer chooses to reveal it.
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        spoiler: bool,
+    },
+    /// Text shown in a collapsible block, highlighted if the language is known.
+    Snippet {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        title: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        language: Option<String>,
+        code: String,
+    },
+    /// Another user's profile, shown as a card.
+    Contact { name: String },
+    /// A point on the map, in degrees, with its accuracy in metres if known.
+    Location {
+        latitude: f64,
+        longitude: f64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        accuracy: Option<f64>,
+    },
+    /// Labelled values a bot lays out as a card, e.g. a build's status and duration. Only
+    /// bots may post them.
+    Embed {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        title: Option<String>,
+        fields: Vec<EmbedField>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct EmbedField {
+    pub name: String,
+    pub value: String,
+    /// May share a row with neighbouring inline fields.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub inline: bool,
+}
+
+impl Attachment {
+    /// Encoded size, as counted against the server's limit.
+    pub fn size(&self) -> usize {
+        match self {
+            Attachment::Image { url, alt, .. } => url.len() + alt.as_ref().map_or(0, String::len),
+            Attachment::Snippet { title, code, .. } => {
+                title.as_ref().map_or(0, String::len) + code.len()
+            }
+            Attachment::Contact { name } => name.len(),
+            Attachment::Location { .. } => 0,
+            Attachment::Embed { title, fields } => {
+                title.as_ref().map_or(0, String::len)
+                    + fields
+                        .iter()
+                        .map(|f| f.name.len() + f.value.len())
+                        .sum::<usize>()
+            }
+        }
+    }
+}
+
+/// Longest image description, in chars.
+pub const MAX_ALT_CHARS: usize = 1000;
+
+/// A superseded version of a message and when it was written.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Revision {
+    pub message: String,
+    pub time: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EditData {
+    pub message_id: u64,
+    pub message: String,
+}
+
+/// Ticks (`done`) or unticks the checklist item numbered `index`, counting from 0 in the order
+/// the items appear in the message.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskToggle {
+    pub message_id: u64,
+    pub index: usize,
+    pub done: bool,
+}
+
+/// A checklist line, `- [ ] text` or `- [x] text` (`*` works too): whether it is ticked and
+/// its text.
+pub fn task_item(line: &str) -> Option<(bool, &str)> {
+    let rest = line.trim_start();
+    let rest = rest
+        .strip_prefix("- ")
+        .or_else(|| rest.strip_prefix("* "))?;
+    let done = match rest.get(..3)? {
+        "[ ]" => false,
+        "[x]" | "[X]" => true,
+        _ => return None,
+    };
+    let text = &rest[3..];
+    (text.is_empty() || text.starts_with(' ')).then_some((done, text.trim_start()))
+}
+
+/// `text` with its checklist item numbered `index` ticked or unticked, or `None` if there is
+/// no such item.
+pub fn set_task(text: &str, index: usize, done: bool) -> Option<String> {
+    let mut line_start = 0;
+    let mut seen = 0;
+    for line in text.split('\n') {
+        if task_item(line).is_some() {
+            if seen == index {
+                let marker = line_start + line.find('[')? + 1;
+                let mut updated = text.to_string();
+                updated.replace_range(marker..marker + 1, if done { "x" } else { " " });
+                return Some(updated);
+            }
+            seen += 1;
+        }
+        line_start += line.len() + 1;
+    }
+    None
+}
+
+/// A line on a whiteboard. Points are fractions of the board's width and height, so boards
+/// drawn at different sizes show the same picture.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Stroke {
+    pub channel: String,
+    /// CSS colour.
+    pub color: String,
+    /// As a fraction of the board's width.
+    pub width: f32,
+    pub points: Vec<[f32; 2]>,
+}
+
+/// Every stroke on one channel's whiteboard, oldest first.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Whiteboard {
+    pub channel: String,
+    pub strokes: Vec<Stroke>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    #[default]
+    Member,
+    Moderator,
+    /// Runs the server; can do everything a moderator can.
+    Admin,
+}
+
+impl Role {
+    /// Whether the role may moderate: post in announcement channels, kick, ban and so on.
+    pub fn moderates(self) -> bool {
+        matches!(self, Role::Moderator | Role::Admin)
+    }
+}
+
+/// What a user has told others about their availability.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    #[default]
+    Online,
+    /// Do not disturb: online, but not taking notifications.
+    Dnd,
+}
+
+/// Entry of the JSON user list carried in the `data` of a `Users` frame. `dataArray` keeps
+/// the bare names for older clients.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct UserInfo {
+    pub name: String,
+    #[serde(default)]
+    pub role: Role,
+    #[serde(default)]
+    pub status: Status,
+    /// What the user says they're up to, if anything.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub activity: Option<String>,
+    /// Posts on behalf of a program, e.g. a webhook, rather than a person.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub bot: bool,
+    /// The server vouches that the nick belongs to who it claims to.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub verified: bool,
+}
+
+/// Longest activity the server keeps; anything past it is cut off.
+pub const MAX_ACTIVITY_CHARS: usize = 80;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize, Serialize)]
+pub struct ChannelInfo {
+    pub name: String,
+    /// Only moderators may post.
+    #[serde(default)]
+    pub announcement: bool,
+    /// `#rrggbb` tinting the channel's header and unread badges; see [`is_accent`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accent: Option<String>,
+    /// Emoji shown in place of the `#` before the channel's name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    /// Slow mode: seconds members must wait between their messages. Moderators are exempt.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slow_mode: Option<u32>,
+}
+
+/// Longest wait slow mode can impose, in seconds.
+pub const MAX_SLOW_MODE_SECS: u32 = 60 * 60;
+
+/// Longest channel icon the server keeps, in chars; enough for emoji joined by ZWJs.
+pub const MAX_ICON_CHARS: usize = 8;
+
+/// Longest channel name, in chars.
+pub const MAX_CHANNEL_NAME_CHARS: usize = 32;
+
+/// Whether `name` can name a channel: lowercase letters, digits, `-` and `_`, at most
+/// [`MAX_CHANNEL_NAME_CHARS`] of them.
+pub fn is_channel_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.chars().count() <= MAX_CHANNEL_NAME_CHARS
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+}
+
+/// Whether `color` is a channel accent: `#` and six hex digits.
+pub fn is_accent(color: &str) -> bool {
+    color
+        .strip_prefix('#')
+        .is_some_and(|hex| hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// Special mentions that ping a whole channel; only moderators may send them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BroadcastMention {
+    /// Every member of the channel.
+    Everyone,
+    /// Only members currently online.
+    Here,
+}
+
+impl BroadcastMention {
+    /// The broadest broadcast mention in `text`, if any.
+    pub fn find(text: &str) -> Option<Self> {
+        let mut found = None;
+        for word in text.split_whitespace() {
+            match word.trim_end_matches(|c: char| c.is_ascii_punctuation()) {
+                "@everyone" => return Some(BroadcastMention::Everyone),
+                "@here" => found = Some(BroadcastMention::Here),
+                _ => {}
+            }
+        }
+        found
+    }
+}
+
+/// Mentions a user has asked not to be pinged by. The messages still arrive as usual.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MentionSuppression {
+    #[default]
+    Nothing,
+    /// `@everyone` and `@here`.
+    Broadcasts,
+    /// Broadcasts and `@name` alike.
+    All,
+}
+
+impl MentionSuppression {
+    /// Whether a mention is suppressed; `broadcast` is false for one by name.
+    pub fn suppresses(&self, broadcast: bool) -> bool {
+        match self {
+            MentionSuppression::Nothing => false,
+            MentionSuppression::Broadcasts => broadcast,
+            MentionSuppression::All => true,
+        }
+    }
+}
+
+/// Whether `text` mentions `name` by `@name`, trailing punctuation aside.
+pub fn mentions(text: &str, name: &str) -> bool {
+    !name.is_empty()
+        && text.split_whitespace().any(|word| {
+            word.strip_prefix('@')
+                .map(|word| word.trim_end_matches(|c: char| c.is_ascii_punctuation()))
+                == Some(name)
+        })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReactionData {
+    pub message_id: u64,
+    pub emoji: String,
+}
+
+/// One emoji on a message and everyone who reacted with it, in reaction order.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Reaction {
+    pub emoji: String,
+    pub users: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageReactions {
+    pub message_id: u64,
+    pub reactions: Vec<Reaction>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum ModerationAction {
+    Delete {
+        #[serde(rename = "messageId")]
+        message_id: u64,
+    },
+    Pin {
+        #[serde(rename = "messageId")]
+        message_id: u64,
+    },
+    Unpin {
+        #[serde(rename = "messageId")]
+        message_id: u64,
+    },
+    /// Disconnects the user.
+    Kick { user: String },
+    /// Disconnects the user and refuses the nick from then on.
+    Ban { user: String },
+    /// Sets the channel's accent and icon; `None` puts back the plain look.
+    Restyle {
+        channel: String,
+        #[serde(default)]
+        accent: Option<String>,
+        #[serde(default)]
+        icon: Option<String>,
+    },
+    /// Turns slow mode on at `seconds` between messages, or off with `None`.
+    SlowMode {
+        channel: String,
+        #[serde(default)]
+        seconds: Option<u32>,
+    },
+    /// Gives the channel a new name; see [`is_channel_name`].
+    Rename { channel: String, name: String },
+}
+
+impl ModerationAction {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ModerationAction::Delete { .. } => "delete",
+            ModerationAction::Pin { .. } => "pin",
+            ModerationAction::Unpin { .. } => "unpin",
+            ModerationAction::Kick { .. } => "kick",
+            ModerationAction::Ban { .. } => "ban",
+            ModerationAction::Restyle { .. } => "restyle",
+            ModerationAction::SlowMode { .. } => "slowmode",
+            ModerationAction::Rename { .. } => "rename",
+        }
+    }
+}
+
+/// A moderation action as recorded in the audit log.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct AuditEntry {
+    pub id: u64,
+    pub time: u64,
+    pub moderator: String,
+    pub action: ModerationAction,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct InviteInfo {
+    pub channel: String,
+    pub token: String,
+}
+
+/// One connection registered under a nick.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionInfo {
+    /// Opaque, and only ever shown to the nick's own sessions.
+    pub id: String,
+    pub device: String,
+    /// Unix time in ms.
+    pub connected_at: u64,
+    /// Unix time in ms of the latest frame other than a heartbeat.
+    pub last_active: u64,
+    /// Whether this is the session that asked.
+    pub current: bool,
+}
+
+/// Most people a typing indicator names; any more read as "Several people are typing…".
+/// Servers need relay no more than one typist past this per channel.
+pub const TYPISTS_NAMED: usize = 3;
+
+/// Something that happened in the chat, shown as a line among the messages.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum SystemEvent {
+    /// The nick's first session registered.
+    Joined { user: String },
+    /// The nick's last session closed.
+    Left { user: String },
+    /// A moderator renamed channel `from` to `to`.
+    Renamed { from: String, to: String },
+}
+
+/// A `SystemEvent` and when the server saw it.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SystemNotice {
+    pub time: u64,
+    #[serde(flatten)]
+    pub event: SystemEvent,
+}
+
+/// Someone typing, as the server relays it.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct TypingInfo {
+    pub name: String,
+    pub channel: String,
+}
+
+/// Where a browser's push service accepts notifications for it, as `PushSubscription.toJSON()`
+/// gives it.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PushSubscription {
+    pub endpoint: String,
+    pub keys: PushKeys,
+}
+
+/// The keys a push payload is encrypted with.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PushKeys {
+    pub p256dh: String,
+    pub auth: String,
+}
+
+/// Payload of a push; the service worker shows it as a notification.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PushNotice {
+    pub title: String,
+    pub body: String,
+    pub channel: String,
+    pub message_id: u64,
+}
+
+/// A request for stored messages in one channel, newest first.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryQuery {
+    pub channel: String,
+    /// Only messages older than this id; `None` starts from the newest.
+    #[serde(default)]
+    pub before: Option<u64>,
+    /// Keep going past `limit` until this message is included.
+    #[serde(default)]
+    pub containing: Option<u64>,
+    pub limit: usize,
+}
+
+/// Answer to a `HistoryQuery`, oldest message first.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct HistoryPage {
+    pub channel: String,
+    pub messages: Vec<MessageData>,
+    /// Older messages remain on the server.
+    pub more: bool,
+}
@@ -0,0 +1,1497 @@
+use std::collections::hash_map::RandomState;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::hash::BuildHasher;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tokio::sync::mpsc::UnboundedSender;
+use yewchat_protocol::{
+    is_accent, is_channel_name, mentions, set_task, Attachment, AuditEntry, BroadcastMention,
+    ChannelInfo, EditData, HistoryPage, HistoryQuery, InviteInfo, MentionSuppression, MessageData,
+    MessageReactions, ModerationAction, MsgTypes, PushNotice, PushSubscription, Reaction,
+    ReactionData, Revision, Role, SessionInfo, Status, Stroke, SystemEvent, SystemNotice,
+    TaskToggle, TypingInfo, UserInfo, WebSocketMessage, Whiteboard, DEFAULT_CHANNEL,
+    MAX_ACTIVITY_CHARS, MAX_ALT_CHARS, MAX_ICON_CHARS, MAX_SLOW_MODE_SECS, TYPISTS_NAMED,
+};
+
+use crate::push;
+
+/// How many recent messages are kept for edits and moderation.
+const HISTORY_LIMIT: usize = 1000;
+/// Largest attachment accepted, encoded; matches the client's default `maxUploadBytes`.
+const MAX_ATTACHMENT_BYTES: usize = 10 * 1024 * 1024;
+/// Strokes kept per whiteboard; the oldest are dropped beyond this.
+const WHITEBOARD_LIMIT: usize = 2000;
+/// Points accepted in one stroke.
+const MAX_STROKE_POINTS: usize = 4096;
+/// Longest device description kept.
+const MAX_DEVICE_CHARS: usize = 100;
+/// Push subscriptions kept per nick; the oldest is dropped beyond this.
+const MAX_PUSH_SUBSCRIPTIONS: usize = 10;
+/// Longest message excerpt put in a push notification.
+const PUSH_EXCERPT_CHARS: usize = 140;
+/// How long a typing notice keeps its sender counted as typing; matches the client.
+const TYPING_WINDOW_MS: u64 = 6_000;
+/// Typing notices from one client closer together than this are dropped.
+const TYPING_MIN_INTERVAL_MS: u64 = 2_000;
+
+struct Client {
+    nick: Option<String>,
+    spectator: bool,
+    status: Status,
+    tx: UnboundedSender<String>,
+    /// Lets the nick's other sessions sign this one out.
+    session: String,
+    device: String,
+    /// What the user says they're up to; shown in the user list.
+    activity: Option<String>,
+    connected_at: u64,
+    /// Time of the latest frame other than a heartbeat.
+    last_active: u64,
+}
+
+struct Hub {
+    clients: BTreeMap<usize, Client>,
+    next_id: usize,
+    channels: Vec<ChannelInfo>,
+    accounts: Accounts,
+    next_message_id: u64,
+    reactions: HashMap<u64, Vec<Reaction>>,
+    /// Most recent messages, oldest first.
+    history: VecDeque<MessageData>,
+    pins: BTreeSet<u64>,
+    banned: HashSet<String>,
+    audit: Vec<AuditEntry>,
+    /// Channel each outstanding invite token leads to.
+    invites: HashMap<String, String>,
+    /// Whiteboard strokes by channel, oldest first.
+    boards: HashMap<String, VecDeque<Stroke>>,
+    /// Where each nick's browsers want mentions pushed while none of its sessions are open.
+    push_subscriptions: HashMap<String, Vec<PushSubscription>>,
+    /// Mentions each nick asked not to be pushed.
+    suppressed_mentions: HashMap<String, MentionSuppression>,
+    /// `http://` URL of the relay that signs and sends Web Push; pushes are dropped without one.
+    push_relay: Option<String>,
+    /// Nicks whose typing notices were relayed recently, by channel, with when.
+    typists: HashMap<String, Vec<(String, u64)>>,
+}
+
+/// Nicks the server gives a role or flag to in the user list, set when it starts.
+#[derive(Default)]
+pub struct Accounts {
+    pub admins: HashSet<String>,
+    pub moderators: HashSet<String>,
+    pub bots: HashSet<String>,
+    pub verified: HashSet<String>,
+    /// Key each of the nicks above has to register with. One without a key can't be taken.
+    pub keys: HashMap<String, String>,
+}
+
+impl Accounts {
+    /// Whether a registration sending `key` may take `nick`. Anyone may take a nick with no
+    /// role or flag.
+    fn may_claim(&self, nick: &str, key: Option<&str>) -> bool {
+        let reserved = self.admins.contains(nick)
+            || self.moderators.contains(nick)
+            || self.bots.contains(nick)
+            || self.verified.contains(nick);
+        !reserved || key.is_some_and(|key| self.keys.get(nick).is_some_and(|k| k == key))
+    }
+}
+
+/// Connected clients, shared between every socket task.
+#[derive(Clone)]
+pub struct SharedHub(Arc<Mutex<Hub>>);
+
+impl SharedHub {
+    /// `accounts` names the admins and moderators allowed to post in announcement channels,
+    /// and who to badge as bots or verified. Mentions of nicks with no open session are
+    /// pushed through `push_relay`, if given.
+    pub fn new(accounts: Accounts, push_relay: Option<String>) -> Self {
+        let channel = |name: &str, announcement| ChannelInfo {
+            name: name.into(),
+            announcement,
+            ..ChannelInfo::default()
+        };
+        Self(Arc::new(Mutex::new(Hub {
+            clients: BTreeMap::new(),
+            next_id: 0,
+            channels: vec![
+                channel(DEFAULT_CHANNEL, false),
+                channel("random", false),
+                channel("announcements", true),
+            ],
+            accounts,
+            next_message_id: 1,
+            reactions: HashMap::new(),
+            history: VecDeque::new(),
+            pins: BTreeSet::new(),
+            banned: HashSet::new(),
+            audit: Vec::new(),
+            invites: HashMap::new(),
+            boards: HashMap::new(),
+            push_subscriptions: HashMap::new(),
+            suppressed_mentions: HashMap::new(),
+            push_relay,
+            typists: HashMap::new(),
+        })))
+    }
+
+    pub fn join(&self, tx: UnboundedSender<String>) -> usize {
+        let mut hub = self.0.lock().unwrap();
+        let id = hub.next_id;
+        hub.next_id += 1;
+        let now = now_millis();
+        hub.clients.insert(
+            id,
+            Client {
+                nick: None,
+                spectator: false,
+                status: Status::Online,
+                tx,
+                session: new_token(id),
+                device: "Unknown device".into(),
+                activity: None,
+                connected_at: now,
+                last_active: now,
+            },
+        );
+        id
+    }
+
+    pub fn leave(&self, id: usize) {
+        let mut hub = self.0.lock().unwrap();
+        if let Some(client) = hub.clients.remove(&id) {
+            if let Some(nick) = client.nick {
+                hub.broadcast_users();
+                if !client.spectator && !hub.is_online(&nick) {
+                    hub.announce(SystemEvent::Left { user: nick });
+                }
+            }
+        }
+    }
+
+    /// Drops the push subscription with this endpoint, whoever it belongs to.
+    pub fn forget_push(&self, endpoint: &str) {
+        self.0.lock().unwrap().forget_push(endpoint);
+    }
+
+    pub fn handle_frame(&self, id: usize, raw: &str) {
+        let msg: WebSocketMessage = match serde_json::from_str(raw) {
+            Ok(msg) => msg,
+            Err(e) => {
+                log::warn!("Error in message: {}", e);
+                return;
+            }
+        };
+        let mut hub = self.0.lock().unwrap();
+        if msg.message_type != MsgTypes::Ping {
+            if let Some(client) = hub.clients.get_mut(&id) {
+                client.last_active = now_millis();
+            }
+        }
+        match msg.message_type {
+            MsgTypes::Register | MsgTypes::Spectate => {
+                if msg
+                    .data
+                    .as_ref()
+                    .is_some_and(|nick| hub.banned.contains(nick))
+                {
+                    return hub.reject(id, "you are banned from this server");
+                }
+                if msg
+                    .data
+                    .as_ref()
+                    .is_some_and(|nick| !hub.accounts.may_claim(nick, msg.key.as_deref()))
+                {
+                    return hub.reject(id, "that name belongs to an account; sign in with its key");
+                }
+                let arriving = msg
+                    .data
+                    .clone()
+                    .filter(|nick| msg.message_type == MsgTypes::Register && !hub.is_online(nick));
+                if let Some(client) = hub.clients.get_mut(&id) {
+                    client.nick = msg.data.clone();
+                    client.spectator = msg.message_type == MsgTypes::Spectate;
+                }
+                let channels = serde_json::to_string(&hub.channels).unwrap();
+                hub.send(
+                    id,
+                    &WebSocketMessage::with_data(MsgTypes::Channels, channels),
+                );
+                hub.send(id, &hub.pins_frame());
+                if msg.data.is_some_and(|nick| hub.role(&nick).moderates()) {
+                    hub.send(id, &audit_frame(&hub.audit));
+                }
+                hub.broadcast_users();
+                if let Some(user) = arriving {
+                    hub.announce(SystemEvent::Joined { user });
+                }
+            }
+            MsgTypes::Moderate => {
+                let Some(from) = hub.poster(id) else {
+                    return;
+                };
+                if !hub.role(&from).moderates() {
+                    return hub.reject(id, "only moderators can do that");
+                }
+                let action: ModerationAction = match msg.data.as_deref().map(serde_json::from_str) {
+                    Some(Ok(action)) => action,
+                    _ => return hub.reject(id, "malformed moderation action"),
+                };
+                if let ModerationAction::Restyle {
+                    channel,
+                    accent,
+                    icon,
+                } = &action
+                {
+                    if !hub.channels.iter().any(|c| &c.name == channel) {
+                        return hub.reject(id, "no such channel");
+                    }
+                    if accent.as_deref().is_some_and(|a| !is_accent(a)) {
+                        return hub.reject(id, "accents are colors like #3b82f6");
+                    }
+                    if icon
+                        .as_deref()
+                        .is_some_and(|i| i.trim().is_empty() || i.chars().count() > MAX_ICON_CHARS)
+                    {
+                        return hub.reject(id, "channel icons are a single emoji");
+                    }
+                }
+                if let ModerationAction::SlowMode { channel, seconds } = &action {
+                    if !hub.channels.iter().any(|c| &c.name == channel) {
+                        return hub.reject(id, "no such channel");
+                    }
+                    if seconds.is_some_and(|s| s == 0 || s > MAX_SLOW_MODE_SECS) {
+                        return hub.reject(id, "slow mode is between a second and an hour");
+                    }
+                }
+                if let ModerationAction::Rename { channel, name } = &action {
+                    if !hub.channels.iter().any(|c| &c.name == channel) {
+                        return hub.reject(id, "no such channel");
+                    }
+                    // Clients file messages without a channel under the default one.
+                    if channel == DEFAULT_CHANNEL {
+                        return hub.reject(id, "the default channel can't be renamed");
+                    }
+                    if !is_channel_name(name) {
+                        return hub
+                            .reject(id, "channel names are lowercase letters, digits, - and _");
+                    }
+                    if hub.channels.iter().any(|c| &c.name == name) {
+                        return hub.reject(id, "a channel by that name already exists");
+                    }
+                }
+                hub.moderate(&action);
+                let entry = AuditEntry {
+                    id: hub.audit.len() as u64 + 1,
+                    time: now_millis(),
+                    moderator: from,
+                    action,
+                };
+                hub.broadcast_to_moderators(&audit_frame(std::slice::from_ref(&entry)));
+                hub.audit.push(entry);
+            }
+            MsgTypes::Message => {
+                let Some(from) = hub.poster(id) else {
+                    return;
+                };
+                let outgoing_id = msg.outgoing_id;
+                let channel = match hub.channels.iter().find(|c| c.name == msg.channel()) {
+                    Some(channel) => channel,
+                    None => return hub.reject_send(id, outgoing_id, "no such channel"),
+                };
+                if channel.announcement && !hub.role(&from).moderates() {
+                    return hub.reject_send(
+                        id,
+                        outgoing_id,
+                        "only moderators can post in this channel",
+                    );
+                }
+                let message = msg.data.unwrap_or_default();
+                if BroadcastMention::find(&message).is_some() && !hub.role(&from).moderates() {
+                    return hub.reject_send(
+                        id,
+                        outgoing_id,
+                        "only moderators can mention @everyone or @here",
+                    );
+                }
+                if let Some(wait) = hub.slow_mode_wait(channel, &from, now_millis()) {
+                    let seconds = wait.div_ceil(1000);
+                    return hub.reject_send(
+                        id,
+                        outgoing_id,
+                        &format!("slow mode: wait {}s to post again", seconds),
+                    );
+                }
+                let attachment = msg.attachment;
+                let tasks_open = msg.tasks_open;
+                if attachment
+                    .as_ref()
+                    .is_some_and(|a| a.size() > MAX_ATTACHMENT_BYTES)
+                {
+                    return hub.reject_send(id, outgoing_id, "attachment is too large");
+                }
+                if matches!(attachment, Some(Attachment::Embed { .. }))
+                    && !hub.accounts.bots.contains(&from)
+                {
+                    return hub.reject_send(id, outgoing_id, "only bots can post embeds");
+                }
+                if let Some(Attachment::Image { alt: Some(alt), .. }) = &attachment {
+                    if alt.chars().count() > MAX_ALT_CHARS {
+                        return hub.reject_send(id, outgoing_id, "image description is too long");
+                    }
+                }
+                // Replies to messages that have aged out or live elsewhere are posted plainly.
+                let reply_to = msg.reply_to.filter(|parent| {
+                    hub.history
+                        .iter()
+                        .any(|m| m.id == *parent && m.channel == channel.name)
+                });
+                let data = MessageData {
+                    id: hub.next_message_id,
+                    from,
+                    message,
+                    time: now_millis(),
+                    channel: channel.name.clone(),
+                    edited_at: 0,
+                    history: Vec::new(),
+                    reply_to,
+                    attachment,
+                    tasks_open,
+                };
+                let frame = WebSocketMessage::with_data(
+                    MsgTypes::Message,
+                    serde_json::to_string(&data).unwrap(),
+                );
+                hub.next_message_id += 1;
+                hub.broadcast(&frame);
+                if let Some(relay) = hub.push_relay.clone() {
+                    for (subscription, notice) in hub.pushes_for(&data) {
+                        push::deliver(self.clone(), relay.clone(), subscription, notice);
+                    }
+                }
+                hub.history.push_back(data);
+                if hub.history.len() > HISTORY_LIMIT {
+                    hub.history.pop_front();
+                }
+            }
+            MsgTypes::Edit => {
+                let Some(from) = hub.poster(id) else {
+                    return;
+                };
+                let data: EditData = match msg.data.as_deref().map(serde_json::from_str) {
+                    Some(Ok(data)) => data,
+                    _ => return hub.reject(id, "malformed edit"),
+                };
+                // Or a member could post something quiet and edit the ping in afterwards.
+                if BroadcastMention::find(&data.message).is_some() && !hub.role(&from).moderates() {
+                    return hub.reject(id, "only moderators can mention @everyone or @here");
+                }
+                let Some(message) = hub.history.iter_mut().find(|m| m.id == data.message_id) else {
+                    return hub.reject(id, "message is too old to edit");
+                };
+                if message.from != from {
+                    return hub.reject(id, "you can only edit your own messages");
+                }
+                let written = if message.edited_at == 0 {
+                    message.time
+                } else {
+                    message.edited_at
+                };
+                let previous = std::mem::replace(&mut message.message, data.message);
+                message.history.push(Revision {
+                    message: previous,
+                    time: written,
+                });
+                message.edited_at = now_millis();
+                let frame = WebSocketMessage::with_data(
+                    MsgTypes::Edited,
+                    serde_json::to_string(message).unwrap(),
+                );
+                hub.broadcast(&frame);
+            }
+            MsgTypes::React => {
+                let Some(from) = hub.poster(id) else {
+                    return;
+                };
+                let data: ReactionData = match msg.data.as_deref().map(serde_json::from_str) {
+                    Some(Ok(data)) => data,
+                    _ => return hub.reject(id, "malformed reaction"),
+                };
+                if data.message_id == 0 || data.message_id >= hub.next_message_id {
+                    return hub.reject(id, "no such message");
+                }
+                hub.toggle_reaction(data, from);
+            }
+            MsgTypes::Status => {
+                let status = match msg.data.as_deref().map(serde_json::from_str) {
+                    Some(Ok(status)) => status,
+                    _ => return hub.reject(id, "malformed status"),
+                };
+                if let Some(client) = hub.clients.get_mut(&id) {
+                    client.status = status;
+                }
+                hub.broadcast_users();
+            }
+            MsgTypes::Invite => {
+                if hub.poster(id).is_none() {
+                    return;
+                }
+                let channel = msg.data.unwrap_or_default();
+                if !hub.channels.iter().any(|c| c.name == channel) {
+                    return hub.reject(id, "no such channel");
+                }
+                let token = new_token(hub.invites.len());
+                hub.invites.insert(token.clone(), channel.clone());
+                let invite = InviteInfo { channel, token };
+                hub.send(
+                    id,
+                    &WebSocketMessage::with_data(
+                        MsgTypes::Invite,
+                        serde_json::to_string(&invite).unwrap(),
+                    ),
+                );
+            }
+            MsgTypes::Join => {
+                let token = msg.data.unwrap_or_default();
+                match hub.invites.get(&token) {
+                    Some(channel) => log::info!("invite to #{} redeemed", channel),
+                    None => hub.reject(id, "this invite link is invalid or has expired"),
+                }
+            }
+            MsgTypes::History => {
+                let query: HistoryQuery = match msg.data.as_deref().map(serde_json::from_str) {
+                    Some(Ok(query)) => query,
+                    _ => return hub.reject(id, "malformed history request"),
+                };
+                let page = serde_json::to_string(&hub.history_page(&query)).unwrap();
+                hub.send(id, &WebSocketMessage::with_data(MsgTypes::History, page));
+            }
+            MsgTypes::ToggleTask => {
+                let Some(from) = hub.poster(id) else {
+                    return;
+                };
+                let data: TaskToggle = match msg.data.as_deref().map(serde_json::from_str) {
+                    Some(Ok(data)) => data,
+                    _ => return hub.reject(id, "malformed checklist update"),
+                };
+                let Some(message) = hub.history.iter_mut().find(|m| m.id == data.message_id) else {
+                    return hub.reject(id, "message is too old to change");
+                };
+                if message.from != from && !message.tasks_open {
+                    return hub.reject(id, "only the author can tick this checklist");
+                }
+                let Some(text) = set_task(&message.message, data.index, data.done) else {
+                    return hub.reject(id, "no such checklist item");
+                };
+                message.message = text;
+                let frame = WebSocketMessage::with_data(
+                    MsgTypes::Edited,
+                    serde_json::to_string(message).unwrap(),
+                );
+                hub.broadcast(&frame);
+            }
+            MsgTypes::Stroke => {
+                if hub.poster(id).is_none() {
+                    return;
+                }
+                let stroke: Stroke = match msg.data.as_deref().map(serde_json::from_str) {
+                    Some(Ok(stroke)) => stroke,
+                    _ => return hub.reject(id, "malformed stroke"),
+                };
+                if !hub.channels.iter().any(|c| c.name == stroke.channel) {
+                    return hub.reject(id, "no such channel");
+                }
+                if stroke.points.is_empty() || stroke.points.len() > MAX_STROKE_POINTS {
+                    return hub.reject(id, "stroke is too long");
+                }
+                let frame = WebSocketMessage::with_data(
+                    MsgTypes::Stroke,
+                    serde_json::to_string(&stroke).unwrap(),
+                );
+                let board = hub.boards.entry(stroke.channel.clone()).or_default();
+                board.push_back(stroke);
+                if board.len() > WHITEBOARD_LIMIT {
+                    board.pop_front();
+                }
+                hub.broadcast(&frame);
+            }
+            MsgTypes::Whiteboard => {
+                let channel = msg.data.unwrap_or_default();
+                let strokes = hub
+                    .boards
+                    .get(&channel)
+                    .map(|board| board.iter().cloned().collect())
+                    .unwrap_or_default();
+                hub.send(id, &whiteboard_frame(channel, strokes));
+            }
+            MsgTypes::ClearWhiteboard => {
+                if hub.poster(id).is_none() {
+                    return;
+                }
+                let channel = msg.data.unwrap_or_default();
+                if hub.boards.remove(&channel).is_some() {
+                    hub.broadcast(&whiteboard_frame(channel, Vec::new()));
+                }
+            }
+            MsgTypes::Ping => hub.send(id, &msg),
+            MsgTypes::Device => {
+                if let Some(client) = hub.clients.get_mut(&id) {
+                    let device = msg.data.unwrap_or_default();
+                    client.device = device.trim().chars().take(MAX_DEVICE_CHARS).collect();
+                }
+            }
+            MsgTypes::Activity => {
+                if let Some(client) = hub.clients.get_mut(&id) {
+                    let activity = msg.data.unwrap_or_default();
+                    let activity: String =
+                        activity.trim().chars().take(MAX_ACTIVITY_CHARS).collect();
+                    client.activity = Some(activity).filter(|a| !a.is_empty());
+                }
+                hub.broadcast_users();
+            }
+            MsgTypes::Sessions => hub.send_sessions(id),
+            MsgTypes::SignOut => {
+                let Some(nick) = hub.clients.get(&id).and_then(|c| c.nick.clone()) else {
+                    return;
+                };
+                let session = msg.data.unwrap_or_default();
+                let target = hub
+                    .clients
+                    .iter()
+                    .find(|(_, c)| c.session == session && c.nick.as_ref() == Some(&nick))
+                    .map(|(target, _)| *target);
+                let Some(target) = target else {
+                    return hub.reject(id, "that session has already ended");
+                };
+                hub.send(target, &WebSocketMessage::new(MsgTypes::SignOut));
+                // Dropping the sender ends the connection's writer, which closes it.
+                hub.clients.remove(&target);
+                hub.broadcast_users();
+                hub.send_sessions(id);
+            }
+            MsgTypes::PushSubscribe => {
+                let Some(nick) = hub.poster(id) else {
+                    return;
+                };
+                let subscription: PushSubscription =
+                    match msg.data.as_deref().map(serde_json::from_str) {
+                        Some(Ok(subscription)) => subscription,
+                        _ => return hub.reject(id, "malformed push subscription"),
+                    };
+                let subscriptions = hub.push_subscriptions.entry(nick).or_default();
+                subscriptions.retain(|s| s.endpoint != subscription.endpoint);
+                subscriptions.push(subscription);
+                if subscriptions.len() > MAX_PUSH_SUBSCRIPTIONS {
+                    subscriptions.remove(0);
+                }
+            }
+            MsgTypes::SuppressMentions => {
+                let Some(nick) = hub.poster(id) else {
+                    return;
+                };
+                let suppression = match msg.data.as_deref().map(serde_json::from_str) {
+                    Some(Ok(suppression)) => suppression,
+                    _ => return hub.reject(id, "malformed mention setting"),
+                };
+                hub.suppressed_mentions.insert(nick, suppression);
+            }
+            MsgTypes::Typing => {
+                let Some(name) = hub.poster(id) else {
+                    return;
+                };
+                let channel = msg.data.unwrap_or_default();
+                if !hub.channels.iter().any(|c| c.name == channel) {
+                    return;
+                }
+                if !hub.note_typist(&channel, &name, now_millis()) {
+                    return;
+                }
+                let typing = TypingInfo { name, channel };
+                let frame = WebSocketMessage::with_data(
+                    MsgTypes::Typing,
+                    serde_json::to_string(&typing).unwrap(),
+                );
+                hub.broadcast_except(id, &frame);
+            }
+            MsgTypes::PushUnsubscribe => {
+                let endpoint = msg.data.unwrap_or_default();
+                hub.forget_push(&endpoint);
+            }
+            MsgTypes::Users
+            | MsgTypes::Spectators
+            | MsgTypes::Channels
+            | MsgTypes::Error
+            | MsgTypes::Reactions
+            | MsgTypes::Edited
+            | MsgTypes::Audit
+            | MsgTypes::Deleted
+            | MsgTypes::Pins
+            | MsgTypes::System => {}
+        }
+    }
+}
+
+impl Hub {
+    fn broadcast(&self, msg: &WebSocketMessage) {
+        let frame = msg.to_json();
+        for client in self.clients.values() {
+            let _ = client.tx.send(frame.clone());
+        }
+    }
+
+    fn broadcast_except(&self, except: usize, msg: &WebSocketMessage) {
+        let frame = msg.to_json();
+        for (_, client) in self.clients.iter().filter(|(id, _)| **id != except) {
+            let _ = client.tx.send(frame.clone());
+        }
+    }
+
+    fn broadcast_to_moderators(&self, msg: &WebSocketMessage) {
+        let frame = msg.to_json();
+        for client in self.clients.values() {
+            if client
+                .nick
+                .as_deref()
+                .is_some_and(|n| self.role(n).moderates())
+            {
+                let _ = client.tx.send(frame.clone());
+            }
+        }
+    }
+
+    /// Carries out a moderator's action; the caller records it in the audit log.
+    fn moderate(&mut self, action: &ModerationAction) {
+        match action {
+            ModerationAction::Delete { message_id } => {
+                self.history.retain(|m| m.id != *message_id);
+                self.reactions.remove(message_id);
+                self.pins.remove(message_id);
+                self.broadcast(&WebSocketMessage::with_data(
+                    MsgTypes::Deleted,
+                    message_id.to_string(),
+                ));
+            }
+            ModerationAction::Pin { message_id } => {
+                self.pins.insert(*message_id);
+                self.broadcast(&self.pins_frame());
+            }
+            ModerationAction::Unpin { message_id } => {
+                self.pins.remove(message_id);
+                self.broadcast(&self.pins_frame());
+            }
+            ModerationAction::Kick { user } | ModerationAction::Ban { user } => {
+                let verb = if matches!(action, ModerationAction::Ban { .. }) {
+                    self.banned.insert(user.clone());
+                    "banned"
+                } else {
+                    "kicked"
+                };
+                let targets: Vec<usize> = self
+                    .clients
+                    .iter()
+                    .filter(|(_, c)| c.nick.as_ref() == Some(user))
+                    .map(|(id, _)| *id)
+                    .collect();
+                let was_online = self.is_online(user);
+                for id in targets {
+                    self.reject(id, &format!("you were {} by a moderator", verb));
+                    // Dropping the sender ends the connection's writer, which closes it.
+                    self.clients.remove(&id);
+                }
+                self.broadcast_users();
+                if was_online {
+                    self.announce(SystemEvent::Left { user: user.clone() });
+                }
+            }
+            ModerationAction::Restyle {
+                channel,
+                accent,
+                icon,
+            } => {
+                if let Some(info) = self.channels.iter_mut().find(|c| &c.name == channel) {
+                    info.accent = accent.clone();
+                    info.icon = icon.as_deref().map(|i| i.trim().to_string());
+                }
+                let channels = serde_json::to_string(&self.channels).unwrap();
+                self.broadcast(&WebSocketMessage::with_data(MsgTypes::Channels, channels));
+            }
+            ModerationAction::SlowMode { channel, seconds } => {
+                if let Some(info) = self.channels.iter_mut().find(|c| &c.name == channel) {
+                    info.slow_mode = *seconds;
+                }
+                let channels = serde_json::to_string(&self.channels).unwrap();
+                self.broadcast(&WebSocketMessage::with_data(MsgTypes::Channels, channels));
+            }
+            ModerationAction::Rename { channel, name } => {
+                if let Some(info) = self.channels.iter_mut().find(|c| &c.name == channel) {
+                    info.name = name.clone();
+                }
+                for message in self.history.iter_mut().filter(|m| &m.channel == channel) {
+                    message.channel = name.clone();
+                }
+                for target in self.invites.values_mut().filter(|c| *c == channel) {
+                    *target = name.clone();
+                }
+                if let Some(board) = self.boards.remove(channel) {
+                    self.boards.insert(name.clone(), board);
+                }
+                self.typists.remove(channel);
+                // Announced before the new list, so clients viewing the channel follow it
+                // rather than fall back to another when its old name disappears.
+                self.announce(SystemEvent::Renamed {
+                    from: channel.clone(),
+                    to: name.clone(),
+                });
+                let channels = serde_json::to_string(&self.channels).unwrap();
+                self.broadcast(&WebSocketMessage::with_data(MsgTypes::Channels, channels));
+            }
+        }
+    }
+
+    /// Whether any of `nick`'s sessions is registered, spectators aside.
+    fn is_online(&self, nick: &str) -> bool {
+        self.clients
+            .values()
+            .any(|c| !c.spectator && c.nick.as_deref() == Some(nick))
+    }
+
+    fn announce(&self, event: SystemEvent) {
+        let notice = SystemNotice {
+            time: now_millis(),
+            event,
+        };
+        self.broadcast(&WebSocketMessage::with_data(
+            MsgTypes::System,
+            serde_json::to_string(&notice).unwrap(),
+        ));
+    }
+
+    /// How many ms `nick` must still wait at `now` before posting in `channel` again under
+    /// its slow mode, if at all. Moderators never wait.
+    fn slow_mode_wait(&self, channel: &ChannelInfo, nick: &str, now: u64) -> Option<u64> {
+        let seconds = channel.slow_mode?;
+        if self.role(nick).moderates() {
+            return None;
+        }
+        let last = self
+            .history
+            .iter()
+            .rev()
+            .find(|m| m.from == nick && m.channel == channel.name)?;
+        let until = last.time + u64::from(seconds) * 1000;
+        (until > now).then(|| until - now)
+    }
+
+    fn pins_frame(&self) -> WebSocketMessage {
+        let pins: Vec<u64> = self.pins.iter().copied().collect();
+        WebSocketMessage::with_data(MsgTypes::Pins, serde_json::to_string(&pins).unwrap())
+    }
+
+    /// The newest `query.limit` matching messages, extended back to `query.containing` if it
+    /// is still stored.
+    fn history_page(&self, query: &HistoryQuery) -> HistoryPage {
+        let older: Vec<&MessageData> = self
+            .history
+            .iter()
+            .filter(|m| m.channel == query.channel && query.before.is_none_or(|b| m.id < b))
+            .collect();
+        let mut start = older.len().saturating_sub(query.limit);
+        if let Some(pos) = query
+            .containing
+            .and_then(|target| older.iter().position(|m| m.id == target))
+        {
+            start = start.min(pos);
+        }
+        HistoryPage {
+            channel: query.channel.clone(),
+            messages: older[start..].iter().map(|m| (*m).clone()).collect(),
+            more: start > 0,
+        }
+    }
+
+    /// Nick of `id` if it has registered and may post.
+    fn poster(&self, id: usize) -> Option<String> {
+        match self.clients.get(&id) {
+            Some(Client {
+                nick: Some(nick),
+                spectator: false,
+                ..
+            }) => Some(nick.clone()),
+            _ => None,
+        }
+    }
+
+    /// Lists the sessions registered under `id`'s nick to it.
+    fn send_sessions(&self, id: usize) {
+        let Some(nick) = self.clients.get(&id).and_then(|c| c.nick.as_ref()) else {
+            return;
+        };
+        let sessions: Vec<SessionInfo> = self
+            .clients
+            .iter()
+            .filter(|(_, c)| c.nick.as_ref() == Some(nick))
+            .map(|(&other, c)| SessionInfo {
+                id: c.session.clone(),
+                device: c.device.clone(),
+                connected_at: c.connected_at,
+                last_active: c.last_active,
+                current: other == id,
+            })
+            .collect();
+        let frame = WebSocketMessage::with_data(
+            MsgTypes::Sessions,
+            serde_json::to_string(&sessions).unwrap(),
+        );
+        self.send(id, &frame);
+    }
+
+    /// A push for each subscription of every nick `message` mentions that has no session
+    /// open to see it, unless the nick suppressed that kind of mention.
+    fn pushes_for(&self, message: &MessageData) -> Vec<(PushSubscription, PushNotice)> {
+        let everyone = BroadcastMention::find(&message.message) == Some(BroadcastMention::Everyone);
+        let mut excerpt: String = message.message.chars().take(PUSH_EXCERPT_CHARS).collect();
+        if excerpt.len() < message.message.len() {
+            excerpt.push('…');
+        }
+        let notice = PushNotice {
+            title: format!("{} mentioned you in #{}", message.from, message.channel),
+            body: excerpt,
+            channel: message.channel.clone(),
+            message_id: message.id,
+        };
+        self.push_subscriptions
+            .iter()
+            .filter(|(nick, _)| **nick != message.from)
+            .filter(|(nick, _)| {
+                let direct = mentions(&message.message, nick);
+                let suppression = self.suppressed_mentions.get(*nick).copied();
+                (everyone || direct) && !suppression.unwrap_or_default().suppresses(!direct)
+            })
+            .filter(|(nick, _)| !self.clients.values().any(|c| c.nick.as_ref() == Some(nick)))
+            .flat_map(|(_, subscriptions)| subscriptions.iter().cloned())
+            .map(|subscription| (subscription, notice.clone()))
+            .collect()
+    }
+
+    /// Records `name` typing in `channel` at `now`, returning whether to relay it. Each
+    /// typist is relayed at most every `TYPING_MIN_INTERVAL_MS`, and once clients would
+    /// show "Several people are typing…" newcomers aren't relayed at all, so a busy channel
+    /// can't flood everyone with typing frames.
+    fn note_typist(&mut self, channel: &str, name: &str, now: u64) -> bool {
+        let typists = self.typists.entry(channel.to_string()).or_default();
+        typists.retain(|(_, at)| now.saturating_sub(*at) < TYPING_WINDOW_MS);
+        let crowded = typists.len() > TYPISTS_NAMED;
+        match typists.iter_mut().find(|(typist, _)| typist == name) {
+            Some((_, at)) if now.saturating_sub(*at) < TYPING_MIN_INTERVAL_MS => false,
+            Some((_, at)) => {
+                *at = now;
+                true
+            }
+            None if crowded => false,
+            None => {
+                typists.push((name.to_string(), now));
+                true
+            }
+        }
+    }
+
+    fn forget_push(&mut self, endpoint: &str) {
+        for subscriptions in self.push_subscriptions.values_mut() {
+            subscriptions.retain(|s| s.endpoint != endpoint);
+        }
+        self.push_subscriptions.retain(|_, s| !s.is_empty());
+    }
+
+    fn send(&self, id: usize, msg: &WebSocketMessage) {
+        if let Some(client) = self.clients.get(&id) {
+            let _ = client.tx.send(msg.to_json());
+        }
+    }
+
+    fn reject(&self, id: usize, reason: &str) {
+        self.send(id, &WebSocketMessage::with_data(MsgTypes::Error, reason));
+    }
+
+    /// Refuses a posted message, naming it by the sender's id so the sender knows which.
+    fn reject_send(&self, id: usize, outgoing_id: Option<u64>, reason: &str) {
+        let mut frame = WebSocketMessage::with_data(MsgTypes::Error, reason);
+        frame.outgoing_id = outgoing_id;
+        self.send(id, &frame);
+    }
+
+    /// Adds or removes `from`'s reaction and sends everyone the message's new reaction list.
+    fn toggle_reaction(&mut self, data: ReactionData, from: String) {
+        let reactions = self.reactions.entry(data.message_id).or_default();
+        match reactions.iter_mut().find(|r| r.emoji == data.emoji) {
+            Some(reaction) => match reaction.users.iter().position(|u| *u == from) {
+                Some(i) => {
+                    reaction.users.remove(i);
+                }
+                None => reaction.users.push(from),
+            },
+            None => reactions.push(Reaction {
+                emoji: data.emoji,
+                users: vec![from],
+            }),
+        }
+        reactions.retain(|r| !r.users.is_empty());
+        let update = MessageReactions {
+            message_id: data.message_id,
+            reactions: reactions.clone(),
+        };
+        self.broadcast(&WebSocketMessage::with_data(
+            MsgTypes::Reactions,
+            serde_json::to_string(&update).unwrap(),
+        ));
+    }
+
+    fn role(&self, nick: &str) -> Role {
+        if self.accounts.admins.contains(nick) {
+            Role::Admin
+        } else if self.accounts.moderators.contains(nick) {
+            Role::Moderator
+        } else {
+            Role::Member
+        }
+    }
+
+    fn nicks(&self, spectators: bool) -> Vec<String> {
+        self.clients
+            .values()
+            .filter(|c| c.spectator == spectators)
+            .filter_map(|c| c.nick.clone())
+            .collect()
+    }
+
+    fn broadcast_users(&self) {
+        let nicks = self.nicks(false);
+        let users: Vec<UserInfo> = self
+            .clients
+            .values()
+            .filter(|c| !c.spectator)
+            .filter_map(|c| {
+                let name = c.nick.clone()?;
+                Some(UserInfo {
+                    role: self.role(&name),
+                    status: c.status,
+                    activity: c.activity.clone(),
+                    bot: self.accounts.bots.contains(&name),
+                    verified: self.accounts.verified.contains(&name),
+                    name,
+                })
+            })
+            .collect();
+        self.broadcast(&WebSocketMessage {
+            data: Some(serde_json::to_string(&users).unwrap()),
+            ..WebSocketMessage::with_data_array(MsgTypes::Users, nicks)
+        });
+        self.broadcast(&WebSocketMessage::with_data_array(
+            MsgTypes::Spectators,
+            self.nicks(true),
+        ));
+    }
+}
+
+fn audit_frame(entries: &[AuditEntry]) -> WebSocketMessage {
+    WebSocketMessage::with_data(MsgTypes::Audit, serde_json::to_string(entries).unwrap())
+}
+
+fn whiteboard_frame(channel: String, strokes: Vec<Stroke>) -> WebSocketMessage {
+    let board = Whiteboard { channel, strokes };
+    WebSocketMessage::with_data(MsgTypes::Whiteboard, serde_json::to_string(&board).unwrap())
+}
+
+/// Hard-to-guess token for an invite link or a session, unique among its kind thanks to `seq`.
+fn new_token(seq: usize) -> String {
+    let half = |salt: u8| RandomState::new().hash_one((seq, salt, now_millis()));
+    format!("{:016x}{:016x}", half(0), half(1))
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+
+    use super::*;
+
+    struct TestClient {
+        id: usize,
+        rx: UnboundedReceiver<String>,
+    }
+
+    impl TestClient {
+        fn join(hub: &SharedHub) -> Self {
+            let (tx, rx) = unbounded_channel();
+            Self {
+                id: hub.join(tx),
+                rx,
+            }
+        }
+
+        /// Joins and registers as `nick`, dropping the frames that greet it.
+        fn register(hub: &SharedHub, nick: &str) -> Self {
+            let mut client = Self::join(hub);
+            client.send(hub, WebSocketMessage::with_data(MsgTypes::Register, nick));
+            client.frames();
+            client
+        }
+
+        fn send(&self, hub: &SharedHub, msg: WebSocketMessage) {
+            hub.handle_frame(self.id, &msg.to_json());
+        }
+
+        fn post(&self, hub: &SharedHub, channel: &str, text: &str) {
+            self.send(
+                hub,
+                WebSocketMessage::with_data(MsgTypes::Message, text).in_channel(channel),
+            );
+        }
+
+        /// Every frame received since the last call.
+        fn frames(&mut self) -> Vec<WebSocketMessage> {
+            std::iter::from_fn(|| self.rx.try_recv().ok())
+                .map(|raw| serde_json::from_str(&raw).unwrap())
+                .collect()
+        }
+
+        fn errors(&mut self) -> Vec<String> {
+            self.frames()
+                .into_iter()
+                .filter(|f| f.message_type == MsgTypes::Error)
+                .filter_map(|f| f.data)
+                .collect()
+        }
+
+        /// Payloads of the `message_type` frames received since the last call.
+        fn payloads<T: serde::de::DeserializeOwned>(&mut self, message_type: MsgTypes) -> Vec<T> {
+            self.frames()
+                .into_iter()
+                .filter(|f| f.message_type == message_type)
+                .map(|f| serde_json::from_str(&f.data.unwrap()).unwrap())
+                .collect()
+        }
+    }
+
+    fn hub() -> SharedHub {
+        SharedHub::new(Accounts::default(), None)
+    }
+
+    /// A hub where "mod" moderates and registers with the key "secret".
+    fn moderated_hub() -> SharedHub {
+        let accounts = Accounts {
+            moderators: HashSet::from(["mod".to_string()]),
+            keys: HashMap::from([("mod".to_string(), "secret".to_string())]),
+            ..Accounts::default()
+        };
+        SharedHub::new(accounts, None)
+    }
+
+    fn register_moderator(hub: &SharedHub) -> TestClient {
+        let mut client = TestClient::join(hub);
+        client.send(
+            hub,
+            WebSocketMessage::with_data(MsgTypes::Register, "mod").with_key("secret"),
+        );
+        client.frames();
+        client
+    }
+
+    fn edit(message_id: u64, message: &str) -> WebSocketMessage {
+        let data = EditData {
+            message_id,
+            message: message.into(),
+        };
+        WebSocketMessage::with_data(MsgTypes::Edit, serde_json::to_string(&data).unwrap())
+    }
+
+    fn moderate(action: ModerationAction) -> WebSocketMessage {
+        WebSocketMessage::with_data(MsgTypes::Moderate, serde_json::to_string(&action).unwrap())
+    }
+
+    #[test]
+    fn registering_lists_and_announces_the_user() {
+        let hub = hub();
+        let mut watcher = TestClient::register(&hub, "bob");
+        let mut alice = TestClient::join(&hub);
+        alice.send(
+            &hub,
+            WebSocketMessage::with_data(MsgTypes::Register, "alice"),
+        );
+
+        let frames = alice.frames();
+        assert!(frames.iter().any(|f| f.message_type == MsgTypes::Channels));
+        let users = frames
+            .iter()
+            .find(|f| f.message_type == MsgTypes::Users)
+            .unwrap();
+        let mut nicks = users.data_array.clone().unwrap();
+        nicks.sort();
+        assert_eq!(nicks, ["alice", "bob"]);
+        let notices: Vec<SystemNotice> = watcher.payloads(MsgTypes::System);
+        assert_eq!(
+            notices[0].event,
+            SystemEvent::Joined {
+                user: "alice".into()
+            }
+        );
+    }
+
+    #[test]
+    fn messages_reach_everyone_with_increasing_ids() {
+        let hub = hub();
+        let mut alice = TestClient::register(&hub, "alice");
+        let mut bob = TestClient::register(&hub, "bob");
+        alice.frames();
+        alice.post(&hub, DEFAULT_CHANNEL, "hi");
+        alice.post(&hub, "random", "anyone?");
+
+        let messages: Vec<MessageData> = bob.payloads(MsgTypes::Message);
+        assert_eq!(messages.len(), 2);
+        assert_eq!(
+            (
+                messages[0].id,
+                messages[0].from.as_str(),
+                messages[0].message.as_str()
+            ),
+            (1, "alice", "hi")
+        );
+        assert_eq!(
+            (messages[1].id, messages[1].channel.as_str()),
+            (2, "random")
+        );
+        assert_eq!(alice.payloads::<MessageData>(MsgTypes::Message).len(), 2);
+    }
+
+    #[test]
+    fn unregistered_clients_and_spectators_cannot_post() {
+        let hub = hub();
+        let mut alice = TestClient::register(&hub, "alice");
+        let anonymous = TestClient::join(&hub);
+        let mut spectator = TestClient::join(&hub);
+        spectator.send(&hub, WebSocketMessage::with_data(MsgTypes::Spectate, "eve"));
+        spectator.frames();
+        alice.frames();
+
+        anonymous.post(&hub, DEFAULT_CHANNEL, "hi");
+        spectator.post(&hub, DEFAULT_CHANNEL, "hi");
+
+        assert!(alice.frames().is_empty());
+    }
+
+    #[test]
+    fn posting_to_an_unknown_channel_is_refused_by_outgoing_id() {
+        let hub = hub();
+        let mut alice = TestClient::register(&hub, "alice");
+        alice.send(
+            &hub,
+            WebSocketMessage::with_data(MsgTypes::Message, "hi")
+                .in_channel("nowhere")
+                .with_outgoing_id(7),
+        );
+
+        let error = alice.frames().pop().unwrap();
+        assert_eq!(error.message_type, MsgTypes::Error);
+        assert_eq!(error.data.as_deref(), Some("no such channel"));
+        assert_eq!(error.outgoing_id, Some(7));
+    }
+
+    #[test]
+    fn only_the_author_can_edit_a_message() {
+        let hub = hub();
+        let mut alice = TestClient::register(&hub, "alice");
+        let mut bob = TestClient::register(&hub, "bob");
+        alice.post(&hub, DEFAULT_CHANNEL, "helo");
+        alice.frames();
+        bob.frames();
+
+        bob.send(&hub, edit(1, "hijacked"));
+        assert_eq!(bob.errors(), ["you can only edit your own messages"]);
+
+        alice.send(&hub, edit(1, "hello"));
+        let edited: Vec<MessageData> = bob.payloads(MsgTypes::Edited);
+        assert_eq!(edited[0].message, "hello");
+        assert_eq!(edited[0].history[0].message, "helo");
+        assert_ne!(edited[0].edited_at, 0);
+
+        alice.send(&hub, edit(99, "gone"));
+        assert_eq!(alice.errors(), ["message is too old to edit"]);
+    }
+
+    #[test]
+    fn only_moderators_post_in_announcement_channels() {
+        let hub = moderated_hub();
+        let mut bob = TestClient::register(&hub, "bob");
+        let mut moderator = register_moderator(&hub);
+        bob.frames();
+
+        bob.send(
+            &hub,
+            WebSocketMessage::with_data(MsgTypes::Message, "news")
+                .in_channel("announcements")
+                .with_outgoing_id(3),
+        );
+        let error = bob.frames().pop().unwrap();
+        assert_eq!(
+            error.data.as_deref(),
+            Some("only moderators can post in this channel")
+        );
+        assert_eq!(error.outgoing_id, Some(3));
+        assert!(moderator.frames().is_empty());
+
+        moderator.post(&hub, "announcements", "news");
+        let messages: Vec<MessageData> = bob.payloads(MsgTypes::Message);
+        assert_eq!(messages[0].channel, "announcements");
+    }
+
+    #[test]
+    fn only_moderators_mention_everyone_when_posting_or_editing() {
+        let hub = moderated_hub();
+        let mut bob = TestClient::register(&hub, "bob");
+        let mut moderator = register_moderator(&hub);
+        bob.frames();
+
+        bob.post(&hub, DEFAULT_CHANNEL, "@everyone look");
+        bob.post(&hub, DEFAULT_CHANNEL, "@here look");
+        assert_eq!(
+            bob.errors(),
+            [
+                "only moderators can mention @everyone or @here",
+                "only moderators can mention @everyone or @here"
+            ]
+        );
+
+        bob.post(&hub, DEFAULT_CHANNEL, "look");
+        bob.send(&hub, edit(1, "@everyone look"));
+        assert_eq!(
+            bob.errors(),
+            ["only moderators can mention @everyone or @here"]
+        );
+
+        moderator.frames();
+        moderator.post(&hub, DEFAULT_CHANNEL, "@everyone look");
+        moderator.send(&hub, edit(2, "@here look"));
+        assert!(moderator.errors().is_empty());
+        let edited: Vec<MessageData> = bob.payloads(MsgTypes::Edited);
+        assert_eq!(edited[0].message, "@here look");
+    }
+
+    #[test]
+    fn slow_mode_holds_back_members_but_not_moderators() {
+        let hub = moderated_hub();
+        let mut bob = TestClient::register(&hub, "bob");
+        let mut moderator = register_moderator(&hub);
+        bob.frames();
+
+        bob.send(
+            &hub,
+            moderate(ModerationAction::SlowMode {
+                channel: DEFAULT_CHANNEL.into(),
+                seconds: Some(30),
+            }),
+        );
+        assert_eq!(bob.errors(), ["only moderators can do that"]);
+
+        moderator.send(
+            &hub,
+            moderate(ModerationAction::SlowMode {
+                channel: DEFAULT_CHANNEL.into(),
+                seconds: Some(30),
+            }),
+        );
+        let channels: Vec<Vec<ChannelInfo>> = bob.payloads(MsgTypes::Channels);
+        assert_eq!(channels[0][0].slow_mode, Some(30));
+
+        bob.post(&hub, DEFAULT_CHANNEL, "first");
+        bob.post(&hub, DEFAULT_CHANNEL, "second");
+        bob.post(&hub, "random", "elsewhere");
+        assert_eq!(bob.errors(), ["slow mode: wait 30s to post again"]);
+
+        moderator.frames();
+        moderator.post(&hub, DEFAULT_CHANNEL, "first");
+        moderator.post(&hub, DEFAULT_CHANNEL, "second");
+        assert!(moderator.errors().is_empty());
+
+        moderator.send(
+            &hub,
+            moderate(ModerationAction::SlowMode {
+                channel: DEFAULT_CHANNEL.into(),
+                seconds: Some(0),
+            }),
+        );
+        assert_eq!(
+            moderator.errors(),
+            ["slow mode is between a second and an hour"]
+        );
+    }
+
+    #[test]
+    fn account_nicks_need_their_key() {
+        let hub = moderated_hub();
+        let mut impostor = TestClient::join(&hub);
+        impostor.send(&hub, WebSocketMessage::with_data(MsgTypes::Register, "mod"));
+        impostor.send(
+            &hub,
+            WebSocketMessage::with_data(MsgTypes::Spectate, "mod").with_key("guess"),
+        );
+        assert_eq!(
+            impostor.errors(),
+            [
+                "that name belongs to an account; sign in with its key",
+                "that name belongs to an account; sign in with its key"
+            ]
+        );
+
+        impostor.post(&hub, "announcements", "news");
+        assert!(impostor.frames().is_empty());
+    }
+
+    #[test]
+    fn kicked_and_banned_users_are_disconnected() {
+        let hub = moderated_hub();
+        let mut bob = TestClient::register(&hub, "bob");
+        let moderator = register_moderator(&hub);
+        bob.frames();
+
+        moderator.send(
+            &hub,
+            moderate(ModerationAction::Kick { user: "bob".into() }),
+        );
+        assert_eq!(bob.errors(), ["you were kicked by a moderator"]);
+        assert!(bob.rx.try_recv().is_err());
+
+        let mut bob = TestClient::register(&hub, "bob");
+        moderator.send(&hub, moderate(ModerationAction::Ban { user: "bob".into() }));
+        assert_eq!(bob.errors(), ["you were banned by a moderator"]);
+
+        let mut bob = TestClient::join(&hub);
+        bob.send(&hub, WebSocketMessage::with_data(MsgTypes::Register, "bob"));
+        assert_eq!(bob.errors(), ["you are banned from this server"]);
+    }
+
+    #[test]
+    fn renaming_a_channel_moves_its_messages() {
+        let hub = moderated_hub();
+        let mut bob = TestClient::register(&hub, "bob");
+        let mut moderator = register_moderator(&hub);
+        bob.post(&hub, "random", "hi");
+        bob.frames();
+
+        let rename = |name: &str| {
+            moderate(ModerationAction::Rename {
+                channel: "random".into(),
+                name: name.into(),
+            })
+        };
+        moderator.send(&hub, rename("Not A Name"));
+        moderator.send(&hub, rename(DEFAULT_CHANNEL));
+        assert_eq!(
+            moderator.errors(),
+            [
+                "channel names are lowercase letters, digits, - and _",
+                "a channel by that name already exists"
+            ]
+        );
+
+        moderator.send(&hub, rename("offtopic"));
+        let notices: Vec<SystemNotice> = bob.payloads(MsgTypes::System);
+        assert_eq!(
+            notices[0].event,
+            SystemEvent::Renamed {
+                from: "random".into(),
+                to: "offtopic".into()
+            }
+        );
+        let hub = hub.0.lock().unwrap();
+        assert!(hub.channels.iter().any(|c| c.name == "offtopic"));
+        assert_eq!(hub.history[0].channel, "offtopic");
+    }
+
+    #[test]
+    fn history_pages_back_and_stretch_to_a_wanted_message() {
+        let hub = hub();
+        let mut alice = TestClient::register(&hub, "alice");
+        for n in 1..=5 {
+            alice.post(&hub, DEFAULT_CHANNEL, &n.to_string());
+        }
+        alice.post(&hub, "random", "elsewhere");
+        alice.frames();
+
+        let page = |before, containing| {
+            let query = HistoryQuery {
+                channel: DEFAULT_CHANNEL.into(),
+                before,
+                containing,
+                limit: 2,
+            };
+            hub.0.lock().unwrap().history_page(&query)
+        };
+        let ids = |page: &HistoryPage| page.messages.iter().map(|m| m.id).collect::<Vec<_>>();
+
+        let newest = page(None, None);
+        assert_eq!((ids(&newest), newest.more), (vec![4, 5], true));
+        let older = page(Some(4), None);
+        assert_eq!((ids(&older), older.more), (vec![2, 3], true));
+        let oldest = page(Some(2), None);
+        assert_eq!((ids(&oldest), oldest.more), (vec![1], false));
+        let stretched = page(None, Some(2));
+        assert_eq!((ids(&stretched), stretched.more), (vec![2, 3, 4, 5], true));
+    }
+
+    #[test]
+    fn checklist_items_are_ticked_by_the_author_unless_opened_up() {
+        let hub = hub();
+        let mut alice = TestClient::register(&hub, "alice");
+        let mut bob = TestClient::register(&hub, "bob");
+        alice.post(&hub, DEFAULT_CHANNEL, "- [ ] milk\n- [ ] eggs");
+        alice.send(
+            &hub,
+            WebSocketMessage::with_data(MsgTypes::Message, "- [ ] chairs").with_open_tasks(),
+        );
+        alice.frames();
+        bob.frames();
+
+        let toggle = |message_id, index| {
+            let data = TaskToggle {
+                message_id,
+                index,
+                done: true,
+            };
+            WebSocketMessage::with_data(MsgTypes::ToggleTask, serde_json::to_string(&data).unwrap())
+        };
+        bob.send(&hub, toggle(1, 0));
+        assert_eq!(bob.errors(), ["only the author can tick this checklist"]);
+        alice.send(&hub, toggle(1, 5));
+        assert_eq!(alice.errors(), ["no such checklist item"]);
+
+        alice.send(&hub, toggle(1, 1));
+        bob.send(&hub, toggle(2, 0));
+        let edited: Vec<MessageData> = bob.payloads(MsgTypes::Edited);
+        assert_eq!(edited[0].message, "- [ ] milk\n- [x] eggs");
+        assert_eq!(edited[1].message, "- [x] chairs");
+    }
+
+    #[test]
+    fn typing_notices_are_throttled_per_typist_and_crowd() {
+        let hub = hub();
+        let mut hub = hub.0.lock().unwrap();
+        assert!(hub.note_typist(DEFAULT_CHANNEL, "alice", 10_000));
+        assert!(!hub.note_typist(DEFAULT_CHANNEL, "alice", 11_000));
+        assert!(hub.note_typist(DEFAULT_CHANNEL, "alice", 12_000));
+
+        for (n, name) in ["bob", "carol", "dave"].into_iter().enumerate() {
+            assert!(hub.note_typist(DEFAULT_CHANNEL, name, 12_000 + n as u64));
+        }
+        assert!(!hub.note_typist(DEFAULT_CHANNEL, "erin", 12_100));
+        assert!(hub.note_typist("random", "erin", 12_100));
+        // Once the others stop, there's room again.
+        assert!(hub.note_typist(DEFAULT_CHANNEL, "erin", 12_000 + TYPING_WINDOW_MS));
+    }
+}
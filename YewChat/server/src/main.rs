@@ -0,0 +1,105 @@
+mod hub;
+mod push;
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        State,
+    },
+    response::Response,
+    routing::get,
+    Router,
+};
+use futures::{SinkExt, StreamExt};
+use tokio::sync::mpsc;
+
+use crate::hub::{Accounts, SharedHub};
+
+#[tokio::main]
+async fn main() {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    let port: u16 = std::env::var("PORT")
+        .ok()
+        .and_then(|p| p.parse().ok())
+        .unwrap_or(8080);
+    let nicks = |var: &str| {
+        std::env::var(var)
+            .map(|v| v.split(',').map(|n| n.trim().to_string()).collect())
+            .unwrap_or_default()
+    };
+    let accounts = Accounts {
+        admins: nicks("YEWCHAT_ADMINS"),
+        moderators: nicks("YEWCHAT_MODERATORS"),
+        bots: nicks("YEWCHAT_BOTS"),
+        verified: nicks("YEWCHAT_VERIFIED"),
+        keys: std::env::var("YEWCHAT_ACCOUNT_KEYS")
+            .map(|v| {
+                v.split(',')
+                    .filter_map(|pair| pair.split_once(':'))
+                    .map(|(nick, key)| (nick.trim().to_string(), key.trim().to_string()))
+                    .collect()
+            })
+            .unwrap_or_default(),
+    };
+    // Web Push needs a relay holding the VAPID key; see `push.rs`.
+    let push_relay = std::env::var("YEWCHAT_PUSH_RELAY").ok();
+
+    let app = Router::new()
+        .route("/", get(upgrade))
+        .with_state(SharedHub::new(accounts, push_relay));
+
+    let listener = tokio::net::TcpListener::bind(("0.0.0.0", port))
+        .await
+        .expect("failed to bind port");
+    log::info!("Listening on port {}", port);
+    axum::serve(listener, app).await.unwrap();
+}
+
+async fn upgrade(ws: WebSocketUpgrade, State(hub): State<SharedHub>) -> Response {
+    ws.on_upgrade(move |socket| handle_socket(socket, hub))
+}
+
+async fn handle_socket(socket: WebSocket, hub: SharedHub) {
+    log::info!("ws connected");
+    let (mut write, mut read) = socket.split();
+    let (tx, mut rx) = mpsc::unbounded_channel::<String>();
+    let id = hub.join(tx);
+
+    let mut writer = tokio::spawn(async move {
+        while let Some(frame) = rx.recv().await {
+            if write.send(Message::Text(frame)).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    loop {
+        let msg = tokio::select! {
+            msg = read.next() => match msg {
+                Some(msg) => msg,
+                None => break,
+            },
+            // The hub dropped our sender, e.g. after a kick.
+            _ = &mut writer => break,
+        };
+        match msg {
+            Ok(Message::Text(data)) => hub.handle_frame(id, &data),
+            Ok(Message::Binary(b)) => {
+                if let Ok(data) = std::str::from_utf8(&b) {
+                    hub.handle_frame(id, data);
+                }
+            }
+            Ok(Message::Close(_)) => break,
+            Ok(_) => {}
+            Err(e) => {
+                log::error!("ws: {:?}", e);
+                break;
+            }
+        }
+    }
+
+    hub.leave(id);
+    writer.abort();
+    log::info!("ws disconnected");
+}
@@ -0,0 +1,77 @@
+//! Hands Web Push notifications to a relay that holds the VAPID private key and encrypts
+//! payloads, so this server needs no crypto of its own. The relay is sent
+//! `{"subscription": PushSubscription, "payload": "<JSON PushNotice>"}` and answers with the
+//! push service's status. No relay ships with this repository: one has to be run separately
+//! and named in `YEWCHAT_PUSH_RELAY`, or no pushes are sent.
+
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use yewchat_protocol::{PushNotice, PushSubscription};
+
+use crate::hub::SharedHub;
+
+/// Longest a push may take to hand over, connecting included, before it's given up on.
+const RELAY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Sends `notice` to `subscription` through the relay at `relay`, an `http://` URL, in the
+/// background. Subscriptions the push service says are gone are forgotten.
+pub fn deliver(hub: SharedHub, relay: String, subscription: PushSubscription, notice: PushNotice) {
+    tokio::spawn(async move {
+        let body = serde_json::json!({
+            "subscription": subscription,
+            "payload": serde_json::to_string(&notice).unwrap(),
+        })
+        .to_string();
+        let answer = tokio::time::timeout(RELAY_TIMEOUT, post(&relay, &body))
+            .await
+            .unwrap_or_else(|_| {
+                Err(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    "relay took too long to answer",
+                ))
+            });
+        match answer {
+            // The push service no longer knows the subscription.
+            Ok(404 | 410) => hub.forget_push(&subscription.endpoint),
+            Ok(status) if (200..300).contains(&status) => {}
+            Ok(status) => log::warn!("push relay answered {}", status),
+            Err(e) => log::warn!("push relay unreachable: {}", e),
+        }
+    });
+}
+
+/// POSTs JSON `body` to `url` and returns the response status.
+async fn post(url: &str, body: &str) -> std::io::Result<u16> {
+    let invalid = || std::io::Error::new(std::io::ErrorKind::InvalidInput, "relay must be http://");
+    let rest = url.strip_prefix("http://").ok_or_else(invalid)?;
+    let (authority, path) = match rest.find('/') {
+        Some(i) => rest.split_at(i),
+        None => (rest, "/"),
+    };
+    let address = if authority.contains(':') {
+        authority.to_string()
+    } else {
+        format!("{}:80", authority)
+    };
+
+    let mut stream = TcpStream::connect(address).await?;
+    let request = format!(
+        "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+        path,
+        authority,
+        body.len(),
+        body
+    );
+    stream.write_all(request.as_bytes()).await?;
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).await?;
+    // "HTTP/1.1 201 Created"
+    String::from_utf8_lossy(&response)
+        .split_whitespace()
+        .nth(1)
+        .and_then(|status| status.parse().ok())
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidData, "no status line"))
+}
@@ -0,0 +1,9 @@
+// Tabs can connect before the module has loaded; their ports are held for it.
+const early = [];
+const hold = (e) => early.push(e.ports[0]);
+self.addEventListener('connect', hold);
+
+import('./pkg').then((module) => {
+    self.removeEventListener('connect', hold);
+    module.run_shared_worker(early);
+});
@@ -0,0 +1,53 @@
+#![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
+
+use tauri::api::notification::Notification;
+use tauri::{AppHandle, CustomMenuItem, Manager, SystemTray, SystemTrayEvent, SystemTrayMenu};
+
+#[tauri::command]
+fn set_unread_badge(app: AppHandle, count: u32) {
+    let tooltip = if count == 0 {
+        "YewChat".to_string()
+    } else {
+        format!("YewChat ({} unread)", count)
+    };
+    if let Err(e) = app.tray_handle().set_tooltip(&tooltip) {
+        eprintln!("failed to update tray: {}", e);
+    }
+}
+
+#[tauri::command]
+fn notify(app: AppHandle, title: String, body: String) {
+    let identifier = app.config().tauri.bundle.identifier.clone();
+    if let Err(e) = Notification::new(identifier).title(title).body(body).show() {
+        eprintln!("failed to show notification: {}", e);
+    }
+}
+
+fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_window("main") {
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+fn main() {
+    let tray_menu = SystemTrayMenu::new()
+        .add_item(CustomMenuItem::new("show", "Show YewChat"))
+        .add_item(CustomMenuItem::new("quit", "Quit"));
+
+    tauri::Builder::default()
+        .plugin(tauri_plugin_window_state::Builder::default().build())
+        .system_tray(SystemTray::new().with_menu(tray_menu))
+        .on_system_tray_event(|app, event| match event {
+            SystemTrayEvent::LeftClick { .. } => show_main_window(app),
+            SystemTrayEvent::MenuItemClick { id, .. } => match id.as_str() {
+                "show" => show_main_window(app),
+                "quit" => app.exit(0),
+                _ => {}
+            },
+            _ => {}
+        })
+        .invoke_handler(tauri::generate_handler![set_unread_badge, notify])
+        .run(tauri::generate_context!())
+        .expect("error while running tauri application");
+}
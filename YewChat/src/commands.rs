@@ -0,0 +1,19 @@
+//! Slash commands typed into the composer. They run on this client and are never sent.
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Command {
+    /// Times a round trip to the server and reports it, with the reconnects so far, in the
+    /// current channel.
+    Ping,
+}
+
+impl Command {
+    /// The command `draft` invokes, if it is one. Anything else, slashes included, is sent
+    /// as a message.
+    pub fn parse(draft: &str) -> Option<Self> {
+        match draft.trim() {
+            "/ping" => Some(Command::Ping),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use yew::prelude::*;
+use yewchat_protocol::{Attachment, MessageData};
+
+use crate::mentions::mention_of;
+use crate::services::notifications;
+use crate::state::Announcements;
+use crate::store::use_store;
+
+/// Announcements kept in the live region. Older ones are dropped; they have been read by then.
+const KEPT: usize = 5;
+/// Longer messages are cut short, so one message doesn't hold up the rest.
+const MAX_SPOKEN_CHARS: usize = 200;
+
+/// What a screen reader says for `m`, e.g. "Alice said: hello". Messages outside the open
+/// channel say where they were posted.
+pub fn announcement(m: &MessageData, channel: &str) -> String {
+    let place = if m.channel == channel {
+        String::new()
+    } else {
+        format!(" in #{}", m.channel)
+    };
+    let text = m.message.trim();
+    let mut spoken: String = text.chars().take(MAX_SPOKEN_CHARS).collect();
+    if spoken.len() < text.len() {
+        spoken.push('…');
+    }
+    match (&m.attachment, spoken.is_empty()) {
+        (Some(attachment), true) => format!("{}{} sent {}", m.from, place, described(attachment)),
+        (Some(attachment), false) => format!(
+            "{}{} sent {}: {}",
+            m.from,
+            place,
+            described(attachment),
+            spoken
+        ),
+        (None, _) => format!("{}{} said: {}", m.from, place, spoken),
+    }
+}
+
+fn described(attachment: &Attachment) -> String {
+    match attachment {
+        // Reading out the description would give the spoiler away.
+        Attachment::Image { spoiler: true, .. } => "an image marked as a spoiler".into(),
+        Attachment::Image { alt: Some(alt), .. } => format!("an image, {}", alt),
+        Attachment::Image { .. } => "an image".into(),
+        Attachment::Snippet { .. } => "a code snippet".into(),
+        Attachment::Contact { name } => format!("{}'s contact card", name),
+        Attachment::Location { .. } => "a location".into(),
+        Attachment::Embed {
+            title: Some(title), ..
+        } => format!("a card, {}", title),
+        Attachment::Embed { .. } => "a card".into(),
+    }
+}
+
+/// Visually hidden live region that reads out messages from others as they arrive.
+#[function_component(Announcer)]
+pub fn announcer() -> Html {
+    let store = use_store();
+    // Ids only grow, so anything above the newest seen is new; history pages are older.
+    let newest = use_mut_ref(|| store.messages.last().map_or(0, |m| m.id));
+    let spoken = use_state(Vec::<(u64, String)>::new);
+
+    {
+        let store = store.clone();
+        let spoken = spoken.clone();
+        use_effect_with_deps(
+            move |_| {
+                let previous = *newest.borrow();
+                let arrived: Vec<_> = store
+                    .messages
+                    .iter()
+                    .rev()
+                    .take_while(|m| m.id > previous)
+                    .collect();
+                if let Some(last) = arrived.first() {
+                    *newest.borrow_mut() = last.id;
+                }
+                let setting = store.preferences.announcements;
+                let new: Vec<_> = arrived
+                    .iter()
+                    .rev()
+                    .filter(|m| m.from != store.me && !store.preferences.is_muted(&m.channel))
+                    .filter(|m| match setting {
+                        Announcements::All => true,
+                        Announcements::Mentions => mention_of(&m.message, &store.me, store.online)
+                            .is_some_and(|kind| notifications::pings(&store.preferences, kind)),
+                        Announcements::Off => false,
+                    })
+                    .map(|m| (m.id, announcement(m, &store.channel)))
+                    .collect();
+                if !new.is_empty() {
+                    let mut kept = (*spoken).clone();
+                    kept.extend(new);
+                    let excess = kept.len().saturating_sub(KEPT);
+                    kept.drain(..excess);
+                    spoken.set(kept);
+                }
+                || ()
+            },
+            store.messages.len(),
+        );
+    }
+
+    // A log only announces what is added to it, so earlier lines aren't read again.
+    html! {
+        <div data-testid="announcer" class="sr-only" role="log" aria-live="polite" aria-atomic="false">
+            { for spoken.iter().map(|(id, text)| html! { <div key={*id}>{text.clone()}</div> }) }
+        </div>
+    }
+}
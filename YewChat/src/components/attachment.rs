@@ -0,0 +1,403 @@
+use wasm_bindgen::JsCast;
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, HtmlImageElement};
+use yew::prelude::*;
+use yewchat_protocol::Attachment;
+
+use crate::components::avatar::Avatar;
+use crate::highlight::{self, Language, Token};
+use crate::motion::use_reduced_motion;
+use crate::services::image;
+use crate::services::location::{self, TILE_SIZE, ZOOM};
+use crate::state::{ChatAction, Presence};
+use crate::store::{use_data_saver, use_gif_autoplay, use_media_hidden, use_store};
+use crate::theme::{use_theme, ThemeClasses};
+
+/// Lines of a snippet shown before it is expanded.
+const COLLAPSED_LINES: usize = 8;
+/// Size of a location's map thumbnail in pixels.
+const MAP_WIDTH: f64 = 240.0;
+const MAP_HEIGHT: f64 = 144.0;
+
+#[derive(Properties, PartialEq)]
+pub struct AttachmentViewProps {
+    pub attachment: Attachment,
+    /// Smaller, for the one-line layout.
+    #[prop_or_default]
+    pub compact: bool,
+    /// Who sent it; their images may be held back by the media filter.
+    #[prop_or_default]
+    pub from: String,
+}
+
+/// Media posted with a message, below its text.
+#[function_component(AttachmentView)]
+pub fn attachment_view(props: &AttachmentViewProps) -> Html {
+    let classes = use_theme().classes();
+    let filtered = use_media_hidden(&props.from);
+    match &props.attachment {
+        Attachment::Image {
+            url,
+            width,
+            height,
+            alt,
+            spoiler,
+        } => {
+            let img = if image::is_animated(url) {
+                html! {
+                    <AnimatedImage url={url.clone()} width={Some(*width)} height={Some(*height)} alt={alt.clone()} compact={props.compact} />
+                }
+            } else {
+                let img = html! {
+                    <img data-testid="message-image" src={url.clone()} loading="lazy"
+                        alt={alt.clone().unwrap_or_else(|| "Image".into())} title={alt.clone()}
+                        width={width.to_string()} height={height.to_string()}
+                        class={image_class(props.compact)} />
+                };
+                if image::is_inline(url) {
+                    img
+                } else {
+                    html! { <ClickToLoad what="image">{img}</ClickToLoad> }
+                }
+            };
+            if filtered {
+                html! { <Reveal reason={filter_reason(&props.from)}>{img}</Reveal> }
+            } else if *spoiler {
+                html! { <Reveal reason="Spoiler">{img}</Reveal> }
+            } else {
+                img
+            }
+        }
+        Attachment::Snippet {
+            title,
+            language,
+            code,
+        } => html! {
+            <Snippet title={title.clone()} language={language.clone()} code={code.clone()} />
+        },
+        Attachment::Contact { name } => html! {
+            <ContactCard name={name.clone()} />
+        },
+        Attachment::Location {
+            latitude,
+            longitude,
+            accuracy,
+        } => html! {
+            <ClickToLoad what="map">
+                { location_map(*latitude, *longitude, *accuracy, classes) }
+            </ClickToLoad>
+        },
+        Attachment::Embed { title, fields } => html! {
+            <div data-testid="message-embed" class={classes!("mt-1", "max-w-sm", "rounded", "border", "border-l-4", "border-l-indigo-500", "p-2", "text-xs", classes.border, classes.panel_bg)}>
+                if let Some(title) = title {
+                    <div data-testid="embed-title" class="mb-1 font-bold">{title.clone()}</div>
+                }
+                <dl class="flex flex-wrap">
+                    { for fields.iter().map(|field| html! {
+                        <div data-testid="embed-field" class={classes!("mb-1", "pr-2", if field.inline { "w-1/2" } else { "w-full" })}>
+                            <dt class={classes!("font-bold", classes.muted_text)}>{field.name.clone()}</dt>
+                            <dd class="whitespace-pre-wrap break-words">{field.value.clone()}</dd>
+                        </div>
+                    }) }
+                </dl>
+            </div>
+        },
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct ClickToLoadProps {
+    /// What is held back, e.g. "GIF".
+    pub what: &'static str,
+    pub children: Children,
+}
+
+/// Shows its children straight away, or in data saver mode a button that does so once
+/// clicked, so nothing they link to is fetched until the user asks.
+#[function_component(ClickToLoad)]
+pub fn click_to_load(props: &ClickToLoadProps) -> Html {
+    let classes = use_theme().classes();
+    let data_saver = use_data_saver();
+    let loaded = use_state(|| false);
+    if !data_saver || *loaded {
+        return html! { <>{ for props.children.iter() }</> };
+    }
+    let onclick = Callback::from(move |_: MouseEvent| loaded.set(true));
+    html! {
+        <button data-testid="click-to-load" {onclick}
+            class={classes!("block", "mt-1", "px-3", "py-2", "rounded", "border", "border-dashed", "text-xs", classes.border, classes.muted_text, classes.hover_bg)}>
+            {format!("⬇ Load {}", props.what)}
+        </button>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct RevealProps {
+    /// Why it's hidden, e.g. "Spoiler"; shown on the overlay.
+    pub reason: String,
+    pub children: Children,
+}
+
+/// Shows its children blurred, out of reach of the pointer, keyboard and screen readers,
+/// behind a button that reveals them.
+#[function_component(Reveal)]
+pub fn reveal(props: &RevealProps) -> Html {
+    let revealed = use_state(|| false);
+    if *revealed {
+        return html! { <>{ for props.children.iter() }</> };
+    }
+    let onclick = Callback::from(move |_: MouseEvent| revealed.set(true));
+    html! {
+        <div data-testid="reveal" class="relative w-fit mt-1 overflow-hidden rounded">
+            <div aria-hidden="true" inert="" class="blur-xl pointer-events-none select-none">
+                { for props.children.iter() }
+            </div>
+            <button data-testid="reveal-button" {onclick} aria-label={format!("{}. Reveal image", props.reason)}
+                class="absolute inset-0 flex flex-col items-center justify-center bg-black/50 text-white text-xs">
+                <span class="font-bold">{props.reason.clone()}</span>
+                <span>{"Click to reveal"}</span>
+            </button>
+        </div>
+    }
+}
+
+/// What the media filter's warning says about media from `from`.
+pub fn filter_reason(from: &str) -> String {
+    format!("Hidden media from {}", from)
+}
+
+fn image_class(compact: bool) -> Classes {
+    classes!(
+        "block",
+        "h-auto",
+        "rounded",
+        "mt-1",
+        if compact {
+            "max-h-32 w-auto"
+        } else {
+            "max-w-full max-h-64 w-auto"
+        }
+    )
+}
+
+#[derive(Properties, PartialEq)]
+pub struct AnimatedImageProps {
+    pub url: String,
+    /// Size in pixels, if known; linked GIFs aren't measured until they load.
+    #[prop_or_default]
+    pub width: Option<u32>,
+    #[prop_or_default]
+    pub height: Option<u32>,
+    /// The sender's description; also shown on hover.
+    #[prop_or_default]
+    pub alt: Option<String>,
+    #[prop_or_default]
+    pub compact: bool,
+}
+
+/// A GIF with a play/pause button. Unless GIFs autoplay and motion is allowed it starts
+/// paused on its first frame, drawn onto a canvas, until the user plays it.
+#[function_component(AnimatedImage)]
+pub fn animated_image(props: &AnimatedImageProps) -> Html {
+    let reduced_motion = use_reduced_motion();
+    let autoplay = use_gif_autoplay() && !reduced_motion;
+    // Whether the user chose to play it, overriding the default.
+    let playing = use_state(|| None::<bool>);
+    let still = use_node_ref();
+    let paused = !playing.unwrap_or(autoplay);
+    let width = props.width.map(|w| w.to_string());
+    let height = props.height.map(|h| h.to_string());
+    let description = props.alt.as_deref().unwrap_or("Animated image");
+
+    let draw_still = {
+        let still = still.clone();
+        Callback::from(move |e: Event| {
+            let image: HtmlImageElement = e.target_unchecked_into();
+            let Some(canvas) = still.cast::<HtmlCanvasElement>() else {
+                return;
+            };
+            canvas.set_width(image.natural_width());
+            canvas.set_height(image.natural_height());
+            let context = canvas.get_context("2d").ok().flatten();
+            if let Some(context) = context.map(|c| c.unchecked_into::<CanvasRenderingContext2d>()) {
+                let (w, h) = (canvas.width() as f64, canvas.height() as f64);
+                let _ = context
+                    .draw_image_with_html_image_element_and_dw_and_dh(&image, 0.0, 0.0, w, h);
+            }
+        })
+    };
+    let toggle = {
+        let playing = playing.clone();
+        Callback::from(move |_: MouseEvent| playing.set(Some(paused)))
+    };
+
+    html! {
+        <div class="relative w-fit">
+            if paused {
+                <img src={props.url.clone()} alt="" class="hidden" onload={draw_still} />
+                <canvas ref={still} data-testid="message-image" role="img" aria-label={format!("{}, paused", description)}
+                    title={props.alt.clone()} width={width} height={height} class={image_class(props.compact)} />
+            } else {
+                <img data-testid="message-image" src={props.url.clone()} alt={description.to_string()} title={props.alt.clone()} loading="lazy"
+                    width={width} height={height} class={image_class(props.compact)} />
+            }
+            <button data-testid="gif-toggle" onclick={toggle}
+                aria-label={if paused { "Play animation" } else { "Pause animation" }}
+                class="absolute left-1 bottom-1 px-1.5 rounded bg-black/60 text-white text-xs font-bold">
+                { if paused { "▶ GIF" } else { "⏸" } }
+            </button>
+        </div>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct ContactCardProps {
+    pub name: String,
+}
+
+/// A shared profile, with a button to start talking to them. There are no direct messages, so
+/// that mentions them in the current channel.
+#[function_component(ContactCard)]
+pub fn contact_card(props: &ContactCardProps) -> Html {
+    let classes = use_theme().classes();
+    let store = use_store();
+    let presence = store
+        .presence
+        .get(&props.name)
+        .copied()
+        .unwrap_or(Presence::Offline);
+    let message = {
+        let store = store.clone();
+        let name = props.name.clone();
+        Callback::from(move |_: MouseEvent| store.dispatch(ChatAction::Mention(name.clone())))
+    };
+    html! {
+        <div data-testid="contact-card" class={classes!("flex", "items-center", "w-60", "mt-1", "p-2", "rounded", "border", classes.border)}>
+            <Avatar name={props.name.clone()} class="w-10 h-10" presence={Some(presence)} />
+            <div class="grow min-w-0 ml-2">
+                <div class="font-bold truncate">{props.name.clone()}</div>
+                <div class={classes!("text-xs", classes.muted_text)}>{presence.label()}</div>
+                if let Some(activity) = store.profile(&props.name).activity {
+                    <div data-testid="contact-activity" class={classes!("text-xs", "truncate", classes.muted_text)}>{activity}</div>
+                }
+            </div>
+            if store.online && props.name != store.me {
+                <button data-testid="contact-message" onclick={message}
+                    class={classes!("ml-2", "px-2", "py-1", "rounded", "text-xs", classes.button_bg)}>{"Message"}</button>
+            }
+        </div>
+    }
+}
+
+/// Map thumbnail centred on the point, linking to the full map. Made of the 3×3 tiles around
+/// the point so it stays covered wherever in its tile the point falls.
+fn location_map(
+    latitude: f64,
+    longitude: f64,
+    accuracy: Option<f64>,
+    classes: &ThemeClasses,
+) -> Html {
+    let (x, y) = location::tile_position(latitude, longitude, ZOOM);
+    let (column, row) = (x.floor(), y.floor());
+    let tiles = (-1..=1).flat_map(|dy| (-1..=1).map(move |dx| (dx, dy)));
+    let description = match accuracy {
+        Some(accuracy) => format!(
+            "{} (±{:.0} m)",
+            location::describe(latitude, longitude),
+            accuracy
+        ),
+        None => location::describe(latitude, longitude),
+    };
+    html! {
+        <a data-testid="location" href={location::map_link(latitude, longitude)} target="_blank" rel="noopener noreferrer"
+            class={classes!("block", "w-60", "mt-1", "rounded", "border", "overflow-hidden", "text-xs", classes.border)}>
+            <div class="relative overflow-hidden bg-gray-200" style={format!("width: {}px; height: {}px", MAP_WIDTH, MAP_HEIGHT)}>
+                { for tiles.filter_map(|(dx, dy)| {
+                    let url = location::tile_url(column as i64 + dx, row as i64 + dy, ZOOM)?;
+                    let left = (column + dx as f64 - x) * TILE_SIZE + MAP_WIDTH / 2.0;
+                    let top = (row + dy as f64 - y) * TILE_SIZE + MAP_HEIGHT / 2.0;
+                    Some(html! {
+                        <img src={url} alt="" loading="lazy" class="absolute max-w-none"
+                            style={format!("left: {:.0}px; top: {:.0}px; width: {}px; height: {}px", left, top, TILE_SIZE, TILE_SIZE)} />
+                    })
+                }) }
+                <span class="absolute text-2xl -translate-x-1/2 -translate-y-full" style="left: 50%; top: 50%" aria-hidden="true">{"📍"}</span>
+            </div>
+            <div class={classes!("flex", "justify-between", "px-2", "py-1", classes.muted_text)}>
+                <span>{description}</span>
+                <span>{"© OpenStreetMap"}</span>
+            </div>
+        </a>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct SnippetProps {
+    pub title: Option<String>,
+    pub language: Option<String>,
+    pub code: String,
+}
+
+/// Code block that starts folded to its first few lines.
+#[function_component(Snippet)]
+pub fn snippet(props: &SnippetProps) -> Html {
+    let classes = use_theme().classes();
+    let expanded = use_state(|| false);
+    let toggle = {
+        let expanded = expanded.clone();
+        Callback::from(move |_| expanded.set(!*expanded))
+    };
+
+    let line_count = props.code.lines().count();
+    let foldable = line_count > COLLAPSED_LINES;
+    let shown = if foldable && !*expanded {
+        let end = props
+            .code
+            .match_indices('\n')
+            .nth(COLLAPSED_LINES - 1)
+            .map_or(props.code.len(), |(i, _)| i);
+        &props.code[..end]
+    } else {
+        props.code.as_str()
+    };
+    let language = props.language.as_deref().and_then(Language::from_key);
+    let body = match language {
+        Some(language) => highlight::highlight(shown, language)
+            .into_iter()
+            .map(|(token, text)| match token_class(token) {
+                Some(class) => html! { <span {class}>{text}</span> },
+                None => html! { {text} },
+            })
+            .collect::<Html>(),
+        None => html! { {shown} },
+    };
+
+    html! {
+        <div data-testid="snippet" class={classes!("mt-1", "rounded", "border", "text-xs", classes.border)}>
+            <div class={classes!("flex", "items-center", "px-2", "py-1", "border-b", classes.border, classes.muted_text)}>
+                <span class="grow truncate font-bold">{props.title.clone().unwrap_or_else(|| "Snippet".into())}</span>
+                <span class="ml-2">{format!("{} · {} lines", language.map_or("Text", |l| l.label()), line_count)}</span>
+            </div>
+            <pre class={classes!("p-2", "overflow-x-auto", "font-mono", "whitespace-pre", classes.panel_bg)}><code>{body}</code></pre>
+            if foldable {
+                <button data-testid="snippet-toggle" onclick={toggle} aria-expanded={expanded.to_string()}
+                    class={classes!("w-full", "py-1", "border-t", classes.border, classes.muted_text, classes.hover_bg)}>
+                    if *expanded {
+                        {"Show less"}
+                    } else {
+                        {format!("Show all {} lines", line_count)}
+                    }
+                </button>
+            }
+        </div>
+    }
+}
+
+fn token_class(token: Token) -> Option<&'static str> {
+    match token {
+        Token::Plain => None,
+        Token::Keyword => Some("text-violet-500 font-semibold"),
+        Token::String => Some("text-emerald-600"),
+        Token::Comment => Some("italic opacity-70"),
+        Token::Number => Some("text-amber-600"),
+    }
+}
@@ -0,0 +1,106 @@
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+use yewchat_protocol::{AuditEntry, ModerationAction};
+
+use crate::components::modal::Modal;
+use crate::store::use_store;
+use crate::theme::use_theme;
+use crate::time::use_time_format;
+
+const KINDS: [&str; 8] = [
+    "delete", "pin", "unpin", "kick", "ban", "restyle", "slowmode", "rename",
+];
+
+#[derive(Properties, PartialEq)]
+pub struct AuditLogProps {
+    pub on_close: Callback<()>,
+}
+
+/// Moderation history for moderators, newest first, filterable by action and by name.
+#[function_component(AuditLog)]
+pub fn audit_log(props: &AuditLogProps) -> Html {
+    let store = use_store();
+    let classes = use_theme().classes();
+    let time_format = use_time_format();
+    let kind = use_state(String::new);
+    let query = use_state(String::new);
+
+    let on_kind = {
+        let kind = kind.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            kind.set(select.value());
+        })
+    };
+    let on_query = {
+        let query = query.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            query.set(input.value().to_lowercase());
+        })
+    };
+    let matches = |entry: &&AuditEntry| {
+        (kind.is_empty() || entry.action.kind() == kind.as_str())
+            && (query.is_empty()
+                || entry.moderator.to_lowercase().contains(query.as_str())
+                || target(&entry.action).to_lowercase().contains(query.as_str()))
+    };
+
+    html! {
+        <Modal title="Audit log" testid="audit-log" width={classes!("w-96")} on_close={props.on_close.clone()}>
+            <div class="flex mb-2">
+                <select data-testid="audit-kind" onchange={on_kind} class={classes!("mr-2", "border", "rounded", classes.border, classes.input_bg)}>
+                    <option value="" selected={kind.is_empty()}>{"All actions"}</option>
+                    { for KINDS.iter().map(|k| html! { <option value={*k} selected={kind.as_str() == *k}>{*k}</option> }) }
+                </select>
+                <input data-testid="audit-query" type="text" placeholder="Filter by name" oninput={on_query}
+                    class={classes!("grow", "min-w-0", "px-1", "border", "rounded", classes.border, classes.input_bg)} />
+            </div>
+            <div class="max-h-64 overflow-auto text-xs">
+                {
+                    store.audit.iter().rev().filter(matches).map(|entry| html! {
+                        <div data-testid="audit-entry" class="py-0.5">
+                            <span class={classes!("mr-2", classes.muted_text)}>{time_format.time(entry.time)}</span>
+                            {format!("{} {} {}", entry.moderator, past_tense(&entry.action), target(&entry.action))}
+                        </div>
+                    }).collect::<Html>()
+                }
+                if store.audit.is_empty() {
+                    <div class={classes!(classes.muted_text)}>{"Nothing yet."}</div>
+                }
+            </div>
+        </Modal>
+    }
+}
+
+fn past_tense(action: &ModerationAction) -> &'static str {
+    match action {
+        ModerationAction::Delete { .. } => "deleted",
+        ModerationAction::Pin { .. } => "pinned",
+        ModerationAction::Unpin { .. } => "unpinned",
+        ModerationAction::Kick { .. } => "kicked",
+        ModerationAction::Ban { .. } => "banned",
+        ModerationAction::Restyle { .. } => "restyled",
+        ModerationAction::SlowMode {
+            seconds: Some(_), ..
+        } => "slowed down",
+        ModerationAction::SlowMode { seconds: None, .. } => "lifted slow mode in",
+        ModerationAction::Rename { .. } => "renamed",
+    }
+}
+
+fn target(action: &ModerationAction) -> String {
+    match action {
+        ModerationAction::Delete { message_id }
+        | ModerationAction::Pin { message_id }
+        | ModerationAction::Unpin { message_id } => format!("message #{}", message_id),
+        ModerationAction::Kick { user } | ModerationAction::Ban { user } => user.clone(),
+        ModerationAction::Restyle { channel, .. } => format!("#{}", channel),
+        ModerationAction::SlowMode {
+            channel,
+            seconds: Some(seconds),
+        } => format!("#{} to a message every {}s", channel, seconds),
+        ModerationAction::SlowMode { channel, .. } => format!("#{}", channel),
+        ModerationAction::Rename { channel, name } => format!("#{} to #{}", channel, name),
+    }
+}
@@ -0,0 +1,39 @@
+use yew::prelude::*;
+
+use crate::services::avatar::use_avatar;
+use crate::state::Presence;
+
+#[derive(Properties, PartialEq)]
+pub struct AvatarProps {
+    pub name: String,
+    #[prop_or_default]
+    pub class: Classes,
+    /// Shown as a dot on the corner; `None` leaves the picture bare.
+    #[prop_or_default]
+    pub presence: Option<Presence>,
+}
+
+#[function_component(Avatar)]
+pub fn avatar(props: &AvatarProps) -> Html {
+    let src = use_avatar(&props.name);
+
+    let presence = match props.presence {
+        Some(presence) => presence,
+        None => return html! { <img class={props.class.clone()} {src} alt="avatar"/> },
+    };
+    let color = match presence {
+        Presence::Online => "bg-green-500",
+        Presence::DoNotDisturb => "bg-red-500",
+        Presence::Spectator => "bg-sky-400",
+        Presence::Offline => "bg-gray-400",
+    };
+    let label = presence.label();
+
+    html! {
+        <span class={classes!("relative", "inline-block", "shrink-0", props.class.clone())}>
+            <img class="w-full h-full rounded-full" {src} alt="avatar"/>
+            <span data-testid="presence-dot" data-presence={format!("{:?}", presence).to_lowercase()} title={label} aria-label={label}
+                class={classes!("absolute", "bottom-0", "right-0", "w-1/3", "h-1/3", "rounded-full", "ring-2", "ring-white", color)} />
+        </span>
+    }
+}
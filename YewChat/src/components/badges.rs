@@ -0,0 +1,36 @@
+use yew::prelude::*;
+use yewchat_protocol::Role;
+
+use crate::state::UserProfile;
+
+#[derive(Properties, PartialEq)]
+pub struct BadgesProps {
+    pub user: UserProfile,
+}
+
+/// Marks after a name for the user's role and the flags the server gives them: a crown for
+/// admins, a shield for moderators, a tag for bots and a check for verified accounts.
+#[function_component(Badges)]
+pub fn badges(props: &BadgesProps) -> Html {
+    let user = &props.user;
+    let role = match user.role {
+        Role::Admin => Some(("badge-admin", "👑", "Admin")),
+        Role::Moderator => Some(("badge-moderator", "🛡️", "Moderator")),
+        Role::Member => None,
+    };
+
+    html! {
+        <>
+            if let Some((testid, icon, label)) = role {
+                <span data-testid={testid} title={label} aria-label={label} class="ml-1 text-xs">{icon}</span>
+            }
+            if user.bot {
+                <span data-testid="badge-bot" title="Bot"
+                    class="ml-1 px-1 rounded bg-indigo-600 text-white text-[10px] font-bold align-middle">{"BOT"}</span>
+            }
+            if user.verified {
+                <span data-testid="badge-verified" title="Verified" aria-label="Verified" class="ml-1 text-xs text-sky-500">{"✔"}</span>
+            }
+        </>
+    }
+}
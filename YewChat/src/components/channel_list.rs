@@ -0,0 +1,339 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yewchat_protocol::{
+    is_channel_name, ChannelInfo, ModerationAction, DEFAULT_CHANNEL, MAX_CHANNEL_NAME_CHARS,
+};
+
+use crate::components::confirm::{use_confirm, Confirm, ConfirmRequest};
+use crate::state::{ChatAction, Confirmation, Unread};
+use crate::store::{use_store, Store};
+use crate::theme::{use_theme, ThemeClasses};
+
+const HOUR_MS: u64 = 60 * 60 * 1000;
+
+/// Offered in each channel's menu; `None` mutes until turned back on.
+const MUTE_OPTIONS: [(&str, Option<u64>); 3] = [
+    ("Mute for 1 hour", Some(HOUR_MS)),
+    ("Mute for 8 hours", Some(8 * HOUR_MS)),
+    ("Mute until I turn it back on", None),
+];
+
+/// Accents moderators can pick for a channel, as name and color.
+const ACCENTS: [(&str, &str); 6] = [
+    ("Blue", "#3b82f6"),
+    ("Green", "#16a34a"),
+    ("Amber", "#d97706"),
+    ("Red", "#dc2626"),
+    ("Purple", "#9333ea"),
+    ("Pink", "#db2777"),
+];
+
+/// Icons moderators can pick for a channel.
+const ICONS: [&str; 6] = ["💬", "🎓", "💻", "📚", "🎉", "☕"];
+
+/// Slow mode settings moderators can pick, as label and seconds between messages.
+const SLOW_MODES: [(&str, Option<u32>); 5] = [
+    ("Off", None),
+    ("10s", Some(10)),
+    ("30s", Some(30)),
+    ("1m", Some(60)),
+    ("5m", Some(5 * 60)),
+];
+
+#[derive(Properties, PartialEq)]
+pub struct ChannelListProps {
+    pub channels: Rc<Vec<ChannelInfo>>,
+    pub current: String,
+    pub unread: HashMap<String, Unread>,
+    pub on_select: Callback<String>,
+    /// Asks the server for an invite link to the named channel.
+    pub on_invite: Callback<String>,
+    /// Restyles or renames a channel; only offered to moderators.
+    pub on_moderate: Callback<ModerationAction>,
+}
+
+#[function_component(ChannelList)]
+pub fn channel_list(props: &ChannelListProps) -> Html {
+    let classes = use_theme().classes();
+    let store = use_store();
+    let confirm = use_confirm();
+    let menu = use_state(|| None::<String>);
+    let is_moderator = store.profile(&store.me).role.moderates();
+
+    html! {
+        <div data-testid="channel-list" class="px-3 pb-2">
+            {
+                props.channels.iter().map(|c| {
+                    let onclick = {
+                        let on_select = props.on_select.clone();
+                        let name = c.name.clone();
+                        Callback::from(move |_| on_select.emit(name.clone()))
+                    };
+                    let toggle_menu = {
+                        let menu = menu.clone();
+                        let name = c.name.clone();
+                        Callback::from(move |_| {
+                            let open = menu.as_deref() == Some(name.as_str());
+                            menu.set((!open).then(|| name.clone()));
+                        })
+                    };
+                    let active = c.name == props.current;
+                    let muted = store.preferences.is_muted(&c.name);
+                    let unread = props.unread.get(&c.name).copied().unwrap_or_default();
+                    html! {
+                        <div class="relative flex items-center">
+                            <button
+                                data-testid="channel-item"
+                                {onclick}
+                                class={classes!("flex", "items-center", "grow", "text-left", "text-sm", "px-2", "py-1", "rounded",
+                                    if active { classes.item_bg } else { classes.muted_text }, muted.then_some("opacity-60"))}
+                            >
+                                {format!("{} {}", c.icon.as_deref().unwrap_or("#"), c.name)}
+                                if c.announcement {
+                                    <span title="Announcements" class="ml-1">{"📢"}</span>
+                                }
+                                if muted {
+                                    <span data-testid="channel-muted" title="Muted" class="ml-1">{"🔕"}</span>
+                                }
+                                <span class="grow" />
+                                if unread.mentions > 0 {
+                                    <span data-testid="mention-badge" title="Mentions" class="ml-1 px-1.5 rounded-full text-xs text-white bg-red-600">
+                                        {format!("@{}", unread.mentions)}
+                                    </span>
+                                }
+                                if unread.messages > 0 {
+                                    <span data-testid="unread-badge" title="Unread messages" class="ml-1 px-1.5 rounded-full text-xs text-white bg-gray-500"
+                                        style={c.accent.as_ref().map(|accent| format!("background-color: {}", accent))}>
+                                        {unread.messages}
+                                    </span>
+                                }
+                            </button>
+                            <button data-testid="channel-menu-toggle" title="Channel options" onclick={toggle_menu}
+                                class={classes!("ml-1", "px-1", "text-sm", "rounded", classes.muted_text, classes.hover_bg)}>
+                                {"⋯"}
+                            </button>
+                            if menu.as_deref() == Some(c.name.as_str()) {
+                                <div data-testid="channel-menu" class={classes!("absolute", "right-0", "top-full", "w-56", "py-1", "rounded-lg", "shadow-lg", "border", "text-sm", "z-20", classes.popover_bg)}>
+                                    { channel_menu(&c.name, muted, &store, &confirm, &menu, &props.on_invite, classes) }
+                                    if is_moderator {
+                                        { restyle_menu(c, &menu, &props.on_moderate, classes) }
+                                        { slow_mode_menu(c, &menu, &props.on_moderate, classes) }
+                                        if c.name != DEFAULT_CHANNEL {
+                                            { rename_menu(c, &menu, &props.on_moderate, classes) }
+                                        }
+                                    }
+                                </div>
+                            }
+                        </div>
+                    }
+                }).collect::<Html>()
+            }
+        </div>
+    }
+}
+
+fn channel_menu(
+    channel: &str,
+    muted: bool,
+    store: &Store,
+    confirm: &Confirm,
+    menu: &UseStateHandle<Option<String>>,
+    on_invite: &Callback<String>,
+    classes: &ThemeClasses,
+) -> Html {
+    // `until` is worked out on click, not when the menu renders.
+    let action = |duration: Option<Option<u64>>| {
+        let store = store.clone();
+        let menu = menu.clone();
+        let channel = channel.to_string();
+        Callback::from(move |_: MouseEvent| {
+            store.dispatch(match duration {
+                Some(duration) => ChatAction::Mute {
+                    channel: channel.clone(),
+                    until: duration.map(|d| js_sys::Date::now() as u64 + d),
+                },
+                None => ChatAction::Unmute(channel.clone()),
+            });
+            menu.set(None);
+        })
+    };
+    let invite = {
+        let menu = menu.clone();
+        let on_invite = on_invite.clone();
+        let channel = channel.to_string();
+        Callback::from(move |_: MouseEvent| {
+            on_invite.emit(channel.clone());
+            menu.set(None);
+        })
+    };
+    let clear_history = {
+        let store = store.clone();
+        let confirm = confirm.clone();
+        let menu = menu.clone();
+        let channel = channel.to_string();
+        Callback::from(move |_: MouseEvent| {
+            menu.set(None);
+            let store = store.clone();
+            let cleared = channel.clone();
+            confirm.ask(ConfirmRequest {
+                kind: Confirmation::ClearHistory,
+                title: "Clear local history?".into(),
+                message: format!(
+                    "Messages loaded for #{} are removed from this window. They stay on the \
+                     server and come back when you reload.",
+                    channel
+                ),
+                action: "Clear",
+                on_confirm: Callback::from(move |_| {
+                    store.dispatch(ChatAction::ClearHistory(cleared.clone()))
+                }),
+            });
+        })
+    };
+    let item = |label: &'static str, onclick: Callback<MouseEvent>| {
+        html! {
+            <button data-testid="channel-menu-item" {onclick} class={classes!("block", "w-full", "text-left", "px-3", "py-1", classes.hover_bg)}>
+                {label}
+            </button>
+        }
+    };
+    html! {
+        <>
+            { item("Invite people", invite) }
+            if muted {
+                { item("Unmute", action(None)) }
+            } else {
+                { for MUTE_OPTIONS.iter().map(|&(label, duration)| item(label, action(Some(duration)))) }
+            }
+            { item("Clear local history", clear_history) }
+        </>
+    }
+}
+
+/// The moderators' part of a channel's menu: picks its accent and icon.
+fn restyle_menu(
+    channel: &ChannelInfo,
+    menu: &UseStateHandle<Option<String>>,
+    on_moderate: &Callback<ModerationAction>,
+    classes: &ThemeClasses,
+) -> Html {
+    let restyle = |accent: Option<&str>, icon: Option<&str>| {
+        let menu = menu.clone();
+        let action = ModerationAction::Restyle {
+            channel: channel.name.clone(),
+            accent: accent.map(String::from),
+            icon: icon.map(String::from),
+        };
+        on_moderate.reform(move |_: MouseEvent| {
+            menu.set(None);
+            action.clone()
+        })
+    };
+    let (accent, icon) = (channel.accent.as_deref(), channel.icon.as_deref());
+    html! {
+        <div data-testid="channel-restyle" class={classes!("mt-1", "px-3", "pt-2", "border-t", classes.border)}>
+            <div class="flex justify-between mb-1">
+                { for ACCENTS.iter().map(|&(name, color)| html! {
+                    <button data-testid="channel-accent" title={name} aria-label={format!("{} accent", name)} aria-pressed={(accent == Some(color)).to_string()}
+                        onclick={restyle(Some(color), icon)} style={format!("background-color: {}", color)}
+                        class={classes!("w-6", "h-6", "rounded-full", (accent == Some(color)).then_some("ring-2 ring-offset-1 ring-current"))} />
+                }) }
+            </div>
+            <div class="flex justify-between mb-1">
+                { for ICONS.iter().map(|&choice| html! {
+                    <button data-testid="channel-icon" aria-label={format!("{} icon", choice)} aria-pressed={(icon == Some(choice)).to_string()}
+                        onclick={restyle(accent, Some(choice))}
+                        class={classes!("w-6", "h-6", "rounded", classes.hover_bg, (icon == Some(choice)).then_some(classes.item_bg))}>{choice}</button>
+                }) }
+            </div>
+            if accent.is_some() || icon.is_some() {
+                <button data-testid="channel-restyle-reset" onclick={restyle(None, None)}
+                    class={classes!("block", "w-full", "text-left", "px-1", "py-1", "rounded", classes.hover_bg)}>{"Reset look"}</button>
+            }
+        </div>
+    }
+}
+
+/// The moderators' slow mode picker in a channel's menu.
+fn slow_mode_menu(
+    channel: &ChannelInfo,
+    menu: &UseStateHandle<Option<String>>,
+    on_moderate: &Callback<ModerationAction>,
+    classes: &ThemeClasses,
+) -> Html {
+    html! {
+        <div data-testid="channel-slow-mode" class={classes!("mt-1", "px-3", "pt-2", "border-t", classes.border)}>
+            <div class={classes!("mb-1", "text-xs", classes.muted_text)}>{"Slow mode"}</div>
+            <div class="flex justify-between mb-1">
+                { for SLOW_MODES.iter().map(|&(label, seconds)| {
+                    let menu = menu.clone();
+                    let action = ModerationAction::SlowMode {
+                        channel: channel.name.clone(),
+                        seconds,
+                    };
+                    let onclick = on_moderate.reform(move |_: MouseEvent| {
+                        menu.set(None);
+                        action.clone()
+                    });
+                    let chosen = channel.slow_mode == seconds;
+                    html! {
+                        <button data-testid="slow-mode-option" aria-pressed={chosen.to_string()} {onclick}
+                            class={classes!("px-1", "rounded", "text-xs", classes.hover_bg, chosen.then_some(classes.item_bg))}>{label}</button>
+                    }
+                }) }
+            </div>
+        </div>
+    }
+}
+
+/// The moderators' rename field in a channel's menu. The default channel can't be renamed.
+fn rename_menu(
+    channel: &ChannelInfo,
+    menu: &UseStateHandle<Option<String>>,
+    on_moderate: &Callback<ModerationAction>,
+    classes: &ThemeClasses,
+) -> Html {
+    let input = NodeRef::default();
+    let onsubmit = {
+        let input = input.clone();
+        let menu = menu.clone();
+        let on_moderate = on_moderate.clone();
+        let channel = channel.name.clone();
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            let Some(input) = input.cast::<HtmlInputElement>() else {
+                return;
+            };
+            let name = input.value().trim().to_lowercase();
+            if name == channel {
+                return;
+            }
+            if !is_channel_name(&name) {
+                input.set_custom_validity("Use lowercase letters, digits, - and _");
+                input.report_validity();
+                return;
+            }
+            menu.set(None);
+            on_moderate.emit(ModerationAction::Rename {
+                channel: channel.clone(),
+                name,
+            });
+        })
+    };
+    // Clears the complaint once the name is edited.
+    let oninput = Callback::from(|e: InputEvent| {
+        let input: HtmlInputElement = e.target_unchecked_into();
+        input.set_custom_validity("");
+    });
+    html! {
+        <form data-testid="channel-rename" {onsubmit} class={classes!("flex", "mt-1", "px-3", "pt-2", "pb-1", "border-t", classes.border)}>
+            <input data-testid="channel-rename-input" ref={input} {oninput} type="text" value={channel.name.clone()} aria-label="Channel name"
+                maxlength={MAX_CHANNEL_NAME_CHARS.to_string()}
+                class={classes!("grow", "min-w-0", "px-1", "mr-1", "text-xs", "border", "rounded", classes.border, classes.input_bg)} />
+            <button type="submit" class={classes!("px-2", "rounded", "text-xs", classes.button_bg)}>{"Rename"}</button>
+        </form>
+    }
+}
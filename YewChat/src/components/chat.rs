@@ -1,299 +1,918 @@
-use serde::{Deserialize, Serialize};
-use web_sys::HtmlInputElement;
-use yew::prelude::*;
-use yew_agent::{Bridge, Bridged};
+use std::rc::Rc;
 
-use crate::{User, services::websocket::WebsocketService};
-use crate::services::event_bus::EventBus;
+use gloo::timers::callback::Interval;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::File;
+use yew::prelude::*;
+use yew_router::prelude::*;
+use yewchat_protocol::{
+    Attachment, BroadcastMention, EditData, HistoryQuery, MentionSuppression, MessageData,
+    ModerationAction, MsgTypes, ReactionData, Status, Stroke, TaskToggle, WebSocketMessage,
+};
 
-#[derive(Clone, PartialEq, Debug)]
-pub enum Theme {
-    Light,
-    Dark,
-}
+use crate::commands::Command;
+use crate::components::composer::Composer;
+use crate::components::confirm::{use_confirm, ConfirmRequest};
+use crate::components::connection_quality::ConnectionQuality;
+use crate::components::invite_panel::InvitePanel;
+use crate::components::message_actions::MessageAction;
+use crate::components::message_list::MessageList;
+use crate::components::onboarding::OnboardingTour;
+use crate::components::outbox::Outbox;
+use crate::components::typing_indicator::TypingIndicator;
+use crate::components::user_sidebar::UserSidebar;
+use crate::components::whiteboard::Whiteboard;
+use crate::services::socket::{SocketError, SocketHandle};
+use crate::services::telemetry::{self, Counter};
+use crate::services::websocket::WebsocketService;
+use crate::config;
+use crate::flags::{use_flag, Flag};
+use crate::services::clipboard;
+use crate::services::history;
+use crate::services::image;
+use crate::services::location;
+use crate::services::query;
+use crate::services::connection_worker::WorkerSocket;
+use crate::services::shared_worker::SharedSocket;
+use crate::services::background_sync::{self, FromWorker};
+use crate::services::device;
+use crate::services::push;
+use crate::services::event_bus::ConnectionState;
+use crate::state::{ChatAction, Confirmation, Outgoing, SendError};
+use crate::store::{use_store, Store};
+use crate::theme::use_theme;
+use crate::{Route, User};
 
-pub enum Msg {
-    HandleMsg(String),
-    SubmitMessage,
-    ToggleEmojiPicker,
-    SelectEmoji(String),
-    ToggleTheme, // New message for toggling theme
-}
+/// How many messages around a linked one are fetched along with it.
+const DEEP_LINK_CONTEXT: usize = 50;
+/// How often the connection is timed while it is up.
+const HEARTBEAT_MS: u32 = 5_000;
+/// How many older messages each pull-to-refresh loads.
+const HISTORY_PAGE: usize = 50;
+/// How often a typing notice is repeated while the user keeps typing; well inside
+/// `TYPING_TIMEOUT_MS`, so the indicator doesn't flicker for others.
+const TYPING_REPEAT_MS: u64 = 3_000;
 
-#[derive(Deserialize)]
-struct MessageData {
-    from: String,
-    message: String,
+#[derive(Properties, PartialEq)]
+pub struct ChatProps {
+    /// Channel to open instead of the last one used.
+    #[prop_or_default]
+    pub channel: Option<String>,
+    /// Message to load, scroll to and highlight once connected.
+    #[prop_or_default]
+    pub focus: Option<u64>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
-#[serde(rename_all = "lowercase")]
-pub enum MsgTypes {
-    Users,
-    Register,
-    Message,
-}
-
-#[derive(Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct WebSocketMessage {
-    message_type: MsgTypes,
-    data_array: Option<Vec<String>>,
-    data: Option<String>,
-}
+#[function_component(Chat)]
+pub fn chat(props: &ChatProps) -> Html {
+    let user = use_context::<User>().expect("context to be set");
+    let spectator = *user.spectator.borrow();
+    let username = user.username.borrow().clone();
+    let injected = use_context::<SocketHandle>();
+    let in_worker = use_flag(Flag::WorkerConnection);
+    // Tests and previews provide their own socket; otherwise connect for real.
+    let socket = {
+        let username = username.clone();
+        use_state(move || {
+            injected.unwrap_or_else(|| {
+                if in_worker {
+                    // One connection for all of the user's tabs where the browser allows it.
+                    SharedSocket::new(&username, spectator)
+                        .map(SocketHandle::new)
+                        .unwrap_or_else(|| SocketHandle::new(WorkerSocket::new()))
+                } else {
+                    SocketHandle::new(WebsocketService::new())
+                }
+            })
+        })
+    };
+    let render_started = telemetry::now();
+    let state = use_store();
+    let theme = use_theme();
+    let classes = theme.classes();
+    let focus = use_state(|| props.focus);
+    let show_whiteboard = use_state(|| false);
+    let confirm = use_confirm();
 
-#[derive(Clone)]
-struct UserProfile {
-    name: String,
-    avatar: String,
-}
+    {
+        let socket = (*socket).clone();
+        let state = state.clone();
+        let channel = props.channel.clone();
+        let focus = props.focus;
+        use_effect_with_deps(
+            move |_| {
+                if let Some(channel) = &channel {
+                    state.dispatch(ChatAction::SwitchChannel(channel.clone()));
+                }
+                let username = user.username.borrow().clone();
+                state.dispatch(ChatAction::Identify {
+                    name: username.clone(),
+                    online: !*user.spectator.borrow(),
+                });
+                let message_type = if *user.spectator.borrow() {
+                    MsgTypes::Spectate
+                } else {
+                    MsgTypes::Register
+                };
+                let mut message = WebSocketMessage::with_data(message_type, username);
+                if let Some(key) = user.key.borrow().clone() {
+                    message = message.with_key(key);
+                }
+                if socket.send(&message).is_ok() {
+                    log::debug!("message sent successfully");
+                }
+                let about = WebSocketMessage::with_data(MsgTypes::Device, device::current());
+                let _ = socket.send(&about);
+                if let Some(token) = user.invite.borrow_mut().take() {
+                    let _ = socket.send(&WebSocketMessage::with_data(MsgTypes::Join, token));
+                }
+                if let Some(id) = focus {
+                    let query = HistoryQuery {
+                        channel: channel.unwrap_or_else(|| state.channel.clone()),
+                        before: None,
+                        containing: Some(id),
+                        limit: DEEP_LINK_CONTEXT,
+                    };
+                    state.dispatch(ChatAction::RequestHistory(query.channel.clone()));
+                    if history::request(&socket, &query).is_err() {
+                        state.dispatch(ChatAction::HistoryFailed(query.channel));
+                    }
+                }
+                || ()
+            },
+            (),
+        );
+    }
 
-pub struct Chat {
-    users: Vec<UserProfile>,
-    chat_input: NodeRef,
-    wss: WebsocketService,
-    messages: Vec<MessageData>,
-    _producer: Box<dyn Bridge<EventBus>>,
-    show_emoji_picker: bool,
-    current_theme: Theme, // New state field for current theme
-}
+    {
+        let socket = (*socket).clone();
+        use_effect_with_deps(
+            move |&dnd| {
+                if !spectator {
+                    let status = if dnd { Status::Dnd } else { Status::Online };
+                    let message = WebSocketMessage::with_data(
+                        MsgTypes::Status,
+                        serde_json::to_string(&status).unwrap(),
+                    );
+                    if let Err(e) = socket.send(&message) {
+                        log::debug!("error sending to channel: {:?}", e);
+                    }
+                }
+                || ()
+            },
+            state.preferences.dnd,
+        );
+    }
 
-impl Component for Chat {
-    type Message = Msg;
-    type Properties = ();
+    {
+        let socket = (*socket).clone();
+        use_effect_with_deps(
+            move |activity: &String| {
+                if !spectator {
+                    let message = WebSocketMessage::with_data(MsgTypes::Activity, activity.trim());
+                    if let Err(e) = socket.send(&message) {
+                        log::debug!("error sending to channel: {:?}", e);
+                    }
+                }
+                || ()
+            },
+            state.preferences.activity.clone(),
+        );
+    }
 
-    fn create(ctx: &Context<Self>) -> Self {
-        let (user, _) = ctx
-            .link()
-            .context::<User>(Callback::noop())
-            .expect("context to be set");
-        let wss = WebsocketService::new();
-        let username = user.username.borrow().clone();
+    {
+        let socket = (*socket).clone();
+        use_effect_with_deps(
+            move |suppression: &MentionSuppression| {
+                if !spectator {
+                    let message = WebSocketMessage::with_data(
+                        MsgTypes::SuppressMentions,
+                        serde_json::to_string(suppression).unwrap(),
+                    );
+                    if let Err(e) = socket.send(&message) {
+                        log::debug!("error sending to channel: {:?}", e);
+                    }
+                }
+                || ()
+            },
+            state.preferences.suppress_mentions,
+        );
+    }
 
-        let message = WebSocketMessage {
-            message_type: MsgTypes::Register,
-            data: Some(username.to_string()),
-            data_array: None,
-        };
+    // Fetched on every open, since strokes drawn while the board was closed aren't kept.
+    {
+        let socket = (*socket).clone();
+        use_effect_with_deps(
+            move |(open, channel)| {
+                if *open {
+                    let message = WebSocketMessage::with_data(MsgTypes::Whiteboard, channel);
+                    if let Err(e) = socket.send(&message) {
+                        log::debug!("error sending to channel: {:?}", e);
+                    }
+                }
+                || ()
+            },
+            (*show_whiteboard, state.channel.clone()),
+        );
+    }
 
-        if let Ok(_) = wss
-            .tx
-            .clone()
-            .try_send(serde_json::to_string(&message).unwrap())
-        {
-            log::debug!("message sent successfully");
-        }
+    // Times the round trip as soon as the connection comes up, then every few seconds.
+    {
+        let socket = (*socket).clone();
+        use_effect_with_deps(
+            move |&connection| {
+                let heartbeat = (connection == ConnectionState::Connected).then(|| {
+                    let send = move || {
+                        if let Err(e) = socket.send(&ping(js_sys::Date::now() as u64)) {
+                            log::debug!("error sending heartbeat: {:?}", e);
+                        }
+                    };
+                    send();
+                    Interval::new(HEARTBEAT_MS, send)
+                });
+                move || drop(heartbeat)
+            },
+            state.connection,
+        );
+    }
 
-        Self {
-            users: vec![],
-            messages: vec![],
-            chat_input: NodeRef::default(),
-            wss,
-            _producer: EventBus::bridge(ctx.link().callback(Msg::HandleMsg)),
-            show_emoji_picker: false,
-            current_theme: Theme::Light, // Initialize with Light theme
-        }
+    // Re-sent on every connect, since the server only keeps subscriptions in memory.
+    {
+        let socket = (*socket).clone();
+        let state = state.clone();
+        use_effect_with_deps(
+            move |&(wanted, connected)| {
+                if connected && !spectator {
+                    spawn_local(async move {
+                        if !wanted {
+                            if let Some(endpoint) = push::unsubscribe().await {
+                                let frame = WebSocketMessage::with_data(
+                                    MsgTypes::PushUnsubscribe,
+                                    endpoint,
+                                );
+                                let _ = socket.send(&frame);
+                            }
+                            return;
+                        }
+                        match push::subscribe().await {
+                            Ok(subscription) => {
+                                let frame = WebSocketMessage::with_data(
+                                    MsgTypes::PushSubscribe,
+                                    subscription,
+                                );
+                                let _ = socket.send(&frame);
+                            }
+                            Err(e) => {
+                                state.dispatch(ChatAction::DisablePush);
+                                state.dispatch(ChatAction::ShowNotice(format!(
+                                    "Couldn't turn on notifications: {}",
+                                    e
+                                )));
+                            }
+                        }
+                    });
+                }
+                || ()
+            },
+            (
+                state.preferences.push,
+                state.connection == ConnectionState::Connected,
+            ),
+        );
     }
-    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
-        match msg {
-            Msg::HandleMsg(s) => {
-                let msg: WebSocketMessage = serde_json::from_str(&s).unwrap();
-                match msg.message_type {
-                    MsgTypes::Users => {
-                        let users_from_message = msg.data_array.unwrap_or_default();
-                        self.users = users_from_message
-                            .iter()
-                            .map(|u| UserProfile {
-                                name: u.into(),
-                                avatar: format!(
-                                    "https://avatars.dicebear.com/api/adventurer-neutral/{}.svg",
-                                    u
-                                )
-                                .into(),
-                            })
-                            .collect();
-                        return true;
-                    }
-                    MsgTypes::Message => {
-                        let message_data: MessageData =
-                            serde_json::from_str(&msg.data.unwrap()).unwrap();
-                        self.messages.push(message_data);
-                        return true;
+
+    let typing_sent_at = use_mut_ref(|| 0u64);
+    let on_input = {
+        let state = state.clone();
+        let socket = (*socket).clone();
+        Callback::from(move |value: String| {
+            let now = js_sys::Date::now() as u64;
+            let mut sent_at = typing_sent_at.borrow_mut();
+            if !value.trim().is_empty() && now.saturating_sub(*sent_at) >= TYPING_REPEAT_MS {
+                *sent_at = now;
+                let _ = socket.send(&WebSocketMessage::with_data(
+                    MsgTypes::Typing,
+                    state.channel.clone(),
+                ));
+            }
+            state.dispatch(ChatAction::UpdateDraft(value))
+        })
+    };
+    // Sends are queued until the server echoes them, so failures can be retried.
+    let send_outgoing = {
+        let state = state.clone();
+        let socket = (*socket).clone();
+        Callback::from(move |outgoing: Outgoing| {
+            let (id, frame) = (outgoing.id, outgoing.frame());
+            state.dispatch(ChatAction::Queue(outgoing));
+            match socket.send(&frame) {
+                Ok(()) => telemetry::record(Counter::MessagesSent),
+                Err(e) => {
+                    if matches!(e, SocketError::Full) {
+                        state.dispatch(ChatAction::HoldSends(js_sys::Date::now() as u64));
                     }
-                    _ => {
-                        return false;
+                    state.dispatch(ChatAction::SendFailed {
+                        id,
+                        reason: e.to_string(),
+                    });
+                }
+            }
+        })
+    };
+    let on_retry = {
+        let state = state.clone();
+        let socket = (*socket).clone();
+        Callback::from(move |id: u64| {
+            let Some(outgoing) = state.outbox.iter().find(|o| o.id == id) else {
+                return;
+            };
+            let frame = outgoing.frame();
+            let now = js_sys::Date::now() as u64;
+            state.dispatch(ChatAction::Retry { id, now });
+            if let Err(e) = socket.send(&frame) {
+                state.dispatch(ChatAction::SendFailed {
+                    id,
+                    reason: e.to_string(),
+                });
+            }
+        })
+    };
+    let on_discard = {
+        let state = state.clone();
+        Callback::from(move |id: u64| state.dispatch(ChatAction::Discard(id)))
+    };
+
+    // The service worker sends what failed once the network is back. While a tab is open it
+    // leaves that to the tab; after all tabs were closed it sends them itself and reports
+    // which got through.
+    let flushed_at = use_state(|| None::<u64>);
+    {
+        let on_discard = on_discard.clone();
+        let flushed_at = flushed_at.clone();
+        use_effect_with_deps(
+            move |_| {
+                let listener = background_sync::listen(move |message| match message {
+                    FromWorker::Delivered { ids } => {
+                        ids.into_iter().for_each(|id| on_discard.emit(id))
                     }
+                    FromWorker::Flush => flushed_at.set(Some(js_sys::Date::now() as u64)),
+                });
+                background_sync::ask_delivered();
+                move || drop(listener)
+            },
+            (),
+        );
+    }
+    {
+        let on_retry = on_retry.clone();
+        let outbox = state.outbox.clone();
+        use_effect_with_deps(
+            move |flushed_at| {
+                if flushed_at.is_some() {
+                    outbox
+                        .iter()
+                        .filter(|o| matches!(o.error, Some(SendError::Network(_))))
+                        .for_each(|o| on_retry.emit(o.id));
                 }
+                || ()
+            },
+            *flushed_at,
+        );
+    }
+    let on_submit = {
+        let state = state.clone();
+        let socket = (*socket).clone();
+        let send_outgoing = send_outgoing.clone();
+        let username = username.clone();
+        let confirm = confirm.clone();
+        Callback::from(move |_| {
+            if state.draft.is_empty() && state.attachment.is_none() {
+                return;
             }
-            Msg::SubmitMessage => {
-                let input = self.chat_input.cast::<HtmlInputElement>();
-                if let Some(input) = input {
-                    if !input.value().is_empty() {
-                        let message = WebSocketMessage {
-                            message_type: MsgTypes::Message,
-                            data: Some(input.value()),
-                            data_array: None,
-                        };
-                        if let Err(e) = self
-                            .wss
-                            .tx
-                            .clone()
-                            .try_send(serde_json::to_string(&message).unwrap())
-                        {
-                            log::debug!("error sending to channel: {:?}", e);
-                        }
-                        input.set_value("");
+            if let Some(command) = Command::parse(&state.draft) {
+                run_command(command, &state, &socket);
+                state.dispatch(ChatAction::ClearDraft);
+                return;
+            }
+            let max_length = config::get().max_message_length;
+            if state.draft.chars().count() > max_length {
+                state.dispatch(ChatAction::ShowNotice(format!(
+                    "Messages can be at most {} characters long.",
+                    max_length
+                )));
+                return;
+            }
+            let now = js_sys::Date::now() as u64;
+            if state.send_cooldown().is_some_and(|c| c.until > now) {
+                return;
+            }
+            if BroadcastMention::find(&state.draft).is_some()
+                && !state.profile(&username).role.moderates()
+            {
+                state.dispatch(ChatAction::ShowNotice(
+                    "Only moderators can mention @everyone or @here.".into(),
+                ));
+                return;
+            }
+            let outgoing = Outgoing {
+                message: state.draft.clone(),
+                reply_to: state.reply_to,
+                attachment: state.attachment.clone(),
+                tasks_open: state.preferences.open_checklists,
+                ..Outgoing::new(state.channel.clone())
+            };
+            let send = {
+                let state = state.clone();
+                let send_outgoing = send_outgoing.clone();
+                Callback::from(move |_| {
+                    send_outgoing.emit(outgoing.clone());
+                    state.dispatch(ChatAction::ClearDraft);
+                })
+            };
+            match BroadcastMention::find(&state.draft) {
+                Some(mention) => confirm.ask(confirm_broadcast(mention, &state.channel, send)),
+                None => send.emit(()),
+            }
+        })
+    };
+    let on_react = {
+        let socket = (*socket).clone();
+        Callback::from(move |(message_id, emoji): (u64, String)| {
+            let data = ReactionData { message_id, emoji };
+            let message =
+                WebSocketMessage::with_data(MsgTypes::React, serde_json::to_string(&data).unwrap());
+            if let Err(e) = socket.send(&message) {
+                log::debug!("error sending to channel: {:?}", e);
+            }
+        })
+    };
+    let on_edit = {
+        let socket = (*socket).clone();
+        Callback::from(move |(message_id, message): (u64, String)| {
+            let data = EditData { message_id, message };
+            let message =
+                WebSocketMessage::with_data(MsgTypes::Edit, serde_json::to_string(&data).unwrap());
+            if let Err(e) = socket.send(&message) {
+                log::debug!("error sending to channel: {:?}", e);
+            }
+        })
+    };
+    let on_toggle_task = {
+        let socket = (*socket).clone();
+        Callback::from(move |(message_id, index, done): (u64, usize, bool)| {
+            let data = TaskToggle {
+                message_id,
+                index,
+                done,
+            };
+            let message = WebSocketMessage::with_data(
+                MsgTypes::ToggleTask,
+                serde_json::to_string(&data).unwrap(),
+            );
+            if let Err(e) = socket.send(&message) {
+                log::debug!("error sending to channel: {:?}", e);
+            }
+        })
+    };
+    let on_action = {
+        let socket = (*socket).clone();
+        let state = state.clone();
+        let confirm = confirm.clone();
+        let on_react = on_react.clone();
+        Callback::from(move |(message, action): (Rc<MessageData>, MessageAction)| {
+            let id = message.id;
+            let frame = match action {
+                MessageAction::React(emoji) => return on_react.emit((id, emoji)),
+                MessageAction::Reply => return state.dispatch(ChatAction::ReplyTo(id)),
+                MessageAction::Copy => return clipboard::write_text(&message.message),
+                MessageAction::CopyLink => {
+                    return clipboard::write_text(&query::message_link(&message.channel, id))
+                }
+                MessageAction::CopyId => return clipboard::write_text(&id.to_string()),
+                MessageAction::Pin => moderate(ModerationAction::Pin { message_id: id }),
+                MessageAction::Unpin => moderate(ModerationAction::Unpin { message_id: id }),
+                MessageAction::Delete => {
+                    let socket = socket.clone();
+                    return confirm.ask(ConfirmRequest {
+                        kind: Confirmation::DeleteMessage,
+                        title: "Delete message?".into(),
+                        message: format!(
+                            "This message from {} will be deleted for everyone.",
+                            message.from
+                        ),
+                        action: "Delete",
+                        on_confirm: Callback::from(move |_| {
+                            let frame = moderate(ModerationAction::Delete { message_id: id });
+                            if let Err(e) = socket.send(&frame) {
+                                log::debug!("error sending to channel: {:?}", e);
+                            }
+                        }),
+                    });
+                }
+            };
+            if let Err(e) = socket.send(&frame) {
+                log::debug!("error sending to channel: {:?}", e);
+            }
+        })
+    };
+    let on_moderate = {
+        let socket = (*socket).clone();
+        let confirm = confirm.clone();
+        Callback::from(move |action: ModerationAction| {
+            let send = {
+                let socket = socket.clone();
+                let action = action.clone();
+                Callback::from(move |_| {
+                    if let Err(e) = socket.send(&moderate(action.clone())) {
+                        log::debug!("error sending to channel: {:?}", e);
                     }
-                };
-                false
+                })
+            };
+            match action {
+                ModerationAction::Kick { user } => confirm.ask(ConfirmRequest {
+                    kind: Confirmation::Kick,
+                    title: format!("Kick {}?", user),
+                    message: format!("{} will be disconnected, but can come straight back.", user),
+                    action: "Kick",
+                    on_confirm: send,
+                }),
+                ModerationAction::Ban { user } => confirm.ask(ConfirmRequest {
+                    kind: Confirmation::Ban,
+                    title: format!("Ban {}?", user),
+                    message: format!("{} will be disconnected and kept off the server.", user),
+                    action: "Ban",
+                    on_confirm: send,
+                }),
+                _ => send.emit(()),
             }
-            Msg::ToggleEmojiPicker => {
-                self.show_emoji_picker = !self.show_emoji_picker;
-                true
+        })
+    };
+    let on_load_older = {
+        let socket = (*socket).clone();
+        let state = state.clone();
+        Callback::from(move |_| {
+            let channel = state.channel.clone();
+            if state.loading_history.contains(&channel)
+                || state.history_exhausted.contains(&channel)
+            {
+                return;
             }
-            Msg::SelectEmoji(emoji) => {
-                if let Some(input) = self.chat_input.cast::<HtmlInputElement>() {
-                    let current_value = input.value();
-                    input.set_value(&format!("{}{}", current_value, emoji));
+            let query = HistoryQuery {
+                before: state.oldest_message(&channel),
+                channel,
+                containing: None,
+                limit: HISTORY_PAGE,
+            };
+            state.dispatch(ChatAction::RequestHistory(query.channel.clone()));
+            if let Err(e) = history::request(&socket, &query) {
+                log::debug!("error requesting history: {:?}", e);
+                state.dispatch(ChatAction::HistoryFailed(query.channel));
+            }
+        })
+    };
+    let on_cancel_reply = {
+        let state = state.clone();
+        Callback::from(move |_| state.dispatch(ChatAction::CancelReply))
+    };
+    let on_attach = {
+        let state = state.clone();
+        Callback::from(move |file: File| {
+            let config = config::get();
+            if let Err(e) = image::validate(&file.type_(), file.size() as u64, &config) {
+                return state.dispatch(ChatAction::ShowNotice(e));
+            }
+            let state = state.clone();
+            let quality = state.preferences.image_quality;
+            spawn_local(async move {
+                match image::prepare(&file, quality, config.max_upload_bytes).await {
+                    Ok(attachment) => state.dispatch(ChatAction::Attach(attachment)),
+                    Err(e) => state.dispatch(ChatAction::ShowNotice(e)),
+                }
+            });
+        })
+    };
+    // Attached rather than sent, so nothing is shared until the user sends the draft.
+    let on_share_location = {
+        let state = state.clone();
+        Callback::from(move |_| {
+            let state = state.clone();
+            spawn_local(async move {
+                match location::current().await {
+                    Ok(attachment) => state.dispatch(ChatAction::Attach(attachment)),
+                    Err(e) => state.dispatch(ChatAction::ShowNotice(e)),
                 }
-                self.show_emoji_picker = false;
-                true
+            });
+        })
+    };
+    let on_snippet = {
+        let state = state.clone();
+        Callback::from(move |snippet| state.dispatch(ChatAction::Attach(snippet)))
+    };
+    let send_attachment = {
+        let state = state.clone();
+        Callback::from(move |attachment: Attachment| {
+            send_outgoing.emit(Outgoing {
+                attachment: Some(attachment),
+                ..Outgoing::new(state.channel.clone())
+            })
+        })
+    };
+    let toggle_whiteboard = {
+        let show_whiteboard = show_whiteboard.clone();
+        Callback::from(move |_| show_whiteboard.set(!*show_whiteboard))
+    };
+    let on_stroke = {
+        let socket = (*socket).clone();
+        Callback::from(move |stroke: Stroke| {
+            let message = WebSocketMessage::with_data(
+                MsgTypes::Stroke,
+                serde_json::to_string(&stroke).unwrap(),
+            );
+            if let Err(e) = socket.send(&message) {
+                log::debug!("error sending to channel: {:?}", e);
             }
-            Msg::ToggleTheme => {
-                self.current_theme = match self.current_theme {
-                    Theme::Light => Theme::Dark,
-                    Theme::Dark => Theme::Light,
-                };
-                true // Re-render is needed
+        })
+    };
+    let on_clear_whiteboard = {
+        let state = state.clone();
+        let socket = (*socket).clone();
+        let confirm = confirm.clone();
+        Callback::from(move |_| {
+            let message =
+                WebSocketMessage::with_data(MsgTypes::ClearWhiteboard, state.channel.clone());
+            let socket = socket.clone();
+            confirm.ask(ConfirmRequest {
+                kind: Confirmation::ClearWhiteboard,
+                title: "Clear the whiteboard?".into(),
+                message: format!(
+                    "Everything drawn in #{} will be erased for everyone.",
+                    state.channel
+                ),
+                action: "Clear",
+                on_confirm: Callback::from(move |_| {
+                    if let Err(e) = socket.send(&message) {
+                        log::debug!("error sending to channel: {:?}", e);
+                    }
+                }),
+            });
+        })
+    };
+    let on_post_whiteboard = {
+        let show_whiteboard = show_whiteboard.clone();
+        let send_attachment = send_attachment.clone();
+        Callback::from(move |snapshot| {
+            send_attachment.emit(snapshot);
+            show_whiteboard.set(false);
+        })
+    };
+    let close_whiteboard = {
+        let show_whiteboard = show_whiteboard.clone();
+        Callback::from(move |_| show_whiteboard.set(false))
+    };
+    let on_remove_attachment = {
+        let state = state.clone();
+        Callback::from(move |_| state.dispatch(ChatAction::RemoveAttachment))
+    };
+    let on_describe_attachment = {
+        let state = state.clone();
+        Callback::from(move |text: String| {
+            let Some(mut attachment) = state.attachment.clone() else {
+                return;
+            };
+            if let Attachment::Image { alt, .. } = &mut attachment {
+                *alt = Some(text).filter(|t| !t.is_empty());
+                state.dispatch(ChatAction::Attach(attachment));
             }
-        }
+        })
+    };
+    let on_toggle_spoiler = {
+        let state = state.clone();
+        Callback::from(move |_| {
+            let Some(mut attachment) = state.attachment.clone() else {
+                return;
+            };
+            if let Attachment::Image { spoiler, .. } = &mut attachment {
+                *spoiler = !*spoiler;
+                state.dispatch(ChatAction::Attach(attachment));
+            }
+        })
+    };
+    let on_invite = {
+        let socket = (*socket).clone();
+        Callback::from(move |channel: String| {
+            let message = WebSocketMessage::with_data(MsgTypes::Invite, channel);
+            if let Err(e) = socket.send(&message) {
+                log::debug!("error sending to channel: {:?}", e);
+            }
+        })
+    };
+    let on_list_sessions = {
+        let socket = (*socket).clone();
+        Callback::from(move |_| {
+            if let Err(e) = socket.send(&WebSocketMessage::new(MsgTypes::Sessions)) {
+                log::debug!("error sending to channel: {:?}", e);
+            }
+        })
+    };
+    let on_sign_out = {
+        let socket = (*socket).clone();
+        Callback::from(move |session: String| {
+            let message = WebSocketMessage::with_data(MsgTypes::SignOut, session);
+            if let Err(e) = socket.send(&message) {
+                log::debug!("error sending to channel: {:?}", e);
+            }
+        })
+    };
+    let close_invite = {
+        let state = state.clone();
+        Callback::from(move |_| state.dispatch(ChatAction::DismissInvite))
+    };
+    let on_focused = {
+        let focus = focus.clone();
+        Callback::from(move |_| focus.set(None))
+    };
+    let on_select_channel = {
+        let state = state.clone();
+        Callback::from(move |name: String| state.dispatch(ChatAction::SwitchChannel(name)))
+    };
+    let on_toggle_emoji_picker = {
+        let state = state.clone();
+        Callback::from(move |_| state.dispatch(ChatAction::ToggleEmojiPicker))
+    };
+    let on_select_emoji = {
+        let state = state.clone();
+        Callback::from(move |emoji: String| state.dispatch(ChatAction::SelectEmoji(emoji)))
+    };
+
+    {
+        let total = state.total_unread();
+        use_effect_with_deps(
+            |&(messages, mentions)| {
+                set_title(messages, mentions);
+                || set_title(0, 0)
+            },
+            (total.messages, total.mentions),
+        );
     }
-    fn view(&self, ctx: &Context<Self>) -> Html {
-        let submit = ctx.link().callback(|_| Msg::SubmitMessage);
-        let toggle_emoji_picker = ctx.link().callback(|_| Msg::ToggleEmojiPicker);
-        let toggle_theme = ctx.link().callback(|_| Msg::ToggleTheme);
-        
-        // Common emoji set
-        let emojis = vec![
-            "😀", "😂", "😍", "🥳", "😎", "🤔", "👍", "❤️", 
-            "🔥", "✨", "🎉", "👋", "🙏", "🤗", "😊", "🥰"
-        ];
 
-        // Define base theme classes
-        let (main_bg_class, main_text_class, base_border_class) = match self.current_theme {
-            Theme::Light => ("bg-white", "text-gray-800", "border-gray-300"),
-            Theme::Dark => ("bg-gray-800", "text-gray-100", "border-gray-600"),
+    use_effect(move || {
+        telemetry::record_render(telemetry::now() - render_started);
+        || ()
+    });
+
+    if state.signed_out {
+        return html! {
+            <div data-testid="signed-out" class={classes!("flex", "flex-col", "w-screen", "h-screen", "items-center", "justify-center", classes.main_bg, classes.main_text)}>
+                <p class="mb-4">{"You were signed out from another session."}</p>
+                <Link<Route> to={Route::Login} classes={classes!("px-4", "py-2", "rounded", "text-white", "bg-violet-600")}>{"Sign in again"}</Link<Route>>
+            </div>
         };
+    }
 
-        // Specific themed classes (some might reuse base_border_class or define their own)
-        let panel_bg_color = if self.current_theme == Theme::Light { "bg-gray-100" } else { "bg-gray-700" };
-        let item_bg_color = if self.current_theme == Theme::Light { "bg-white" } else { "bg-gray-600" };
-        let input_bg_color = if self.current_theme == Theme::Light { "bg-gray-100" } else { "bg-gray-700" };
-        let input_text_color = if self.current_theme == Theme::Light { "focus:text-gray-700" } else { "text-gray-100 placeholder-gray-400 focus:text-gray-100" };
-        let emoji_button_bg = if self.current_theme == Theme::Light { "bg-gray-200" } else { "bg-gray-600 hover:bg-gray-500" };
-        let emoji_picker_bg = if self.current_theme == Theme::Light { "bg-white border-gray-300" } else { "bg-gray-700 border-gray-600" }; // Uses its own border or could use base_border_class
-        let emoji_picker_item_hover_bg = if self.current_theme == Theme::Light { "hover:bg-gray-100" } else { "hover:bg-gray-600" };
-        // Use base_border_class for consistent border colors where needed, or define specific ones
-        let border_color_class = base_border_class; 
-        
-        html! {
-            <div class={classes!("flex", "w-screen", main_bg_class, main_text_class)}>
-                <div class={classes!("flex-none", "w-56", "h-screen", panel_bg_color)}>
-                    <div class={classes!("text-xl", "p-3", main_text_class)}>
-                        {"Users"}
-                        <button onclick={toggle_theme.clone()} class={classes!("ml-4", "p-1", "text-sm", "border", border_color_class, "rounded")}>
-                            { if self.current_theme == Theme::Light { "Dark Mode" } else { "Light Mode" } }
-                        </button>
-                    </div>
-                    {
-                        self.users.clone().iter().map(|u| {
-                            html!{
-                                <div class={classes!("flex", "m-3", item_bg_color, "rounded-lg", "p-2")}>
-                                    <div>
-                                        <img class="w-12 h-12 rounded-full" src={u.avatar.clone()} alt="avatar"/>
-                                    </div>
-                                    <div class="flex-grow p-3">
-                                        <div class={classes!("flex", "text-xs", "justify-between", if self.current_theme == Theme::Dark { "text-gray-100"} else {main_text_class})}>
-                                            <div>{u.name.clone()}</div>
-                                        </div>
-                                        <div class={classes!("text-xs", if self.current_theme == Theme::Dark { "text-gray-300"} else {"text-gray-400"})}>
-                                            {"Hi there!"}
-                                        </div>
-                                    </div>
-                                </div>
-                            }
-                        }).collect::<Html>()
+    let current = state.current_channel();
+    let accent = current.and_then(|c| c.accent.as_deref());
+    let icon = current.and_then(|c| c.icon.as_deref());
+    let slow_mode = current.and_then(|c| c.slow_mode);
+    let wallpaper = state
+        .preferences
+        .wallpaper
+        .style(&theme, state.preferences.wallpaper_dim);
+    html! {
+        <div class={classes!("flex", "w-screen", classes.main_bg, classes.main_text)}>
+            <UserSidebar
+                users={state.users.clone()}
+                spectators={state.spectators.clone()}
+                channels={state.channels.clone()}
+                channel={state.channel.clone()}
+                unread={state.unread.clone()}
+                {on_select_channel}
+                {on_invite}
+                {on_moderate}
+                {on_list_sessions}
+                {on_sign_out}
+                loading={!state.users_loaded}
+            />
+            <div class="grow h-screen flex flex-col relative">
+                if let Some(invite) = &state.invite {
+                    <InvitePanel invite={invite.clone()} on_close={close_invite} />
+                }
+                if *show_whiteboard {
+                    <Whiteboard channel={state.channel.clone()} strokes={state.whiteboards.get(&state.channel).cloned()} readonly={spectator}
+                        {on_stroke} on_clear={on_clear_whiteboard} on_post={on_post_whiteboard} on_close={close_whiteboard} />
+                }
+                <div data-testid="channel-header" class={classes!("w-full", "h-14", "flex", "items-center", "border-b-2", classes.border)}
+                    style={accent.map(|accent| format!("border-bottom-color: {0}; background-color: {0}1a", accent))}>
+                    <div class={classes!("grow", "text-xl", "p-3", classes.main_text)}>{format!("{} #{}", icon.unwrap_or("💬"), state.channel)}</div>
+                    if let Some(seconds) = slow_mode {
+                        <span data-testid="slow-mode-indicator" title={format!("Slow mode: one message every {}s", seconds)}
+                            class={classes!("mr-3", "text-xs", classes.muted_text)}>{format!("🐢 {}s", seconds)}</span>
                     }
+                    <ConnectionQuality connection={state.connection} latency={state.latency} />
+                    <button data-testid="whiteboard-toggle" title="Whiteboard" onclick={toggle_whiteboard}
+                        class={classes!("mr-3", "px-2", "py-1", "rounded", classes.hover_bg)}>{"🖍️"}</button>
                 </div>
-                <div class="grow h-screen flex flex-col">
-                    <div class={classes!("w-full", "h-14", "border-b-2", border_color_class)}>
-                        <div class={classes!("text-xl", "p-3", main_text_class)}>{"💬 Chat!"}</div>
+                <MessageList messages={state.messages.clone()} system={state.system.clone()} profiles={state.profiles.clone()} presence={state.presence.clone()} reactions={state.reactions.clone()} pinned={state.pinned.clone()} {on_react} {on_edit} display_mode={state.preferences.display_mode} bubble_style={state.preferences.bubble_style} suppress_mentions={state.preferences.suppress_mentions} channel={state.channel.clone()} focus={*focus} {on_focused} {on_action}
+                    loading={!state.users_loaded}
+                    loading_older={state.loading_history.contains(&state.channel)}
+                    history_failed={state.history_failed.contains(&state.channel)}
+                    has_older={!state.history_exhausted.contains(&state.channel)}
+                    {on_load_older} {on_toggle_task}
+                    divider={state.divider} {wallpaper} />
+                <Outbox outbox={state.outbox.clone()} channel={state.channel.clone()} {on_retry} {on_discard} />
+                <TypingIndicator names={state.typing_in(&state.channel)} />
+                if let Some(notice) = &state.notice {
+                    <div data-testid="server-notice" class="w-full px-3 py-1 text-sm text-red-600">{notice.clone()}</div>
+                }
+                if spectator {
+                    <div data-testid="spectator-notice" class={classes!("w-full", "h-14", "flex", "items-center", "justify-center", "text-sm", classes.muted_text)}>
+                        {"👀 You're watching as a spectator and can't post."}
                     </div>
-                    <div class={classes!("w-full", "grow", "overflow-auto", "border-b-2", border_color_class)}>
-                        {
-                            self.messages.iter().map(|m| {
-                                let user = self.users.iter().find(|u| u.name == m.from).unwrap();
-                                let message_bubble_bg = if self.current_theme == Theme::Light { "bg-gray-100" } else { "bg-gray-700" };
-                                html!{
-                                    <div class={classes!("flex", "items-end", "w-3/6", message_bubble_bg, "m-8", "rounded-tl-lg", "rounded-tr-lg", "rounded-br-lg")}>
-                                        <img class="w-8 h-8 rounded-full m-3" src={user.avatar.clone()} alt="avatar"/>
-                                        <div class="p-3">
-                                            <div class={classes!("text-sm", if self.current_theme == Theme::Dark { "text-gray-100"} else {main_text_class})}>
-                                                {m.from.clone()}
-                                            </div>
-                                            <div class={classes!("text-xs", if self.current_theme == Theme::Dark { "text-gray-300"} else {"text-gray-500"})}>
-                                                if m.message.ends_with(".gif") {
-                                                    <img class="mt-3" src={m.message.clone()}/>
-                                                } else {
-                                                    {m.message.clone()}
-                                                }
-                                            </div>
-                                        </div>
-                                    </div>
-                                }
-                            }).collect::<Html>()
-                        }
-
+                } else if !state.can_post(&username) {
+                    <div data-testid="read-only-notice" class={classes!("w-full", "h-14", "flex", "items-center", "justify-center", "text-sm", classes.muted_text)}>
+                        {"📢 This channel is read only. Only moderators can post here."}
                     </div>
-                    <div class="w-full h-14 flex px-3 items-center relative">
-                        <input ref={self.chat_input.clone()} type="text" placeholder="Message" class={classes!("block", "w-full", "py-2", "pl-4", "mx-3", input_bg_color, "rounded-full", "outline-none", input_text_color, border_color_class, "border")} name="message" required=true />
-                        
-                        <button onclick={toggle_emoji_picker} class={classes!("p-2", "mr-2", "shadow-sm", emoji_button_bg, "w-10", "h-10", "rounded-full", "flex", "justify-center", "items-center", if self.current_theme == Theme::Dark { "text-gray-100" } else { main_text_class } )}>
-                            {"😊"}
-                        </button>
-                        
-                        <button onclick={submit} class="p-3 shadow-sm bg-blue-600 w-10 h-10 rounded-full flex justify-center items-center color-white">
-                            <svg fill="#000000" viewBox="0 0 24 24" xmlns="http://www.w3.org/2000/svg" class="fill-white">
-                                <path d="M0 0h24v24H0z" fill="none"></path><path d="M2.01 21L23 12 2.01 3 2 10l15 2-15 2z"></path>
-                            </svg>
-                        </button>
-                        
-                        // Emoji picker
-                        {
-                            if self.show_emoji_picker {
-                                html! {
-                                    <div class={classes!("absolute", "bottom-16", "right-16", emoji_picker_bg, "p-2", "rounded-lg", "shadow-lg", "border", "grid", "grid-cols-4", "gap-2", "z-10")}> // emoji_picker_bg includes border
-                                        {
-                                            emojis.iter().map(|emoji| {
-                                                let emoji_clone = emoji.to_string();
-                                                let select_emoji = ctx.link().callback(move |_| Msg::SelectEmoji(emoji_clone.clone()));
-                                                
-                                                html! {
-                                                    <button onclick={select_emoji} class={classes!("text-2xl", "p-2", emoji_picker_item_hover_bg, "rounded", "cursor-pointer", if self.current_theme == Theme::Dark { "text-gray-100"} else {main_text_class})}>
-                                                        {emoji}
-                                                    </button>
-                                                }
-                                            }).collect::<Html>()
-                                        }
-                                    </div>
-                                }
-                            } else {
-                                html! {}
-                            }
-                        }
-                    </div>
-                </div>
+                } else {
+                    <Composer
+                        draft={state.draft.clone()}
+                        show_emoji_picker={state.show_emoji_picker}
+                        {on_input}
+                        {on_submit}
+                        {on_toggle_emoji_picker}
+                        {on_select_emoji}
+                        replying={state.reply_to.and_then(|id| state.message(id).cloned())}
+                        {on_cancel_reply}
+                        attachment={state.attachment.clone()}
+                        {on_attach}
+                        {on_remove_attachment}
+                        {on_describe_attachment}
+                        {on_toggle_spoiler}
+                        {on_snippet}
+                        on_send_snippet={send_attachment}
+                        {on_share_location}
+                        cooldown={state.send_cooldown()}
+                    />
+                }
             </div>
+            if !spectator {
+                <OnboardingTour />
+            }
+        </div>
+    }
+}
+
+fn run_command(command: Command, state: &Store, socket: &SocketHandle) {
+    match command {
+        Command::Ping => {
+            let sent_at = js_sys::Date::now() as u64;
+            if state.connection != ConnectionState::Connected
+                || socket.send(&ping(sent_at)).is_err()
+            {
+                state.dispatch(ChatAction::PostLocal(
+                    "Ping failed: not connected to the server.".into(),
+                ));
+                return;
+            }
+            state.dispatch(ChatAction::Ping(sent_at));
         }
     }
-}
\ No newline at end of file
+}
+
+/// Asks before pinging a whole channel.
+fn confirm_broadcast(
+    mention: BroadcastMention,
+    channel: &str,
+    send: Callback<()>,
+) -> ConfirmRequest {
+    let who = match mention {
+        BroadcastMention::Everyone => "everyone",
+        BroadcastMention::Here => "everyone online",
+    };
+    ConfirmRequest {
+        kind: Confirmation::Broadcast,
+        title: format!("Notify {}?", who),
+        message: format!("This will notify {} in #{}.", who, channel),
+        action: "Send",
+        on_confirm: send,
+    }
+}
+
+/// A heartbeat stamped with the unix time in ms it is sent at.
+fn ping(sent_at: u64) -> WebSocketMessage {
+    WebSocketMessage::with_data(MsgTypes::Ping, sent_at.to_string())
+}
+
+fn moderate(action: ModerationAction) -> WebSocketMessage {
+    WebSocketMessage::with_data(MsgTypes::Moderate, serde_json::to_string(&action).unwrap())
+}
+
+/// Puts unread counts in the tab title, mentions first so they stand out.
+fn set_title(messages: usize, mentions: usize) {
+    let title = match (messages, mentions) {
+        (0, _) => "Yewchat!".to_string(),
+        (n, 0) => format!("({}) Yewchat!", n),
+        (n, m) => format!("(@{}) ({}) Yewchat!", m, n),
+    };
+    if let Some(document) = web_sys::window().and_then(|w| w.document()) {
+        document.set_title(&title);
+    }
+}
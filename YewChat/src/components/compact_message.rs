@@ -0,0 +1,95 @@
+use std::rc::Rc;
+
+use yew::prelude::*;
+use yewchat_protocol::MessageData;
+
+use crate::components::attachment::AttachmentView;
+use crate::components::external_link::ExternalLink;
+use crate::components::message_text::MessageText;
+use crate::services::emoji::{self, EmojiDataset};
+use crate::theme::use_theme;
+use crate::time::use_time_format;
+
+/// Lines from the same author this close together share one header.
+const GROUP_WINDOW_MS: u64 = 5 * 60 * 1000;
+
+/// Whether `next` continues the group started at or before `prev`.
+pub fn continues(prev: &MessageData, next: &MessageData) -> bool {
+    prev.from == next.from
+        && prev.channel == next.channel
+        && prev.time != 0
+        && next.time >= prev.time
+        && next.time - prev.time < GROUP_WINDOW_MS
+}
+
+#[derive(Properties, PartialEq)]
+pub struct CompactMessageProps {
+    pub message: Rc<MessageData>,
+    /// Resolves `:shortcode:`s once loaded; text shows verbatim until then.
+    #[prop_or_default]
+    pub emoji: Option<EmojiDataset>,
+    /// Highlighted as the target of a message link.
+    #[prop_or_default]
+    pub focused: bool,
+    /// Under the keyboard selection cursor.
+    #[prop_or_default]
+    pub selected: bool,
+    /// Opens the context menu of the message it's called with.
+    #[prop_or_default]
+    pub on_context_menu: Callback<(MouseEvent, Rc<MessageData>)>,
+    /// Follows a line from the same author; the name is left out and the time only shows on
+    /// hover.
+    #[prop_or_default]
+    pub continuation: bool,
+    /// Called with the message id, a checklist item's index and its new state, if the user
+    /// may tick them.
+    #[prop_or_default]
+    pub on_toggle_task: Option<Callback<(u64, usize, bool)>>,
+}
+
+/// IRC-style single line: timestamp, name, text. No avatar or bubble.
+#[function_component(CompactMessage)]
+pub fn compact_message(props: &CompactMessageProps) -> Html {
+    let theme = use_theme();
+    let classes = theme.classes();
+    let time_format = use_time_format();
+    let m = &props.message;
+    let oncontextmenu = {
+        let m = m.clone();
+        props
+            .on_context_menu
+            .reform(move |e: MouseEvent| (e, m.clone()))
+    };
+    let id = m.id;
+    let on_toggle_task = props.on_toggle_task.as_ref().map(|on_toggle_task| {
+        on_toggle_task.reform(move |(index, done): (usize, bool)| (id, index, done))
+    });
+
+    html! {
+        <div data-testid="message-line" {oncontextmenu} id={format!("message-{}", m.id)} tabindex="-1" data-selected={props.selected.then_some("true")} class={classes!("group", "px-3", "py-0.5", "text-sm", "font-mono", "break-words", "outline-none", classes.main_text, props.focused.then_some("bg-amber-100"), props.selected.then_some("ring-2 ring-inset ring-violet-500"))}>
+            <span data-testid="message-time" title={time_format.date_time(m.time)} class={classes!("inline-block", "w-24", classes.muted_text, props.continuation.then_some("invisible group-hover:visible"))}>
+                if props.continuation {
+                    {time_format.time_exact(m.time)}
+                } else {
+                    {format!("[{}]", time_format.time(m.time))}
+                }
+            </span>
+            <span data-testid="message-author" aria-hidden={props.continuation.then_some("true")} class={classes!("mr-2", "font-bold", theme.name_color(&m.from), props.continuation.then_some("invisible"))}>{format!("<{}>", m.from)}</span>
+            <span data-testid="message-text">
+                if m.message.ends_with(".gif") {
+                    <ExternalLink href={m.message.clone()} class={classes!("underline")}>{m.message.clone()}</ExternalLink>
+                } else {
+                    <MessageText text={emoji::with_emoji(&m.message, props.emoji.as_deref())} {on_toggle_task} />
+                }
+            </span>
+            if m.edited_at != 0 {
+                <span class={classes!("ml-1", "italic", classes.muted_text)}>{"(edited)"}</span>
+            }
+            if let Some(attachment) = &m.attachment {
+                <div class="pl-24">
+                    <AttachmentView attachment={attachment.clone()} compact=true from={m.from.clone()} />
+                </div>
+            }
+        </div>
+    }
+}
@@ -0,0 +1,429 @@
+use std::rc::Rc;
+
+use gloo::timers::callback::Interval;
+use wasm_bindgen::JsCast;
+use web_sys::{ClipboardEvent, File, HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+use yewchat_protocol::{Attachment, MessageData, MAX_ALT_CHARS};
+
+use crate::components::emoji_picker::EmojiPicker;
+use crate::components::snippet_composer::SnippetComposer;
+use crate::components::text_prompt::TextPrompt;
+use crate::config;
+use crate::highlight::Language;
+use crate::services::emoji::{self, EmojiEntry};
+use crate::services::location;
+use crate::state::{Cooldown, CooldownCause};
+use crate::theme::{use_theme, ThemeClasses};
+
+const MAX_SUGGESTIONS: usize = 8;
+/// Pastes longer than this are offered as a snippet.
+const SNIPPET_PASTE_CHARS: usize = 1500;
+/// How often a running cooldown's countdown is redrawn.
+const COOLDOWN_TICK_MS: u32 = 250;
+/// Of the ring drawn round the send button while sending waits, in its 40-unit box.
+const COUNTDOWN_RADIUS: f64 = 18.5;
+const COUNTDOWN_LENGTH: f64 = 2.0 * std::f64::consts::PI * COUNTDOWN_RADIUS;
+
+/// What the send button says while `cause` holds sending back for `seconds`.
+fn cooldown_title(cause: CooldownCause, seconds: u64) -> String {
+    match cause {
+        CooldownCause::SlowMode => format!("Slow mode: you can send again in {}s", seconds),
+        CooldownCause::RateLimit => format!("Sending too fast; try again in {}s", seconds),
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct ComposerProps {
+    pub draft: String,
+    pub show_emoji_picker: bool,
+    pub on_input: Callback<String>,
+    pub on_submit: Callback<()>,
+    pub on_toggle_emoji_picker: Callback<()>,
+    pub on_select_emoji: Callback<String>,
+    /// Message the draft answers, quoted above the input.
+    #[prop_or_default]
+    pub replying: Option<Rc<MessageData>>,
+    #[prop_or_default]
+    pub on_cancel_reply: Callback<()>,
+    /// Sent with the draft, previewed next to the input.
+    #[prop_or_default]
+    pub attachment: Option<Attachment>,
+    /// Called with an image the user picked to attach.
+    #[prop_or_default]
+    pub on_attach: Callback<File>,
+    #[prop_or_default]
+    pub on_remove_attachment: Callback<()>,
+    /// Called with the new description of an attached image; empty clears it.
+    #[prop_or_default]
+    pub on_describe_attachment: Callback<String>,
+    /// Marks or unmarks an attached image as a spoiler.
+    #[prop_or_default]
+    pub on_toggle_spoiler: Callback<()>,
+    /// Called with a snippet made from a long paste, to attach in place of the text.
+    #[prop_or_default]
+    pub on_snippet: Callback<Attachment>,
+    /// Called with a snippet written in the snippet dialog, to send on its own.
+    #[prop_or_default]
+    pub on_send_snippet: Callback<Attachment>,
+    /// Called when the user asks to share where they are.
+    #[prop_or_default]
+    pub on_share_location: Callback<()>,
+    /// The wait before the next send; sending is off until it's over.
+    #[prop_or_default]
+    pub cooldown: Option<Cooldown>,
+}
+
+#[function_component(Composer)]
+pub fn composer(props: &ComposerProps) -> Html {
+    let classes = use_theme().classes();
+
+    let oninput = props.on_input.reform(|e: InputEvent| {
+        let input: HtmlInputElement = e.target_unchecked_into();
+        input.value()
+    });
+
+    let now = use_state(|| js_sys::Date::now() as u64);
+    {
+        let now = now.clone();
+        use_effect_with_deps(
+            move |cooldown: &Option<Cooldown>| {
+                let start = js_sys::Date::now() as u64;
+                now.set(start);
+                let until = cooldown.map(|c| c.until).filter(|&until| until > start);
+                let ticker = until.map(|until| {
+                    Interval::new(COOLDOWN_TICK_MS, move || {
+                        let time = js_sys::Date::now() as u64;
+                        // One last tick past the end re-enables sending, then it goes quiet.
+                        if time < until + u64::from(COOLDOWN_TICK_MS) {
+                            now.set(time);
+                        }
+                    })
+                });
+                move || drop(ticker)
+            },
+            props.cooldown,
+        );
+    }
+    let cooldown = props.cooldown.filter(|c| c.until > *now);
+    let wait = cooldown.map_or(0, |c| c.seconds_left(*now));
+    let submit = props.on_submit.reform(|_: MouseEvent| ());
+    let toggle_emoji_picker = props.on_toggle_emoji_picker.reform(|_: MouseEvent| ());
+
+    // `:sm` at the end of the draft suggests matching emoji; Esc hides them until the draft
+    // changes again.
+    let dataset = emoji::use_dataset();
+    let selected = use_state(|| 0usize);
+    let dismissed = use_state(|| None::<String>);
+    let query = emoji::shortcode_query(&props.draft)
+        .filter(|_| dismissed.as_deref() != Some(props.draft.as_str()));
+    let suggestions: Vec<EmojiEntry> = match (&dataset, query) {
+        (Some(dataset), Some((_, query))) => emoji::search(dataset, query, MAX_SUGGESTIONS)
+            .into_iter()
+            .cloned()
+            .collect(),
+        _ => Vec::new(),
+    };
+    {
+        let selected = selected.clone();
+        use_effect_with_deps(
+            move |_| {
+                selected.set(0);
+                || ()
+            },
+            query.map(|(_, q)| q.to_string()),
+        );
+    }
+    let insert = {
+        let on_input = props.on_input.clone();
+        let draft = props.draft.clone();
+        Callback::from(move |emoji: String| {
+            if let Some((start, _)) = emoji::shortcode_query(&draft) {
+                on_input.emit(format!("{}{}", &draft[..start], emoji));
+            }
+        })
+    };
+    let onkeydown = {
+        let suggestions = suggestions.clone();
+        let selected = selected.clone();
+        let dismissed = dismissed.clone();
+        let draft = props.draft.clone();
+        let insert = insert.clone();
+        Callback::from(move |e: KeyboardEvent| {
+            if suggestions.is_empty() {
+                return;
+            }
+            let len = suggestions.len();
+            match e.key().as_str() {
+                "ArrowDown" => selected.set((*selected + 1) % len),
+                "ArrowUp" => selected.set((*selected + len - 1) % len),
+                "Enter" | "Tab" => insert.emit(suggestions[(*selected).min(len - 1)].emoji.clone()),
+                "Escape" => dismissed.set(Some(draft.clone())),
+                _ => return,
+            }
+            e.prevent_default();
+        })
+    };
+
+    let cancel_reply = props.on_cancel_reply.reform(|_: MouseEvent| ());
+    let file_input = use_node_ref();
+    let pick_file = {
+        let file_input = file_input.clone();
+        Callback::from(move |_: MouseEvent| {
+            if let Some(input) = file_input.cast::<HtmlInputElement>() {
+                input.click();
+            }
+        })
+    };
+    let on_file = {
+        let on_attach = props.on_attach.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            if let Some(file) = input.files().and_then(|files| files.get(0)) {
+                on_attach.emit(file);
+            }
+            // So picking the same file again still fires `change`.
+            input.set_value("");
+        })
+    };
+    let remove_attachment = props.on_remove_attachment.reform(|_: MouseEvent| ());
+    let share_location = props.on_share_location.reform(|_: MouseEvent| ());
+    let toggle_spoiler = props.on_toggle_spoiler.reform(|_: MouseEvent| ());
+    let describing = use_state(|| false);
+    let describe_attachment = {
+        let describing = describing.clone();
+        Callback::from(move |_: MouseEvent| describing.set(true))
+    };
+    let close_description = {
+        let describing = describing.clone();
+        Callback::from(move |_| describing.set(false))
+    };
+    let save_description = {
+        let describing = describing.clone();
+        let on_describe = props.on_describe_attachment.clone();
+        Callback::from(move |text: String| {
+            on_describe.emit(text.trim().to_string());
+            describing.set(false);
+        })
+    };
+
+    // Long pastes are held back and offered as a snippet instead of flooding the channel.
+    let pasted = use_state(|| None::<String>);
+    let paste_language = use_state(|| None::<Language>);
+    let onpaste = {
+        let pasted = pasted.clone();
+        Callback::from(move |e: Event| {
+            let text = e
+                .dyn_ref::<ClipboardEvent>()
+                .and_then(|e| e.clipboard_data())
+                .and_then(|data| data.get_data("text").ok());
+            if let Some(text) = text.filter(|t| t.chars().count() > SNIPPET_PASTE_CHARS) {
+                e.prevent_default();
+                pasted.set(Some(text));
+            }
+        })
+    };
+    let set_paste_language = {
+        let paste_language = paste_language.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            // The first option is plain text.
+            let index = (select.selected_index() as usize).checked_sub(1);
+            paste_language.set(index.and_then(|i| Language::ALL.get(i)).copied());
+        })
+    };
+    let paste_as_snippet = {
+        let pasted = pasted.clone();
+        let paste_language = paste_language.clone();
+        let on_snippet = props.on_snippet.clone();
+        Callback::from(move |_: MouseEvent| {
+            if let Some(code) = (*pasted).clone() {
+                on_snippet.emit(Attachment::Snippet {
+                    title: None,
+                    language: paste_language.map(|l| l.key().to_string()),
+                    code,
+                });
+            }
+            pasted.set(None);
+        })
+    };
+    let paste_as_text = {
+        let pasted = pasted.clone();
+        let on_input = props.on_input.clone();
+        let draft = props.draft.clone();
+        Callback::from(move |_: MouseEvent| {
+            if let Some(text) = &*pasted {
+                on_input.emit(format!("{}{}", draft, text));
+            }
+            pasted.set(None);
+        })
+    };
+
+    let show_snippet_composer = use_state(|| false);
+    let open_snippet_composer = {
+        let show_snippet_composer = show_snippet_composer.clone();
+        Callback::from(move |_: MouseEvent| show_snippet_composer.set(true))
+    };
+    let close_snippet_composer = {
+        let show_snippet_composer = show_snippet_composer.clone();
+        Callback::from(move |_| show_snippet_composer.set(false))
+    };
+    let send_snippet = {
+        let show_snippet_composer = show_snippet_composer.clone();
+        let on_send_snippet = props.on_send_snippet.clone();
+        Callback::from(move |snippet| {
+            on_send_snippet.emit(snippet);
+            show_snippet_composer.set(false);
+        })
+    };
+
+    html! {
+        <div class="w-full h-14 flex px-3 items-center relative">
+            <div class="absolute bottom-14 left-6 right-6 flex flex-col">
+                if let Some(text) = &*pasted {
+                    <div data-testid="snippet-offer" class={classes!("flex", "items-center", "px-3", "py-1", "rounded-t-lg", "border", "text-xs", classes.popover_bg, classes.main_text)}>
+                        <span class="grow truncate">{format!("That's a long paste ({} characters). Send it as a snippet?", text.chars().count())}</span>
+                        <select data-testid="snippet-language" onchange={set_paste_language} class={classes!("mx-2", "border", "rounded", "px-1", classes.border, classes.input_bg)}>
+                            <option selected={paste_language.is_none()}>{"Plain text"}</option>
+                            { for Language::ALL.iter().map(|l| html! { <option selected={*paste_language == Some(*l)}>{l.label()}</option> }) }
+                        </select>
+                        <button data-testid="paste-as-snippet" onclick={paste_as_snippet} class={classes!("px-2", "rounded", classes.button_bg)}>{"Snippet"}</button>
+                        <button data-testid="paste-as-text" onclick={paste_as_text} class="ml-2 underline">{"Paste as text"}</button>
+                    </div>
+                }
+                if let Some(Cooldown { cause: CooldownCause::SlowMode, .. }) = cooldown {
+                    <div data-testid="slow-mode-banner" role="status" class={classes!("px-3", "py-1", "rounded-t-lg", "border", "text-xs", classes.popover_bg, classes.muted_text)}>
+                        {format!("🐢 Slow mode is on. You can post again in {}s.", wait)}
+                    </div>
+                }
+                if let Some(parent) = &props.replying {
+                    <div data-testid="reply-banner" class={classes!("flex", "items-center", "px-3", "py-1", "rounded-t-lg", "border", "text-xs", classes.popover_bg, classes.muted_text)}>
+                        <span class="grow truncate">{format!("Replying to {}: {}", parent.from, parent.message)}</span>
+                        <button data-testid="cancel-reply" title="Cancel reply" onclick={cancel_reply} class="ml-2">{"✕"}</button>
+                    </div>
+                }
+            </div>
+            if let Some(attachment) = &props.attachment {
+                <div data-testid="attachment-preview" class="relative ml-3 shrink-0">
+                    {attachment_preview(attachment, classes)}
+                    <button data-testid="remove-attachment" title="Remove attachment" onclick={remove_attachment}
+                        class={classes!("absolute", "-top-1", "-right-1", "w-4", "h-4", "rounded-full", "text-xs", "leading-none", classes.button_bg)}>{"✕"}</button>
+                    if let Attachment::Image { alt, spoiler, .. } = attachment {
+                        <button data-testid="describe-attachment" onclick={describe_attachment}
+                            title={alt.clone().unwrap_or_else(|| "Add a description".into())}
+                            aria-label={if alt.is_some() { "Edit description" } else { "Add a description" }}
+                            class={classes!("absolute", "-bottom-1", "left-0", "px-1", "rounded", "text-[10px]", "font-bold", "leading-tight", if alt.is_some() { classes.selected_bg } else { classes.button_bg })}>
+                            {"ALT"}
+                        </button>
+                        <button data-testid="spoiler-attachment" onclick={toggle_spoiler}
+                            title={if *spoiler { "Shown blurred until revealed" } else { "Mark as spoiler" }}
+                            aria-pressed={spoiler.to_string()} aria-label="Spoiler"
+                            class={classes!("absolute", "-bottom-1", "right-0", "px-0.5", "rounded", "text-[10px]", "leading-tight", if *spoiler { classes.selected_bg } else { classes.button_bg })}>
+                            {"🙈"}
+                        </button>
+                    }
+                </div>
+            }
+            <input data-testid="chat-input" value={props.draft.clone()} {oninput} {onkeydown} {onpaste} type="text" maxlength={config::get().max_message_length.to_string()} placeholder="Message" class={classes!("block", "w-full", "py-2", "pl-4", "mx-3", classes.input_bg, "rounded-full", "outline-none", classes.input_text, classes.border, "border")} name="message" required=true />
+
+            <input ref={file_input} data-testid="attach-input" type="file" accept={config::get().allowed_upload_types.join(",")} class="hidden" onchange={on_file} />
+            <button data-testid="attach-button" title="Attach an image" onclick={pick_file} class={classes!("p-2", "mr-2", "shadow-sm", classes.button_bg, "w-10", "h-10", "rounded-full", "flex", "justify-center", "items-center", classes.main_text)}>
+                {"📎"}
+            </button>
+            <button data-testid="snippet-button" title="Write a snippet" onclick={open_snippet_composer} class={classes!("p-2", "mr-2", "shadow-sm", classes.button_bg, "w-10", "h-10", "rounded-full", "flex", "justify-center", "items-center", "font-mono", "text-xs", classes.main_text)}>
+                {"</>"}
+            </button>
+            <button data-testid="location-button" title="Share your location" onclick={share_location} class={classes!("p-2", "mr-2", "shadow-sm", classes.button_bg, "w-10", "h-10", "rounded-full", "flex", "justify-center", "items-center", classes.main_text)}>
+                {"📍"}
+            </button>
+            <button data-testid="emoji-toggle" onclick={toggle_emoji_picker} class={classes!("p-2", "mr-2", "shadow-sm", classes.button_bg, "w-10", "h-10", "rounded-full", "flex", "justify-center", "items-center", classes.main_text)}>
+                {"😊"}
+            </button>
+
+            if let Some(cooldown) = cooldown {
+                <button data-testid="send-button" disabled=true title={cooldown_title(cooldown.cause, wait)}
+                    class="relative shadow-sm bg-blue-300 w-10 h-10 shrink-0 rounded-full flex justify-center items-center text-white text-xs font-bold">
+                    <svg data-testid="send-countdown" viewBox="0 0 40 40" aria-hidden="true" class="absolute inset-0 -rotate-90">
+                        <circle cx="20" cy="20" r={COUNTDOWN_RADIUS.to_string()} fill="none" stroke="#2563eb" stroke-width="3"
+                            stroke-dasharray={COUNTDOWN_LENGTH.to_string()}
+                            stroke-dashoffset={format!("{:.2}", COUNTDOWN_LENGTH * (1.0 - cooldown.remaining(*now)))} />
+                    </svg>
+                    <span class="relative">{wait}</span>
+                </button>
+            } else {
+                <button data-testid="send-button" onclick={submit} class="p-3 shadow-sm bg-blue-600 w-10 h-10 rounded-full flex justify-center items-center color-white">
+                    <svg fill="#000000" viewBox="0 0 24 24" xmlns="http://www.w3.org/2000/svg" class="fill-white">
+                        <path d="M0 0h24v24H0z" fill="none"></path><path d="M2.01 21L23 12 2.01 3 2 10l15 2-15 2z"></path>
+                    </svg>
+                </button>
+            }
+
+            if !suggestions.is_empty() {
+                <div data-testid="shortcode-suggestions" role="listbox" class={classes!("absolute", "bottom-14", "left-6", "w-64", "py-1", "rounded-lg", "shadow-lg", "border", "z-10", classes.popover_bg, classes.main_text)}>
+                    {
+                        suggestions.iter().enumerate().map(|(i, entry)| {
+                            let emoji = entry.emoji.clone();
+                            let onclick = insert.reform(move |_: MouseEvent| emoji.clone());
+                            html! {
+                                <div data-testid="shortcode-suggestion" role="option" aria-selected={(i == *selected).to_string()} {onclick}
+                                    class={classes!("px-3", "py-1", "cursor-pointer", "text-sm", classes.hover_bg, (i == *selected).then_some(classes.selected_bg))}>
+                                    {format!("{} :{}:", entry.emoji, entry.shortcodes[0])}
+                                </div>
+                            }
+                        }).collect::<Html>()
+                    }
+                </div>
+            }
+
+            if props.show_emoji_picker {
+                <EmojiPicker on_select={props.on_select_emoji.clone()} />
+            }
+
+            if *show_snippet_composer {
+                <SnippetComposer on_send={send_snippet} on_close={close_snippet_composer} />
+            }
+
+            if let (true, Some(Attachment::Image { alt, .. })) = (*describing, &props.attachment) {
+                <TextPrompt title="Describe this image" placeholder="For people who can't see it" value={alt.clone().unwrap_or_default()}
+                    max_chars={Some(MAX_ALT_CHARS)} action="Save" testid="describe-attachment-dialog"
+                    on_submit={save_description} on_close={close_description} />
+            }
+        </div>
+    }
+}
+
+/// Thumbnail of what will be sent with the draft.
+fn attachment_preview(attachment: &Attachment, classes: &ThemeClasses) -> Html {
+    match attachment {
+        Attachment::Image {
+            url, alt, spoiler, ..
+        } => html! {
+            <img src={url.clone()} alt={alt.clone().unwrap_or_else(|| "Attached image".into())}
+                class={classes!("w-10", "h-10", "object-cover", "rounded", spoiler.then_some("blur-sm"))} />
+        },
+        Attachment::Snippet { title, code, .. } => html! {
+            <div class={classes!("h-10", "w-40", "flex", "items-center", "px-2", "rounded", "text-xs", classes.button_bg)}>
+                <span class="truncate">{format!("📄 {} · {} lines", title.as_deref().unwrap_or("Snippet"), code.lines().count())}</span>
+            </div>
+        },
+        Attachment::Contact { name } => html! {
+            <div class={classes!("h-10", "w-40", "flex", "items-center", "px-2", "rounded", "text-xs", classes.button_bg)}>
+                <span class="truncate">{format!("👤 {}", name)}</span>
+            </div>
+        },
+        Attachment::Location {
+            latitude,
+            longitude,
+            ..
+        } => html! {
+            <div class={classes!("h-10", "w-40", "flex", "items-center", "px-2", "rounded", "text-xs", classes.button_bg)}>
+                <span class="truncate">{format!("📍 {}", location::describe(*latitude, *longitude))}</span>
+            </div>
+        },
+        Attachment::Embed { title, fields } => html! {
+            <div class={classes!("h-10", "w-40", "flex", "items-center", "px-2", "rounded", "text-xs", classes.button_bg)}>
+                <span class="truncate">{format!("🗂️ {} · {} fields", title.as_deref().unwrap_or("Embed"), fields.len())}</span>
+            </div>
+        },
+    }
+}
@@ -0,0 +1,122 @@
+use std::rc::Rc;
+
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+use crate::components::modal::Modal;
+use crate::state::{ChatAction, Confirmation};
+use crate::store::use_store;
+use crate::theme::use_theme;
+
+/// A destructive action waiting on the user's go-ahead.
+#[derive(Clone, PartialEq)]
+pub struct ConfirmRequest {
+    pub kind: Confirmation,
+    pub title: String,
+    pub message: String,
+    /// Label of the button that goes ahead, e.g. "Delete".
+    pub action: &'static str,
+    pub on_confirm: Callback<()>,
+}
+
+/// Asks for confirmation through the nearest `ConfirmProvider`.
+#[derive(Clone, PartialEq)]
+pub struct Confirm(Callback<ConfirmRequest>);
+
+impl Confirm {
+    /// Runs `request.on_confirm` once the user agrees, or straight away if they asked not to
+    /// be asked about this kind of action.
+    pub fn ask(&self, request: ConfirmRequest) {
+        self.0.emit(request);
+    }
+}
+
+pub fn use_confirm() -> Confirm {
+    use_context::<Confirm>().expect("ConfirmProvider to be mounted")
+}
+
+#[derive(Properties, PartialEq)]
+pub struct ConfirmProviderProps {
+    pub children: Children,
+}
+
+/// Shows one confirmation dialog at a time for everything below it.
+#[function_component(ConfirmProvider)]
+pub fn confirm_provider(props: &ConfirmProviderProps) -> Html {
+    let store = use_store();
+    let pending = use_state(|| None::<Rc<ConfirmRequest>>);
+
+    let ask = {
+        let pending = pending.clone();
+        let skipped = store.preferences.skip_confirmations.clone();
+        Callback::from(move |request: ConfirmRequest| {
+            if skipped.contains(&request.kind) {
+                request.on_confirm.emit(());
+            } else {
+                pending.set(Some(Rc::new(request)));
+            }
+        })
+    };
+
+    html! {
+        <ContextProvider<Confirm> context={Confirm(ask)}>
+            { for props.children.iter() }
+            if let Some(request) = (*pending).clone() {
+                <ConfirmDialog {request} on_done={Callback::from(move |_| pending.set(None))} />
+            }
+        </ContextProvider<Confirm>>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+struct ConfirmDialogProps {
+    request: Rc<ConfirmRequest>,
+    /// Called after either answer.
+    on_done: Callback<()>,
+}
+
+#[function_component(ConfirmDialog)]
+fn confirm_dialog(props: &ConfirmDialogProps) -> Html {
+    let classes = use_theme().classes();
+    let store = use_store();
+    let skip = use_state(|| false);
+
+    let toggle_skip = {
+        let skip = skip.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            skip.set(input.checked());
+        })
+    };
+    let accept = {
+        let request = props.request.clone();
+        let on_done = props.on_done.clone();
+        let skip = *skip;
+        Callback::from(move |_: MouseEvent| {
+            if skip {
+                let mut preferences = store.preferences.clone();
+                preferences.skip_confirmations.insert(request.kind);
+                store.dispatch(ChatAction::SetPreferences(preferences));
+            }
+            on_done.emit(());
+            request.on_confirm.emit(());
+        })
+    };
+    let cancel = props.on_done.reform(|_: MouseEvent| ());
+
+    html! {
+        <Modal title={props.request.title.clone()} testid="confirm-dialog" on_close={props.on_done.clone()}>
+            <p class="mb-3">{props.request.message.clone()}</p>
+            <label class={classes!("flex", "items-center", "mb-3", "text-xs", classes.muted_text)}>
+                <input data-testid="confirm-skip" type="checkbox" class="mr-2" checked={*skip} onchange={toggle_skip} />
+                {"Don't ask again"}
+            </label>
+            <div class="flex justify-end">
+                <button data-testid="confirm-cancel" autofocus=true onclick={cancel}
+                    class={classes!("px-3", "py-1", "mr-2", "rounded", classes.button_bg)}>{"Cancel"}</button>
+                <button data-testid="confirm-accept" onclick={accept}
+                    class="px-3 py-1 rounded bg-red-600 text-white">{props.request.action}</button>
+            </div>
+        </Modal>
+    }
+}
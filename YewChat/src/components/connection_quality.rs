@@ -0,0 +1,96 @@
+use yew::prelude::*;
+
+use crate::services::event_bus::ConnectionState;
+use crate::theme::use_theme;
+
+/// Round trips up to this many ms feel instant.
+const GOOD_MS: u64 = 300;
+/// Past this many ms, delivery is visibly slow.
+const POOR_MS: u64 = 1_000;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Quality {
+    Good,
+    Ok,
+    Poor,
+}
+
+impl Quality {
+    /// How a heartbeat round trip of `latency` ms rates.
+    pub fn of(latency: u64) -> Self {
+        if latency <= GOOD_MS {
+            Quality::Good
+        } else if latency <= POOR_MS {
+            Quality::Ok
+        } else {
+            Quality::Poor
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Quality::Good => "Good",
+            Quality::Ok => "OK",
+            Quality::Poor => "Poor",
+        }
+    }
+
+    fn dot(&self) -> &'static str {
+        match self {
+            Quality::Good => "bg-green-500",
+            Quality::Ok => "bg-yellow-400",
+            Quality::Poor => "bg-red-500",
+        }
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct ConnectionQualityProps {
+    pub connection: ConnectionState,
+    /// Latest heartbeat round trip in ms, if one has been answered.
+    pub latency: Option<u64>,
+}
+
+/// A coloured dot in the channel header rating the connection, with a warning beside it
+/// while round trips are slow enough to hold messages up.
+#[function_component(ConnectionQuality)]
+pub fn connection_quality(props: &ConnectionQualityProps) -> Html {
+    let classes = use_theme().classes();
+    let quality = match props.connection {
+        ConnectionState::Connected => props.latency.map(Quality::of),
+        _ => None,
+    };
+    let (dot, label, title) = match (quality, props.latency) {
+        (Some(quality), Some(latency)) => (
+            quality.dot(),
+            quality.label(),
+            format!(
+                "Connection: {} ({} ms round trip)",
+                quality.label(),
+                latency
+            ),
+        ),
+        _ if props.connection == ConnectionState::Disconnected => (
+            "bg-red-500",
+            "Offline",
+            "Not connected to the server".to_string(),
+        ),
+        _ => (
+            "bg-gray-400",
+            "Connecting",
+            "Measuring the connection…".to_string(),
+        ),
+    };
+
+    html! {
+        <div data-testid="connection-quality" data-quality={label} title={title.clone()} aria-label={title}
+            class={classes!("flex", "items-center", "mr-3", "text-xs", classes.muted_text)}>
+            if quality == Some(Quality::Poor) {
+                <span data-testid="connection-warning" class="mr-2 text-red-600">
+                    {"⚠ Slow connection: messages may take a while to arrive"}
+                </span>
+            }
+            <span class={classes!("inline-block", "w-2.5", "h-2.5", "rounded-full", dot)}></span>
+        </div>
+    }
+}
@@ -0,0 +1,181 @@
+use gloo::events::{EventListener, EventListenerOptions};
+use wasm_bindgen::JsCast;
+use web_sys::{Element, HtmlElement, Node};
+use yew::prelude::*;
+
+use crate::theme::use_theme;
+
+/// Gap kept between the menu and the edges of the window.
+const EDGE_MARGIN: i32 = 4;
+
+#[derive(Clone, PartialEq)]
+pub struct MenuItem {
+    pub icon: &'static str,
+    pub label: String,
+    pub on_select: Callback<()>,
+    /// Destructive; drawn in red.
+    pub danger: bool,
+}
+
+impl MenuItem {
+    pub fn new(icon: &'static str, label: impl Into<String>, on_select: Callback<()>) -> Self {
+        Self {
+            icon,
+            label: label.into(),
+            on_select,
+            danger: false,
+        }
+    }
+
+    pub fn danger(self) -> Self {
+        Self {
+            danger: true,
+            ..self
+        }
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct ContextMenuProps {
+    /// Client coordinates of the click that opened the menu.
+    pub x: i32,
+    pub y: i32,
+    pub items: Vec<MenuItem>,
+    /// Called after an item is chosen, and on any click outside, Escape, scroll or resize.
+    pub on_close: Callback<()>,
+    /// Shown above the items, e.g. a row of quick reactions.
+    #[prop_or_default]
+    pub children: Children,
+}
+
+/// Menu opened by a right click, kept inside the window.
+#[function_component(ContextMenu)]
+pub fn context_menu(props: &ContextMenuProps) -> Html {
+    let classes = use_theme().classes();
+    let menu = use_node_ref();
+    let position = use_state(|| (props.x, props.y));
+
+    {
+        let menu = menu.clone();
+        let position = position.clone();
+        use_effect_with_deps(
+            move |&(x, y)| {
+                position.set(fit_in_window(&menu, x, y));
+                // Lets the menu be used from the keyboard as soon as it opens.
+                let first = menu
+                    .cast::<Element>()
+                    .and_then(|menu| menu.query_selector("[role=menuitem]").ok().flatten());
+                if let Some(first) = first.and_then(|el| el.dyn_into::<HtmlElement>().ok()) {
+                    let _ = first.focus();
+                }
+                || ()
+            },
+            (props.x, props.y),
+        );
+    }
+    {
+        let menu = menu.clone();
+        let on_close = props.on_close.clone();
+        use_effect_with_deps(
+            move |_| {
+                let document = gloo::utils::document();
+                let window = gloo::utils::window();
+                let outside = {
+                    let on_close = on_close.clone();
+                    EventListener::new(&document, "mousedown", move |e| {
+                        let inside = match (menu.get(), e.target()) {
+                            (Some(menu), Some(target)) => menu.contains(target.dyn_ref::<Node>()),
+                            _ => false,
+                        };
+                        if !inside {
+                            on_close.emit(());
+                        }
+                    })
+                };
+                let escape = {
+                    let on_close = on_close.clone();
+                    EventListener::new(&document, "keydown", move |e| {
+                        if e.dyn_ref::<KeyboardEvent>().map(|e| e.key()).as_deref()
+                            == Some("Escape")
+                        {
+                            on_close.emit(());
+                        }
+                    })
+                };
+                // Scroll events don't bubble; capturing sees the message list scrolling too.
+                let on_scroll = on_close.clone();
+                let scroll = EventListener::new_with_options(
+                    &document,
+                    "scroll",
+                    EventListenerOptions::run_in_capture_phase(),
+                    move |_| on_scroll.emit(()),
+                );
+                let resize = EventListener::new(&window, "resize", move |_| on_close.emit(()));
+                move || drop((outside, escape, scroll, resize))
+            },
+            (),
+        );
+    }
+
+    let onkeydown = Callback::from(|e: KeyboardEvent| {
+        let Some(item) = e.target_dyn_into::<Element>() else {
+            return;
+        };
+        let sibling = match e.key().as_str() {
+            "ArrowDown" => item.next_element_sibling(),
+            "ArrowUp" => item.previous_element_sibling(),
+            _ => return,
+        };
+        e.prevent_default();
+        let sibling = sibling
+            .filter(|el| el.get_attribute("role").as_deref() == Some("menuitem"))
+            .and_then(|el| el.dyn_into::<HtmlElement>().ok());
+        if let Some(sibling) = sibling {
+            let _ = sibling.focus();
+        }
+    });
+
+    let (x, y) = *position;
+    html! {
+        <div data-testid="context-menu" role="menu" ref={menu} {onkeydown}
+            oncontextmenu={Callback::from(|e: MouseEvent| e.prevent_default())}
+            style={format!("left: {}px; top: {}px", x, y)}
+            class={classes!("fixed", "z-30", "w-48", "py-1", "rounded-lg", "border", "shadow-lg", "text-sm", classes.popover_bg, classes.main_text)}>
+            { for props.children.iter() }
+            {
+                props.items.iter().map(|item| {
+                    let on_select = item.on_select.clone();
+                    let on_close = props.on_close.clone();
+                    let onclick = Callback::from(move |_| {
+                        on_select.emit(());
+                        on_close.emit(());
+                    });
+                    html! {
+                        <button data-testid="context-menu-item" role="menuitem" {onclick}
+                            class={classes!("flex", "w-full", "items-center", "px-3", "py-1", "text-left", classes.hover_bg, item.danger.then_some("text-red-600"))}>
+                            <span class="w-6">{item.icon}</span>
+                            {item.label.clone()}
+                        </button>
+                    }
+                }).collect::<Html>()
+            }
+        </div>
+    }
+}
+
+/// Moves the menu left or up if opening it at `(x, y)` would run past the window's edge.
+fn fit_in_window(menu: &NodeRef, x: i32, y: i32) -> (i32, i32) {
+    let window = gloo::utils::window();
+    let size =
+        |v: Result<wasm_bindgen::JsValue, _>| v.ok().and_then(|v| v.as_f64()).unwrap_or(0.0) as i32;
+    let (width, height) = (size(window.inner_width()), size(window.inner_height()));
+    match menu.cast::<HtmlElement>() {
+        Some(el) => (
+            x.min(width - el.offset_width() - EDGE_MARGIN)
+                .max(EDGE_MARGIN),
+            y.min(height - el.offset_height() - EDGE_MARGIN)
+                .max(EDGE_MARGIN),
+        ),
+        None => (x, y),
+    }
+}
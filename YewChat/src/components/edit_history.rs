@@ -0,0 +1,36 @@
+use std::rc::Rc;
+
+use yew::prelude::*;
+use yewchat_protocol::MessageData;
+
+use crate::theme::use_theme;
+use crate::time::use_time_format;
+
+#[derive(Properties, PartialEq)]
+pub struct EditHistoryProps {
+    pub message: Rc<MessageData>,
+}
+
+/// Every version of an edited message, oldest first, ending with the current text.
+#[function_component(EditHistory)]
+pub fn edit_history(props: &EditHistoryProps) -> Html {
+    let classes = use_theme().classes();
+    let time_format = use_time_format();
+    let m = &props.message;
+    let row = |time: u64, text: &str, label: &'static str| {
+        html! {
+            <div data-testid="edit-revision" class="py-0.5">
+                <span class={classes!("mr-2", classes.muted_text)}>{format!("{}{}", time_format.time(time), label)}</span>
+                {text.to_string()}
+            </div>
+        }
+    };
+
+    html! {
+        <div data-testid="edit-history" class={classes!("mt-1", "p-2", "rounded", "border", "text-xs", classes.popover_bg, classes.main_text)}>
+            <div class="font-bold mb-1">{"Edit history"}</div>
+            { for m.history.iter().map(|r| row(r.time, &r.message, "")) }
+            { row(m.edited_at, &m.message, " (current)") }
+        </div>
+    }
+}
@@ -0,0 +1,72 @@
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+
+use crate::services::emoji::{self, EmojiDataset};
+use crate::theme::use_theme;
+
+#[derive(Properties, PartialEq)]
+pub struct EmojiPickerProps {
+    pub on_select: Callback<String>,
+}
+
+/// Only mounted while open; the dataset is fetched on the first open and cached afterwards.
+#[function_component(EmojiPicker)]
+pub fn emoji_picker(props: &EmojiPickerProps) -> Html {
+    let classes = use_theme().classes();
+    let dataset = use_state(emoji::cached);
+    let error = use_state(|| None::<String>);
+
+    {
+        let dataset = dataset.clone();
+        let error = error.clone();
+        use_effect_with_deps(
+            move |_| {
+                if dataset.is_none() {
+                    spawn_local(async move {
+                        match emoji::load().await {
+                            Ok(loaded) => dataset.set(Some(loaded)),
+                            Err(e) => {
+                                log::error!("failed to load emoji dataset: {}", e);
+                                error.set(Some(e));
+                            }
+                        }
+                    });
+                }
+                || ()
+            },
+            (),
+        );
+    }
+
+    let body = match (&*dataset, &*error) {
+        (Some(entries), _) => render_entries(entries, &props.on_select, classes.hover_bg, classes.main_text),
+        (None, Some(_)) => html! { <div class="col-span-8 p-2 text-sm">{"Couldn't load emoji"}</div> },
+        (None, None) => html! { <div class="col-span-8 p-2 text-sm">{"Loading…"}</div> },
+    };
+
+    html! {
+        <div data-testid="emoji-picker" class={classes!("absolute", "bottom-16", "right-16", classes.popover_bg, "p-2", "rounded-lg", "shadow-lg", "border", "grid", "grid-cols-8", "gap-1", "max-h-64", "overflow-auto", "z-10", "pop-in")}>
+            { body }
+        </div>
+    }
+}
+
+fn render_entries(
+    entries: &EmojiDataset,
+    on_select: &Callback<String>,
+    hover_bg: &'static str,
+    text: &'static str,
+) -> Html {
+    entries
+        .iter()
+        .map(|entry| {
+            let emoji = entry.emoji.clone();
+            let select_emoji = on_select.reform(move |_: MouseEvent| emoji.clone());
+            html! {
+                <button data-testid="emoji-option" title={format!(":{}:", entry.shortcodes[0])} onclick={select_emoji} class={classes!("text-2xl", "p-1", hover_bg, "rounded", "cursor-pointer", text)}>
+                    {entry.emoji.clone()}
+                </button>
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,125 @@
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+use crate::components::modal::Modal;
+use crate::links;
+use crate::state::ChatAction;
+use crate::store::Store;
+use crate::theme::use_theme;
+
+#[derive(Properties, PartialEq)]
+pub struct ExternalLinkProps {
+    pub href: String,
+    #[prop_or_default]
+    pub class: Classes,
+    /// Put on the link, for tests.
+    #[prop_or("message-link")]
+    pub testid: &'static str,
+    pub children: Children,
+}
+
+/// A link out of the chat. Unless its site is trusted, clicking it first shows where it
+/// really goes.
+#[function_component(ExternalLink)]
+pub fn external_link(props: &ExternalLinkProps) -> Html {
+    let store = use_context::<Store>();
+    let asking = use_state(|| false);
+
+    let trusted = links::host(&props.href)
+        .is_some_and(|host| store.as_ref().is_some_and(|s| s.preferences.trusts(&host)));
+    let onclick = {
+        let asking = asking.clone();
+        Callback::from(move |e: MouseEvent| {
+            if !trusted {
+                e.prevent_default();
+                asking.set(true);
+            }
+        })
+    };
+
+    html! {
+        <>
+            <a data-testid={props.testid} href={props.href.clone()} target="_blank" rel="noopener noreferrer"
+                class={props.class.clone()} {onclick}>
+                { for props.children.iter() }
+            </a>
+            if *asking {
+                <LeaveWarning href={props.href.clone()} on_close={Callback::from(move |_| asking.set(false))} />
+            }
+        </>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+struct LeaveWarningProps {
+    href: String,
+    /// Called after either answer.
+    on_close: Callback<()>,
+}
+
+/// The full address with its host picked out, since link text and look-alike letters can
+/// both hide where a link leads.
+#[function_component(LeaveWarning)]
+fn leave_warning(props: &LeaveWarningProps) -> Html {
+    let classes = use_theme().classes();
+    let store = use_context::<Store>();
+    let trust = use_state(|| false);
+    let host = links::host(&props.href);
+
+    let toggle_trust = {
+        let trust = trust.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            trust.set(input.checked());
+        })
+    };
+    let open = {
+        let href = props.href.clone();
+        let host = host.clone();
+        let on_close = props.on_close.clone();
+        let trust = *trust;
+        Callback::from(move |_: MouseEvent| {
+            if let (true, Some(store), Some(host)) = (trust, &store, &host) {
+                let mut preferences = store.preferences.clone();
+                preferences.trusted_domains.insert(host.clone());
+                store.dispatch(ChatAction::SetPreferences(preferences));
+            }
+            on_close.emit(());
+            let _ = gloo::utils::window().open_with_url_and_target_and_features(
+                &href,
+                "_blank",
+                "noopener,noreferrer",
+            );
+        })
+    };
+    let cancel = props.on_close.reform(|_: MouseEvent| ());
+
+    html! {
+        <Modal title="Leave YewChat?" testid="link-warning" on_close={props.on_close.clone()}>
+            if let Some(host) = &host {
+                <p>{"This link goes to"}</p>
+                <p data-testid="link-host" class="mb-2 font-bold break-all">{host.clone()}</p>
+                if links::is_lookalike(host) {
+                    <p data-testid="link-lookalike" class="mb-2 text-xs text-red-600">
+                        {"This address uses letters that can pass for others. Make sure it's the site you expect."}
+                    </p>
+                }
+            }
+            <p data-testid="link-url" class={classes!("mb-3", "text-xs", "font-mono", "break-all", classes.muted_text)}>
+                {props.href.clone()}
+            </p>
+            if let Some(host) = &host {
+                <label class={classes!("flex", "items-center", "mb-3", "text-xs", classes.muted_text)}>
+                    <input data-testid="link-trust" type="checkbox" class="mr-2" checked={*trust} onchange={toggle_trust} />
+                    {format!("Don't ask again for {}", host)}
+                </label>
+            }
+            <div class="flex justify-end">
+                <button data-testid="link-cancel" autofocus=true onclick={cancel}
+                    class={classes!("px-3", "py-1", "mr-2", "rounded", classes.button_bg)}>{"Cancel"}</button>
+                <button data-testid="link-open" onclick={open}
+                    class="px-3 py-1 rounded bg-blue-600 text-white">{"Open link"}</button>
+            </div>
+        </Modal>
+    }
+}
@@ -0,0 +1,331 @@
+//! Every component in its interesting states, fed mock data, for working on how things look
+//! without a server. Built with the `gallery` feature and served at `/gallery`.
+
+use std::rc::Rc;
+
+use yew::prelude::*;
+use yewchat_protocol::{
+    Attachment, EmbedField, MessageData, Reaction, Role, SystemEvent, SystemNotice, DEFAULT_CHANNEL,
+};
+
+use crate::components::badges::Badges;
+use crate::components::connection_quality::ConnectionQuality;
+use crate::components::emoji_picker::EmojiPicker;
+use crate::components::message_bubble::MessageBubble;
+use crate::components::modal::Modal;
+use crate::components::outbox::Outbox;
+use crate::components::reaction_bar::ReactionBar;
+use crate::components::skeleton::Skeleton;
+use crate::components::system_line::SystemLine;
+use crate::components::typing_indicator::TypingIndicator;
+use crate::mentions::Mention;
+use crate::services::event_bus::ConnectionState;
+use crate::state::{BubbleStyle, Outgoing, Presence, SendError, UserProfile};
+use crate::theme::{use_theme, Theme};
+
+/// When every mock message was sent: 14 Nov 2023, so the page looks the same every time.
+const TIME: u64 = 1_700_000_000_000;
+
+fn message(id: u64, from: &str, text: &str) -> MessageData {
+    MessageData {
+        id,
+        from: from.into(),
+        message: text.into(),
+        time: TIME + id * 60_000,
+        channel: DEFAULT_CHANNEL.into(),
+        edited_at: 0,
+        history: Vec::new(),
+        reply_to: None,
+        attachment: None,
+        tasks_open: false,
+    }
+}
+
+fn profile(name: &str, role: Role, bot: bool, verified: bool) -> UserProfile {
+    UserProfile {
+        role,
+        bot,
+        verified,
+        ..UserProfile::new(name)
+    }
+}
+
+fn outgoing(id: u64, text: &str, error: Option<&str>) -> Outgoing {
+    Outgoing {
+        id,
+        message: text.into(),
+        sent_at: TIME,
+        error: error.map(|e| SendError::Network(e.into())),
+        ..Outgoing::new(DEFAULT_CHANNEL.into())
+    }
+}
+
+#[derive(Properties, PartialEq)]
+struct SectionProps {
+    title: &'static str,
+    children: Children,
+}
+
+#[function_component(Section)]
+fn section(props: &SectionProps) -> Html {
+    let classes = use_theme().classes();
+    html! {
+        <section class="mb-6">
+            <h3 class={classes!("mb-2", "text-xs", "font-bold", "uppercase", "tracking-wide", classes.muted_text)}>{props.title}</h3>
+            { for props.children.iter() }
+        </section>
+    }
+}
+
+/// Everything once, in whichever theme is provided.
+#[function_component(Specimens)]
+fn specimens() -> Html {
+    let theme = use_theme();
+    let classes = theme.classes();
+    let modal_open = use_state(|| false);
+    let open_modal = {
+        let modal_open = modal_open.clone();
+        Callback::from(move |_: MouseEvent| modal_open.set(true))
+    };
+    let close_modal = {
+        let modal_open = modal_open.clone();
+        Callback::from(move |_| modal_open.set(false))
+    };
+
+    let alice = profile("alice", Role::Member, false, false);
+    let reply = Rc::new(message(1, "alice", "Has anyone tried the **new** build?"));
+    let bubbles: Vec<(&str, MessageBubbleCase)> = vec![
+        (
+            "Plain",
+            MessageBubbleCase::new(message(2, "alice", "Hello there :wave:"), alice.clone()),
+        ),
+        (
+            "Own",
+            MessageBubbleCase {
+                own: true,
+                ..MessageBubbleCase::new(
+                    message(3, "me", "Works on my machine"),
+                    profile("me", Role::Member, false, false),
+                )
+            },
+        ),
+        (
+            "Reply",
+            MessageBubbleCase {
+                reply: Some(reply),
+                ..MessageBubbleCase::new(
+                    MessageData {
+                        reply_to: Some(1),
+                        ..message(4, "bob", "Yes, it's quick")
+                    },
+                    profile("bob", Role::Moderator, false, true),
+                )
+            },
+        ),
+        (
+            "Edited and pinned",
+            MessageBubbleCase {
+                pinned: true,
+                ..MessageBubbleCase::new(
+                    MessageData {
+                        edited_at: TIME,
+                        ..message(5, "carol", "Meeting moved to *3pm*")
+                    },
+                    profile("carol", Role::Admin, false, false),
+                )
+            },
+        ),
+        (
+            "Mentioning you",
+            MessageBubbleCase {
+                mention: Some(Mention::Direct),
+                ..MessageBubbleCase::new(message(6, "alice", "@me can you review?"), alice.clone())
+            },
+        ),
+        (
+            "Broadcast",
+            MessageBubbleCase {
+                mention: Some(Mention::Broadcast),
+                ..MessageBubbleCase::new(
+                    message(7, "carol", "@everyone release at 5"),
+                    profile("carol", Role::Admin, false, false),
+                )
+            },
+        ),
+        (
+            "Selected",
+            MessageBubbleCase {
+                selected: true,
+                ..MessageBubbleCase::new(
+                    message(8, "alice", "- [x] write tests\n- [ ] ship it"),
+                    alice.clone(),
+                )
+            },
+        ),
+        (
+            "Bot embed",
+            MessageBubbleCase::new(
+                MessageData {
+                    attachment: Some(Attachment::Embed {
+                        title: Some("Build #42 passed".into()),
+                        fields: vec![
+                            EmbedField {
+                                name: "Branch".into(),
+                                value: "main".into(),
+                                inline: true,
+                            },
+                            EmbedField {
+                                name: "Took".into(),
+                                value: "3m 12s".into(),
+                                inline: true,
+                            },
+                        ],
+                    }),
+                    ..message(9, "ci", "")
+                },
+                profile("ci", Role::Member, true, false),
+            ),
+        ),
+    ];
+    let reactions = Some(Rc::new(vec![
+        Reaction {
+            emoji: "👍".into(),
+            users: vec!["alice".into(), "me".into()],
+        },
+        Reaction {
+            emoji: "🎉".into(),
+            users: vec!["bob".into()],
+        },
+    ]));
+    let outbox = Rc::new(vec![
+        outgoing(1, "Sending this now", None),
+        outgoing(
+            2,
+            "This one didn't make it",
+            Some("The connection dropped before this was sent."),
+        ),
+    ]);
+    let notices = [
+        SystemEvent::Joined {
+            user: "dave".into(),
+        },
+        SystemEvent::Left {
+            user: "erin".into(),
+        },
+        SystemEvent::Renamed {
+            from: "random".into(),
+            to: DEFAULT_CHANNEL.into(),
+        },
+    ];
+
+    html! {
+        <div class={classes!("p-4", classes.main_bg, classes.main_text)}>
+            <h2 class="mb-4 text-lg font-bold">{theme.label()}</h2>
+            <Section title="Message bubbles">
+                { for bubbles.into_iter().map(|(label, case)| html! {
+                    <div title={label}>{ case.render(BubbleStyle::Classic) }</div>
+                }) }
+            </Section>
+            { for BubbleStyle::ALL.iter().map(|&style| html! {
+                <Section title={style.label()}>
+                    { MessageBubbleCase::new(message(10, "alice", "Someone else's message"), profile("alice", Role::Member, false, false)).render(style) }
+                    { MessageBubbleCase { own: true, ..MessageBubbleCase::new(message(11, "me", "One of mine"), profile("me", Role::Member, false, false)) }.render(style) }
+                </Section>
+            }) }
+            <Section title="Reactions">
+                <MessageBubble message={Rc::new(message(12, "bob", "Lunch?"))} author={profile("bob", Role::Moderator, false, true)} reactions={reactions.clone()} />
+                <ReactionBar {reactions} on_toggle={Callback::noop()} />
+            </Section>
+            <Section title="Badges">
+                <div class="flex gap-4">
+                    { for [profile("admin", Role::Admin, false, false), profile("mod", Role::Moderator, false, false), profile("bot", Role::Member, true, false), profile("verified", Role::Member, false, true)]
+                        .into_iter()
+                        .map(|user| html! { <span>{user.name.clone()}<Badges {user} /></span> }) }
+                </div>
+            </Section>
+            <Section title="System lines">
+                { for notices.into_iter().map(|event| html! { <SystemLine notice={SystemNotice { time: TIME, event }} /> }) }
+            </Section>
+            <Section title="Typing">
+                <TypingIndicator names={vec!["alice".to_string()]} />
+                <TypingIndicator names={vec!["alice".to_string(), "bob".to_string()]} />
+                <TypingIndicator names={["alice", "bob", "carol", "dave"].iter().map(|n| n.to_string()).collect::<Vec<_>>()} />
+            </Section>
+            <Section title="Connection">
+                <div class="flex gap-4">
+                    <ConnectionQuality connection={ConnectionState::Connected} latency={Some(40)} />
+                    <ConnectionQuality connection={ConnectionState::Connected} latency={Some(900)} />
+                    <ConnectionQuality connection={ConnectionState::Connecting} latency={None} />
+                    <ConnectionQuality connection={ConnectionState::Disconnected} latency={None} />
+                </div>
+            </Section>
+            <Section title="Outbox">
+                <Outbox {outbox} channel={DEFAULT_CHANNEL} on_retry={Callback::noop()} on_discard={Callback::noop()} />
+            </Section>
+            <Section title="Loading">
+                <Skeleton rows={2} avatar={classes!("w-8", "h-8")} testid="gallery-skeleton" />
+            </Section>
+            <Section title="Emoji picker">
+                <div class="w-80">
+                    <EmojiPicker on_select={Callback::noop()} />
+                </div>
+            </Section>
+            <Section title="Modal">
+                <button onclick={open_modal}
+                    class={classes!("px-3", "py-1", "rounded", classes.button_bg)}>{"Open modal"}</button>
+                if *modal_open {
+                    <Modal title="A modal" on_close={close_modal}>
+                        <p class="text-sm">{"Modals trap focus and close on Escape."}</p>
+                    </Modal>
+                }
+            </Section>
+        </div>
+    }
+}
+
+/// Props for one bubble in the gallery, with the states worth showing.
+struct MessageBubbleCase {
+    message: MessageData,
+    author: UserProfile,
+    own: bool,
+    pinned: bool,
+    selected: bool,
+    mention: Option<Mention>,
+    reply: Option<Rc<MessageData>>,
+}
+
+impl MessageBubbleCase {
+    fn new(message: MessageData, author: UserProfile) -> Self {
+        Self {
+            message,
+            author,
+            own: false,
+            pinned: false,
+            selected: false,
+            mention: None,
+            reply: None,
+        }
+    }
+
+    fn render(self, bubble_style: BubbleStyle) -> Html {
+        html! {
+            <MessageBubble message={Rc::new(self.message)} author={self.author} presence={Some(Presence::Online)}
+                own={self.own} pinned={self.pinned} selected={self.selected} mention={self.mention} reply={self.reply}
+                {bubble_style} />
+        }
+    }
+}
+
+/// The gallery page: every specimen once per theme, side by side on wide screens.
+#[function_component(Gallery)]
+pub fn gallery() -> Html {
+    html! {
+        <div data-testid="gallery" class="w-full h-full overflow-auto grid lg:grid-cols-3">
+            { for Theme::ALL.iter().map(|theme| html! {
+                <ContextProvider<Theme> context={theme.clone()}>
+                    <Specimens />
+                </ContextProvider<Theme>>
+            }) }
+        </div>
+    }
+}
@@ -0,0 +1,38 @@
+use yew::prelude::*;
+use yewchat_protocol::InviteInfo;
+
+use crate::services::invite;
+use crate::theme::use_theme;
+
+#[derive(Properties, PartialEq)]
+pub struct InvitePanelProps {
+    pub invite: InviteInfo,
+    pub on_close: Callback<()>,
+}
+
+/// The invite link for a channel, with a QR code for opening it on a phone.
+#[function_component(InvitePanel)]
+pub fn invite_panel(props: &InvitePanelProps) -> Html {
+    let classes = use_theme().classes();
+    let link = invite::link(&props.invite);
+    let qr = invite::qr_data_url(&link);
+    let on_close = props.on_close.reform(|_: MouseEvent| ());
+    let select_all = Callback::from(|e: FocusEvent| {
+        let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+        input.select();
+    });
+
+    html! {
+        <div data-testid="invite-panel" class={classes!("absolute", "right-3", "top-16", "w-72", "p-3", "rounded-lg", "shadow-lg", "border", "z-20", "text-sm", classes.popover_bg, classes.main_text)}>
+            <div class="flex justify-between items-center mb-2">
+                <span class="font-bold">{format!("Invite to #{}", props.invite.channel)}</span>
+                <button data-testid="invite-close" title="Close" onclick={on_close}>{"✕"}</button>
+            </div>
+            <input data-testid="invite-link" type="text" readonly=true value={link} onfocus={select_all}
+                class={classes!("w-full", "px-2", "py-1", "border", "rounded", "text-xs", classes.border, classes.input_bg)} />
+            if let Some(src) = qr {
+                <img data-testid="invite-qr" src={src} alt="QR code for the invite link" class="mx-auto mt-3 w-48 h-48 bg-white" />
+            }
+        </div>
+    }
+}
@@ -0,0 +1,60 @@
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+use crate::services::invite;
+use crate::state::ChatAction;
+use crate::store::use_store;
+use crate::Route;
+use crate::User;
+
+/// Landing page for invite links: picks a name, then opens the chat in the invited channel.
+#[function_component(Join)]
+pub fn join() -> Html {
+    let username = use_state(String::new);
+    let user = use_context::<User>().expect("No context found.");
+    let store = use_store();
+    let invite = invite::from_query();
+
+    let oninput = {
+        let username = username.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            username.set(input.value());
+        })
+    };
+
+    let invite = match invite {
+        Some(invite) => invite,
+        None => {
+            return html! {
+                <div data-testid="join-invalid" class="bg-gray-800 flex w-screen justify-center items-center text-gray-200">
+                    {"This invite link is incomplete. Ask for a new one."}
+                </div>
+            }
+        }
+    };
+
+    let onclick = {
+        let username = username.clone();
+        let invite = invite.clone();
+        Callback::from(move |_| {
+            *user.username.borrow_mut() = (*username).clone();
+            *user.spectator.borrow_mut() = false;
+            *user.invite.borrow_mut() = Some(invite.token.clone());
+            store.dispatch(ChatAction::SwitchChannel(invite.channel.clone()));
+        })
+    };
+
+    html! {
+        <div data-testid="join-screen" class="bg-gray-800 flex w-screen">
+            <div class="container mx-auto flex flex-col justify-center items-center">
+                <div class="text-gray-200 text-xl mb-2">{format!("You've been invited to #{}", invite.channel)}</div>
+                <form class="m-4 flex">
+                    <input {oninput} class="rounded-l-lg p-4 border-t mr-0 border-b border-l text-gray-800 border-gray-200 bg-white" placeholder="Username"/>
+                    <Link<Route> to={Route::Chat}> <button data-testid="join-button" {onclick} disabled={username.is_empty()} class="px-8 rounded-r-lg bg-violet-600 text-white font-bold p-4 uppercase border-violet-600 border-t border-b border-r">{"Join"}</button></Link<Route>>
+                </form>
+            </div>
+        </div>
+    }
+}
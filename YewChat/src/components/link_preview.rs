@@ -0,0 +1,98 @@
+use gloo::storage::{LocalStorage, Storage};
+use yew::prelude::*;
+
+use crate::components::external_link::ExternalLink;
+use crate::links;
+use crate::state::ChatAction;
+use crate::store::{use_data_saver, Store};
+use crate::theme::use_theme;
+
+/// Ids of the messages whose previews this browser keeps collapsed.
+const COLLAPSED_KEY: &str = "yewchat.collapsed_previews";
+
+fn is_collapsed(id: u64) -> bool {
+    LocalStorage::get::<Vec<u64>>(COLLAPSED_KEY).is_ok_and(|ids| ids.contains(&id))
+}
+
+fn remember_collapsed(id: u64, on: bool) {
+    let mut ids: Vec<u64> = LocalStorage::get(COLLAPSED_KEY).unwrap_or_default();
+    links::set_collapsed(&mut ids, id, on);
+    if let Err(e) = LocalStorage::set(COLLAPSED_KEY, ids) {
+        log::warn!("failed to save collapsed previews: {}", e);
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct LinkPreviewProps {
+    /// The message the link is in; 0 for servers that don't number messages, whose previews
+    /// can't be remembered as collapsed.
+    pub message_id: u64,
+    pub url: String,
+}
+
+/// A card under a message naming the site its link goes to, which can be folded away.
+/// Nothing is shown for sites the user turned previews off for.
+#[function_component(LinkPreview)]
+pub fn link_preview(props: &LinkPreviewProps) -> Html {
+    let classes = use_theme().classes();
+    let store = use_context::<Store>();
+    let data_saver = use_data_saver();
+    let id = props.message_id;
+    let collapsed = use_state(|| id != 0 && is_collapsed(id));
+
+    let Some(host) = links::host(&props.url) else {
+        return html! {};
+    };
+    if let Some(store) = &store {
+        if !store.preferences.previews(&host) {
+            return html! {};
+        }
+    }
+    let toggle = {
+        let collapsed = collapsed.clone();
+        Callback::from(move |_: MouseEvent| {
+            if id != 0 {
+                remember_collapsed(id, !*collapsed);
+            }
+            collapsed.set(!*collapsed);
+        })
+    };
+    let stop_previewing = store.map(|store| {
+        let host = host.clone();
+        Callback::from(move |_: MouseEvent| {
+            let mut preferences = store.preferences.clone();
+            preferences.unpreviewed_domains.insert(host.clone());
+            store.dispatch(ChatAction::SetPreferences(preferences));
+        })
+    });
+    let path = web_sys::Url::new(&props.url)
+        .map(|url| format!("{}{}", url.pathname(), url.search()))
+        .unwrap_or_default();
+
+    html! {
+        <div data-testid="link-preview" data-collapsed={(*collapsed).then_some("true")}
+            class={classes!("mt-2", "rounded", "border", "text-xs", "max-w-sm", classes.border, classes.panel_bg)}>
+            <div class="flex items-center px-2 py-1">
+                <button data-testid="link-preview-toggle" onclick={toggle} aria-expanded={(!*collapsed).to_string()}
+                    title={if *collapsed { "Show preview" } else { "Hide preview" }}
+                    class={classes!("mr-1", "px-1", "rounded", classes.hover_bg)}>
+                    {if *collapsed { "▸" } else { "▾" }}
+                </button>
+                <span class="flex-1 truncate font-bold">{host.clone()}</span>
+                if let Some(onclick) = stop_previewing {
+                    <button data-testid="link-preview-off" {onclick} title={format!("Don't preview links to {}", host)}
+                        class={classes!("px-1", "rounded", classes.hover_bg, classes.muted_text)}>{"✕"}</button>
+                }
+            </div>
+            if !*collapsed {
+                <ExternalLink href={props.url.clone()} testid="link-preview-card" class={classes!("flex", "items-center", "px-2", "pb-2")}>
+                    // Site icons come from the site itself, so data saver leaves them out.
+                    if !data_saver {
+                        <img src={format!("https://{}/favicon.ico", host)} alt="" loading="lazy" class="w-6 h-6 mr-2 shrink-0" />
+                    }
+                    <span class={classes!("truncate", classes.muted_text)}>{path}</span>
+                </ExternalLink>
+            }
+        </div>
+    }
+}
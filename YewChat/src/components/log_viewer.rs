@@ -0,0 +1,81 @@
+use gloo::events::EventListener;
+use log::LevelFilter;
+use wasm_bindgen::JsCast;
+use web_sys::{HtmlSelectElement, KeyboardEvent};
+use yew::prelude::*;
+
+use crate::flags::{use_flag, Flag};
+use crate::logging;
+use crate::time::use_time_format;
+
+/// Hidden overlay with the buffered log, opened with Ctrl+Shift+L.
+#[function_component(LogViewer)]
+pub fn log_viewer() -> Html {
+    let enabled = use_flag(Flag::LogViewer);
+    let open = use_state(|| false);
+    let entries = use_state(Vec::new);
+    let time_format = use_time_format();
+
+    {
+        let open = open.clone();
+        let entries = entries.clone();
+        use_effect_with_deps(
+            move |_| {
+                let window = web_sys::window().expect("window");
+                let listener = EventListener::new(&window, "keydown", move |e| {
+                    if !enabled {
+                        return;
+                    }
+                    let e = e.dyn_ref::<KeyboardEvent>().expect("keyboard event");
+                    if e.ctrl_key() && e.shift_key() && e.key().eq_ignore_ascii_case("l") {
+                        e.prevent_default();
+                        entries.set(logging::recent());
+                        open.set(!*open);
+                    }
+                });
+                move || drop(listener)
+            },
+            (),
+        );
+    }
+
+    if !*open {
+        return html! {};
+    }
+
+    let refresh = {
+        let entries = entries.clone();
+        Callback::from(move |_| entries.set(logging::recent()))
+    };
+    let close = {
+        let open = open.clone();
+        Callback::from(move |_| open.set(false))
+    };
+    let set_level = Callback::from(|e: Event| {
+        let select: HtmlSelectElement = e.target_unchecked_into();
+        if let Ok(level) = select.value().parse::<LevelFilter>() {
+            logging::set_level(level);
+        }
+    });
+    let text = entries
+        .iter()
+        .map(|e| format!("{} {}", time_format.time(e.time as u64), e))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    html! {
+        <div data-testid="log-viewer" class="fixed inset-x-0 bottom-0 h-1/2 z-50 flex flex-col bg-gray-900 text-gray-100 text-xs font-mono shadow-2xl">
+            <div class="flex items-center gap-2 p-2 border-b border-gray-700">
+                <span class="font-bold grow">{format!("Log ({} entries)", entries.len())}</span>
+                <select onchange={set_level} class="bg-gray-800 p-1 rounded">
+                    { for ["error", "warn", "info", "debug", "trace"].iter().map(|level| html! {
+                        <option value={*level} selected={log::max_level().to_string().eq_ignore_ascii_case(level)}>{*level}</option>
+                    }) }
+                </select>
+                <button onclick={refresh} class="px-2 py-1 rounded bg-gray-700">{"Refresh"}</button>
+                <button onclick={close} class="px-2 py-1 rounded bg-gray-700">{"Close"}</button>
+            </div>
+            <textarea readonly=true class="grow p-2 bg-gray-900 resize-none outline-none" value={text} />
+        </div>
+    }
+}
@@ -20,18 +20,37 @@ pub fn login() -> Html {
         })
     };
 
-    let onclick = {
+    let key = use_state(String::new);
+    let set_key = {
+        let key = key.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            key.set(input.value());
+        })
+    };
+
+    let join = |spectator: bool| {
         let username = username.clone();
+        let key = key.clone();
         let user = user.clone();
-        Callback::from(move |_| *user.username.borrow_mut() = (*username).clone())
+        Callback::from(move |_| {
+            *user.username.borrow_mut() = (*username).clone();
+            *user.spectator.borrow_mut() = spectator;
+            *user.key.borrow_mut() = Some((*key).clone()).filter(|k| !k.is_empty());
+        })
     };
+    let onclick = join(false);
+    let watch = join(true);
 
     html! {
         <div class="bg-gray-800 flex w-screen">
             <div class="container mx-auto flex flex-col justify-center items-center	">
                 <form class="m-4 flex">
                     <input {oninput} class="rounded-l-lg p-4 border-t mr-0 border-b border-l text-gray-800 border-gray-200 bg-white" placeholder="Username"/>
+                    <input data-testid="account-key" oninput={set_key} type="password" title="Only needed for names the server keeps for an account"
+                        class="p-4 border-t mr-0 border-b border-l text-gray-800 border-gray-200 bg-white" placeholder="Account key (optional)"/>
                     <Link<Route> to={Route::Chat}> <button {onclick} disabled={username.len()<1} class="px-8 rounded-r-lg bg-violet-600	  text-white font-bold p-4 uppercase border-violet-600 border-t border-b border-r" >{"Go Chatting!"}</button></Link<Route>>
+                    <Link<Route> to={Route::Chat}> <button data-testid="watch-button" onclick={watch} disabled={username.len()<1} title="Watch without posting" class="ml-2 px-4 rounded-lg text-gray-300 font-bold p-4 uppercase border border-gray-500" >{"Watch"}</button></Link<Route>>
                 </form>
             </div>
         </div>
@@ -0,0 +1,155 @@
+use yew::prelude::*;
+
+use crate::components::context_menu::MenuItem;
+use crate::components::reaction_bar::QUICK_REACTIONS;
+use crate::theme::use_theme;
+
+/// Something the user can do to a single message.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MessageAction {
+    React(String),
+    Reply,
+    Copy,
+    /// Puts a deep link to the message on the clipboard.
+    CopyLink,
+    CopyId,
+    Pin,
+    Unpin,
+    Delete,
+}
+
+impl MessageAction {
+    pub fn icon(&self) -> &'static str {
+        match self {
+            MessageAction::React(_) => "☺",
+            MessageAction::Reply => "↩️",
+            MessageAction::Copy => "📋",
+            MessageAction::CopyLink => "🔗",
+            MessageAction::CopyId => "#",
+            MessageAction::Pin => "📌",
+            MessageAction::Unpin => "📍",
+            MessageAction::Delete => "🗑️",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            MessageAction::React(_) => "Add reaction",
+            MessageAction::Reply => "Reply",
+            MessageAction::Copy => "Copy text",
+            MessageAction::CopyLink => "Copy link",
+            MessageAction::CopyId => "Copy message ID",
+            MessageAction::Pin => "Pin",
+            MessageAction::Unpin => "Unpin",
+            MessageAction::Delete => "Delete",
+        }
+    }
+}
+
+/// Actions offered on a message. Only messages the server numbered can be reacted to, replied
+/// to or moderated; spectators can only copy.
+pub fn available(
+    numbered: bool,
+    spectator: bool,
+    moderator: bool,
+    pinned: bool,
+) -> Vec<MessageAction> {
+    let mut actions = Vec::new();
+    if numbered && !spectator {
+        actions.push(MessageAction::React(String::new()));
+        actions.push(MessageAction::Reply);
+    }
+    actions.push(MessageAction::Copy);
+    if numbered && moderator {
+        actions.push(if pinned {
+            MessageAction::Unpin
+        } else {
+            MessageAction::Pin
+        });
+        actions.push(MessageAction::Delete);
+    }
+    actions
+}
+
+/// The same actions for a context menu, with extras too niche for the toolbar. `React` is
+/// left out; the menu shows quick reactions separately.
+pub fn menu_items(
+    actions: &[MessageAction],
+    numbered: bool,
+    on_action: &Callback<MessageAction>,
+) -> Vec<MenuItem> {
+    let extras = [MessageAction::CopyLink, MessageAction::CopyId];
+    actions
+        .iter()
+        .filter(|a| !matches!(a, MessageAction::React(_)))
+        .chain(extras.iter().filter(|_| numbered))
+        .map(|action| {
+            let chosen = action.clone();
+            let item = MenuItem::new(
+                action.icon(),
+                action.label(),
+                on_action.reform(move |_| chosen.clone()),
+            );
+            if *action == MessageAction::Delete {
+                item.danger()
+            } else {
+                item
+            }
+        })
+        .collect()
+}
+
+#[derive(Properties, PartialEq)]
+pub struct MessageActionsProps {
+    /// From `available`; `React` opens the quick reaction picker.
+    pub actions: Vec<MessageAction>,
+    pub on_action: Callback<MessageAction>,
+}
+
+/// Toolbar shown while hovering a message.
+#[function_component(MessageActions)]
+pub fn message_actions(props: &MessageActionsProps) -> Html {
+    let classes = use_theme().classes();
+    let show_quick = use_state(|| false);
+
+    html! {
+        <div data-testid="message-actions" role="toolbar" aria-label="Message actions"
+            class={classes!("flex", "items-center", "rounded-lg", "border", "shadow", "text-sm", classes.popover_bg)}>
+            if *show_quick {
+                {
+                    QUICK_REACTIONS.iter().map(|&emoji| {
+                        let on_action = props.on_action.clone();
+                        let show_quick = show_quick.clone();
+                        let onclick = Callback::from(move |_| {
+                            show_quick.set(false);
+                            on_action.emit(MessageAction::React(emoji.to_string()));
+                        });
+                        html! {
+                            <button data-testid="quick-reaction" {onclick} class={classes!("px-1", classes.hover_bg)}>{emoji}</button>
+                        }
+                    }).collect::<Html>()
+                }
+            }
+            {
+                props.actions.iter().map(|action| {
+                    let onclick = match action {
+                        MessageAction::React(_) => {
+                            let show_quick = show_quick.clone();
+                            Callback::from(move |_| show_quick.set(!*show_quick))
+                        }
+                        action => {
+                            let action = action.clone();
+                            props.on_action.reform(move |_: MouseEvent| action.clone())
+                        }
+                    };
+                    html! {
+                        <button data-testid="message-action" title={action.label()} aria-label={action.label()} {onclick}
+                            class={classes!("px-1.5", "py-0.5", "rounded", classes.hover_bg)}>
+                            {action.icon()}
+                        </button>
+                    }
+                }).collect::<Html>()
+            }
+        </div>
+    }
+}
@@ -0,0 +1,290 @@
+use std::rc::Rc;
+
+use yew::prelude::*;
+use yewchat_protocol::{MessageData, Reaction};
+
+use crate::components::attachment::{
+    filter_reason, AnimatedImage, AttachmentView, ClickToLoad, Reveal,
+};
+use crate::components::avatar::Avatar;
+use crate::components::badges::Badges;
+use crate::components::edit_history::EditHistory;
+use crate::components::link_preview::LinkPreview;
+use crate::components::message_actions::{MessageAction, MessageActions};
+use crate::components::message_text::MessageText;
+use crate::components::reaction_bar::ReactionBar;
+use crate::components::text_prompt::TextPrompt;
+use crate::links;
+use crate::mentions::Mention;
+use crate::state::{BubbleStyle, Presence, UserProfile};
+use crate::services::emoji::{self, EmojiDataset};
+use crate::store::use_media_hidden;
+use crate::theme::use_theme;
+use crate::touch;
+
+/// How far a bubble has to be swiped right to reply to it.
+const SWIPE_REPLY_PX: i32 = 60;
+/// The bubble stops following the finger past this.
+const SWIPE_MAX_PX: i32 = 80;
+
+#[derive(Properties, PartialEq)]
+pub struct MessageBubbleProps {
+    pub message: Rc<MessageData>,
+    pub author: UserProfile,
+    #[prop_or_default]
+    pub reactions: Option<Rc<Vec<Reaction>>>,
+    /// Toggles a reaction, called with the message id and emoji; only offered for messages
+    /// the server numbered.
+    #[prop_or_default]
+    pub on_react: Option<Callback<(u64, String)>>,
+    /// Offered on the user's own messages; called with the message id and new text.
+    #[prop_or_default]
+    pub on_edit: Option<Callback<(u64, String)>>,
+    #[prop_or_default]
+    pub pinned: bool,
+    /// Set when the message pings the current user.
+    #[prop_or_default]
+    pub mention: Option<Mention>,
+    /// The mention is of a kind the user suppressed, so it's marked more faintly.
+    #[prop_or_default]
+    pub mention_suppressed: bool,
+    /// Highlighted as the target of a message link.
+    #[prop_or_default]
+    pub focused: bool,
+    /// Under the keyboard selection cursor.
+    #[prop_or_default]
+    pub selected: bool,
+    /// Opens the context menu of the message it's called with.
+    #[prop_or_default]
+    pub on_context_menu: Callback<(MouseEvent, Rc<MessageData>)>,
+    /// Resolves `:shortcode:`s once loaded; text shows verbatim until then.
+    #[prop_or_default]
+    pub emoji: Option<EmojiDataset>,
+    /// Offered in the hover toolbar.
+    #[prop_or_default]
+    pub actions: Vec<MessageAction>,
+    #[prop_or_default]
+    pub on_action: Callback<(Rc<MessageData>, MessageAction)>,
+    /// The message this one answers, if it is loaded.
+    #[prop_or_default]
+    pub reply: Option<Rc<MessageData>>,
+    /// The author's current presence, shown on their avatar.
+    #[prop_or_default]
+    pub presence: Option<Presence>,
+    /// Called with the message id, a checklist item's index and its new state, if the user
+    /// may tick them.
+    #[prop_or_default]
+    pub on_toggle_task: Option<Callback<(u64, usize, bool)>>,
+    #[prop_or_default]
+    pub bubble_style: BubbleStyle,
+    /// Sent by the current user; drawn on the right in `BubbleStyle::Sides`.
+    #[prop_or_default]
+    pub own: bool,
+}
+
+#[function_component(MessageBubble)]
+pub fn message_bubble(props: &MessageBubbleProps) -> Html {
+    let m = &props.message;
+    let theme = use_theme();
+    let classes = theme.classes();
+    let show_history = use_state(|| false);
+    let toggle_history = {
+        let show_history = show_history.clone();
+        Callback::from(move |_| show_history.set(!*show_history))
+    };
+    // The parent hands every row the same callbacks; this row's message goes along with them.
+    let id = m.id;
+    let on_action = {
+        let m = m.clone();
+        props.on_action.reform(move |action| (m.clone(), action))
+    };
+    let oncontextmenu = {
+        let m = m.clone();
+        props
+            .on_context_menu
+            .reform(move |e: MouseEvent| (e, m.clone()))
+    };
+    let on_toggle_task = props.on_toggle_task.as_ref().map(|on_toggle_task| {
+        on_toggle_task.reform(move |(index, done): (usize, bool)| (id, index, done))
+    });
+    let editing = use_state(|| false);
+    let edit = props.on_edit.as_ref().map(|_| {
+        let editing = editing.clone();
+        Callback::from(move |_: MouseEvent| editing.set(true))
+    });
+    let close_edit = {
+        let editing = editing.clone();
+        Callback::from(move |_| editing.set(false))
+    };
+    let save_edit = props.on_edit.clone().map(|on_edit| {
+        let editing = editing.clone();
+        let current = m.message.clone();
+        Callback::from(move |text: String| {
+            if !text.is_empty() && text != current {
+                on_edit.emit((id, text));
+            }
+            editing.set(false);
+        })
+    });
+    // Swiping right on a touch screen replies. A mostly vertical drag is a scroll and cancels.
+    let swipe_start = use_mut_ref(|| None::<(i32, i32)>);
+    let swipe = use_state(|| 0);
+    let can_reply = props.actions.contains(&MessageAction::Reply);
+    let ontouchstart = {
+        let swipe_start = swipe_start.clone();
+        Callback::from(move |e: TouchEvent| {
+            if can_reply {
+                *swipe_start.borrow_mut() = touch::point(&e);
+            }
+        })
+    };
+    let ontouchmove = {
+        let swipe_start = swipe_start.clone();
+        let swipe = swipe.clone();
+        Callback::from(move |e: TouchEvent| {
+            let (start, now) = match (*swipe_start.borrow(), touch::point(&e)) {
+                (Some(start), Some(now)) => (start, now),
+                _ => return,
+            };
+            let (dx, dy) = (now.0 - start.0, now.1 - start.1);
+            if dy.abs() > dx.abs() {
+                *swipe_start.borrow_mut() = None;
+                swipe.set(0);
+            } else {
+                swipe.set(dx.clamp(0, SWIPE_MAX_PX));
+            }
+        })
+    };
+    let ontouchend = {
+        let swipe = swipe.clone();
+        let on_action = on_action.clone();
+        Callback::from(move |_: TouchEvent| {
+            *swipe_start.borrow_mut() = None;
+            if *swipe >= SWIPE_REPLY_PX {
+                on_action.emit(MessageAction::Reply);
+            }
+            swipe.set(0);
+        })
+    };
+    let highlight = props.mention.map(|mention| match (mention, props.mention_suppressed) {
+        (Mention::Direct, false) => "border-l-4 border-amber-400",
+        (Mention::Broadcast, false) => "border-l-4 border-violet-500",
+        (Mention::Direct, true) => "border-l-2 border-amber-200",
+        (Mention::Broadcast, true) => "border-l-2 border-violet-200",
+    });
+    // Bots get a stripe of their own so automated posts stand apart; a mention still wins.
+    let highlight = highlight.or(props.author.bot.then_some("border-l-4 border-indigo-500"));
+    let media_hidden = use_media_hidden(&m.from);
+    let text = emoji::with_emoji(&m.message, props.emoji.as_deref());
+    // A few emoji on their own are shown large, straight on the background.
+    let jumbo = m.attachment.is_none() && emoji::jumbo_count(&text).is_some();
+    let preview = (!m.message.ends_with(".gif"))
+        .then(|| links::first_link(&m.message))
+        .flatten();
+    let bubble_bg = (!jumbo).then_some(classes.bubble_bg);
+    let shape = match props.bubble_style {
+        BubbleStyle::Classic => classes!(
+            "w-3/6",
+            "m-8",
+            bubble_bg,
+            "rounded-tl-lg",
+            "rounded-tr-lg",
+            "rounded-br-lg"
+        ),
+        BubbleStyle::Flat => classes!("w-full", "px-5", classes.hover_bg),
+        BubbleStyle::Sides if props.own => classes!(
+            "w-3/6",
+            "mx-8",
+            "my-4",
+            "ml-auto",
+            "flex-row-reverse",
+            (!jumbo).then_some(classes.selected_bg),
+            "rounded-tl-lg",
+            "rounded-tr-lg",
+            "rounded-bl-lg"
+        ),
+        BubbleStyle::Sides => classes!(
+            "w-3/6",
+            "mx-8",
+            "my-4",
+            bubble_bg,
+            "rounded-tl-lg",
+            "rounded-tr-lg",
+            "rounded-br-lg"
+        ),
+    };
+
+    html! {
+        <div data-testid="message-bubble" {oncontextmenu} {ontouchstart} {ontouchmove} ontouchend={ontouchend.clone()} ontouchcancel={ontouchend}
+            style={format!("transform: translateX({}px)", *swipe)} id={format!("message-{}", m.id)} tabindex="-1" data-focused={props.focused.then_some("true")} data-selected={props.selected.then_some("true")} data-mention={props.mention.map(|m| format!("{:?}", m).to_lowercase())} data-bubble-style={format!("{:?}", props.bubble_style).to_lowercase()} data-bot={props.author.bot.then_some("true")} class={classes!("flex", "items-end", shape, "relative", "group", highlight, props.focused.then_some("ring-2 ring-amber-400 transition"), "outline-none", props.selected.then_some("ring-2 ring-violet-500"), (*swipe == 0).then_some("transition-transform"))}>
+            if *swipe > 0 {
+                <div data-testid="swipe-reply" aria-hidden="true" class="absolute -left-8 top-1/2 -translate-y-1/2"
+                    style={format!("opacity: {:.2}", (*swipe as f32 / SWIPE_REPLY_PX as f32).min(1.0))}>
+                    {"↩️"}
+                </div>
+            }
+            if !props.actions.is_empty() {
+                <div class="absolute -top-4 right-2 hidden group-hover:block z-10">
+                    <MessageActions actions={props.actions.clone()} {on_action} />
+                </div>
+            }
+            <Avatar name={m.from.clone()} class={classes!("w-8", "h-8", "rounded-full", "m-3")} presence={props.presence} />
+            <div class="p-3 min-w-0">
+                <div data-testid="message-author" class={classes!("text-sm", if props.author.known { theme.name_color(&m.from) } else { classes.muted_text })}
+                    title={(!props.author.known).then(|| "No longer in this chat")}>
+                    {m.from.clone()}
+                    <Badges user={props.author.clone()} />
+                    if props.pinned {
+                        <span data-testid="pinned-marker" title="Pinned" class="ml-2 text-xs">{"📌"}</span>
+                    }
+                    if let Some(onclick) = edit {
+                        <button data-testid="edit-message" title="Edit" {onclick} class={classes!("ml-2", "text-xs", classes.muted_text)}>{"✏️"}</button>
+                    }
+                </div>
+                if let Some(reply) = &props.reply {
+                    <div data-testid="reply-quote" class={classes!("text-xs", "italic", "border-l-2", "pl-2", "my-1", "truncate", classes.border, classes.muted_text)}>
+                        {format!("↪ {}: {}", reply.from, reply.message)}
+                    </div>
+                }
+                <div data-testid="message-text" class={classes!("text-xs", classes.secondary_text)}>
+                    if m.message.ends_with(".gif") {
+                        <ClickToLoad what="GIF">
+                            if media_hidden {
+                                <Reveal reason={filter_reason(&m.from)}>
+                                    <div class="mt-3"><AnimatedImage url={m.message.clone()} /></div>
+                                </Reveal>
+                            } else {
+                                <div class="mt-3"><AnimatedImage url={m.message.clone()} /></div>
+                            }
+                        </ClickToLoad>
+                    } else if jumbo {
+                        <p data-testid="jumbo-emoji" class="text-5xl leading-tight">{text}</p>
+                    } else {
+                        <MessageText {text} {on_toggle_task} />
+                    }
+                </div>
+                if let Some(url) = preview {
+                    <LinkPreview message_id={m.id} url={url.to_string()} />
+                }
+                if let Some(attachment) = &m.attachment {
+                    <AttachmentView attachment={attachment.clone()} from={m.from.clone()} />
+                }
+                if m.edited_at != 0 {
+                    <button data-testid="edited-label" onclick={toggle_history} class={classes!("text-xs", "italic", classes.muted_text)}>
+                        {"(edited)"}
+                    </button>
+                    if *show_history {
+                        <EditHistory message={m.clone()} />
+                    }
+                }
+                if let Some(on_react) = &props.on_react {
+                    <ReactionBar reactions={props.reactions.clone()} on_toggle={on_react.reform(move |emoji: String| (id, emoji))} />
+                }
+            </div>
+            if let (true, Some(on_submit)) = (*editing, save_edit) {
+                <TextPrompt title="Edit message" value={m.message.clone()} action="Save" testid="edit-message-dialog"
+                    {on_submit} on_close={close_edit} />
+            }
+        </div>
+    }
+}
@@ -0,0 +1,586 @@
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use gloo::timers::callback::Timeout;
+use wasm_bindgen::JsCast;
+use web_sys::{
+    HtmlElement, Node, ScrollBehavior, ScrollIntoViewOptions, ScrollLogicalPosition,
+    ScrollToOptions,
+};
+use yew::prelude::*;
+use yewchat_protocol::{MentionSuppression, MessageData, Reaction, SystemNotice};
+
+use crate::components::compact_message::{self, CompactMessage};
+use crate::components::context_menu::ContextMenu;
+use crate::components::message_actions::{self, MessageAction};
+use crate::components::message_bubble::MessageBubble;
+use crate::components::reaction_bar::QUICK_REACTIONS;
+use crate::components::skeleton::Skeleton;
+use crate::components::system_line::{self, SystemLine};
+use crate::mentions::{mention_of, Mention};
+use crate::motion::use_reduced_motion;
+use crate::services::emoji;
+use crate::state::{BubbleStyle, DisplayMode, Presence, UserProfile};
+use crate::theme::use_theme;
+use crate::touch;
+use crate::User;
+
+#[derive(Properties, PartialEq)]
+pub struct MessageListProps {
+    pub messages: Rc<Vec<Rc<MessageData>>>,
+    /// Joins, leaves and renames, placed among the messages by time.
+    #[prop_or_default]
+    pub system: Rc<Vec<SystemNotice>>,
+    pub profiles: Rc<HashMap<String, UserProfile>>,
+    pub presence: Rc<HashMap<String, Presence>>,
+    pub reactions: Rc<HashMap<u64, Rc<Vec<Reaction>>>>,
+    pub pinned: Rc<HashSet<u64>>,
+    /// Called with the message id and emoji.
+    pub on_react: Callback<(u64, String)>,
+    /// Called with the message id and new text.
+    pub on_edit: Callback<(u64, String)>,
+    pub display_mode: DisplayMode,
+    /// How rows are drawn outside the compact display mode.
+    #[prop_or_default]
+    pub bubble_style: BubbleStyle,
+    /// Mentions only faintly marked.
+    #[prop_or_default]
+    pub suppress_mentions: MentionSuppression,
+    /// Only messages posted to this channel are shown.
+    pub channel: String,
+    /// Message to scroll to and highlight as soon as it is in the list.
+    #[prop_or_default]
+    pub focus: Option<u64>,
+    /// Called once the focused message has been shown.
+    #[prop_or_default]
+    pub on_focused: Callback<()>,
+    /// Called with the message and what to do with it, from its toolbar or context menu.
+    #[prop_or_default]
+    pub on_action: Callback<(Rc<MessageData>, MessageAction)>,
+    /// Nothing has come from the server yet; placeholders stand in for the messages.
+    #[prop_or_default]
+    pub loading: bool,
+    /// A request for older messages is in flight.
+    #[prop_or_default]
+    pub loading_older: bool,
+    /// The last request for older messages was lost; a retry row is shown at the top.
+    #[prop_or_default]
+    pub history_failed: bool,
+    /// The server may have messages older than the first one shown.
+    #[prop_or_default]
+    pub has_older: bool,
+    /// Asks for the page of messages before the first one shown.
+    #[prop_or_default]
+    pub on_load_older: Callback<()>,
+    /// First unread message; the list opens scrolled to it rather than to the bottom.
+    #[prop_or_default]
+    pub divider: Option<u64>,
+    /// Called with the message id, checklist item index and new state.
+    #[prop_or_default]
+    pub on_toggle_task: Callback<(u64, usize, bool)>,
+    /// Inline style drawing the wallpaper, if there is one.
+    #[prop_or_default]
+    pub wallpaper: Option<String>,
+}
+
+/// How long a linked message stays highlighted.
+const FOCUS_HIGHLIGHT_MS: u32 = 2_000;
+/// How far the list has to be pulled down, at the top, to load older messages.
+const PULL_REFRESH_PX: i32 = 60;
+/// Within this distance of the bottom the list counts as showing the latest messages.
+const NEAR_BOTTOM_PX: i32 = 80;
+
+/// Where the keyboard selection cursor goes for `key`, given the ids of the rows in the order
+/// shown; `None` if the key doesn't move it.
+pub fn next_cursor(ids: &[u64], cursor: Option<u64>, key: &str) -> Option<u64> {
+    let last = ids.len().checked_sub(1)?;
+    let at = cursor.and_then(|cursor| ids.iter().position(|&id| id == cursor));
+    let index = match (key, at) {
+        ("Home", _) => 0,
+        ("End", _) => last,
+        ("ArrowUp", Some(i)) => i.saturating_sub(1),
+        ("ArrowDown", Some(i)) => (i + 1).min(last),
+        // Starts from the newest message, where the reader usually is.
+        ("ArrowUp" | "ArrowDown", None) => last,
+        _ => return None,
+    };
+    Some(ids[index])
+}
+
+#[function_component(MessageList)]
+pub fn message_list(props: &MessageListProps) -> Html {
+    let classes = use_theme().classes();
+    let reduced_motion = use_reduced_motion();
+    let emoji = emoji::use_dataset();
+    let user = use_context::<User>().expect("context to be set");
+    let me = user.username.borrow().clone();
+    let online = !*user.spectator.borrow();
+    let moderator = props.profiles.get(&me).is_some_and(|p| p.role.moderates());
+    let actions_for = |m: &MessageData| {
+        message_actions::available(m.id != 0, !online, moderator, props.pinned.contains(&m.id))
+    };
+    let menu = use_state(|| None::<(i32, i32, Rc<MessageData>)>);
+    // Message under the keyboard selection cursor.
+    let cursor = use_state(|| None::<u64>);
+
+    // Pulling down while scrolled to the top loads older history, like refreshing a feed.
+    let list = use_node_ref();
+    let pull_start = use_mut_ref(|| None::<i32>);
+    let pull = use_state(|| 0);
+    let ontouchstart = {
+        let list = list.clone();
+        let pull_start = pull_start.clone();
+        let enabled = props.has_older && !props.loading_older;
+        Callback::from(move |e: TouchEvent| {
+            let at_top = list
+                .cast::<web_sys::Element>()
+                .is_some_and(|list| list.scroll_top() <= 0);
+            *pull_start.borrow_mut() = touch::point(&e)
+                .filter(|_| enabled && at_top)
+                .map(|(_, y)| y);
+        })
+    };
+    let ontouchmove = {
+        let pull_start = pull_start.clone();
+        let pull = pull.clone();
+        Callback::from(move |e: TouchEvent| {
+            if let (Some(start), Some((_, y))) = (*pull_start.borrow(), touch::point(&e)) {
+                // Resist the finger so the indicator doesn't run away from the list.
+                pull.set(((y - start) / 2).clamp(0, PULL_REFRESH_PX + 20));
+            }
+        })
+    };
+    let ontouchend = {
+        let pull = pull.clone();
+        let on_load_older = props.on_load_older.clone();
+        Callback::from(move |_: TouchEvent| {
+            *pull_start.borrow_mut() = None;
+            if *pull >= PULL_REFRESH_PX {
+                on_load_older.emit(());
+            }
+            pull.set(0);
+        })
+    };
+    // Every row gets the same callbacks, called with its message, so rows whose props haven't
+    // changed skip re-rendering.
+    let on_react = use_forwarding(&props.on_react);
+    let on_edit = use_forwarding(&props.on_edit);
+    let on_action = use_forwarding(&props.on_action);
+    let on_toggle_task = use_forwarding(&props.on_toggle_task);
+    let on_context_menu = {
+        let menu = menu.clone();
+        use_forwarding(&Callback::from(
+            move |(e, m): (MouseEvent, Rc<MessageData>)| {
+                e.prevent_default();
+                menu.set(Some((e.client_x(), e.client_y(), m)));
+            },
+        ))
+    };
+
+    {
+        let found = props
+            .focus
+            .filter(|id| props.messages.iter().any(|m| m.id == *id));
+        let on_focused = props.on_focused.clone();
+        use_effect_with_deps(
+            move |found| {
+                let timeout = found.map(|id| {
+                    let row = web_sys::window()
+                        .and_then(|w| w.document())
+                        .and_then(|d| d.get_element_by_id(&format!("message-{}", id)));
+                    if let Some(row) = row {
+                        let mut options = ScrollIntoViewOptions::new();
+                        options
+                            .behavior(scroll_behavior(reduced_motion))
+                            .block(ScrollLogicalPosition::Center);
+                        row.scroll_into_view_with_scroll_into_view_options(&options);
+                    }
+                    Timeout::new(FOCUS_HIGHLIGHT_MS, move || on_focused.emit(()))
+                });
+                move || drop(timeout)
+            },
+            found,
+        );
+    }
+
+    // Opening a channel shows its first unread message, or the latest if everything is read.
+    let near_bottom = use_state(|| true);
+    {
+        let list = list.clone();
+        use_effect_with_deps(
+            move |(_, divider)| {
+                let divider = divider
+                    .and_then(|_| gloo::utils::document().get_element_by_id("unread-divider"));
+                match divider {
+                    Some(divider) => divider.scroll_into_view(),
+                    None => scroll_to_bottom(&list),
+                }
+                || ()
+            },
+            (props.channel.clone(), props.divider),
+        );
+    }
+    // Afterwards, follow new messages only while already at the bottom, or when they're our
+    // own; otherwise count them on the jump button. History inserted above keeps its place.
+    let unseen = use_state(|| 0);
+    let anchor = use_mut_ref(ScrollAnchor::default);
+    {
+        let shown = props.messages.iter().filter(|m| m.channel == props.channel);
+        let last = shown.clone().last();
+        let mine = last.is_some_and(|m| m.from == me);
+        let current = ScrollAnchor {
+            channel: props.channel.clone(),
+            first: shown.clone().next().map(|m| m.id),
+            last: last.map(|m| m.id),
+            len: shown.count(),
+            height: 0,
+        };
+        let list = list.clone();
+        let follow = *near_bottom;
+        let unseen = unseen.clone();
+        use_effect_with_deps(
+            move |current| {
+                if let Some(el) = list.cast::<web_sys::Element>() {
+                    let mut anchor = anchor.borrow_mut();
+                    if anchor.channel == current.channel {
+                        if current.last != anchor.last {
+                            if follow || mine {
+                                scroll_to_bottom(&list);
+                            } else {
+                                unseen.set(*unseen + current.len.saturating_sub(anchor.len));
+                            }
+                        } else if current.first != anchor.first {
+                            el.set_scroll_top(el.scroll_top() + el.scroll_height() - anchor.height);
+                        }
+                    }
+                    *anchor = ScrollAnchor {
+                        height: el.scroll_height(),
+                        ..current.clone()
+                    };
+                }
+                || ()
+            },
+            current,
+        );
+    }
+    let onscroll = {
+        let list = list.clone();
+        let near_bottom = near_bottom.clone();
+        let unseen = unseen.clone();
+        Callback::from(move |_: Event| {
+            if let Some(list) = list.cast::<web_sys::Element>() {
+                let distance = list.scroll_height() - list.scroll_top() - list.client_height();
+                let now = distance < NEAR_BOTTOM_PX;
+                if now != *near_bottom {
+                    near_bottom.set(now);
+                }
+                if now && *unseen > 0 {
+                    unseen.set(0);
+                }
+            }
+        })
+    };
+    let jump_to_latest = {
+        let list = list.clone();
+        Callback::from(move |_: MouseEvent| {
+            if let Some(list) = list.cast::<web_sys::Element>() {
+                let mut options = ScrollToOptions::new();
+                options
+                    .top(list.scroll_height() as f64)
+                    .behavior(scroll_behavior(reduced_motion));
+                list.scroll_to_with_scroll_to_options(&options);
+            }
+        })
+    };
+
+    // Arrow keys move the cursor, which focuses its row so screen readers read it out; Enter
+    // opens the row's menu. Only keys pressed on the list or a row count, not on their buttons.
+    let onkeydown = {
+        let messages = props.messages.clone();
+        let channel = props.channel.clone();
+        let cursor = cursor.clone();
+        let menu = menu.clone();
+        Callback::from(move |e: KeyboardEvent| {
+            let on_row = e
+                .target_dyn_into::<web_sys::Element>()
+                .is_some_and(|el| el.id().starts_with("message-"));
+            if !on_row && e.target() != e.current_target() {
+                return;
+            }
+            let shown = messages
+                .iter()
+                .filter(|m| m.channel == channel && m.id != 0);
+            let key = e.key();
+            if key == "Enter" {
+                let selected = (*cursor).and_then(|id| shown.clone().find(|m| m.id == id));
+                let row = (*cursor).and_then(row_element);
+                if let (Some(m), Some(row)) = (selected, row) {
+                    e.prevent_default();
+                    let rect = row.get_bounding_client_rect();
+                    menu.set(Some((
+                        rect.left() as i32 + 32,
+                        rect.bottom() as i32,
+                        m.clone(),
+                    )));
+                }
+                return;
+            }
+            if key == "Escape" && menu.is_none() {
+                cursor.set(None);
+                return;
+            }
+            let ids: Vec<u64> = shown.map(|m| m.id).collect();
+            if let Some(next) = next_cursor(&ids, *cursor, &key) {
+                e.prevent_default();
+                cursor.set(Some(next));
+            }
+        })
+    };
+    // Leaving the list drops the cursor, so its highlight doesn't linger.
+    let onfocusout = {
+        let list = list.clone();
+        let cursor = cursor.clone();
+        Callback::from(move |e: FocusEvent| {
+            let inside = match (list.get(), e.related_target()) {
+                (Some(list), Some(target)) => list.contains(target.dyn_ref::<Node>()),
+                _ => false,
+            };
+            if !inside {
+                cursor.set(None);
+            }
+        })
+    };
+    {
+        // Focuses the selected row, and gives focus back to it when its menu closes.
+        let menu_open = menu.is_some();
+        use_effect_with_deps(
+            |&(cursor, menu_open)| {
+                if let Some(row) = cursor.filter(|_| !menu_open).and_then(row_element) {
+                    let _ = row.focus();
+                }
+                || ()
+            },
+            (*cursor, menu_open),
+        );
+    }
+
+    let by_id: HashMap<u64, &Rc<MessageData>> = props.messages.iter().map(|m| (m.id, m)).collect();
+    let row = |key: String, m: &Rc<MessageData>, prev: Option<&Rc<MessageData>>| -> Html {
+        let on_context_menu = on_context_menu.clone();
+        let on_toggle_task =
+            (m.id != 0 && online && (m.from == me || m.tasks_open)).then(|| on_toggle_task.clone());
+        let selected = m.id != 0 && *cursor == Some(m.id);
+        if props.display_mode == DisplayMode::Compact {
+            let focused = props.focus == Some(m.id);
+            // The unread divider starts a new group so the name shows below it.
+            let continuation = props.divider != Some(m.id)
+                && prev.is_some_and(|prev| compact_message::continues(prev, m));
+            return html! { <CompactMessage key={key} message={m.clone()} emoji={emoji.clone()} {focused} {selected} {on_context_menu} {continuation} {on_toggle_task} /> };
+        }
+        let author = props
+            .profiles
+            .get(&m.from)
+            .cloned()
+            .unwrap_or_else(|| UserProfile::placeholder(&m.from));
+        let on_react = (m.id != 0).then(|| on_react.clone());
+        let on_edit = (m.id != 0 && m.from == me).then(|| on_edit.clone());
+        let reactions = props.reactions.get(&m.id).cloned();
+        let mention = mention_of(&m.message, &me, online);
+        let mention_suppressed =
+            mention.is_some_and(|m| props.suppress_mentions.suppresses(m == Mention::Broadcast));
+        let pinned = props.pinned.contains(&m.id);
+        let focused = props.focus == Some(m.id);
+        let actions = actions_for(m);
+        let reply = m.reply_to.and_then(|id| by_id.get(&id)).map(|&p| p.clone());
+        html! {
+            <MessageBubble key={key} message={m.clone()} {author} {reactions} {on_react} {on_edit} {mention} {pinned} {focused} emoji={emoji.clone()} {actions} on_action={on_action.clone()} {reply} {on_context_menu} presence={props.presence.get(&m.from).copied()} {on_toggle_task} {selected}
+                bubble_style={props.bubble_style} own={m.from == me} {mention_suppressed} />
+        }
+    };
+
+    // Joins, leaves and renames for this channel, each shown before the first message newer
+    // than it.
+    let mut system = props
+        .system
+        .iter()
+        .enumerate()
+        .filter(|(_, notice)| system_line::shows_in(&notice.event, &props.channel))
+        .peekable();
+    let system_row = |(i, notice): (usize, &SystemNotice)| {
+        html! { <SystemLine key={format!("system-{}", i)} notice={notice.clone()} /> }
+    };
+    let empty = !props.messages.iter().any(|m| m.channel == props.channel);
+    let load_older = props.on_load_older.reform(|_: MouseEvent| ());
+
+    html! {
+        <div data-testid="message-list" ref={list} tabindex="0" aria-label="Messages. Use the arrow keys to move between them and Enter for actions."
+            {onkeydown} {onfocusout} {onscroll} {ontouchstart} {ontouchmove} ontouchend={ontouchend.clone()} ontouchcancel={ontouchend}
+            style={props.wallpaper.clone()}
+            class={classes!("w-full", "grow", "overflow-auto", "border-b-2", classes.border)}>
+            if props.loading_older || *pull > 0 {
+                <div data-testid="history-spinner" role="status" aria-label="Loading older messages"
+                    style={(!props.loading_older).then(|| format!("height: {}px", *pull))}
+                    class={classes!("flex", "justify-center", "items-center", "overflow-hidden", "text-xl", props.loading_older.then_some("h-12"), classes.muted_text)}>
+                    <span class={classes!((props.loading_older || *pull >= PULL_REFRESH_PX).then_some("animate-spin"))}>{"⟳"}</span>
+                </div>
+            } else if props.history_failed {
+                <div data-testid="history-error" role="alert"
+                    class={classes!("flex", "justify-center", "items-center", "h-12", "text-sm", classes.muted_text)}>
+                    {"Couldn't load older messages"}
+                    <button data-testid="history-retry" onclick={load_older.clone()}
+                        class={classes!("ml-2", "px-3", "py-1", "rounded", "text-xs", classes.button_bg)}>{"Retry"}</button>
+                </div>
+            }
+            {
+                // Rows are keyed by message, so they keep their component, and what it holds,
+                // when history is loaded above them or a message is deleted. Local notes have
+                // no id and are keyed by their place among the channel's notes, which only grow.
+                props
+                    .messages
+                    .iter()
+                    .filter(|m| m.channel == props.channel)
+                    .scan((None, 0), |(prev, notes), m| {
+                        let key = if m.id == 0 {
+                            *notes += 1;
+                            format!("note-{}", notes)
+                        } else {
+                            format!("message-{}", m.id)
+                        };
+                        Some((key, m, std::mem::replace(prev, Some(m))))
+                    })
+                    .flat_map(|(key, m, prev)| {
+                        let earlier: Vec<Html> =
+                            std::iter::from_fn(|| system.next_if(|(_, notice)| notice.time <= m.time))
+                                .map(system_row)
+                                .collect();
+                        let divider = (props.divider == Some(m.id)).then(|| html! {
+                            <div data-testid="unread-divider" id="unread-divider" key="unread-divider" class="flex items-center mx-8 text-xs text-red-500">
+                                <span class="grow border-t border-red-400" />
+                                <span class="px-2">{"New messages"}</span>
+                                <span class="grow border-t border-red-400" />
+                            </div>
+                        });
+                        earlier.into_iter().chain(divider).chain(Some(row(key, m, prev)))
+                    })
+                    .collect::<Html>()
+            }
+            { for system.map(system_row) }
+            if empty && props.loading {
+                <Skeleton rows={4} avatar={classes!("w-8", "h-8")} testid="message-skeleton" />
+            } else if empty && !props.loading_older && !props.history_failed {
+                <div data-testid="empty-channel" class={classes!("flex", "flex-col", "items-center", "py-16", "text-center", classes.muted_text)}>
+                    <div aria-hidden="true" class="mb-2 text-4xl">{"👋"}</div>
+                    <div class="text-sm">{if online { "No messages yet — say hi!" } else { "No messages yet." }}</div>
+                    if props.has_older {
+                        <button data-testid="empty-load-older" onclick={load_older}
+                            class={classes!("mt-3", "px-3", "py-1", "rounded", "text-xs", classes.button_bg)}>{"Load earlier messages"}</button>
+                    }
+                </div>
+            }
+            if !*near_bottom {
+                <div class="sticky bottom-2 flex justify-end pr-4 pointer-events-none">
+                    <button data-testid="jump-to-latest" onclick={jump_to_latest}
+                        class="pointer-events-auto px-3 py-1 rounded-full shadow text-sm text-white bg-violet-600">
+                        if *unseen > 0 {
+                            {format!("{} new message{} ↓", *unseen, if *unseen == 1 { "" } else { "s" })}
+                        } else {
+                            {"Jump to latest ↓"}
+                        }
+                    </button>
+                </div>
+            }
+            if let Some((x, y, m)) = (*menu).clone() {
+                { message_menu(x, y, &m, &actions_for(&m), &props.on_action, menu.clone()) }
+            }
+        </div>
+    }
+}
+
+/// Right-click menu for `m`: quick reactions, then the toolbar's actions and a few more.
+fn message_menu(
+    x: i32,
+    y: i32,
+    m: &Rc<MessageData>,
+    actions: &[MessageAction],
+    on_action: &Callback<(Rc<MessageData>, MessageAction)>,
+    menu: UseStateHandle<Option<(i32, i32, Rc<MessageData>)>>,
+) -> Html {
+    let on_action = {
+        let m = m.clone();
+        on_action.reform(move |action| (m.clone(), action))
+    };
+    let items = message_actions::menu_items(actions, m.id != 0, &on_action);
+    let can_react = actions.iter().any(|a| matches!(a, MessageAction::React(_)));
+    let close = {
+        let menu = menu.clone();
+        Callback::from(move |_| menu.set(None))
+    };
+
+    html! {
+        <ContextMenu {x} {y} {items} on_close={close}>
+            if can_react {
+                <div class="flex justify-around px-2 pb-1 border-b">
+                    {
+                        QUICK_REACTIONS.iter().map(|&emoji| {
+                            let on_action = on_action.clone();
+                            let menu = menu.clone();
+                            let onclick = Callback::from(move |_| {
+                                on_action.emit(MessageAction::React(emoji.to_string()));
+                                menu.set(None);
+                            });
+                            html! { <button data-testid="quick-reaction" {onclick}>{emoji}</button> }
+                        }).collect::<Html>()
+                    }
+                </div>
+            }
+        </ContextMenu>
+    }
+}
+
+/// What the list showed when its scroll position was last settled.
+#[derive(Clone, Default, PartialEq)]
+struct ScrollAnchor {
+    channel: String,
+    first: Option<u64>,
+    last: Option<u64>,
+    len: usize,
+    /// Scroll height at the time; not part of what changed.
+    height: i32,
+}
+
+/// A callback that stays the same across renders and calls whichever `callback` was passed
+/// last, so props holding it compare equal even though the parent makes a new one each time.
+fn use_forwarding<IN: 'static>(callback: &Callback<IN>) -> Callback<IN> {
+    let latest = use_mut_ref(|| callback.clone());
+    *latest.borrow_mut() = callback.clone();
+    let forwarding = use_state(move || {
+        Callback::from(move |input| {
+            // Emitting may re-render this component, which swaps in the next `callback`.
+            let callback = latest.borrow().clone();
+            callback.emit(input);
+        })
+    });
+    (*forwarding).clone()
+}
+
+/// Glides to user-requested scroll targets, unless motion is reduced.
+fn scroll_behavior(reduced_motion: bool) -> ScrollBehavior {
+    if reduced_motion {
+        ScrollBehavior::Auto
+    } else {
+        ScrollBehavior::Smooth
+    }
+}
+
+fn row_element(id: u64) -> Option<HtmlElement> {
+    gloo::utils::document()
+        .get_element_by_id(&format!("message-{}", id))?
+        .dyn_into()
+        .ok()
+}
+
+fn scroll_to_bottom(list: &NodeRef) {
+    if let Some(list) = list.cast::<web_sys::Element>() {
+        list.set_scroll_top(list.scroll_height());
+    }
+}
@@ -0,0 +1,131 @@
+use yew::prelude::*;
+
+use crate::components::external_link::ExternalLink;
+use crate::links::{self, Piece};
+use crate::markdown::{self, Align, Block, Table, Task};
+use crate::math::{self, Segment};
+use crate::theme::{use_theme, ThemeClasses};
+
+#[derive(Properties, PartialEq)]
+pub struct MessageTextProps {
+    pub text: String,
+    /// Called with a checklist item's index and new state; `None` shows the boxes read-only.
+    #[prop_or_default]
+    pub on_toggle_task: Option<Callback<(usize, bool)>>,
+}
+
+/// A message's text, with tables and checklists laid out, `$...$` and `$$...$$` typeset as
+/// math and web addresses made into links.
+#[function_component(MessageText)]
+pub fn message_text(props: &MessageTextProps) -> Html {
+    let classes = use_theme().classes();
+    markdown::blocks(&props.text)
+        .into_iter()
+        .map(|block| match block {
+            Block::Text(text) => inline(text),
+            Block::Table(table) => table_html(&table, classes),
+            Block::Checklist(tasks) => checklist_html(&tasks, props.on_toggle_task.as_ref()),
+        })
+        .collect()
+}
+
+/// The box only changes once the server echoes the update, so everyone sees the same state.
+fn checklist_html(tasks: &[Task], on_toggle: Option<&Callback<(usize, bool)>>) -> Html {
+    html! {
+        <ul data-testid="checklist" class="my-1">
+            { for tasks.iter().map(|task| {
+                let onclick = on_toggle.map(|on_toggle| {
+                    let (index, done) = (task.index, !task.done);
+                    let on_toggle = on_toggle.clone();
+                    Callback::from(move |e: MouseEvent| {
+                        e.prevent_default();
+                        on_toggle.emit((index, done));
+                    })
+                });
+                html! {
+                    <li class="flex items-start">
+                        <input data-testid="checklist-item" type="checkbox" class="mt-0.5 mr-2" checked={task.done} disabled={onclick.is_none()} {onclick} />
+                        <span class={classes!(task.done.then_some("line-through opacity-70"))}>{inline(task.text)}</span>
+                    </li>
+                }
+            }) }
+        </ul>
+    }
+}
+
+fn inline(text: &str) -> Html {
+    math::split_math(text)
+        .into_iter()
+        .map(|segment| match segment {
+            Segment::Text(text) => links_html(text),
+            Segment::Math { tex, display } => math_html(tex, display),
+        })
+        .collect()
+}
+
+fn links_html(text: &str) -> Html {
+    links::split_links(text)
+        .into_iter()
+        .map(|piece| match piece {
+            Piece::Text(text) => html! { {text} },
+            Piece::Link(href) => html! {
+                <ExternalLink href={href.to_string()} class={classes!("underline", "break-all")}>{href}</ExternalLink>
+            },
+        })
+        .collect()
+}
+
+/// Wide tables scroll sideways inside the bubble instead of stretching it.
+fn table_html(table: &Table, classes: &ThemeClasses) -> Html {
+    let cell = |text: &str, align: Align, header: bool| {
+        let align = match align {
+            Align::Left => "text-left",
+            Align::Center => "text-center",
+            Align::Right => "text-right",
+        };
+        let class = classes!("px-2", "py-1", "border", classes.border, align);
+        if header {
+            html! { <th class={classes!(class, "font-bold", classes.panel_bg)}>{inline(text)}</th> }
+        } else {
+            html! { <td {class}>{inline(text)}</td> }
+        }
+    };
+    html! {
+        <div data-testid="message-table" class="max-w-full overflow-x-auto my-1">
+            <table class="border-collapse whitespace-nowrap">
+                <thead>
+                    <tr>{ for table.header.iter().zip(&table.align).map(|(text, align)| cell(text, *align, true)) }</tr>
+                </thead>
+                <tbody>
+                    { for table.rows.iter().map(|row| html! {
+                        <tr>{ for row.iter().zip(&table.align).map(|(text, align)| cell(text, *align, false)) }</tr>
+                    }) }
+                </tbody>
+            </table>
+        </div>
+    }
+}
+
+fn math_html(tex: &str, display: bool) -> Html {
+    let class = if display {
+        "block my-1 overflow-x-auto"
+    } else {
+        "inline"
+    };
+    let typeset = math::render(tex, display).and_then(|rendered| {
+        // KaTeX hands back markup, which only a raw DOM node can carry.
+        let span = gloo::utils::document().create_element("span").ok()?;
+        span.set_inner_html(&rendered);
+        span.set_class_name(class);
+        let _ = span.set_attribute("data-testid", "math");
+        let _ = span.set_attribute("title", tex);
+        Some(span)
+    });
+    match typeset {
+        Some(span) => Html::VRef(span.into()),
+        None => {
+            let delimiter = if display { "$$" } else { "$" };
+            html! { <code data-testid="math" {class}>{format!("{0}{1}{0}", delimiter, tex)}</code> }
+        }
+    }
+}
@@ -1,2 +1,41 @@
+pub mod announcer;
+pub mod attachment;
+pub mod audit_log;
+pub mod avatar;
+pub mod badges;
+pub mod channel_list;
 pub mod chat;
-pub mod login;
\ No newline at end of file
+pub mod compact_message;
+pub mod confirm;
+pub mod connection_quality;
+pub mod composer;
+pub mod context_menu;
+pub mod edit_history;
+pub mod emoji_picker;
+pub mod external_link;
+#[cfg(feature = "gallery")]
+pub mod gallery;
+pub mod invite_panel;
+pub mod join;
+pub mod link_preview;
+pub mod log_viewer;
+pub mod login;
+pub mod message_actions;
+pub mod message_bubble;
+pub mod message_list;
+pub mod message_text;
+pub mod modal;
+pub mod onboarding;
+pub mod outbox;
+pub mod popover;
+pub mod reaction_bar;
+pub mod sessions_panel;
+pub mod settings;
+pub mod skeleton;
+pub mod snippet_composer;
+pub mod sound_notifier;
+pub mod system_line;
+pub mod text_prompt;
+pub mod typing_indicator;
+pub mod user_sidebar;
+pub mod whiteboard;
\ No newline at end of file
@@ -0,0 +1,141 @@
+use wasm_bindgen::JsCast;
+use web_sys::{Element, HtmlElement};
+use yew::prelude::*;
+
+use crate::theme::use_theme;
+
+/// What Tab can land on inside a dialog.
+const FOCUSABLE: &str = "a[href], button:not([disabled]), input:not([disabled]), \
+    select:not([disabled]), textarea:not([disabled]), [tabindex]:not([tabindex='-1'])";
+
+/// Where focus wraps to when Tab is pressed on the `current` of `count` focusable elements,
+/// or `None` to let the browser move it. `current` is `None` when focus is on the dialog
+/// itself or has escaped it.
+pub fn wrap_focus(count: usize, current: Option<usize>, backwards: bool) -> Option<usize> {
+    let last = count.checked_sub(1)?;
+    match current {
+        None => Some(if backwards { last } else { 0 }),
+        Some(0) if backwards => Some(last),
+        Some(i) if !backwards && i >= last => Some(0),
+        _ => None,
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct ModalProps {
+    /// Shown in the header and read out as the dialog's name.
+    pub title: String,
+    /// Called on Escape, the close button and clicks on the backdrop.
+    pub on_close: Callback<()>,
+    /// Tailwind width of the dialog.
+    #[prop_or_else(|| classes!("w-80"))]
+    pub width: Classes,
+    /// Put on the dialog, for tests.
+    #[prop_or("modal")]
+    pub testid: &'static str,
+    /// Controls shown in the header, before the close button.
+    #[prop_or_default]
+    pub actions: Html,
+    pub children: Children,
+}
+
+/// Dialog over a dimmed backdrop, rendered at the end of `<body>` so it sits above whatever
+/// opened it. Keeps Tab inside while open and gives focus back when closed.
+#[function_component(Modal)]
+pub fn modal(props: &ModalProps) -> Html {
+    let classes = use_theme().classes();
+    let dialog = use_node_ref();
+    let body = use_node_ref();
+
+    {
+        let dialog = dialog.clone();
+        let body = body.clone();
+        use_effect_with_deps(
+            move |_| {
+                let previous = gloo::utils::document()
+                    .active_element()
+                    .and_then(|el| el.dyn_into::<HtmlElement>().ok());
+                // An `autofocus` element if there is one, else the first past the header, so the
+                // close button isn't what a keyboard user starts on.
+                let chosen = body
+                    .cast::<Element>()
+                    .and_then(|body| body.query_selector("[autofocus]").ok().flatten())
+                    .and_then(|el| el.dyn_into::<HtmlElement>().ok());
+                let first = chosen.or_else(|| focusable(&body).into_iter().next());
+                if let Some(el) = first.or_else(|| dialog.cast::<HtmlElement>()) {
+                    let _ = el.focus();
+                }
+                move || {
+                    if let Some(previous) = previous {
+                        let _ = previous.focus();
+                    }
+                }
+            },
+            (),
+        );
+    }
+
+    let onkeydown = {
+        let dialog = dialog.clone();
+        let on_close = props.on_close.clone();
+        Callback::from(move |e: KeyboardEvent| match e.key().as_str() {
+            "Escape" => {
+                // Only the topmost dialog closes.
+                e.stop_propagation();
+                on_close.emit(());
+            }
+            // Left alone when a field has claimed Tab, as the snippet editor does to indent.
+            "Tab" if !e.default_prevented() => {
+                let targets = focusable(&dialog);
+                let active = gloo::utils::document().active_element();
+                let current = targets
+                    .iter()
+                    .position(|el| Some(el.unchecked_ref::<Element>()) == active.as_ref());
+                if let Some(i) = wrap_focus(targets.len(), current, e.shift_key()) {
+                    e.prevent_default();
+                    let _ = targets[i].focus();
+                }
+            }
+            _ => {}
+        })
+    };
+    let onclick = {
+        let on_close = props.on_close.clone();
+        Callback::from(move |e: MouseEvent| {
+            if e.target() == e.current_target() {
+                on_close.emit(());
+            }
+        })
+    };
+    let close = props.on_close.reform(|_: MouseEvent| ());
+
+    let modal = html! {
+        <div class="fixed inset-0 z-40 flex items-center justify-center bg-black/50" {onclick}>
+            <div ref={dialog} data-testid={props.testid} role="dialog" aria-modal="true" aria-label={props.title.clone()} tabindex="-1" {onkeydown}
+                class={classes!(props.width.clone(), "max-w-full", "max-h-full", "overflow-auto", "p-4", "rounded-lg", "shadow-lg", "border", "text-sm", "outline-none", classes.popover_bg, classes.main_text)}>
+                <div class="flex items-center mb-3">
+                    <span class="grow font-bold">{props.title.clone()}</span>
+                    { props.actions.clone() }
+                    <button data-testid="modal-close" title="Close" aria-label="Close" class="ml-3" onclick={close}>{"✕"}</button>
+                </div>
+                <div ref={body}>
+                    { for props.children.iter() }
+                </div>
+            </div>
+        </div>
+    };
+    create_portal(modal, gloo::utils::body().into())
+}
+
+/// Focusable elements under `node`, in document order.
+fn focusable(node: &NodeRef) -> Vec<HtmlElement> {
+    let Some(nodes) = node
+        .cast::<Element>()
+        .and_then(|node| node.query_selector_all(FOCUSABLE).ok())
+    else {
+        return Vec::new();
+    };
+    (0..nodes.length())
+        .filter_map(|i| nodes.item(i)?.dyn_into::<HtmlElement>().ok())
+        .collect()
+}
@@ -0,0 +1,197 @@
+use gloo::events::EventListener;
+use gloo::storage::{LocalStorage, Storage};
+use web_sys::HtmlElement;
+use yew::prelude::*;
+
+use crate::theme::use_theme;
+
+/// Set once the tour has been finished or skipped in this browser.
+const ONBOARDED_KEY: &str = "yewchat.onboarded";
+/// Room left around a highlighted element, in pixels.
+const SPOTLIGHT_PAD: f64 = 6.0;
+/// Size the card is laid out for, in pixels; it only grows for long text.
+const CARD_WIDTH: f64 = 288.0;
+const CARD_HEIGHT: f64 = 160.0;
+/// Closest the card comes to the edge of the window, in pixels.
+const CARD_MARGIN: f64 = 12.0;
+
+/// One coach mark: the element it points at, by `data-testid`, and what it says about it.
+pub struct TourStep {
+    pub target: &'static str,
+    pub title: &'static str,
+    pub body: &'static str,
+}
+
+pub const STEPS: [TourStep; 4] = [
+    TourStep {
+        target: "chat-input",
+        title: "Say hello",
+        body: "Type here and press Enter to send. Start with / for commands.",
+    },
+    TourStep {
+        target: "emoji-toggle",
+        title: "Emoji",
+        body: "Pick an emoji here, or type : and part of its name.",
+    },
+    TourStep {
+        target: "settings-toggle",
+        title: "Make it yours",
+        body: "Switch to a dark theme, and much more, in Settings.",
+    },
+    TourStep {
+        target: "channel-list",
+        title: "Channels",
+        body: "Move between channels here. Unread messages and mentions show as badges.",
+    },
+];
+
+/// A highlighted element's box in the window: top, left, width and height in pixels.
+pub type Spot = (f64, f64, f64, f64);
+
+/// The step after `step`, or the one before going `back`; `None` once past the last.
+pub fn advance(step: usize, back: bool) -> Option<usize> {
+    if back {
+        Some(step.saturating_sub(1))
+    } else {
+        (step + 1 < STEPS.len()).then_some(step + 1)
+    }
+}
+
+/// Where the card's top left corner goes in a `viewport` of that width and height: below
+/// `spot` if it fits, else above, kept inside the window. Centered when there's no spot.
+pub fn card_position(spot: Option<Spot>, viewport: (f64, f64)) -> (f64, f64) {
+    let (width, height) = viewport;
+    let Some((top, left, _, spot_height)) = spot else {
+        return ((height - CARD_HEIGHT) / 2.0, (width - CARD_WIDTH) / 2.0);
+    };
+    let below = top + spot_height + SPOTLIGHT_PAD + CARD_MARGIN;
+    let top = if below + CARD_HEIGHT <= height - CARD_MARGIN {
+        below
+    } else {
+        top - SPOTLIGHT_PAD - CARD_MARGIN - CARD_HEIGHT
+    };
+    let left = left.min(width - CARD_WIDTH - CARD_MARGIN).max(CARD_MARGIN);
+    (top.max(CARD_MARGIN), left)
+}
+
+fn viewport() -> (f64, f64) {
+    let window = gloo::utils::window();
+    let width = window.inner_width().ok().and_then(|w| w.as_f64());
+    let height = window.inner_height().ok().and_then(|h| h.as_f64());
+    (width.unwrap_or_default(), height.unwrap_or_default())
+}
+
+fn measure(target: &str) -> Option<Spot> {
+    let el = gloo::utils::document()
+        .query_selector(&format!("[data-testid='{}']", target))
+        .ok()??;
+    let rect = el.get_bounding_client_rect();
+    Some((rect.top(), rect.left(), rect.width(), rect.height()))
+}
+
+/// Coach marks walking a new user through the composer, emoji, settings and channels, one
+/// at a time. Shown until finished or skipped once in this browser.
+#[function_component(OnboardingTour)]
+pub fn onboarding_tour() -> Html {
+    let classes = use_theme().classes();
+    let step = use_state(|| {
+        let onboarded = LocalStorage::get::<bool>(ONBOARDED_KEY).unwrap_or(false);
+        (!onboarded).then_some(0)
+    });
+    let window_size = use_state(viewport);
+    let spot = use_state(|| None::<Spot>);
+    let next_button = use_node_ref();
+
+    {
+        let window_size = window_size.clone();
+        use_effect_with_deps(
+            move |_| {
+                let listener = EventListener::new(&gloo::utils::window(), "resize", move |_| {
+                    window_size.set(viewport())
+                });
+                move || drop(listener)
+            },
+            (),
+        );
+    }
+    {
+        // Measured after rendering, so the target is in the document and laid out.
+        let spot = spot.clone();
+        let next_button = next_button.clone();
+        use_effect_with_deps(
+            move |&(step, _)| {
+                spot.set(step.and_then(|step| measure(STEPS[step].target)));
+                if let Some(button) = next_button.cast::<HtmlElement>() {
+                    let _ = button.focus();
+                }
+                || ()
+            },
+            (*step, *window_size),
+        );
+    }
+
+    let Some(current) = *step else {
+        return html! {};
+    };
+    let finish = {
+        let step = step.clone();
+        Callback::from(move |_| {
+            if let Err(e) = LocalStorage::set(ONBOARDED_KEY, true) {
+                log::warn!("failed to save onboarding: {}", e);
+            }
+            step.set(None);
+        })
+    };
+    let go = |back: bool| {
+        let step = step.clone();
+        let finish = finish.clone();
+        Callback::from(move |_: MouseEvent| match advance(current, back) {
+            Some(next) => step.set(Some(next)),
+            None => finish.emit(()),
+        })
+    };
+    let onkeydown = {
+        let finish = finish.clone();
+        Callback::from(move |e: KeyboardEvent| {
+            if e.key() == "Escape" {
+                finish.emit(());
+            }
+        })
+    };
+    let (title, body) = (STEPS[current].title, STEPS[current].body);
+    let (card_top, card_left) = card_position(*spot, *window_size);
+    let last = current + 1 == STEPS.len();
+
+    html! {
+        <div data-testid="onboarding" role="dialog" aria-modal="true" aria-labelledby="onboarding-title" {onkeydown}
+            class="fixed inset-0 z-50">
+            if let Some((top, left, width, height)) = *spot {
+                <div data-testid="onboarding-spotlight" aria-hidden="true"
+                    style={format!(
+                        "top: {}px; left: {}px; width: {}px; height: {}px; box-shadow: 0 0 0 9999px rgba(0, 0, 0, 0.5)",
+                        top - SPOTLIGHT_PAD, left - SPOTLIGHT_PAD, width + 2.0 * SPOTLIGHT_PAD, height + 2.0 * SPOTLIGHT_PAD,
+                    )}
+                    class="absolute rounded-lg ring-2 ring-blue-400 pointer-events-none" />
+            } else {
+                <div aria-hidden="true" class="absolute inset-0 bg-black/50" />
+            }
+            <div data-testid="onboarding-card" style={format!("top: {}px; left: {}px; width: {}px", card_top, card_left, CARD_WIDTH)}
+                class={classes!("absolute", "p-4", "rounded-lg", "shadow-lg", "border", classes.popover_bg, classes.main_text)}>
+                <div class={classes!("text-xs", classes.muted_text)}>{format!("{} of {}", current + 1, STEPS.len())}</div>
+                <h2 id="onboarding-title" class="mb-1 font-bold">{title}</h2>
+                <p class="mb-3 text-sm">{body}</p>
+                <div class="flex items-center">
+                    <button data-testid="onboarding-skip" onclick={finish.reform(|_: MouseEvent| ())}
+                        class={classes!("text-xs", "underline", classes.muted_text)}>{"Skip tour"}</button>
+                    <span class="grow" />
+                    if current > 0 {
+                        <button data-testid="onboarding-back" onclick={go(true)}
+                            class={classes!("px-3", "py-1", "mr-2", "rounded", classes.button_bg)}>{"Back"}</button>
+                    }
+                    <button data-testid="onboarding-next" ref={next_button} onclick={go(false)}
+                        class="px-3 py-1 rounded bg-blue-600 text-white">{if last { "Done" } else { "Next" }}</button>
+                </div>
+            </div>
+        </div>
+    }
+}
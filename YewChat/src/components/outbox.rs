@@ -0,0 +1,80 @@
+use std::rc::Rc;
+
+use yew::prelude::*;
+use yewchat_protocol::Attachment;
+
+use crate::state::Outgoing;
+use crate::theme::use_theme;
+
+#[derive(Properties, PartialEq)]
+pub struct OutboxProps {
+    pub outbox: Rc<Vec<Outgoing>>,
+    /// Only messages to this channel are shown.
+    pub channel: String,
+    /// Called with the id of a failed message to send again.
+    pub on_retry: Callback<u64>,
+    /// Called with the id of a failed message to give up on.
+    pub on_discard: Callback<u64>,
+}
+
+/// Messages that are on their way, and ones that didn't make it with retry and discard
+/// buttons, between the message list and the composer.
+#[function_component(Outbox)]
+pub fn outbox(props: &OutboxProps) -> Html {
+    let classes = use_theme().classes();
+    let shown: Vec<&Outgoing> = props
+        .outbox
+        .iter()
+        .filter(|o| o.channel == props.channel)
+        .collect();
+    if shown.is_empty() {
+        return html! {};
+    }
+    let failed = shown.iter().filter(|o| o.error.is_some()).count();
+
+    html! {
+        <div data-testid="outbox" class={classes!("w-full", "px-3", "py-1", "text-sm", "border-t", classes.border)}>
+            if failed > 0 {
+                <div class="text-xs font-bold text-red-600">{"Failed to send"}</div>
+            }
+            { for shown.into_iter().map(|o| {
+                let id = o.id;
+                html! {
+                    <div data-testid="outbox-item" key={id} class="flex items-center py-0.5">
+                        <span class={classes!("grow", "truncate", o.error.is_none().then_some(classes.muted_text))}>
+                            { preview(o) }
+                        </span>
+                        if let Some(error) = &o.error {
+                            <span data-testid="outbox-error" class="ml-2 text-xs text-red-600 truncate" title={error.to_string()}>{format!("⚠ {}", error)}</span>
+                            <button data-testid="outbox-retry" onclick={props.on_retry.reform(move |_| id)}
+                                class={classes!("ml-2", "px-2", "rounded", "text-xs", classes.button_bg)}>{"Retry"}</button>
+                            <button data-testid="outbox-discard" onclick={props.on_discard.reform(move |_| id)}
+                                class={classes!("ml-1", "px-2", "rounded", "text-xs", classes.hover_bg)}>{"Discard"}</button>
+                        } else {
+                            <span class={classes!("ml-2", "text-xs", classes.muted_text)}>{"Sending…"}</span>
+                        }
+                    </div>
+                }
+            }) }
+        </div>
+    }
+}
+
+/// One line standing in for the message: its text, or what is attached.
+fn preview(outgoing: &Outgoing) -> String {
+    if !outgoing.message.trim().is_empty() {
+        return outgoing.message.clone();
+    }
+    match &outgoing.attachment {
+        Some(Attachment::Image { .. }) => "🖼️ Image".into(),
+        Some(Attachment::Snippet { title, .. }) => {
+            format!("📄 {}", title.as_deref().unwrap_or("Snippet"))
+        }
+        Some(Attachment::Contact { name }) => format!("👤 {}", name),
+        Some(Attachment::Location { .. }) => "📍 Location".into(),
+        Some(Attachment::Embed { title, .. }) => {
+            format!("🗂️ {}", title.as_deref().unwrap_or("Embed"))
+        }
+        None => String::new(),
+    }
+}
@@ -0,0 +1,36 @@
+use yew::prelude::*;
+
+use crate::theme::use_theme;
+
+#[derive(Properties, PartialEq)]
+pub struct PopoverProps {
+    /// Shown above the children while the pointer is over them.
+    pub content: Html,
+    pub children: Children,
+}
+
+/// Hover popover anchored to its children.
+#[function_component(Popover)]
+pub fn popover(props: &PopoverProps) -> Html {
+    let classes = use_theme().classes();
+    let open = use_state(|| false);
+    let onmouseenter = {
+        let open = open.clone();
+        Callback::from(move |_| open.set(true))
+    };
+    let onmouseleave = {
+        let open = open.clone();
+        Callback::from(move |_| open.set(false))
+    };
+
+    html! {
+        <div class="relative inline-block" {onmouseenter} {onmouseleave}>
+            { for props.children.iter() }
+            if *open {
+                <div data-testid="popover" role="tooltip" class={classes!("absolute", "bottom-full", "left-0", "mb-1", "p-2", "rounded-lg", "shadow-lg", "border", "text-xs", "whitespace-nowrap", "z-20", classes.popover_bg, classes.main_text)}>
+                    { props.content.clone() }
+                </div>
+            }
+        </div>
+    }
+}
@@ -0,0 +1,86 @@
+use std::rc::Rc;
+
+use yew::prelude::*;
+use yewchat_protocol::Reaction;
+
+use crate::components::popover::Popover;
+use crate::theme::use_theme;
+use crate::User;
+
+/// Offered by the add-reaction button.
+pub const QUICK_REACTIONS: [&str; 6] = ["👍", "❤️", "😂", "🎉", "😮", "😢"];
+
+#[derive(Properties, PartialEq)]
+pub struct ReactionBarProps {
+    pub reactions: Option<Rc<Vec<Reaction>>>,
+    /// Toggles the current user's reaction with the given emoji.
+    pub on_toggle: Callback<String>,
+}
+
+#[function_component(ReactionBar)]
+pub fn reaction_bar(props: &ReactionBarProps) -> Html {
+    let classes = use_theme().classes();
+    let user = use_context::<User>().expect("context to be set");
+    let me = user.username.borrow().clone();
+    let spectator = *user.spectator.borrow();
+    let show_quick = use_state(|| false);
+    let reactions = props.reactions.clone().unwrap_or_default();
+
+    let chips = reactions
+        .iter()
+        .map(|r| {
+            let mine = r.users.contains(&me);
+            let emoji = r.emoji.clone();
+            let onclick = props.on_toggle.reform(move |_: MouseEvent| emoji.clone());
+            html! {
+                <button data-testid="reaction-chip" {onclick} disabled={spectator}
+                    class={classes!("mr-1", "px-1.5", "rounded-full", "border", "text-xs", classes.hover_bg,
+                        if mine { "border-violet-500" } else { classes.border })}>
+                    {format!("{} {}", r.emoji, r.users.len())}
+                </button>
+            }
+        })
+        .collect::<Html>();
+    let summary = html! {
+        <div data-testid="reaction-summary">
+            { for reactions.iter().map(|r| html! {
+                <div>{format!("{} {}", r.emoji, r.users.join(", "))}</div>
+            }) }
+        </div>
+    };
+    let toggle_quick = {
+        let show_quick = show_quick.clone();
+        Callback::from(move |_| show_quick.set(!*show_quick))
+    };
+
+    html! {
+        <div data-testid="reaction-bar" class="flex items-center mt-1">
+            if !reactions.is_empty() {
+                <Popover content={summary}>{chips}</Popover>
+            }
+            if !spectator {
+                <button data-testid="add-reaction" title="Add reaction" onclick={toggle_quick}
+                    class={classes!("px-1", "rounded", "text-xs", classes.muted_text, classes.hover_bg)}>
+                    {"☺+"}
+                </button>
+                if *show_quick {
+                    <div class={classes!("flex", "ml-1", "rounded", "border", classes.popover_bg)}>
+                        {
+                            QUICK_REACTIONS.iter().map(|&emoji| {
+                                let on_toggle = props.on_toggle.clone();
+                                let show_quick = show_quick.clone();
+                                let onclick = Callback::from(move |_| {
+                                    show_quick.set(false);
+                                    on_toggle.emit(emoji.to_string());
+                                });
+                                html! {
+                                    <button data-testid="quick-reaction" {onclick} class={classes!("px-1", classes.hover_bg)}>{emoji}</button>
+                                }
+                            }).collect::<Html>()
+                        }
+                    </div>
+                }
+            }
+        </div>
+    }
+}
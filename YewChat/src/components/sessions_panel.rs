@@ -0,0 +1,73 @@
+use yew::prelude::*;
+
+use crate::components::modal::Modal;
+use crate::store::use_store;
+use crate::theme::use_theme;
+use crate::time::{self, use_time_format};
+
+#[derive(Properties, PartialEq)]
+pub struct SessionsPanelProps {
+    /// Asks the server for the current list; called on open.
+    pub on_refresh: Callback<()>,
+    /// Called with the id of a session to sign out.
+    pub on_sign_out: Callback<String>,
+    pub on_close: Callback<()>,
+}
+
+/// Everywhere the user is signed in under their name, with a way to sign the others out.
+#[function_component(SessionsPanel)]
+pub fn sessions_panel(props: &SessionsPanelProps) -> Html {
+    let classes = use_theme().classes();
+    let time_format = use_time_format();
+    let store = use_store();
+
+    {
+        let on_refresh = props.on_refresh.clone();
+        use_effect_with_deps(
+            move |_| {
+                on_refresh.emit(());
+                || ()
+            },
+            (),
+        );
+    }
+
+    let now = js_sys::Date::now() as u64;
+    let refresh = props.on_refresh.reform(|_: MouseEvent| ());
+    let actions = html! {
+        <button data-testid="sessions-refresh" title="Refresh" aria-label="Refresh" onclick={refresh}>{"⟳"}</button>
+    };
+
+    html! {
+        <Modal title="Sessions" testid="sessions-panel" width={classes!("w-96")} on_close={props.on_close.clone()} {actions}>
+            if store.sessions.is_empty() {
+                <p class={classes.muted_text}>{"Loading…"}</p>
+            }
+            { for store.sessions.iter().map(|session| {
+                let sign_out = {
+                    let id = session.id.clone();
+                    props.on_sign_out.reform(move |_: MouseEvent| id.clone())
+                };
+                html! {
+                    <div data-testid="session-item" key={session.id.clone()} class={classes!("flex", "items-center", "py-2", "border-b", classes.border)}>
+                        <div class="grow min-w-0">
+                            <div class="font-bold truncate">{session.device.clone()}</div>
+                            <div class={classes!("text-xs", classes.muted_text)}
+                                title={format!("Connected {}", time_format.date_time(session.connected_at))}>
+                                if session.current {
+                                    {"This device"}
+                                } else {
+                                    {format!("Active {}", time::ago(now.saturating_sub(session.last_active)))}
+                                }
+                            </div>
+                        </div>
+                        if !session.current {
+                            <button data-testid="session-sign-out" onclick={sign_out}
+                                class={classes!("ml-2", "px-2", "py-1", "rounded", "text-xs", classes.button_bg)}>{"Sign out"}</button>
+                        }
+                    </div>
+                }
+            }) }
+        </Modal>
+    }
+}
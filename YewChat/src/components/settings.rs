@@ -0,0 +1,613 @@
+use std::collections::HashSet;
+
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+use yewchat_protocol::{MentionSuppression, MAX_ACTIVITY_CHARS};
+
+use crate::components::modal::Modal;
+use crate::flags::{use_flag, Flag};
+use crate::motion::Motion;
+use crate::services::data_usage::{self, format_bytes};
+use crate::services::error_report;
+use crate::services::image::ImageQuality;
+use crate::services::push;
+use crate::services::sound::{self, NotificationSound};
+use crate::services::telemetry;
+use crate::state::{Announcements, BubbleStyle, ChatAction, DisplayMode, Preferences, QuietHours};
+use crate::store::use_store;
+use crate::theme::{use_theme, Theme};
+use crate::time::{self, format_minute_of_day, parse_minute_of_day, Clock};
+use crate::wallpaper::{self, Wallpaper};
+
+/// The choices for which mentions not to be pinged by, in the order offered.
+const SUPPRESSIONS: [(MentionSuppression, &str); 3] = [
+    (MentionSuppression::Nothing, "Nothing"),
+    (MentionSuppression::Broadcasts, "@everyone, @here"),
+    (MentionSuppression::All, "Any mention"),
+];
+
+#[derive(Properties, PartialEq)]
+pub struct SettingsPanelProps {
+    pub on_close: Callback<()>,
+}
+
+/// Dialog with the user's display preferences.
+#[function_component(SettingsPanel)]
+pub fn settings_panel(props: &SettingsPanelProps) -> Html {
+    let store = use_store();
+    let theme = use_theme();
+    let classes = theme.classes();
+    let preferences = store.preferences.clone();
+    let compact_available = use_flag(Flag::CompactMode);
+    let telemetry_available = use_flag(Flag::Telemetry);
+
+    let update = {
+        let store = store.clone();
+        move |f: fn(&mut Preferences, bool)| {
+            let store = store.clone();
+            Callback::from(move |e: Event| {
+                let input: HtmlInputElement = e.target_unchecked_into();
+                let mut preferences = store.preferences.clone();
+                f(&mut preferences, input.checked());
+                store.dispatch(ChatAction::SetPreferences(preferences));
+            })
+        }
+    };
+    let update_quiet_hours = {
+        let store = store.clone();
+        move |f: fn(&mut QuietHours, u32)| {
+            let store = store.clone();
+            Callback::from(move |e: Event| {
+                let input: HtmlInputElement = e.target_unchecked_into();
+                if let Some(minute) = parse_minute_of_day(&input.value()) {
+                    let mut preferences = store.preferences.clone();
+                    f(&mut preferences.quiet_hours, minute);
+                    store.dispatch(ChatAction::SetPreferences(preferences));
+                }
+            })
+        }
+    };
+    let timezones = use_state(time::timezones);
+    let set_timezone = {
+        let store = store.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            let mut preferences = store.preferences.clone();
+            preferences.time_format.timezone = Some(select.value()).filter(|zone| !zone.is_empty());
+            store.dispatch(ChatAction::SetPreferences(preferences));
+        })
+    };
+    let set_clock = {
+        let store = store.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            let mut preferences = store.preferences.clone();
+            preferences.time_format.clock = match select.value().as_str() {
+                "12h" => Some(Clock::TwelveHour),
+                "24h" => Some(Clock::TwentyFourHour),
+                _ => None,
+            };
+            store.dispatch(ChatAction::SetPreferences(preferences));
+        })
+    };
+    let clock = preferences.time_format.clock;
+    let set_image_quality = {
+        let store = store.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            if let Some(quality) = ImageQuality::ALL.get(select.selected_index() as usize) {
+                let mut preferences = store.preferences.clone();
+                preferences.image_quality = *quality;
+                store.dispatch(ChatAction::SetPreferences(preferences));
+            }
+        })
+    };
+    let set_sound = {
+        let store = store.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            if let Some(sound) = NotificationSound::ALL.get(select.selected_index() as usize) {
+                let mut preferences = store.preferences.clone();
+                preferences.sound = *sound;
+                store.dispatch(ChatAction::SetPreferences(preferences));
+                sound::play(*sound);
+            }
+        })
+    };
+    let preview_sound = {
+        let sound = preferences.sound;
+        Callback::from(move |_: MouseEvent| sound::play(sound))
+    };
+    let clip_error = use_state(|| None::<String>);
+    let clip_input = use_node_ref();
+    let pick_clip = {
+        let clip_input = clip_input.clone();
+        Callback::from(move |_: MouseEvent| {
+            if let Some(input) = clip_input.cast::<HtmlInputElement>() {
+                input.click();
+            }
+        })
+    };
+    let on_clip = {
+        let clip_error = clip_error.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            let Some(file) = input.files().and_then(|files| files.get(0)) else {
+                return;
+            };
+            input.set_value("");
+            let clip_error = clip_error.clone();
+            spawn_local(async move {
+                match sound::set_custom_clip(&file).await {
+                    Ok(()) => {
+                        clip_error.set(None);
+                        sound::play(NotificationSound::Custom);
+                    }
+                    Err(e) => clip_error.set(Some(e)),
+                }
+            });
+        })
+    };
+    let set_theme = {
+        let store = store.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            if let Some(theme) = Theme::ALL.get(select.selected_index() as usize) {
+                let mut preferences = store.preferences.clone();
+                preferences.theme = theme.clone();
+                store.dispatch(ChatAction::SetPreferences(preferences));
+            }
+        })
+    };
+    let set_suppression = {
+        let store = store.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            if let Some((suppression, _)) = SUPPRESSIONS.get(select.selected_index() as usize) {
+                let mut preferences = store.preferences.clone();
+                preferences.suppress_mentions = *suppression;
+                store.dispatch(ChatAction::SetPreferences(preferences));
+            }
+        })
+    };
+    let set_announcements = {
+        let store = store.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            if let Some(announcements) = Announcements::ALL.get(select.selected_index() as usize) {
+                let mut preferences = store.preferences.clone();
+                preferences.announcements = *announcements;
+                store.dispatch(ChatAction::SetPreferences(preferences));
+            }
+        })
+    };
+    let reset_confirmations = {
+        let store = store.clone();
+        Callback::from(move |_: MouseEvent| {
+            let mut preferences = store.preferences.clone();
+            preferences.skip_confirmations.clear();
+            store.dispatch(ChatAction::SetPreferences(preferences));
+        })
+    };
+    let set_activity = {
+        let store = store.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            let mut preferences = store.preferences.clone();
+            preferences.activity = input.value().trim().to_string();
+            store.dispatch(ChatAction::SetPreferences(preferences));
+        })
+    };
+    let set_wallpaper = {
+        let store = store.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            let mut preferences = store.preferences.clone();
+            // The image option follows the patterns.
+            let index = select.selected_index() as usize;
+            preferences.wallpaper = match Wallpaper::PATTERNS.get(index) {
+                Some(pattern) => pattern.clone(),
+                None => Wallpaper::Image(String::new()),
+            };
+            store.dispatch(ChatAction::SetPreferences(preferences));
+        })
+    };
+    let set_wallpaper_url = {
+        let store = store.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            let mut preferences = store.preferences.clone();
+            preferences.wallpaper = Wallpaper::Image(input.value().trim().to_string());
+            store.dispatch(ChatAction::SetPreferences(preferences));
+        })
+    };
+    let set_wallpaper_dim = {
+        let store = store.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            if let Ok(dim) = input.value().parse() {
+                let mut preferences = store.preferences.clone();
+                preferences.wallpaper_dim = dim;
+                store.dispatch(ChatAction::SetPreferences(preferences));
+            }
+        })
+    };
+    let set_bubble_style = {
+        let store = store.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            if let Some(style) = BubbleStyle::ALL.get(select.selected_index() as usize) {
+                let mut preferences = store.preferences.clone();
+                preferences.bubble_style = *style;
+                store.dispatch(ChatAction::SetPreferences(preferences));
+            }
+        })
+    };
+    let allow_media = {
+        let store = store.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            let name = input.value().trim().trim_start_matches('@').to_string();
+            input.set_value("");
+            if !name.is_empty() {
+                let mut preferences = store.preferences.clone();
+                preferences.media_flagged.remove(&name);
+                preferences.media_allowlist.insert(name);
+                store.dispatch(ChatAction::SetPreferences(preferences));
+            }
+        })
+    };
+    // Takes a name off the allowlist, or off the flagged list.
+    let forget_media = |flagged: bool, name: &str| {
+        let store = store.clone();
+        let name = name.to_string();
+        Callback::from(move |_: MouseEvent| {
+            let mut preferences = store.preferences.clone();
+            if flagged {
+                preferences.media_flagged.remove(&name);
+            } else {
+                preferences.media_allowlist.remove(&name);
+            }
+            store.dispatch(ChatAction::SetPreferences(preferences));
+        })
+    };
+    let media_names = |names: &HashSet<String>, flagged: bool| {
+        let mut names: Vec<&String> = names.iter().collect();
+        names.sort();
+        names
+            .into_iter()
+            .map(|name| {
+                html! {
+                    <li class="flex items-center justify-between">
+                        <span class="truncate">{name.clone()}</span>
+                        <button title={format!("Remove {}", name)} onclick={forget_media(flagged, name)}
+                            class={classes!("px-1", "rounded", classes.hover_bg)}>{"✕"}</button>
+                    </li>
+                }
+            })
+            .collect::<Html>()
+    };
+    // Lists the sites in one of the per-domain preferences, each with a button taking it off.
+    let domain_names = |list: fn(&mut Preferences) -> &mut HashSet<String>, undo: &str| {
+        let mut domains: Vec<String> = list(&mut preferences.clone()).iter().cloned().collect();
+        domains.sort();
+        domains
+            .into_iter()
+            .map(|domain| {
+                let onclick = {
+                    let store = store.clone();
+                    let domain = domain.clone();
+                    Callback::from(move |_: MouseEvent| {
+                        let mut preferences = store.preferences.clone();
+                        list(&mut preferences).remove(&domain);
+                        store.dispatch(ChatAction::SetPreferences(preferences));
+                    })
+                };
+                html! {
+                    <li class="flex items-center justify-between">
+                        <span class="truncate">{domain.clone()}</span>
+                        <button title={format!("{} for {}", undo, domain)} {onclick}
+                            class={classes!("px-1", "rounded", classes.hover_bg)}>{"✕"}</button>
+                    </li>
+                }
+            })
+            .collect::<Html>()
+    };
+    let set_motion = {
+        let store = store.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            if let Some(motion) = Motion::ALL.get(select.selected_index() as usize) {
+                let mut preferences = store.preferences.clone();
+                preferences.motion = *motion;
+                store.dispatch(ChatAction::SetPreferences(preferences));
+            }
+        })
+    };
+
+    html! {
+        <Modal title="Settings" testid="settings-panel" on_close={props.on_close.clone()}>
+            <label class="flex items-center justify-between py-1">
+                <span>{"Theme"}</span>
+                <select data-testid="theme-select" onchange={set_theme}
+                    class={classes!("w-32", "border", "rounded", "px-1", "text-xs", classes.border, classes.input_bg)}>
+                    { for Theme::ALL.iter().map(|option| html! {
+                        <option selected={theme == *option}>{option.label()}</option>
+                    }) }
+                </select>
+            </label>
+            <label class="flex items-center justify-between py-1" title="Not shown in high contrast">
+                <span>{"Wallpaper"}</span>
+                <select data-testid="wallpaper-select" onchange={set_wallpaper}
+                    class={classes!("w-32", "border", "rounded", "px-1", "text-xs", classes.border, classes.input_bg)}>
+                    { for Wallpaper::PATTERNS.iter().map(|option| html! {
+                        <option selected={preferences.wallpaper == *option}>{option.label()}</option>
+                    }) }
+                    <option selected={matches!(preferences.wallpaper, Wallpaper::Image(_))}>{Wallpaper::Image(String::new()).label()}</option>
+                </select>
+            </label>
+            if let Wallpaper::Image(url) = &preferences.wallpaper {
+                <label class="flex items-center justify-between pl-2 pb-1 text-xs">
+                    <span>{"Image link"}</span>
+                    <input data-testid="wallpaper-url" type="url" placeholder="https://…" value={url.clone()} onchange={set_wallpaper_url}
+                        aria-invalid={(!url.is_empty() && !wallpaper::is_image_url(url)).to_string()}
+                        class={classes!("w-32", "border", "rounded", "px-1", classes.border, classes.input_bg)} />
+                </label>
+            }
+            if preferences.wallpaper != Wallpaper::None {
+                <label class="flex items-center justify-between pl-2 pb-1 text-xs" title="Fades the wallpaper so messages stay easy to read">
+                    <span>{"Dim"}</span>
+                    <input data-testid="wallpaper-dim" type="range" min="0" max="90" step="10"
+                        value={preferences.wallpaper_dim.to_string()} onchange={set_wallpaper_dim} class="w-32" />
+                </label>
+            }
+            <label class="flex items-center justify-between py-1" title="Transitions, smooth scrolling and animated images">
+                <span>{"Motion"}</span>
+                <select data-testid="motion-select" onchange={set_motion}
+                    class={classes!("w-32", "border", "rounded", "px-1", "text-xs", classes.border, classes.input_bg)}>
+                    { for Motion::ALL.iter().map(|option| html! {
+                        <option selected={preferences.motion == *option}>{option.label()}</option>
+                    }) }
+                </select>
+            </label>
+            <label class="flex items-center justify-between py-1">
+                <span>{"Time zone"}</span>
+                <select data-testid="timezone-select" onchange={set_timezone}
+                    class={classes!("w-32", "border", "rounded", "px-1", "text-xs", classes.border, classes.input_bg)}>
+                    <option value="" selected={preferences.time_format.timezone.is_none()}>{"Local time"}</option>
+                    { for timezones.iter().map(|zone| html! {
+                        <option value={zone.clone()} selected={preferences.time_format.timezone.as_ref() == Some(zone)}>{zone.replace('_', " ")}</option>
+                    }) }
+                </select>
+            </label>
+            <label class="flex items-center justify-between py-1">
+                <span>{"Clock"}</span>
+                <select data-testid="clock-select" onchange={set_clock}
+                    class={classes!("w-32", "border", "rounded", "px-1", "text-xs", classes.border, classes.input_bg)}>
+                    <option value="" selected={clock.is_none()}>{"Automatic"}</option>
+                    <option value="12h" selected={clock == Some(Clock::TwelveHour)}>{"12-hour"}</option>
+                    <option value="24h" selected={clock == Some(Clock::TwentyFourHour)}>{"24-hour"}</option>
+                </select>
+            </label>
+            <label class="flex items-center justify-between py-1">
+                <span>{"Image quality"}</span>
+                <select data-testid="image-quality-select" onchange={set_image_quality}
+                    class={classes!("w-32", "border", "rounded", "px-1", "text-xs", classes.border, classes.input_bg)}>
+                    { for ImageQuality::ALL.iter().map(|quality| html! {
+                        <option selected={preferences.image_quality == *quality}>{quality.label()}</option>
+                    }) }
+                </select>
+            </label>
+            <label class="flex items-center justify-between py-1" title="Linked GIFs and maps load when clicked, and avatars are smaller">
+                <span>{"Data saver"}</span>
+                <input data-testid="data-saver-toggle" type="checkbox"
+                    checked={preferences.data_saver}
+                    onchange={update(|p, on| p.data_saver = on)} />
+            </label>
+            <label class="flex items-center justify-between py-1" title="Off in data saver mode">
+                <span>{"Autoplay GIFs"}</span>
+                <input data-testid="gif-autoplay-toggle" type="checkbox"
+                    checked={preferences.autoplay_gifs}
+                    onchange={update(|p, on| p.autoplay_gifs = on)} />
+            </label>
+            <label class="flex items-center justify-between py-1" title="Images and GIFs from anyone else wait behind a warning">
+                <span>{"Only show media from people I allow"}</span>
+                <input data-testid="media-filter-toggle" type="checkbox"
+                    checked={preferences.media_filter}
+                    onchange={update(|p, on| p.media_filter = on)} />
+            </label>
+            if preferences.media_filter {
+                <div data-testid="media-allowlist" class="pl-2 pb-1 text-xs">
+                    <ul>{ media_names(&preferences.media_allowlist, false) }</ul>
+                    <input data-testid="media-allow-input" type="text" placeholder="Allow someone…" onchange={allow_media}
+                        class={classes!("w-full", "mt-1", "border", "rounded", "px-1", classes.border, classes.input_bg)} />
+                </div>
+            }
+            if !preferences.media_flagged.is_empty() {
+                <div data-testid="media-flagged" class="pl-2 pb-1 text-xs">
+                    <div class={classes!(classes.muted_text)}>{"Always hidden"}</div>
+                    <ul>{ media_names(&preferences.media_flagged, true) }</ul>
+                </div>
+            }
+            if !preferences.trusted_domains.is_empty() {
+                <div data-testid="trusted-domains" class="pb-1 text-xs">
+                    <div class={classes!(classes.muted_text)}>{"Links that open without asking"}</div>
+                    <ul>{ domain_names(|p| &mut p.trusted_domains, "Ask again") }</ul>
+                </div>
+            }
+            if !preferences.unpreviewed_domains.is_empty() {
+                <div data-testid="unpreviewed-domains" class="pb-1 text-xs">
+                    <div class={classes!(classes.muted_text)}>{"Links shown without a preview"}</div>
+                    <ul>{ domain_names(|p| &mut p.unpreviewed_domains, "Preview again") }</ul>
+                </div>
+            }
+            <label class="flex items-center justify-between py-1">
+                <span>{"Do not disturb"}</span>
+                <input data-testid="dnd-toggle" type="checkbox"
+                    checked={preferences.dnd}
+                    onchange={update(|p, on| p.dnd = on)} />
+            </label>
+            <label class="flex items-center justify-between py-1" title="Shown to everyone under your name">
+                <span>{"Activity"}</span>
+                <input data-testid="activity-input" type="text" placeholder="In a meeting"
+                    maxlength={MAX_ACTIVITY_CHARS.to_string()}
+                    value={preferences.activity.clone()} onchange={set_activity}
+                    class={classes!("w-32", "border", "rounded", "px-1", "text-xs", classes.border, classes.input_bg)} />
+            </label>
+            if push::available() {
+                <label class="flex items-center justify-between py-1" title="Uses your browser's push service">
+                    <span>{"Notify me of mentions while closed"}</span>
+                    <input data-testid="push-toggle" type="checkbox"
+                        checked={preferences.push}
+                        onchange={update(|p, on| p.push = on)} />
+                </label>
+            }
+            <label class="flex items-center justify-between py-1" title="Those messages still arrive, just without pinging you">
+                <span>{"Don't ping me for"}</span>
+                <select data-testid="suppress-mentions-select" onchange={set_suppression}
+                    class={classes!("w-32", "border", "rounded", "px-1", "text-xs", classes.border, classes.input_bg)}>
+                    { for SUPPRESSIONS.iter().map(|(option, label)| html! {
+                        <option selected={preferences.suppress_mentions == *option}>{*label}</option>
+                    }) }
+                </select>
+            </label>
+            <label class="flex items-center justify-between py-1">
+                <span>{"Quiet hours"}</span>
+                <input data-testid="quiet-hours-toggle" type="checkbox"
+                    checked={preferences.quiet_hours.enabled}
+                    onchange={update(|p, on| p.quiet_hours.enabled = on)} />
+            </label>
+            if preferences.quiet_hours.enabled {
+                <div data-testid="quiet-hours" class="flex items-center justify-between pl-2 pb-1 text-xs">
+                    <input type="time" value={format_minute_of_day(preferences.quiet_hours.start)}
+                        onchange={update_quiet_hours(|q, minute| q.start = minute)}
+                        class={classes!("border", "rounded", "px-1", classes.border, classes.input_bg)} />
+                    <span>{"to"}</span>
+                    <input type="time" value={format_minute_of_day(preferences.quiet_hours.end)}
+                        onchange={update_quiet_hours(|q, minute| q.end = minute)}
+                        class={classes!("border", "rounded", "px-1", classes.border, classes.input_bg)} />
+                </div>
+            }
+            <div class="flex items-center justify-between py-1">
+                <span>{"Sound"}</span>
+                <span class="flex items-center">
+                    <button data-testid="sound-preview" title="Play" onclick={preview_sound} disabled={preferences.sound == NotificationSound::Off}
+                        class="mr-1 px-1 disabled:opacity-50">{"▶"}</button>
+                    <select data-testid="sound-select" aria-label="Notification sound" onchange={set_sound}
+                        class={classes!("w-28", "border", "rounded", "px-1", "text-xs", classes.border, classes.input_bg)}>
+                        { for NotificationSound::ALL.iter().map(|sound| html! {
+                            <option selected={preferences.sound == *sound}>{sound.label()}</option>
+                        }) }
+                    </select>
+                </span>
+            </div>
+            if preferences.sound == NotificationSound::Custom {
+                <div class="flex items-center justify-between pl-2 pb-1 text-xs">
+                    <span class={classes.muted_text}>{format!("Audio, up to {} KB", sound::MAX_CLIP_BYTES / 1024)}</span>
+                    <input ref={clip_input} data-testid="sound-clip-input" type="file" accept="audio/*" class="hidden" onchange={on_clip} />
+                    <button data-testid="sound-clip-button" onclick={pick_clip} class={classes!("px-2", "py-0.5", "rounded", classes.button_bg)}>{"Choose clip…"}</button>
+                </div>
+                if let Some(error) = &*clip_error {
+                    <div data-testid="sound-clip-error" class="pl-2 pb-1 text-xs text-red-600">{error.clone()}</div>
+                }
+            }
+            <label class="flex items-center justify-between py-1" title="What screen readers read out as it arrives">
+                <span>{"Announce"}</span>
+                <select data-testid="announcements-select" onchange={set_announcements}
+                    class={classes!("w-32", "border", "rounded", "px-1", "text-xs", classes.border, classes.input_bg)}>
+                    { for Announcements::ALL.iter().map(|option| html! {
+                        <option selected={preferences.announcements == *option}>{option.label()}</option>
+                    }) }
+                </select>
+            </label>
+            <label class="flex items-center justify-between py-1">
+                <span>{"Anyone can tick my checklists"}</span>
+                <input data-testid="open-checklists-toggle" type="checkbox"
+                    checked={preferences.open_checklists}
+                    onchange={update(|p, on| p.open_checklists = on)} />
+            </label>
+            if !preferences.skip_confirmations.is_empty() {
+                <div class="flex items-center justify-between py-1">
+                    <span title="Deleting, kicking and clearing history">{"Skipped confirmations"}</span>
+                    <button data-testid="reset-confirmations" onclick={reset_confirmations}
+                        class={classes!("px-2", "py-0.5", "rounded", "text-xs", classes.button_bg)}>{"Ask again"}</button>
+                </div>
+            }
+            if compact_available {
+                <label class="flex items-center justify-between py-1">
+                    <span>{"Compact mode"}</span>
+                    <input data-testid="compact-toggle" type="checkbox"
+                        checked={preferences.display_mode == DisplayMode::Compact}
+                        onchange={update(|p, on| p.display_mode = if on { DisplayMode::Compact } else { DisplayMode::Comfortable })} />
+                </label>
+            }
+            if preferences.display_mode != DisplayMode::Compact {
+                <label class="flex items-center justify-between py-1">
+                    <span>{"Message style"}</span>
+                    <select data-testid="bubble-style-select" onchange={set_bubble_style}
+                        class={classes!("w-32", "border", "rounded", "px-1", "text-xs", classes.border, classes.input_bg)}>
+                        { for BubbleStyle::ALL.iter().map(|option| html! {
+                            <option selected={preferences.bubble_style == *option}>{option.label()}</option>
+                        }) }
+                    </select>
+                </label>
+            }
+            { data_usage_stats(classes.muted_text) }
+            if telemetry_available {
+                <label class="flex items-center justify-between py-1">
+                    <span>{"Share anonymous usage stats"}</span>
+                    <input data-testid="telemetry-toggle" type="checkbox"
+                        checked={preferences.telemetry}
+                        onchange={update(|p, on| p.telemetry = on)} />
+                </label>
+                if preferences.telemetry {
+                    { usage_stats(classes.muted_text) }
+                }
+            }
+            if let Some(endpoint) = error_report::endpoint() {
+                <label class="flex items-center justify-between py-1">
+                    <span>{"Send error reports"}</span>
+                    <input data-testid="error-reports-toggle" type="checkbox"
+                        checked={preferences.error_reports}
+                        onchange={update(|p, on| p.error_reports = on)} />
+                </label>
+                <div class={classes!("pl-2", "text-xs", classes.muted_text)}>
+                    {format!("Errors and crashes, with the app's recent log, go to {} so they can be fixed.", endpoint)}
+                </div>
+            }
+        </Modal>
+    }
+}
+
+/// Bytes moved since the page loaded, as of when the panel was opened.
+fn data_usage_stats(text: &'static str) -> Html {
+    let usage = data_usage::snapshot();
+    html! {
+        <details data-testid="data-usage" class={classes!("py-1", "text-xs", text)}>
+            <summary class="cursor-pointer">{format!("Data used this session: {}", format_bytes(usage.total()))}</summary>
+            <div class="pl-2">{format!("Sent: {}", format_bytes(usage.sent))}</div>
+            <div class="pl-2">{format!("Received, attachments included: {}", format_bytes(usage.received))}</div>
+            <div class="pl-2">{format!("Avatars: {}", format_bytes(usage.media))}</div>
+        </details>
+    }
+}
+
+/// What has been collected locally, so users can see exactly what would be shared.
+fn usage_stats(text: &'static str) -> Html {
+    let metrics = telemetry::snapshot();
+    let destination = match telemetry::endpoint() {
+        Some(endpoint) => format!("Sent to {}", endpoint),
+        None => "Kept on this device".to_string(),
+    };
+    html! {
+        <details data-testid="usage-stats" class={classes!("text-xs", "pl-2", text)}>
+            <summary class="cursor-pointer">{"View stats"}</summary>
+            <div>{format!("Messages sent: {}", metrics.messages_sent)}</div>
+            <div>{format!("Messages received: {}", metrics.messages_received)}</div>
+            <div>{format!("Reconnects: {}", metrics.reconnects)}</div>
+            <div>{format!("Chat renders: {} (avg {:.1} ms, max {:.1} ms)", metrics.renders, metrics.render_ms_avg(), metrics.render_ms_max)}</div>
+            <div class="italic">{destination}</div>
+        </details>
+    }
+}
@@ -0,0 +1,41 @@
+use yew::prelude::*;
+
+use crate::motion::use_reduced_motion;
+use crate::theme::use_theme;
+
+/// Widths of the placeholder text lines, cycled so the rows don't look stamped out.
+const LINE_WIDTHS: [&str; 3] = ["w-3/4", "w-1/2", "w-2/3"];
+
+#[derive(Properties, PartialEq)]
+pub struct SkeletonProps {
+    pub rows: usize,
+    /// Tailwind size of each row's avatar circle.
+    pub avatar: Classes,
+    /// Put on the placeholder, for tests.
+    pub testid: &'static str,
+}
+
+/// Grey rows standing in for a list whose first payload hasn't arrived, pulsing unless
+/// motion is reduced.
+#[function_component(Skeleton)]
+pub fn skeleton(props: &SkeletonProps) -> Html {
+    let classes = use_theme().classes();
+    let reduced_motion = use_reduced_motion();
+    let bar = |width: &'static str| {
+        html! { <div class={classes!("h-3", "my-1", "rounded", width, classes.selected_bg)} /> }
+    };
+
+    html! {
+        <div data-testid={props.testid} role="status" aria-label="Loading" class={classes!((!reduced_motion).then_some("animate-pulse"))}>
+            { for (0..props.rows).map(|i| html! {
+                <div aria-hidden="true" class="flex items-center m-3">
+                    <div class={classes!("shrink-0", "rounded-full", "mr-3", props.avatar.clone(), classes.selected_bg)} />
+                    <div class="grow">
+                        { bar("w-1/4") }
+                        { bar(LINE_WIDTHS[i % LINE_WIDTHS.len()]) }
+                    </div>
+                </div>
+            }) }
+        </div>
+    }
+}
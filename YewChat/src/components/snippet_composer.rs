@@ -0,0 +1,108 @@
+use web_sys::{HtmlInputElement, HtmlSelectElement, HtmlTextAreaElement};
+use yew::prelude::*;
+use yewchat_protocol::Attachment;
+
+use crate::components::modal::Modal;
+use crate::highlight::Language;
+use crate::theme::use_theme;
+
+const INDENT: &str = "    ";
+
+#[derive(Properties, PartialEq)]
+pub struct SnippetComposerProps {
+    /// Called with the finished snippet; the dialog is closed by the parent.
+    pub on_send: Callback<Attachment>,
+    pub on_close: Callback<()>,
+}
+
+/// Dialog for writing a code snippet with a title and language.
+#[function_component(SnippetComposer)]
+pub fn snippet_composer(props: &SnippetComposerProps) -> Html {
+    let classes = use_theme().classes();
+    let title = use_state(String::new);
+    let language = use_state(|| None::<Language>);
+    let code = use_state(String::new);
+
+    let set_title = {
+        let title = title.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            title.set(input.value());
+        })
+    };
+    let set_language = {
+        let language = language.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            // The first option is plain text.
+            let index = (select.selected_index() as usize).checked_sub(1);
+            language.set(index.and_then(|i| Language::ALL.get(i)).copied());
+        })
+    };
+    let set_code = {
+        let code = code.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlTextAreaElement = e.target_unchecked_into();
+            code.set(input.value());
+        })
+    };
+    let send = {
+        let title = title.clone();
+        let language = language.clone();
+        let code = code.clone();
+        let on_send = props.on_send.clone();
+        Callback::from(move |_: MouseEvent| {
+            if code.trim().is_empty() {
+                return;
+            }
+            on_send.emit(Attachment::Snippet {
+                title: Some(title.trim().to_string()).filter(|t| !t.is_empty()),
+                language: language.map(|l| l.key().to_string()),
+                code: (*code).clone(),
+            });
+        })
+    };
+    let close = props.on_close.reform(|_: MouseEvent| ());
+    // Tab indents instead of leaving the textarea.
+    let indent = {
+        let code = code.clone();
+        Callback::from(move |e: KeyboardEvent| {
+            if e.key() != "Tab" || e.shift_key() {
+                return;
+            }
+            e.prevent_default();
+            let textarea: HtmlTextAreaElement = e.target_unchecked_into();
+            let start = textarea.selection_start().ok().flatten().unwrap_or(0);
+            let end = textarea.selection_end().ok().flatten().unwrap_or(start);
+            if textarea
+                .set_range_text_with_start_and_end(INDENT, start, end)
+                .is_ok()
+            {
+                let caret = start + INDENT.len() as u32;
+                let _ = textarea.set_selection_range(caret, caret);
+                code.set(textarea.value());
+            }
+        })
+    };
+
+    html! {
+        <Modal title="New snippet" testid="snippet-composer" width={classes!("w-[40rem]")} on_close={props.on_close.clone()}>
+            <div class="flex mb-2">
+                <input data-testid="snippet-title" type="text" placeholder="Title (optional)" value={(*title).clone()} oninput={set_title}
+                    class={classes!("grow", "px-2", "py-1", "border", "rounded", classes.border, classes.input_bg)} />
+                <select data-testid="snippet-composer-language" onchange={set_language}
+                    class={classes!("ml-2", "px-1", "border", "rounded", classes.border, classes.input_bg)}>
+                    <option selected={language.is_none()}>{"Plain text"}</option>
+                    { for Language::ALL.iter().map(|l| html! { <option selected={*language == Some(*l)}>{l.label()}</option> }) }
+                </select>
+            </div>
+            <textarea data-testid="snippet-code" rows="14" spellcheck="false" value={(*code).clone()} oninput={set_code} onkeydown={indent}
+                class={classes!("w-full", "p-2", "border", "rounded", "font-mono", "text-xs", "whitespace-pre", classes.border, classes.input_bg)} />
+            <div class="flex justify-end mt-3">
+                <button onclick={close} class={classes!("px-3", "py-1", "mr-2", "rounded", classes.button_bg)}>{"Cancel"}</button>
+                <button data-testid="snippet-send" onclick={send} disabled={code.trim().is_empty()}
+                    class="px-3 py-1 rounded bg-blue-600 text-white disabled:opacity-50">{"Send"}</button>
+            </div>
+        </Modal>
+    }
+}
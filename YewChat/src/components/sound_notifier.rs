@@ -0,0 +1,44 @@
+use yew::prelude::*;
+
+use crate::services::{notifications, sound};
+use crate::store::use_store;
+
+/// Plays the chosen sound when messages from others arrive while the tab is hidden or in a
+/// channel other than the one open.
+#[function_component(SoundNotifier)]
+pub fn sound_notifier() -> Html {
+    let store = use_store();
+    // Ids only grow, so anything above the newest seen is new; history pages are older.
+    let newest = use_mut_ref(|| store.messages.last().map_or(0, |m| m.id));
+
+    {
+        let store = store.clone();
+        use_effect_with_deps(
+            move |_| {
+                let previous = *newest.borrow();
+                let arrived: Vec<_> = store
+                    .messages
+                    .iter()
+                    .rev()
+                    .take_while(|m| m.id > previous)
+                    .collect();
+                if let Some(last) = arrived.first() {
+                    *newest.borrow_mut() = last.id;
+                }
+                let hidden = gloo::utils::document().hidden();
+                let audible = arrived.iter().any(|m| {
+                    m.from != store.me
+                        && !store.preferences.is_muted(&m.channel)
+                        && (hidden || m.channel != store.channel)
+                });
+                if audible && notifications::allowed(&store.preferences) {
+                    sound::play(store.preferences.sound);
+                }
+                || ()
+            },
+            store.messages.len(),
+        );
+    }
+
+    html! {}
+}
@@ -0,0 +1,43 @@
+use yew::prelude::*;
+use yewchat_protocol::{SystemEvent, SystemNotice};
+
+use crate::theme::use_theme;
+use crate::time::use_time_format;
+
+/// What the line says for `event`.
+pub fn describe(event: &SystemEvent) -> String {
+    match event {
+        SystemEvent::Joined { user } => format!("{} joined", user),
+        SystemEvent::Left { user } => format!("{} left", user),
+        SystemEvent::Renamed { from, to } => format!("#{} was renamed to #{}", from, to),
+    }
+}
+
+/// Whether `event` is shown in `channel`: joins and leaves everywhere, a rename only in the
+/// channel renamed.
+pub fn shows_in(event: &SystemEvent, channel: &str) -> bool {
+    match event {
+        SystemEvent::Joined { .. } | SystemEvent::Left { .. } => true,
+        SystemEvent::Renamed { to, .. } => to == channel,
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct SystemLineProps {
+    pub notice: SystemNotice,
+}
+
+/// A join, leave or rename, centered among the messages.
+#[function_component(SystemLine)]
+pub fn system_line(props: &SystemLineProps) -> Html {
+    let classes = use_theme().classes();
+    let time_format = use_time_format();
+    let notice = &props.notice;
+
+    html! {
+        <div data-testid="system-line" title={time_format.date_time(notice.time)}
+            class={classes!("flex", "justify-center", "my-1", "px-8", "text-xs", "italic", classes.muted_text)}>
+            {describe(&notice.event)}
+        </div>
+    }
+}
@@ -0,0 +1,81 @@
+use web_sys::HtmlTextAreaElement;
+use yew::prelude::*;
+
+use crate::components::modal::Modal;
+use crate::theme::use_theme;
+
+/// The first `max` chars of `text`.
+pub fn limit_chars(text: &str, max: usize) -> &str {
+    match text.char_indices().nth(max) {
+        Some((end, _)) => &text[..end],
+        None => text,
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct TextPromptProps {
+    pub title: String,
+    /// What the textarea starts with.
+    #[prop_or_default]
+    pub value: String,
+    #[prop_or_default]
+    pub placeholder: &'static str,
+    /// Longest text that can be entered, in chars; typing past it is cut off as it happens.
+    #[prop_or_default]
+    pub max_chars: Option<usize>,
+    /// Label of the button that submits, e.g. "Save".
+    pub action: &'static str,
+    /// Put on the dialog, for tests.
+    pub testid: &'static str,
+    /// Called with the entered text; the dialog is closed by the parent.
+    pub on_submit: Callback<String>,
+    pub on_close: Callback<()>,
+}
+
+/// Dialog asking for a piece of text, in place of `window.prompt`.
+#[function_component(TextPrompt)]
+pub fn text_prompt(props: &TextPromptProps) -> Html {
+    let classes = use_theme().classes();
+    let text = {
+        let value = props.value.clone();
+        use_state(move || value)
+    };
+
+    let oninput = {
+        let text = text.clone();
+        let max_chars = props.max_chars;
+        Callback::from(move |e: InputEvent| {
+            let textarea: HtmlTextAreaElement = e.target_unchecked_into();
+            let value = textarea.value();
+            let limited = max_chars.map_or(value.as_str(), |max| limit_chars(&value, max));
+            if limited.len() < value.len() {
+                // The state may not change, so nothing would re-render to take the extra out.
+                textarea.set_value(limited);
+            }
+            text.set(limited.to_string());
+        })
+    };
+    let submit = {
+        let text = text.clone();
+        let on_submit = props.on_submit.clone();
+        Callback::from(move |_: MouseEvent| on_submit.emit((*text).clone()))
+    };
+    let close = props.on_close.reform(|_: MouseEvent| ());
+
+    html! {
+        <Modal title={props.title.clone()} testid={props.testid} width={classes!("w-96")} on_close={props.on_close.clone()}>
+            <textarea data-testid="text-prompt-input" rows="4" autofocus=true placeholder={props.placeholder} aria-label={props.title.clone()}
+                value={(*text).clone()} {oninput}
+                class={classes!("w-full", "p-2", "border", "rounded", classes.border, classes.input_bg)} />
+            if let Some(max) = props.max_chars {
+                <div data-testid="text-prompt-count" class={classes!("text-right", "text-xs", classes.muted_text)}>
+                    {format!("{}/{}", text.chars().count(), max)}
+                </div>
+            }
+            <div class="flex justify-end mt-3">
+                <button onclick={close} class={classes!("px-3", "py-1", "mr-2", "rounded", classes.button_bg)}>{"Cancel"}</button>
+                <button data-testid="text-prompt-submit" onclick={submit} class="px-3 py-1 rounded bg-blue-600 text-white">{props.action}</button>
+            </div>
+        </Modal>
+    }
+}
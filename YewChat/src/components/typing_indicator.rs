@@ -0,0 +1,58 @@
+use yew::prelude::*;
+use yewchat_protocol::TYPISTS_NAMED;
+
+use crate::motion::use_reduced_motion;
+use crate::theme::use_theme;
+
+/// What the indicator says for `names` typing, or `None` when nobody is. Past
+/// `TYPISTS_NAMED` people it stops naming them.
+pub fn describe(names: &[String]) -> Option<String> {
+    if names.len() > TYPISTS_NAMED {
+        return Some("Several people are typing…".into());
+    }
+    match names {
+        [] => None,
+        [one] => Some(format!("{} is typing…", one)),
+        [first, second] => Some(format!("{} and {} are typing…", first, second)),
+        [rest @ .., last] => Some(format!("{} and {} are typing…", rest.join(", "), last)),
+    }
+}
+
+/// Three dots bouncing in turn, or sitting still when motion is reduced.
+#[function_component(TypingDots)]
+pub fn typing_dots() -> Html {
+    let reduced_motion = use_reduced_motion();
+    let dot = |delay: &'static str| {
+        html! {
+            <span class={classes!("inline-block", "w-1", "h-1", "mx-px", "rounded-full", "bg-current", (!reduced_motion).then_some("animate-bounce"))}
+                style={format!("animation-delay: {}", delay)}></span>
+        }
+    };
+    html! {
+        <span aria-hidden="true" class="inline-flex items-end h-3">
+            { dot("0ms") }{ dot("150ms") }{ dot("300ms") }
+        </span>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct TypingIndicatorProps {
+    /// Who is typing in the open channel.
+    pub names: Vec<String>,
+}
+
+/// "alice is typing…" above the composer, kept to one short line.
+#[function_component(TypingIndicator)]
+pub fn typing_indicator(props: &TypingIndicatorProps) -> Html {
+    let classes = use_theme().classes();
+    let Some(text) = describe(&props.names) else {
+        return html! {};
+    };
+    html! {
+        <div data-testid="typing-indicator"
+            class={classes!("w-full", "px-3", "text-xs", "italic", "truncate", classes.muted_text)}>
+            <TypingDots />
+            <span class="ml-1">{text}</span>
+        </div>
+    }
+}
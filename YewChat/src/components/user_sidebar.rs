@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use yew::prelude::*;
+use yewchat_protocol::{Attachment, ChannelInfo, ModerationAction, Status};
+
+use crate::components::audit_log::AuditLog;
+use crate::components::avatar::Avatar;
+use crate::components::badges::Badges;
+use crate::components::channel_list::ChannelList;
+use crate::components::context_menu::{ContextMenu, MenuItem};
+use crate::components::sessions_panel::SessionsPanel;
+use crate::components::settings::SettingsPanel;
+use crate::components::skeleton::Skeleton;
+use crate::components::typing_indicator::TypingDots;
+use crate::services::clipboard;
+use crate::state::{ChatAction, Unread, UserProfile};
+use crate::store::{use_store, Store};
+use crate::theme::use_theme;
+
+#[derive(Properties, PartialEq)]
+pub struct UserSidebarProps {
+    pub users: Rc<Vec<UserProfile>>,
+    pub spectators: Rc<Vec<String>>,
+    pub channels: Rc<Vec<ChannelInfo>>,
+    pub channel: String,
+    pub unread: HashMap<String, Unread>,
+    pub on_select_channel: Callback<String>,
+    pub on_invite: Callback<String>,
+    /// Kicks or bans from a user's context menu, or restyles a channel; only offered to
+    /// moderators.
+    pub on_moderate: Callback<ModerationAction>,
+    /// Asks for the sessions signed in under our name.
+    pub on_list_sessions: Callback<()>,
+    /// Signs out the session with the given id.
+    pub on_sign_out: Callback<String>,
+    /// The user list hasn't arrived yet; placeholders stand in for it.
+    #[prop_or_default]
+    pub loading: bool,
+}
+
+#[function_component(UserSidebar)]
+pub fn user_sidebar(props: &UserSidebarProps) -> Html {
+    let classes = use_theme().classes();
+    let show_settings = use_state(|| false);
+    let show_audit = use_state(|| false);
+    let show_sessions = use_state(|| false);
+    let store = use_store();
+    let is_moderator = store.profile(&store.me).role.moderates();
+    let menu = use_state(|| None::<(i32, i32, String)>);
+    let toggle_settings = {
+        let show_settings = show_settings.clone();
+        let show_audit = show_audit.clone();
+        let show_sessions = show_sessions.clone();
+        Callback::from(move |_| {
+            show_audit.set(false);
+            show_sessions.set(false);
+            show_settings.set(!*show_settings)
+        })
+    };
+    let toggle_audit = {
+        let show_settings = show_settings.clone();
+        let show_audit = show_audit.clone();
+        let show_sessions = show_sessions.clone();
+        Callback::from(move |_| {
+            show_settings.set(false);
+            show_sessions.set(false);
+            show_audit.set(!*show_audit)
+        })
+    };
+    let toggle_sessions = {
+        let show_settings = show_settings.clone();
+        let show_audit = show_audit.clone();
+        let show_sessions = show_sessions.clone();
+        Callback::from(move |_| {
+            show_settings.set(false);
+            show_audit.set(false);
+            show_sessions.set(!*show_sessions)
+        })
+    };
+
+    let close_settings = {
+        let show_settings = show_settings.clone();
+        Callback::from(move |_| show_settings.set(false))
+    };
+    let close_audit = {
+        let show_audit = show_audit.clone();
+        Callback::from(move |_| show_audit.set(false))
+    };
+    let close_sessions = {
+        let show_sessions = show_sessions.clone();
+        Callback::from(move |_| show_sessions.set(false))
+    };
+
+    html! {
+        <div data-testid="user-list" class={classes!("flex-none", "w-56", "h-screen", "relative", classes.panel_bg)}>
+            <div class={classes!("flex", "justify-between", "items-center", "text-xl", "p-3", classes.main_text)}>
+                {"Users"}
+                <span class="grow" />
+                if is_moderator {
+                    <button data-testid="audit-toggle" title="Audit log" onclick={toggle_audit} class={classes!("p-1", "mr-1", "text-sm", "border", classes.border, "rounded")}>
+                        {"🛡️"}
+                    </button>
+                }
+                <button data-testid="sessions-toggle" title="Sessions" onclick={toggle_sessions} class={classes!("p-1", "mr-1", "text-sm", "border", classes.border, "rounded")}>
+                    {"💻"}
+                </button>
+                <button data-testid="settings-toggle" title="Settings" onclick={toggle_settings} class={classes!("p-1", "text-sm", "border", classes.border, "rounded")}>
+                    {"⚙️"}
+                </button>
+            </div>
+            if *show_settings {
+                <SettingsPanel on_close={close_settings} />
+            }
+            if *show_audit {
+                <AuditLog on_close={close_audit} />
+            }
+            if *show_sessions {
+                <SessionsPanel on_refresh={props.on_list_sessions.clone()} on_sign_out={props.on_sign_out.clone()} on_close={close_sessions} />
+            }
+            <ChannelList channels={props.channels.clone()} current={props.channel.clone()} unread={props.unread.clone()} on_select={props.on_select_channel.clone()} on_invite={props.on_invite.clone()} on_moderate={props.on_moderate.clone()} />
+            {
+                props.users.iter().map(|u| {
+                    let oncontextmenu = {
+                        let menu = menu.clone();
+                        let name = u.name.clone();
+                        Callback::from(move |e: MouseEvent| {
+                            e.prevent_default();
+                            menu.set(Some((e.client_x(), e.client_y(), name.clone())));
+                        })
+                    };
+                    html!{
+                        <div data-testid="user-item" {oncontextmenu} class={classes!("flex", "m-3", classes.item_bg, "rounded-lg", "p-2")}>
+                            <div>
+                                <Avatar name={u.name.clone()} class={classes!("w-12", "h-12", "rounded-full")} presence={store.presence.get(&u.name).copied()} />
+                            </div>
+                            <div class="flex-grow p-3">
+                                <div class={classes!("flex", "text-xs", "justify-between", classes.main_text)}>
+                                    <div data-testid="user-name">{u.name.clone()}<Badges user={u.clone()} /></div>
+                                    if let Some(typing) = store.typing.get(&u.name) {
+                                        <span data-testid="user-typing" title={format!("Typing in #{}", typing.channel)}
+                                            class={classes!("flex", "items-center", classes.muted_text)}>
+                                            <TypingDots />
+                                            <span class="sr-only">{"typing"}</span>
+                                        </span>
+                                    }
+                                </div>
+                                <div class={classes!("text-xs", classes.muted_text)}>
+                                    if u.status == Status::Dnd {
+                                        <div data-testid="user-dnd">{"⛔ Do not disturb"}</div>
+                                    }
+                                    if let Some(activity) = &u.activity {
+                                        <div data-testid="user-activity" class="truncate" title={activity.clone()}>{activity.clone()}</div>
+                                    } else if u.status != Status::Dnd {
+                                        {"Hi there!"}
+                                    }
+                                </div>
+                            </div>
+                        </div>
+                    }
+                }).collect::<Html>()
+            }
+            if props.loading {
+                <Skeleton rows={3} avatar={classes!("w-12", "h-12")} testid="user-skeleton" />
+            } else if props.users.is_empty() {
+                <div data-testid="no-users" class={classes!("px-3", "py-2", "text-xs", classes.muted_text)}>{"Nobody's online yet."}</div>
+            }
+            if !props.spectators.is_empty() {
+                <div data-testid="spectator-list" class={classes!("px-3", "text-xs", classes.muted_text)}>
+                    <div class="uppercase mb-1">{format!("Watching ({})", props.spectators.len())}</div>
+                    { for props.spectators.iter().map(|name| html! { <div class="truncate">{format!("👀 {}", name)}</div> }) }
+                </div>
+            }
+            if let Some((x, y, name)) = (*menu).clone() {
+                <ContextMenu {x} {y} items={user_menu_items(&name, &store, is_moderator, &props.on_moderate)} on_close={Callback::from(move |_| menu.set(None))} />
+            }
+        </div>
+    }
+}
+
+fn user_menu_items(
+    name: &str,
+    store: &Store,
+    is_moderator: bool,
+    on_moderate: &Callback<ModerationAction>,
+) -> Vec<MenuItem> {
+    let mention = {
+        let store = store.clone();
+        let name = name.to_string();
+        Callback::from(move |_| store.dispatch(ChatAction::Mention(name.clone())))
+    };
+    let share = {
+        let store = store.clone();
+        let contact = Attachment::Contact {
+            name: name.to_string(),
+        };
+        Callback::from(move |_| store.dispatch(ChatAction::Attach(contact.clone())))
+    };
+    let copy = {
+        let name = name.to_string();
+        Callback::from(move |_| clipboard::write_text(&name))
+    };
+    let mut items = vec![
+        MenuItem::new("@", "Mention", mention),
+        MenuItem::new("👤", "Share contact", share),
+        MenuItem::new("📋", "Copy name", copy),
+    ];
+    if name != store.me {
+        let hidden = store.preferences.hides_media_from(name, &store.me);
+        let store = store.clone();
+        let user = name.to_string();
+        let toggle = Callback::from(move |_| {
+            let mut preferences = store.preferences.clone();
+            if hidden {
+                preferences.media_flagged.remove(&user);
+                if preferences.media_filter {
+                    preferences.media_allowlist.insert(user.clone());
+                }
+            } else {
+                preferences.media_allowlist.remove(&user);
+                preferences.media_flagged.insert(user.clone());
+            }
+            store.dispatch(ChatAction::SetPreferences(preferences));
+        });
+        let label = if hidden {
+            "Show their media"
+        } else {
+            "Hide their media"
+        };
+        items.push(MenuItem::new("🖼️", label, toggle));
+    }
+    if is_moderator && name != store.me {
+        let user = name.to_string();
+        items.push(
+            MenuItem::new(
+                "👢",
+                "Kick",
+                on_moderate.reform(move |_| ModerationAction::Kick { user: user.clone() }),
+            )
+            .danger(),
+        );
+        let user = name.to_string();
+        items.push(
+            MenuItem::new(
+                "⛔",
+                "Ban",
+                on_moderate.reform(move |_| ModerationAction::Ban { user: user.clone() }),
+            )
+            .danger(),
+        );
+    }
+    items
+}
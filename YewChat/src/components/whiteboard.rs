@@ -0,0 +1,230 @@
+use std::rc::Rc;
+
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
+use yew::prelude::*;
+use yewchat_protocol::{Attachment, Stroke};
+
+use crate::components::modal::Modal;
+use crate::theme::use_theme;
+
+/// Size of the drawing surface in pixels; it is scaled to fit the dialog.
+const BOARD_WIDTH: u32 = 960;
+const BOARD_HEIGHT: u32 = 600;
+/// Pen width as a fraction of the board's width.
+const PEN_WIDTH: f32 = 0.004;
+/// Pointer moves shorter than this, as a fraction of the board, don't add a point.
+const MIN_STEP: f32 = 0.002;
+const COLORS: [(&str, &str); 5] = [
+    ("#111827", "Black"),
+    ("#dc2626", "Red"),
+    ("#2563eb", "Blue"),
+    ("#16a34a", "Green"),
+    ("#f59e0b", "Yellow"),
+];
+
+#[derive(Properties, PartialEq)]
+pub struct WhiteboardProps {
+    pub channel: String,
+    /// `None` until the server has sent the board.
+    pub strokes: Option<Rc<Vec<Stroke>>>,
+    /// Shows the board without letting the user draw.
+    #[prop_or_default]
+    pub readonly: bool,
+    /// Called with each finished stroke; it is drawn for good once the server echoes it.
+    pub on_stroke: Callback<Stroke>,
+    pub on_clear: Callback<()>,
+    /// Called with a PNG snapshot of the board.
+    pub on_post: Callback<Attachment>,
+    pub on_close: Callback<()>,
+}
+
+/// Shared drawing surface for the current channel.
+#[function_component(Whiteboard)]
+pub fn whiteboard(props: &WhiteboardProps) -> Html {
+    let classes = use_theme().classes();
+    let canvas = use_node_ref();
+    let color = use_state(|| COLORS[0].0);
+    // The stroke being drawn, kept out of state so pointer moves don't re-render.
+    let pending = use_mut_ref(|| None::<Stroke>);
+
+    {
+        let canvas = canvas.clone();
+        let pending = pending.clone();
+        use_effect_with_deps(
+            move |strokes| {
+                if let Some(context) = context(&canvas) {
+                    redraw(&context, strokes.iter().flat_map(|s| s.iter()));
+                    if let Some(stroke) = &*pending.borrow() {
+                        draw(&context, stroke);
+                    }
+                }
+                || ()
+            },
+            props.strokes.clone(),
+        );
+    }
+
+    let onpointerdown = {
+        let canvas = canvas.clone();
+        let pending = pending.clone();
+        let color = *color;
+        let channel = props.channel.clone();
+        let readonly = props.readonly;
+        Callback::from(move |e: PointerEvent| {
+            if readonly || e.button() != 0 {
+                return;
+            }
+            let Some(point) = board_point(&canvas, &e) else {
+                return;
+            };
+            // Keeps the moves coming when the pointer leaves the canvas mid-stroke.
+            if let Some(el) = canvas.cast::<HtmlCanvasElement>() {
+                let _ = el.set_pointer_capture(e.pointer_id());
+            }
+            let stroke = Stroke {
+                channel: channel.clone(),
+                color: color.to_string(),
+                width: PEN_WIDTH,
+                points: vec![point],
+            };
+            if let Some(context) = context(&canvas) {
+                draw(&context, &stroke);
+            }
+            *pending.borrow_mut() = Some(stroke);
+        })
+    };
+    let onpointermove = {
+        let canvas = canvas.clone();
+        let pending = pending.clone();
+        Callback::from(move |e: PointerEvent| {
+            let mut pending = pending.borrow_mut();
+            let (Some(stroke), Some(point)) = (pending.as_mut(), board_point(&canvas, &e)) else {
+                return;
+            };
+            let last = *stroke.points.last().unwrap_or(&point);
+            if (point[0] - last[0]).hypot(point[1] - last[1]) < MIN_STEP {
+                return;
+            }
+            stroke.points.push(point);
+            if let Some(context) = context(&canvas) {
+                draw(
+                    &context,
+                    &Stroke {
+                        points: vec![last, point],
+                        ..stroke.clone()
+                    },
+                );
+            }
+        })
+    };
+    let finish = {
+        let pending = pending.clone();
+        let on_stroke = props.on_stroke.clone();
+        Callback::from(move |_: PointerEvent| {
+            if let Some(stroke) = pending.borrow_mut().take() {
+                on_stroke.emit(stroke);
+            }
+        })
+    };
+    let post = {
+        let canvas = canvas.clone();
+        let on_post = props.on_post.clone();
+        Callback::from(move |_: MouseEvent| {
+            let Some(el) = canvas.cast::<HtmlCanvasElement>() else {
+                return;
+            };
+            if let Ok(url) = el.to_data_url_with_type("image/png") {
+                on_post.emit(Attachment::Image {
+                    url,
+                    width: BOARD_WIDTH,
+                    height: BOARD_HEIGHT,
+                    alt: Some("Whiteboard sketch".into()),
+                    spoiler: false,
+                });
+            }
+        })
+    };
+    let clear = props.on_clear.reform(|_: MouseEvent| ());
+
+    let actions = html! {
+        if !props.readonly {
+            { for COLORS.iter().map(|&(value, name)| {
+                let color = color.clone();
+                let selected = *color == value;
+                html! {
+                    <button title={name} aria-label={name} aria-pressed={selected.to_string()}
+                        onclick={Callback::from(move |_: MouseEvent| color.set(value))}
+                        style={format!("background-color: {}", value)}
+                        class={classes!("w-5", "h-5", "mr-1", "rounded-full", "border-2", if selected { "border-gray-400" } else { "border-transparent" })} />
+                }
+            }) }
+            <button data-testid="whiteboard-clear" onclick={clear}
+                class={classes!("ml-2", "px-3", "py-1", "rounded", classes.button_bg)}>{"Clear"}</button>
+            <button data-testid="whiteboard-post" onclick={post}
+                class="ml-2 px-3 py-1 rounded bg-blue-600 text-white">{"Post to chat"}</button>
+        }
+    };
+
+    html! {
+        <Modal title={format!("Whiteboard · #{}", props.channel)} testid="whiteboard" width={classes!("w-[64rem]")} {actions} on_close={props.on_close.clone()}>
+            <canvas ref={canvas} width={BOARD_WIDTH.to_string()} height={BOARD_HEIGHT.to_string()}
+                {onpointerdown} {onpointermove} onpointerup={finish.clone()} onpointercancel={finish}
+                class={classes!("block", "w-full", "h-auto", "rounded", "border", "bg-white", "touch-none", classes.border, (!props.readonly).then_some("cursor-crosshair"))} />
+            if props.strokes.is_none() {
+                <div class={classes!("mt-2", classes.muted_text)}>{"Loading the board…"}</div>
+            }
+        </Modal>
+    }
+}
+
+fn context(canvas: &NodeRef) -> Option<CanvasRenderingContext2d> {
+    canvas
+        .cast::<HtmlCanvasElement>()?
+        .get_context("2d")
+        .ok()
+        .flatten()
+        .map(|context| context.unchecked_into())
+}
+
+/// Where `e` falls on the board, as fractions of its size.
+fn board_point(canvas: &NodeRef, e: &PointerEvent) -> Option<[f32; 2]> {
+    let el = canvas.cast::<HtmlCanvasElement>()?;
+    let (width, height) = (el.client_width() as f32, el.client_height() as f32);
+    if width == 0.0 || height == 0.0 {
+        return None;
+    }
+    let x = (e.offset_x() as f32 / width).clamp(0.0, 1.0);
+    let y = (e.offset_y() as f32 / height).clamp(0.0, 1.0);
+    Some([x, y])
+}
+
+/// Paints a white backdrop, so posted snapshots aren't transparent, then every stroke.
+fn redraw<'a>(context: &CanvasRenderingContext2d, strokes: impl Iterator<Item = &'a Stroke>) {
+    context.set_fill_style(&JsValue::from_str("white"));
+    context.fill_rect(0.0, 0.0, BOARD_WIDTH as f64, BOARD_HEIGHT as f64);
+    for stroke in strokes {
+        draw(context, stroke);
+    }
+}
+
+fn draw(context: &CanvasRenderingContext2d, stroke: &Stroke) {
+    let Some(&[x, y]) = stroke.points.first() else {
+        return;
+    };
+    let (width, height) = (BOARD_WIDTH as f64, BOARD_HEIGHT as f64);
+    context.set_stroke_style(&JsValue::from_str(&stroke.color));
+    context.set_line_width(stroke.width as f64 * width);
+    context.set_line_cap("round");
+    context.set_line_join("round");
+    context.begin_path();
+    context.move_to(x as f64 * width, y as f64 * height);
+    if stroke.points.len() == 1 {
+        // A zero-length line, which the round cap turns into a dot.
+        context.line_to(x as f64 * width, y as f64 * height);
+    }
+    for &[x, y] in &stroke.points[1..] {
+        context.line_to(x as f64 * width, y as f64 * height);
+    }
+    context.stroke();
+}
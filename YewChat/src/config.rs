@@ -0,0 +1,119 @@
+//! Deployment settings, loaded once at startup before the app mounts.
+//!
+//! Sources, first match wins: `config.json` next to `index.html`, then a
+//! `<meta name="yewchat-config" content='{...}'>` tag, then the built-in defaults. Any field
+//! left out of a source keeps its default. Only the page loads it: the connection workers
+//! can't see the meta tag, so the page hands them its own in their first message.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use reqwasm::http::Request;
+use serde::{Deserialize, Serialize};
+
+const CONFIG_URL: &str = "config.json";
+const META_NAME: &str = "yewchat-config";
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Config {
+    pub server_url: String,
+    /// Avatar image URL; `{name}` is replaced with the URL-encoded username and `{size}` with
+    /// the width in pixels wanted. Any image type will do.
+    pub avatar_url: String,
+    pub max_message_length: usize,
+    /// Largest attachment sent, once compressed.
+    pub max_upload_bytes: u64,
+    /// Largest file that can be picked for attaching, before compression.
+    pub max_file_bytes: u64,
+    /// MIME types that can be attached; `image/*` style wildcards are allowed.
+    pub allowed_upload_types: Vec<String>,
+    pub telemetry_endpoint: Option<String>,
+    /// Where error reports are sent, for users who agree to it.
+    pub error_report_endpoint: Option<String>,
+    /// Join every session read-only, e.g. for a public event stream.
+    pub spectator: bool,
+    /// Base64url public key of the server's push relay. Web Push is only offered with one.
+    pub vapid_public_key: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            server_url: "ws://127.0.0.1:8080".into(),
+            avatar_url:
+                "https://avatars.dicebear.com/api/adventurer-neutral/{name}.svg?size={size}".into(),
+            max_message_length: 2000,
+            max_upload_bytes: 10 * 1024 * 1024,
+            max_file_bytes: 25 * 1024 * 1024,
+            allowed_upload_types: ["image/png", "image/jpeg", "image/gif", "image/webp"]
+                .iter()
+                .map(|t| t.to_string())
+                .collect(),
+            telemetry_endpoint: option_env!("YEWCHAT_TELEMETRY_ENDPOINT").map(String::from),
+            error_report_endpoint: option_env!("YEWCHAT_ERROR_REPORT_ENDPOINT").map(String::from),
+            spectator: false,
+            vapid_public_key: None,
+        }
+    }
+}
+
+impl Config {
+    pub fn avatar_url_for(&self, name: &str, size: u32) -> String {
+        self.avatar_url
+            .replace("{name}", &String::from(js_sys::encode_uri_component(name)))
+            .replace("{size}", &size.to_string())
+    }
+}
+
+thread_local! {
+    static CONFIG: RefCell<Rc<Config>> = RefCell::new(Rc::new(Config::default()));
+}
+
+/// The active configuration. Defaults until [`load`] has finished.
+pub fn get() -> Rc<Config> {
+    CONFIG.with(|c| c.borrow().clone())
+}
+
+pub async fn load() {
+    let config = match fetch_json().await {
+        Some(config) => config,
+        None => from_meta().unwrap_or_default(),
+    };
+    log::debug!("config: {:?}", config);
+    set(config);
+}
+
+/// Makes `config` the active configuration, as a worker does with the one the page sent.
+pub fn set(config: Config) {
+    CONFIG.with(|c| *c.borrow_mut() = Rc::new(config));
+}
+
+async fn fetch_json() -> Option<Config> {
+    let response = Request::get(CONFIG_URL).send().await.ok()?;
+    if !response.ok() {
+        return None;
+    }
+    match response.json().await {
+        Ok(config) => Some(config),
+        Err(e) => {
+            log::error!("invalid {}: {}", CONFIG_URL, e);
+            None
+        }
+    }
+}
+
+fn from_meta() -> Option<Config> {
+    let meta = web_sys::window()?
+        .document()?
+        .query_selector(&format!("meta[name=\"{}\"]", META_NAME))
+        .ok()??;
+    let content = meta.get_attribute("content")?;
+    match serde_json::from_str(&content) {
+        Ok(config) => Some(config),
+        Err(e) => {
+            log::error!("invalid {} meta tag: {}", META_NAME, e);
+            None
+        }
+    }
+}
@@ -0,0 +1,125 @@
+//! What happens when the app panics. A panicked Yew app stops rendering and leaves a frozen
+//! page behind, so the hook replaces the page with a crash screen built straight in the DOM,
+//! offering a reload and the details to paste into a bug report.
+
+use web_sys::Element;
+
+use crate::logging::{self, LogEntry};
+use crate::services::error_report;
+
+/// Everything a bug report needs about a panic, as plain text.
+pub fn diagnostics(message: &str, location: &str, log: &[LogEntry]) -> String {
+    let user_agent = web_sys::window()
+        .and_then(|w| w.navigator().user_agent().ok())
+        .unwrap_or_default();
+    let mut text = format!(
+        "YewChat {} panicked at {}:\n{}\n\n{}\n",
+        env!("CARGO_PKG_VERSION"),
+        location,
+        message,
+        user_agent
+    );
+    if !log.is_empty() {
+        text.push_str("\nRecent log:\n");
+        for entry in log {
+            text.push_str(&format!("{}\n", entry));
+        }
+    }
+    text
+}
+
+/// Logs panics to the console, reports them if the user agreed to it, and shows the crash
+/// screen. Call once at startup.
+pub fn install_panic_hook() {
+    std::panic::set_hook(Box::new(|info| {
+        let payload = info.payload();
+        let message = match payload.downcast_ref::<&str>() {
+            Some(message) => message.to_string(),
+            None => payload
+                .downcast_ref::<String>()
+                .cloned()
+                .unwrap_or_default(),
+        };
+        let location = info
+            .location()
+            .map(|l| format!("{}:{}", l.file(), l.line()))
+            .unwrap_or_default();
+        web_sys::console::error_1(&format!("panicked at {}: {}", location, message).into());
+        error_report::capture_panic(&location, &message);
+        show(&diagnostics(&message, &location, &logging::recent()));
+    }));
+}
+
+/// Swaps the page for the crash screen. The buttons use inline handlers because nothing in
+/// the wasm module can be trusted to run once it has panicked.
+fn show(diagnostics: &str) {
+    let Some(body) = web_sys::window().and_then(|w| w.document()?.body()) else {
+        return;
+    };
+    let build = || -> Option<Element> {
+        let document = body.owner_document()?;
+        let element = |tag: &str, class: &str, text: &str| {
+            let element = document.create_element(tag).ok()?;
+            element.set_class_name(class);
+            element.set_text_content(Some(text));
+            Some(element)
+        };
+        let screen = element(
+            "div",
+            "flex flex-col items-center justify-center w-screen h-screen p-6 bg-gray-100 text-gray-900",
+            "",
+        )?;
+        screen.set_attribute("data-testid", "crash-screen").ok()?;
+        screen.set_attribute("role", "alert").ok()?;
+        let title = element("h1", "mb-2 text-2xl font-bold", "Something went wrong")?;
+        let hint = element(
+            "p",
+            "mb-4 text-sm text-center",
+            "YewChat hit a bug and stopped. Reloading usually gets you going again. If it keeps \
+             happening, please include the details below when you report it.",
+        )?;
+        let details = element(
+            "textarea",
+            "w-full max-w-2xl h-48 p-2 mb-4 font-mono text-xs border rounded bg-white",
+            diagnostics,
+        )?;
+        details.set_id("crash-diagnostics");
+        details
+            .set_attribute("data-testid", "crash-diagnostics")
+            .ok()?;
+        details.set_attribute("readonly", "").ok()?;
+        details.set_attribute("aria-label", "Diagnostics").ok()?;
+        let buttons = element("div", "flex", "")?;
+        let reload = element(
+            "button",
+            "px-4 py-2 mr-2 rounded bg-blue-600 text-white",
+            "Reload",
+        )?;
+        reload.set_attribute("data-testid", "crash-reload").ok()?;
+        reload.set_attribute("onclick", "location.reload()").ok()?;
+        let copy = element(
+            "button",
+            "px-4 py-2 rounded border border-gray-400",
+            "Copy details",
+        )?;
+        copy.set_attribute("data-testid", "crash-copy").ok()?;
+        copy.set_attribute(
+            "onclick",
+            "navigator.clipboard.writeText(document.getElementById('crash-diagnostics').value)\
+             .then(() => { this.textContent = 'Copied' })",
+        )
+        .ok()?;
+        buttons.append_with_node_2(&reload, &copy).ok()?;
+        screen
+            .append_with_node_4(&title, &hint, &details, &buttons)
+            .ok()?;
+        Some(screen)
+    };
+    match build() {
+        Some(screen) => {
+            body.set_inner_html("");
+            let _ = body.append_child(&screen);
+        }
+        None => web_sys::console::error_1(&"failed to show the crash screen".into()),
+    }
+}
@@ -0,0 +1,107 @@
+//! Bridge to the Tauri shell in `src-tauri/`. Only compiled with the `desktop` feature, and
+//! inert when the app is opened in a normal browser.
+
+use gloo::events::EventListener;
+use js_sys::{Function, Object, Promise, Reflect};
+use wasm_bindgen::{JsCast, JsValue};
+use yew::prelude::*;
+
+use crate::services::notifications;
+use crate::store::use_store;
+use crate::User;
+
+fn invoke(cmd: &str, args: &[(&str, JsValue)]) {
+    let invoke = web_sys::window()
+        .and_then(|w| Reflect::get(&w, &"__TAURI__".into()).ok())
+        .filter(|tauri| !tauri.is_undefined())
+        .and_then(|tauri| Reflect::get(&tauri, &"invoke".into()).ok())
+        .and_then(|f| f.dyn_into::<Function>().ok());
+    let invoke = match invoke {
+        Some(invoke) => invoke,
+        None => return,
+    };
+    let payload = Object::new();
+    for (key, value) in args {
+        let _ = Reflect::set(&payload, &(*key).into(), value);
+    }
+    match invoke.call2(&JsValue::NULL, &cmd.into(), &payload) {
+        Ok(promise) => {
+            if let Ok(promise) = promise.dyn_into::<Promise>() {
+                wasm_bindgen_futures::spawn_local(async move {
+                    if let Err(e) = wasm_bindgen_futures::JsFuture::from(promise).await {
+                        log::error!("tauri: {:?}", e);
+                    }
+                });
+            }
+        }
+        Err(e) => log::error!("tauri: {:?}", e),
+    }
+}
+
+pub fn notify(title: &str, body: &str) {
+    invoke("notify", &[("title", title.into()), ("body", body.into())]);
+}
+
+pub fn set_unread_badge(count: u32) {
+    invoke("set_unread_badge", &[("count", count.into())]);
+}
+
+fn window_focused() -> bool {
+    web_sys::window()
+        .and_then(|w| w.document())
+        .and_then(|d| d.has_focus().ok())
+        .unwrap_or(true)
+}
+
+/// Counts messages that arrive while the window is unfocused, mirrors that on the tray badge,
+/// and raises native notifications for them.
+#[function_component(DesktopBridge)]
+pub fn desktop_bridge() -> Html {
+    let store = use_store();
+    let user = use_context::<User>().expect("context to be set");
+    let unread = use_mut_ref(|| 0u32);
+    let seen = use_mut_ref(|| store.messages.len());
+
+    {
+        let unread = unread.clone();
+        use_effect_with_deps(
+            move |_| {
+                let window = web_sys::window().expect("window");
+                let listener = EventListener::new(&window, "focus", move |_| {
+                    *unread.borrow_mut() = 0;
+                    set_unread_badge(0);
+                });
+                move || drop(listener)
+            },
+            (),
+        );
+    }
+
+    {
+        let messages = store.messages.clone();
+        let preferences = store.preferences.clone();
+        use_effect_with_deps(
+            move |len| {
+                let me = user.username.borrow().clone();
+                let start = (*seen.borrow()).min(*len);
+                *seen.borrow_mut() = *len;
+                if !window_focused() {
+                    for m in messages[start..].iter().filter(|m| m.from != me) {
+                        if preferences.is_muted(&m.channel) {
+                            continue;
+                        }
+                        *unread.borrow_mut() += 1;
+                        if notifications::allowed(&preferences) {
+                            notify(&m.from, &m.message);
+                        }
+                    }
+                    set_unread_badge(*unread.borrow());
+                }
+                || ()
+            },
+            store.messages.len(),
+        );
+    }
+
+    html! {}
+}
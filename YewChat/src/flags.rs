@@ -0,0 +1,116 @@
+//! Feature flags, so experimental features can ship dark and be switched on per user.
+//!
+//! Defaults come from the build (`--features experimental` turns every experimental flag on).
+//! They can be overridden per browser with a comma-separated list in the `yewchat.flags`
+//! localStorage key or the `?flags=` query parameter (query wins), e.g.
+//! `?flags=compact_mode,-worker_connection`.
+
+use std::collections::HashMap;
+
+use gloo::storage::{LocalStorage, Storage};
+use yew::prelude::*;
+
+const STORAGE_KEY: &str = "yewchat.flags";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Flag {
+    /// Run the WebSocket inside a web worker instead of on the UI thread, one shared by all
+    /// open tabs where the browser supports shared workers.
+    WorkerConnection,
+    /// Offer the IRC-style compact layout in settings.
+    CompactMode,
+    /// Offer the anonymous usage stats opt-in in settings.
+    Telemetry,
+    /// Ctrl+Shift+L log viewer.
+    LogViewer,
+}
+
+impl Flag {
+    pub const ALL: [Flag; 4] = [
+        Flag::WorkerConnection,
+        Flag::CompactMode,
+        Flag::Telemetry,
+        Flag::LogViewer,
+    ];
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            Flag::WorkerConnection => "worker_connection",
+            Flag::CompactMode => "compact_mode",
+            Flag::Telemetry => "telemetry",
+            Flag::LogViewer => "log_viewer",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        Flag::ALL.iter().copied().find(|f| f.key() == key)
+    }
+
+    fn default_enabled(&self) -> bool {
+        let experimental = cfg!(feature = "experimental");
+        match self {
+            Flag::WorkerConnection | Flag::LogViewer => true,
+            Flag::CompactMode | Flag::Telemetry => experimental,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FeatureFlags {
+    overrides: HashMap<Flag, bool>,
+}
+
+impl FeatureFlags {
+    /// Build defaults with the localStorage and query-string overrides applied.
+    pub fn load() -> Self {
+        let mut flags = Self {
+            overrides: HashMap::new(),
+        };
+        if let Ok(Some(stored)) = LocalStorage::raw().get_item(STORAGE_KEY) {
+            flags.apply(&stored);
+        }
+        if let Some(query) = query_flags() {
+            flags.apply(&query);
+        }
+        flags
+    }
+
+    /// Applies `a,-b,c` style overrides; unknown names are ignored.
+    pub fn apply(&mut self, spec: &str) {
+        for item in spec.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            let (key, on) = match item.strip_prefix('-') {
+                Some(key) => (key, false),
+                None => (item, true),
+            };
+            match Flag::from_key(key) {
+                Some(flag) => {
+                    self.overrides.insert(flag, on);
+                }
+                None => log::warn!("unknown feature flag: {}", key),
+            }
+        }
+    }
+
+    pub fn enabled(&self, flag: Flag) -> bool {
+        self.overrides
+            .get(&flag)
+            .copied()
+            .unwrap_or_else(|| flag.default_enabled())
+    }
+}
+
+fn query_flags() -> Option<String> {
+    let search = web_sys::window()?.location().search().ok()?;
+    search
+        .trim_start_matches('?')
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("flags="))
+        .and_then(|v| js_sys::decode_uri_component(v).ok())
+        .map(String::from)
+}
+
+pub fn use_flag(flag: Flag) -> bool {
+    use_context::<FeatureFlags>()
+        .map(|flags| flags.enabled(flag))
+        .unwrap_or_else(|| flag.default_enabled())
+}
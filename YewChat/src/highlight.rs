@@ -0,0 +1,226 @@
+//! Lightweight syntax highlighting for code snippets: keywords, strings, comments and numbers.
+//! Deliberately approximate; it only has to make code easier to scan.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Language {
+    Rust,
+    JavaScript,
+    Python,
+    Json,
+    Shell,
+    Sql,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Token {
+    Plain,
+    Keyword,
+    String,
+    Comment,
+    Number,
+}
+
+impl Language {
+    pub const ALL: [Language; 6] = [
+        Language::Rust,
+        Language::JavaScript,
+        Language::Python,
+        Language::Json,
+        Language::Shell,
+        Language::Sql,
+    ];
+
+    /// Name carried in the snippet payload.
+    pub fn key(&self) -> &'static str {
+        match self {
+            Language::Rust => "rust",
+            Language::JavaScript => "javascript",
+            Language::Python => "python",
+            Language::Json => "json",
+            Language::Shell => "shell",
+            Language::Sql => "sql",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Language::Rust => "Rust",
+            Language::JavaScript => "JavaScript",
+            Language::Python => "Python",
+            Language::Json => "JSON",
+            Language::Shell => "Shell",
+            Language::Sql => "SQL",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Language::ALL.iter().copied().find(|l| l.key() == key)
+    }
+
+    fn keywords(&self) -> &'static [&'static str] {
+        match self {
+            Language::Rust => &[
+                "as", "async", "await", "break", "const", "continue", "crate", "else", "enum",
+                "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+                "mut", "pub", "ref", "return", "self", "Self", "static", "struct", "super",
+                "trait", "true", "type", "unsafe", "use", "where", "while",
+            ],
+            Language::JavaScript => &[
+                "async",
+                "await",
+                "break",
+                "case",
+                "catch",
+                "class",
+                "const",
+                "continue",
+                "default",
+                "delete",
+                "do",
+                "else",
+                "export",
+                "extends",
+                "false",
+                "finally",
+                "for",
+                "function",
+                "if",
+                "import",
+                "in",
+                "instanceof",
+                "let",
+                "new",
+                "null",
+                "return",
+                "switch",
+                "this",
+                "throw",
+                "true",
+                "try",
+                "typeof",
+                "undefined",
+                "var",
+                "while",
+                "yield",
+            ],
+            Language::Python => &[
+                "and", "as", "assert", "async", "await", "break", "class", "continue", "def",
+                "del", "elif", "else", "except", "False", "finally", "for", "from", "if", "import",
+                "in", "is", "lambda", "None", "not", "or", "pass", "raise", "return", "True",
+                "try", "while", "with", "yield",
+            ],
+            Language::Json => &["true", "false", "null"],
+            Language::Shell => &[
+                "case", "do", "done", "elif", "else", "esac", "export", "fi", "for", "function",
+                "if", "in", "local", "return", "then", "while",
+            ],
+            Language::Sql => &[
+                "and", "as", "by", "create", "delete", "from", "group", "insert", "into", "join",
+                "left", "limit", "not", "null", "on", "or", "order", "select", "set", "table",
+                "update", "values", "where",
+            ],
+        }
+    }
+
+    fn line_comment(&self) -> Option<&'static str> {
+        match self {
+            Language::Rust | Language::JavaScript => Some("//"),
+            Language::Python | Language::Shell => Some("#"),
+            Language::Sql => Some("--"),
+            Language::Json => None,
+        }
+    }
+
+    fn block_comments(&self) -> bool {
+        matches!(self, Language::Rust | Language::JavaScript | Language::Sql)
+    }
+
+    fn quotes(&self) -> &'static [char] {
+        match self {
+            Language::JavaScript => &['"', '\'', '`'],
+            Language::Json => &['"'],
+            // A Rust `'` is as often a lifetime as a char literal.
+            Language::Rust => &['"'],
+            _ => &['"', '\''],
+        }
+    }
+
+    fn case_insensitive(&self) -> bool {
+        *self == Language::Sql
+    }
+}
+
+/// Splits `code` into runs of one kind. Concatenating the runs gives back `code`.
+pub fn highlight(code: &str, language: Language) -> Vec<(Token, &str)> {
+    let mut tokens: Vec<(Token, &str)> = Vec::new();
+    let mut start = 0;
+    while start < code.len() {
+        let (token, len) = next_token(&code[start..], language);
+        let end = start + len;
+        match tokens.last_mut() {
+            // Merging neighbours keeps the rendered node count down.
+            Some((last, run)) if *last == token => *run = &code[start - run.len()..end],
+            _ => tokens.push((token, &code[start..end])),
+        }
+        start = end;
+    }
+    tokens
+}
+
+/// Kind and byte length of the token `rest` starts with.
+fn next_token(rest: &str, language: Language) -> (Token, usize) {
+    let c = rest.chars().next().unwrap_or_default();
+    if language.line_comment().is_some_and(|m| rest.starts_with(m)) {
+        (Token::Comment, rest.find('\n').unwrap_or(rest.len()))
+    } else if language.block_comments() && rest.starts_with("/*") {
+        (
+            Token::Comment,
+            rest[2..].find("*/").map_or(rest.len(), |i| i + 4),
+        )
+    } else if language.quotes().contains(&c) {
+        (Token::String, string_len(rest, c))
+    } else if c.is_ascii_digit() {
+        (
+            Token::Number,
+            run_len(rest, |c| c.is_ascii_alphanumeric() || c == '.' || c == '_'),
+        )
+    } else if c.is_alphabetic() || c == '_' {
+        let len = run_len(rest, |c| c.is_alphanumeric() || c == '_');
+        let word = &rest[..len];
+        let keyword = language.keywords().iter().any(|k| {
+            if language.case_insensitive() {
+                k.eq_ignore_ascii_case(word)
+            } else {
+                *k == word
+            }
+        });
+        let token = if keyword {
+            Token::Keyword
+        } else {
+            Token::Plain
+        };
+        (token, len)
+    } else {
+        (Token::Plain, c.len_utf8())
+    }
+}
+
+fn run_len(rest: &str, continues: impl Fn(char) -> bool) -> usize {
+    rest.find(|c| !continues(c)).unwrap_or(rest.len())
+}
+
+/// Length of the string literal opening `rest`, up to and including the closing quote, or to
+/// the end of the line if it is never closed.
+fn string_len(rest: &str, quote: char) -> usize {
+    let mut escaped = false;
+    for (i, c) in rest.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '\n' if quote != '`' => return i,
+            c if c == quote => return i + c.len_utf8(),
+            _ => {}
+        }
+    }
+    rest.len()
+}
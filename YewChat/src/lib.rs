@@ -1,10 +1,42 @@
 #![recursion_limit = "512"]
 
-mod components;
-mod services;
+pub mod commands;
+pub mod components;
+pub mod config;
+pub mod crash;
+#[cfg(feature = "desktop")]
+pub mod desktop;
+pub mod flags;
+pub mod highlight;
+pub mod links;
+pub mod logging;
+pub mod markdown;
+pub mod math;
+pub mod mentions;
+pub mod motion;
+pub mod services;
+pub mod state;
+pub mod store;
+pub mod theme;
+pub mod time;
+pub mod touch;
+pub mod wallpaper;
+#[cfg(feature = "test-hooks")]
+pub mod test_hooks;
 
 use components::login::Login;
+use components::announcer::Announcer;
 use components::chat::Chat;
+use components::confirm::ConfirmProvider;
+use components::join::Join;
+use components::log_viewer::LogViewer;
+use components::sound_notifier::SoundNotifier;
+use flags::FeatureFlags;
+use motion::MotionProvider;
+use services::query;
+use store::StoreProvider;
+use theme::ThemeProvider;
+use time::TimeFormatProvider;
 use wasm_bindgen::prelude::*;
 use yew::functional::*;
 use yew::prelude::*;
@@ -17,6 +49,12 @@ pub type User = Rc<UserInner>;
 #[derive(Debug, PartialEq)]
 pub struct UserInner {
     pub username: RefCell<String>,
+    /// Joined read-only: can watch the chat but not post.
+    pub spectator: RefCell<bool>,
+    /// Invite token from the join screen, redeemed once connected.
+    pub invite: RefCell<Option<String>>,
+    /// Account key, for nicks the server reserves for an account.
+    pub key: RefCell<Option<String>>,
 }
 
 // When the `wee_alloc` feature is enabled, this uses `wee_alloc` as the global
@@ -33,6 +71,15 @@ pub enum Route {
     Login,
     #[at("/chat")]
     Chat,
+    /// Opens a channel directly; `?msg=<id>` scrolls to and highlights that message.
+    #[at("/channel/:name")]
+    Channel { name: String },
+    /// Target of invite links; reads `channel` and `token` from the query string.
+    #[at("/join")]
+    Join,
+    /// Component previews with mock data; only in builds with the `gallery` feature.
+    #[at("/gallery")]
+    Gallery,
     #[not_found]
     #[at("/404")]
     NotFound,
@@ -42,33 +89,101 @@ fn switch(selected_route: &Route) -> Html {
     match selected_route {
         Route::Login => html! {<Login />},
         Route::Chat => html! {<Chat/>},
+        Route::Channel { name } => {
+            let focus = query::param("msg").and_then(|id| id.parse().ok());
+            html! {<Chat channel={Some(name.clone())} {focus} />}
+        }
+        Route::Join => html! {<Join />},
+        Route::Gallery => gallery(),
         Route::NotFound => html! {<h1>{"404 baby"}</h1>},
     }
 }
 
+#[cfg(feature = "desktop")]
+fn desktop_bridge() -> Html {
+    html! {<desktop::DesktopBridge />}
+}
+
+#[cfg(not(feature = "desktop"))]
+fn desktop_bridge() -> Html {
+    html! {}
+}
+
+#[cfg(feature = "gallery")]
+fn gallery() -> Html {
+    html! {<components::gallery::Gallery />}
+}
+
+#[cfg(not(feature = "gallery"))]
+fn gallery() -> Html {
+    switch(&Route::NotFound)
+}
+
 #[function_component(Main)]
 fn main() -> Html {
 
     let ctx = use_state(|| {
         Rc::new(UserInner {
             username: RefCell::new("initial".into()),
+            spectator: RefCell::new(config::get().spectator),
+            invite: RefCell::new(None),
+            key: RefCell::new(None),
         })
     });
 
+    let flags = use_state(FeatureFlags::load);
+
     html! {
+        <ContextProvider<FeatureFlags> context={(*flags).clone()}>
         <ContextProvider<User> context={(*ctx).clone()}>
+        <StoreProvider>
+        <ThemeProvider>
+        <MotionProvider>
+        <ConfirmProvider>
+        <TimeFormatProvider>
+        { desktop_bridge() }
+        <SoundNotifier />
+        <Announcer />
         <BrowserRouter>
             <div class="flex w-screen h-screen">
                 <Switch<Route> render={Switch::render(switch)}/>
             </div>
         </BrowserRouter>
+        <LogViewer />
+        </TimeFormatProvider>
+        </ConfirmProvider>
+        </MotionProvider>
+        </ThemeProvider>
+        </StoreProvider>
         </ContextProvider<User>>
+        </ContextProvider<FeatureFlags>>
     }
 }
 
+/// Entry point for `worker.js`: serves the connection worker instead of mounting the app.
+#[wasm_bindgen]
+pub fn run_worker() {
+    use yew_agent::Threaded;
+    logging::init();
+    services::connection_worker::ConnectionWorker::register();
+}
+
+/// Entry point for `shared_worker.js`: serves one connection to every open tab. `early` are
+/// the ports of tabs that connected while the module was loading.
+#[wasm_bindgen]
+pub fn run_shared_worker(early: js_sys::Array) {
+    logging::init();
+    services::shared_worker::serve(early);
+}
+
 #[wasm_bindgen]
 pub fn run_app() -> Result<(), JsValue> {
-    wasm_logger::init(wasm_logger::Config::default());
-    yew::start_app::<Main>();
+    logging::init();
+    crash::install_panic_hook();
+    services::service_worker::register();
+    wasm_bindgen_futures::spawn_local(async {
+        config::load().await;
+        yew::start_app::<Main>();
+    });
     Ok(())
 }
\ No newline at end of file
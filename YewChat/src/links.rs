@@ -0,0 +1,86 @@
+//! Web links in message text: finding them, and telling the user where one really goes
+//! before it is followed.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Piece<'a> {
+    Text(&'a str),
+    /// An `http(s)://` address.
+    Link(&'a str),
+}
+
+/// Punctuation at the end of a link that more likely ends the sentence around it.
+const TRAILING: [char; 10] = ['.', ',', '!', '?', ';', ':', ')', '>', '\'', '"'];
+
+/// Splits `text` into plain runs and the web links in them. A link starts a word, perhaps
+/// after `(` or `<`, and runs to the next whitespace.
+pub fn split_links(text: &str) -> Vec<Piece<'_>> {
+    let mut pieces = Vec::new();
+    let mut plain = 0;
+    let mut offset = 0;
+    for word in text.split_inclusive(char::is_whitespace) {
+        let start = offset + word.len() - word.trim_start_matches(['(', '<']).len();
+        offset += word.len();
+        let candidate = text[start..offset].trim_end().trim_end_matches(TRAILING);
+        if is_link(candidate) {
+            if plain < start {
+                pieces.push(Piece::Text(&text[plain..start]));
+            }
+            pieces.push(Piece::Link(candidate));
+            plain = start + candidate.len();
+        }
+    }
+    if plain < text.len() {
+        pieces.push(Piece::Text(&text[plain..]));
+    }
+    pieces
+}
+
+fn is_link(word: &str) -> bool {
+    ["https://", "http://"].iter().any(|scheme| {
+        word.len() > scheme.len()
+            && word
+                .get(..scheme.len())
+                .is_some_and(|start| start.eq_ignore_ascii_case(scheme))
+    })
+}
+
+/// The host `url` leads to, as the browser will resolve it: lowercased, with international
+/// names in their `xn--` form. `None` for anything but an `http(s)` address.
+pub fn host(url: &str) -> Option<String> {
+    let url = web_sys::Url::new(url).ok()?;
+    let host = url.hostname();
+    (matches!(url.protocol().as_str(), "http:" | "https:") && !host.is_empty()).then_some(host)
+}
+
+/// Whether `host` spells part of its name in non-Latin letters, the usual way to imitate
+/// another site's address with characters that look the same.
+pub fn is_lookalike(host: &str) -> bool {
+    host.split('.').any(|label| label.starts_with("xn--"))
+}
+
+/// Whether `host` is `domain` or one of its subdomains.
+pub fn is_within(host: &str, domain: &str) -> bool {
+    host.strip_suffix(domain)
+        .is_some_and(|rest| rest.is_empty() || rest.ends_with('.'))
+}
+
+/// The link a message's preview card is for: its first.
+pub fn first_link(text: &str) -> Option<&str> {
+    split_links(text).into_iter().find_map(|piece| match piece {
+        Piece::Link(link) => Some(link),
+        Piece::Text(_) => None,
+    })
+}
+
+/// How many messages' collapsed previews are remembered; the oldest are forgotten first.
+pub const COLLAPSED_KEPT: usize = 500;
+
+/// Marks message `id`'s preview as collapsed or not in `collapsed`, oldest first.
+pub fn set_collapsed(collapsed: &mut Vec<u64>, id: u64, on: bool) {
+    collapsed.retain(|&other| other != id);
+    if on {
+        collapsed.push(id);
+        let excess = collapsed.len().saturating_sub(COLLAPSED_KEPT);
+        collapsed.drain(..excess);
+    }
+}
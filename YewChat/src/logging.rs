@@ -0,0 +1,118 @@
+//! Leveled logger backing the `log` macros: writes to the console, tags each line with the
+//! module it came from, and keeps the most recent entries for bug reports. Errors are also
+//! passed on to [`error_report`](crate::services::error_report).
+//!
+//! The level defaults to `info` and can be raised at runtime with `?log=debug` (or `trace`,
+//! `warn`, ...) in the page URL, or via [`set_level`].
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use wasm_bindgen::JsValue;
+
+use crate::services::error_report;
+
+const BUFFER_CAPACITY: usize = 500;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LogEntry {
+    /// Milliseconds since the unix epoch.
+    pub time: f64,
+    pub level: Level,
+    pub module: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for LogEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:>5} [{}] {}", self.level, self.module, self.message)
+    }
+}
+
+thread_local! {
+    static BUFFER: RefCell<VecDeque<LogEntry>> = RefCell::new(VecDeque::with_capacity(BUFFER_CAPACITY));
+}
+
+struct AppLogger;
+
+static LOGGER: AppLogger = AppLogger;
+
+impl Log for AppLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let entry = LogEntry {
+            time: js_sys::Date::now(),
+            level: record.level(),
+            module: module_tag(record.target()).to_string(),
+            message: record.args().to_string(),
+        };
+        let line = JsValue::from_str(&entry.to_string());
+        match entry.level {
+            Level::Error => web_sys::console::error_1(&line),
+            Level::Warn => web_sys::console::warn_1(&line),
+            Level::Info => web_sys::console::info_1(&line),
+            Level::Debug | Level::Trace => web_sys::console::debug_1(&line),
+        }
+        BUFFER.with(|b| {
+            let mut buffer = b.borrow_mut();
+            if buffer.len() == BUFFER_CAPACITY {
+                buffer.pop_front();
+            }
+            buffer.push_back(entry.clone());
+        });
+        if entry.level == Level::Error {
+            error_report::capture(&entry.module, &entry.message);
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// `yewchat::services::websocket` -> `services::websocket`.
+fn module_tag(target: &str) -> &str {
+    target.strip_prefix("yewchat::").unwrap_or(target)
+}
+
+fn level_from_query() -> Option<LevelFilter> {
+    let search = web_sys::window()?.location().search().ok()?;
+    search
+        .trim_start_matches('?')
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("log="))
+        .and_then(|level| level.parse().ok())
+}
+
+/// Installs the logger. Safe to call more than once; later calls only adjust the level.
+pub fn init() {
+    let _ = log::set_logger(&LOGGER);
+    set_level(level_from_query().unwrap_or(LevelFilter::Info));
+}
+
+pub fn set_level(level: LevelFilter) {
+    log::set_max_level(level);
+}
+
+/// Snapshot of the buffered entries, oldest first. Empty if called while an entry is being
+/// added, as from a panic in the logger.
+pub fn recent() -> Vec<LogEntry> {
+    BUFFER.with(|b| match b.try_borrow() {
+        Ok(buffer) => buffer.iter().cloned().collect(),
+        Err(_) => Vec::new(),
+    })
+}
+
+/// Buffered entries as plain text, ready to paste into a bug report.
+pub fn dump() -> String {
+    recent()
+        .iter()
+        .map(|e| e.to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
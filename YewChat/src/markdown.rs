@@ -0,0 +1,151 @@
+//! Block-level markdown in message text. Only GitHub-style tables and checklists are
+//! recognised; everything else is passed through as text.
+
+use yewchat_protocol::task_item;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Table<'a> {
+    pub header: Vec<&'a str>,
+    /// One per column; columns without a `:` marker are `Left`.
+    pub align: Vec<Align>,
+    /// Each padded or cut to the header's width.
+    pub rows: Vec<Vec<&'a str>>,
+}
+
+/// A `- [ ]` or `- [x]` line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Task<'a> {
+    /// Position among all the message's checklist items, as sent in a `TaskToggle`.
+    pub index: usize,
+    pub done: bool,
+    pub text: &'a str,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Block<'a> {
+    Text(&'a str),
+    Table(Table<'a>),
+    Checklist(Vec<Task<'a>>),
+}
+
+/// Splits `text` into runs of plain text and the tables and checklists between them.
+pub fn blocks(text: &str) -> Vec<Block<'_>> {
+    let lines: Vec<(usize, &str)> = line_spans(text).collect();
+    let mut blocks = Vec::new();
+    let mut text_start = 0;
+    // Checklist items are numbered across the whole message, the way `set_task` counts them.
+    let mut tasks_seen = 0;
+    let mut i = 0;
+    while i < lines.len() {
+        let (start, line) = lines[i];
+        let table = lines
+            .get(i + 1)
+            .and_then(|(_, delimiter)| table_head(line, delimiter));
+        let block = if let Some((header, align)) = table {
+            tasks_seen += usize::from(task_item(line).is_some());
+            let mut rows = Vec::new();
+            i += 2;
+            while let Some((_, line)) = lines.get(i).filter(|(_, l)| l.contains('|')) {
+                tasks_seen += usize::from(task_item(line).is_some());
+                let mut cells = cells(line);
+                cells.resize(header.len(), "");
+                rows.push(cells);
+                i += 1;
+            }
+            Block::Table(Table {
+                header,
+                align,
+                rows,
+            })
+        } else if task_item(line).is_some() {
+            let mut tasks = Vec::new();
+            while let Some((done, text)) = lines.get(i).and_then(|(_, l)| task_item(l)) {
+                tasks.push(Task {
+                    index: tasks_seen,
+                    done,
+                    text,
+                });
+                tasks_seen += 1;
+                i += 1;
+            }
+            Block::Checklist(tasks)
+        } else {
+            i += 1;
+            continue;
+        };
+        if text_start < start {
+            blocks.push(Block::Text(text[text_start..start].trim_end_matches('\n')));
+        }
+        blocks.push(block);
+        text_start = lines.get(i).map_or(text.len(), |(start, _)| *start);
+    }
+    if text_start < text.len() {
+        blocks.push(Block::Text(&text[text_start..]));
+    }
+    blocks
+}
+
+/// Byte offset and content of each line.
+fn line_spans(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.split('\n').scan(0, |offset, line| {
+        let start = *offset;
+        *offset += line.len() + 1;
+        Some((start, line))
+    })
+}
+
+/// The header cells and alignments if `header` and `delimiter` open a table.
+fn table_head<'a>(header: &'a str, delimiter: &str) -> Option<(Vec<&'a str>, Vec<Align>)> {
+    if !header.contains('|') || !delimiter.contains('-') {
+        return None;
+    }
+    let header = cells(header);
+    let align = cells(delimiter)
+        .into_iter()
+        .map(|cell| {
+            let dashes = cell.trim_start_matches(':').trim_end_matches(':');
+            if dashes.is_empty() || !dashes.chars().all(|c| c == '-') {
+                return None;
+            }
+            Some(match (cell.starts_with(':'), cell.ends_with(':')) {
+                (true, true) => Align::Center,
+                (false, true) => Align::Right,
+                _ => Align::Left,
+            })
+        })
+        .collect::<Option<Vec<_>>>()?;
+    (align.len() == header.len()).then_some((header, align))
+}
+
+/// Cells of a table row, trimmed, without the optional outer pipes. `\|` stays in its cell.
+fn cells(line: &str) -> Vec<&str> {
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = match line.strip_suffix('|') {
+        Some(inner) if !inner.ends_with('\\') => inner,
+        _ => line,
+    };
+    let mut cells = Vec::new();
+    let mut start = 0;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '|' => {
+                cells.push(line[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    cells.push(line[start..].trim());
+    cells
+}
@@ -0,0 +1,96 @@
+//! Finding `$...$` and `$$...$$` math in message text and rendering it with KaTeX, which
+//! `index.html` loads alongside the app.
+
+use js_sys::{Function, Object, Reflect};
+use wasm_bindgen::{JsCast, JsValue};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Segment<'a> {
+    Text(&'a str),
+    /// TeX source without the dollar signs. `display` is true for `$$...$$`, set on its own line.
+    Math {
+        tex: &'a str,
+        display: bool,
+    },
+}
+
+/// Splits `text` into plain text and math. Follows pandoc's rules so prices don't turn into
+/// math: an inline `$` must not be followed by a space when it opens, nor preceded by one or
+/// followed by a digit when it closes. `\$` is a literal dollar.
+pub fn split_math(text: &str) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    let mut plain_start = 0;
+    let mut i = 0;
+    let bytes = text.as_bytes();
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'$' => match math_at(text, i) {
+                Some((tex, display, end)) => {
+                    if plain_start < i {
+                        segments.push(Segment::Text(&text[plain_start..i]));
+                    }
+                    segments.push(Segment::Math { tex, display });
+                    i = end;
+                    plain_start = end;
+                }
+                None => i += 1,
+            },
+            _ => i += 1,
+        }
+    }
+    if plain_start < text.len() {
+        segments.push(Segment::Text(&text[plain_start..]));
+    }
+    segments
+}
+
+/// The math opening at byte `start`, if it is closed: its source, whether it is display math,
+/// and the byte offset just past the closing delimiter.
+fn math_at(text: &str, start: usize) -> Option<(&str, bool, usize)> {
+    let rest = &text[start..];
+    if let Some(body) = rest.strip_prefix("$$") {
+        let end = body.find("$$")?;
+        let tex = body[..end].trim();
+        return (!tex.is_empty()).then_some((tex, true, start + 2 + end + 2));
+    }
+    let body = &rest[1..];
+    if body.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let mut escaped = false;
+    for (i, c) in body.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '\n' => return None,
+            '$' => {
+                let closes = i > 0
+                    && !body[..i].ends_with(char::is_whitespace)
+                    && !body[i + 1..].starts_with(|c: char| c.is_ascii_digit());
+                if closes {
+                    return Some((&body[..i], false, start + 1 + i + 1));
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// KaTeX's HTML for `tex`, or `None` if KaTeX isn't loaded. Invalid TeX renders as KaTeX's
+/// inline error rather than failing.
+pub fn render(tex: &str, display: bool) -> Option<String> {
+    let katex = Reflect::get(&gloo::utils::window(), &JsValue::from_str("katex")).ok()?;
+    let render = Reflect::get(&katex, &JsValue::from_str("renderToString"))
+        .ok()?
+        .dyn_into::<Function>()
+        .ok()?;
+    let options = Object::new();
+    let _ = Reflect::set(&options, &"displayMode".into(), &display.into());
+    let _ = Reflect::set(&options, &"throwOnError".into(), &false.into());
+    render
+        .call2(&katex, &JsValue::from_str(tex), &options)
+        .ok()?
+        .as_string()
+}
@@ -0,0 +1,22 @@
+//! Working out whether a message pings the current user.
+
+use yewchat_protocol::{mentions, BroadcastMention};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mention {
+    /// `@name` aimed at this user.
+    Direct,
+    /// `@everyone`, or `@here` while online.
+    Broadcast,
+}
+
+/// How `text` mentions `me`, if at all. `online` is false for spectators, whom `@here` skips.
+pub fn mention_of(text: &str, me: &str, online: bool) -> Option<Mention> {
+    if mentions(text, me) {
+        return Some(Mention::Direct);
+    }
+    match BroadcastMention::find(text)? {
+        BroadcastMention::Everyone => Some(Mention::Broadcast),
+        BroadcastMention::Here => online.then_some(Mention::Broadcast),
+    }
+}
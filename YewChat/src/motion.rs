@@ -0,0 +1,100 @@
+//! Honouring `prefers-reduced-motion`, or the user's own choice in settings.
+
+use gloo::events::EventListener;
+use serde::{Deserialize, Serialize};
+use web_sys::MediaQueryList;
+use yew::prelude::*;
+
+use crate::store::use_store;
+
+const QUERY: &str = "(prefers-reduced-motion: reduce)";
+/// Set on `<html>` while motion is reduced; `index.html` stops transitions and animations
+/// under it.
+const ROOT_CLASS: &str = "reduce-motion";
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum Motion {
+    /// Follow the operating system's setting.
+    #[default]
+    System,
+    Reduced,
+    Full,
+}
+
+impl Motion {
+    pub const ALL: [Motion; 3] = [Motion::System, Motion::Reduced, Motion::Full];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Motion::System => "Match system",
+            Motion::Reduced => "Reduced",
+            Motion::Full => "Full",
+        }
+    }
+
+    /// Whether to hold animation back, given whether the system asks for that.
+    pub fn reduced(&self, system_reduced: bool) -> bool {
+        match self {
+            Motion::System => system_reduced,
+            Motion::Reduced => true,
+            Motion::Full => false,
+        }
+    }
+}
+
+fn media_query() -> Option<MediaQueryList> {
+    gloo::utils::window().match_media(QUERY).ok().flatten()
+}
+
+fn system_reduced() -> bool {
+    media_query().is_some_and(|query| query.matches())
+}
+
+/// Whether motion is reduced, as published by `MotionProvider`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ReducedMotion(pub bool);
+
+#[derive(Properties, PartialEq)]
+pub struct MotionProviderProps {
+    pub children: Children,
+}
+
+/// Works out whether motion is reduced from the setting and the system, follows changes to
+/// the system setting, and publishes the answer to everything below it.
+#[function_component(MotionProvider)]
+pub fn motion_provider(props: &MotionProviderProps) -> Html {
+    let setting = use_store().preferences.motion;
+    let system = use_state(system_reduced);
+    {
+        let system = system.clone();
+        use_effect_with_deps(
+            move |_| {
+                let listener = media_query().map(|query| {
+                    EventListener::new(&query, "change", move |_| system.set(system_reduced()))
+                });
+                move || drop(listener)
+            },
+            (),
+        );
+    }
+    let reduced = setting.reduced(*system);
+    use_effect_with_deps(
+        |&reduced| {
+            if let Some(root) = gloo::utils::document().document_element() {
+                let _ = root.class_list().toggle_with_force(ROOT_CLASS, reduced);
+            }
+            || ()
+        },
+        reduced,
+    );
+
+    html! {
+        <ContextProvider<ReducedMotion> context={ReducedMotion(reduced)}>
+            { for props.children.iter() }
+        </ContextProvider<ReducedMotion>>
+    }
+}
+
+pub fn use_reduced_motion() -> bool {
+    use_context::<ReducedMotion>().is_some_and(|reduced| reduced.0)
+}
@@ -0,0 +1,220 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use gloo::timers::callback::Timeout;
+use reqwasm::http::Request;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::{Blob, Url};
+use yew::prelude::*;
+
+use crate::config;
+use crate::services::data_usage::{self, Traffic};
+use crate::store::use_data_saver;
+
+/// Width in pixels avatars are fetched at; comfortably sharp at the largest size shown.
+const SIZE: u32 = 128;
+/// Width fetched at instead in data saver mode.
+const SAVER_SIZE: u32 = 48;
+/// Fetches of one avatar before settling on initials for the rest of the session.
+pub const MAX_ATTEMPTS: u32 = 4;
+/// Wait before the first retry, in ms; doubled for each one after.
+const RETRY_BASE_MS: u32 = 2_000;
+
+enum Entry {
+    /// Fetch in flight or waiting to be retried; these callbacks get the image's object URL
+    /// once it lands. Counts the fetches that have failed so far.
+    Pending {
+        waiters: Vec<Callback<String>>,
+        failures: u32,
+    },
+    Ready(String),
+    Failed,
+}
+
+thread_local! {
+    // Keyed by remote URL, so each size is fetched once.
+    static CACHE: RefCell<HashMap<String, Entry>> = RefCell::new(HashMap::new());
+}
+
+pub fn remote_url(name: &str, size: u32) -> String {
+    config::get().avatar_url_for(name, size)
+}
+
+fn cached(url: &str) -> Option<String> {
+    CACHE.with(|c| match c.borrow().get(url) {
+        Some(Entry::Ready(url)) => Some(url.clone()),
+        _ => None,
+    })
+}
+
+/// How long to wait after the `failures`th failed fetch before trying again, in ms.
+pub fn retry_delay(failures: u32) -> u32 {
+    RETRY_BASE_MS << failures.saturating_sub(1).min(4)
+}
+
+/// Up to two letters standing in for `name`: the first of each of its first two words, or
+/// just its first letter.
+pub fn initials(name: &str) -> String {
+    let initials: String = name
+        .split(|c: char| c.is_whitespace() || matches!(c, '_' | '-' | '.'))
+        .filter_map(|word| word.chars().next())
+        .take(2)
+        .flat_map(char::to_uppercase)
+        .collect();
+    if initials.is_empty() {
+        "?".to_string()
+    } else {
+        initials
+    }
+}
+
+/// An avatar drawn locally: `name`'s initials on a color picked from the name, used until
+/// the remote one loads and instead of it when it can't be.
+pub fn initials_url(name: &str) -> String {
+    // FNV-1a, like the name colors, so a name keeps its color between sessions.
+    let hash = name.bytes().fold(0x811c_9dc5_u32, |hash, byte| {
+        (hash ^ byte as u32).wrapping_mul(0x0100_0193)
+    });
+    let svg = format!(
+        "<svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 64 64'>\
+         <rect width='64' height='64' fill='hsl({}, 55%, 45%)'/>\
+         <text x='32' y='32' dy='.35em' text-anchor='middle' fill='white' \
+         font-family='sans-serif' font-size='26'>{}</text></svg>",
+        hash % 360,
+        escape(&initials(name))
+    );
+    format!(
+        "data:image/svg+xml;charset=utf-8,{}",
+        js_sys::encode_uri_component(&svg)
+    )
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Resolves the avatar at `url` once per session, however many components ask for it.
+fn request(url: &str, on_ready: Callback<String>) {
+    let (start, ready) = CACHE.with(|c| {
+        let mut cache = c.borrow_mut();
+        match cache.get_mut(url) {
+            Some(Entry::Pending { waiters, .. }) => {
+                waiters.push(on_ready.clone());
+                (false, None)
+            }
+            Some(Entry::Ready(url)) => (false, Some(url.clone())),
+            Some(Entry::Failed) => (false, None),
+            None => {
+                let entry = Entry::Pending {
+                    waiters: vec![on_ready.clone()],
+                    failures: 0,
+                };
+                cache.insert(url.to_string(), entry);
+                (true, None)
+            }
+        }
+    });
+    if let Some(url) = ready {
+        on_ready.emit(url);
+    }
+    if start {
+        fetch(url.to_string());
+    }
+}
+
+/// Fetches `url` for its pending entry, retrying with backoff until `MAX_ATTEMPTS` fetches
+/// have failed.
+fn fetch(url: String) {
+    spawn_local(async move {
+        let resolved = fetch_object_url(&url).await;
+        let (waiters, failures) = match CACHE.with(|c| c.borrow_mut().remove(&url)) {
+            Some(Entry::Pending { waiters, failures }) => (waiters, failures + 1),
+            _ => return,
+        };
+        match resolved {
+            Some(object_url) => {
+                CACHE.with(|c| c.borrow_mut().insert(url, Entry::Ready(object_url.clone())));
+                for waiter in waiters {
+                    waiter.emit(object_url.clone());
+                }
+            }
+            None if failures >= MAX_ATTEMPTS => {
+                log::debug!("giving up on avatar {} after {} tries", url, failures);
+                CACHE.with(|c| c.borrow_mut().insert(url, Entry::Failed));
+            }
+            None => {
+                let entry = Entry::Pending { waiters, failures };
+                CACHE.with(|c| c.borrow_mut().insert(url.clone(), entry));
+                Timeout::new(retry_delay(failures), move || fetch(url)).forget();
+            }
+        }
+    });
+}
+
+/// Fetches the image at `url` into a blob and returns an object URL for it, kept for the
+/// session. Anything that isn't an image counts as a failed fetch.
+async fn fetch_object_url(url: &str) -> Option<String> {
+    let response = match Request::get(url).send().await {
+        Ok(response) if response.ok() => response,
+        Ok(response) => {
+            log::debug!("avatar {} returned {}", url, response.status());
+            return None;
+        }
+        Err(e) => {
+            log::debug!("avatar {} failed: {}", url, e);
+            return None;
+        }
+    };
+    let blob: Blob = JsFuture::from(response.as_raw().blob().ok()?)
+        .await
+        .ok()?
+        .dyn_into()
+        .ok()?;
+    data_usage::record(Traffic::Media, blob.size() as u64);
+    if !blob.type_().starts_with("image/") {
+        log::debug!("avatar {} is {:?}, not an image", url, blob.type_());
+        return None;
+    }
+    Url::create_object_url_with_blob(&blob).ok()
+}
+
+/// The cached URL of `name`'s avatar, or its initials until it has been resolved or
+/// if it can't be. Smaller in data saver mode.
+pub fn use_avatar(name: &str) -> String {
+    let size = if use_data_saver() { SAVER_SIZE } else { SIZE };
+    let url = remote_url(name, size);
+    let resolved = use_state(|| cached(&url));
+
+    {
+        let resolved = resolved.clone();
+        use_effect_with_deps(
+            move |url: &String| {
+                // A new name drops the last one's avatar straight away, rather than showing
+                // it until this one has loaded, or for good if it can't be.
+                let current = cached(url);
+                if *resolved != current {
+                    resolved.set(current.clone());
+                }
+                // And the last name's fetch, should it land later, is ignored.
+                let stale = Rc::new(Cell::new(false));
+                if current.is_none() {
+                    let stale = stale.clone();
+                    let on_ready = Callback::from(move |object_url| {
+                        if !stale.get() {
+                            resolved.set(Some(object_url));
+                        }
+                    });
+                    request(url, on_ready);
+                }
+                move || stale.set(true)
+            },
+            url,
+        );
+    }
+
+    (*resolved).clone().unwrap_or_else(|| initials_url(name))
+}
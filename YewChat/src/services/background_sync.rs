@@ -0,0 +1,105 @@
+//! Hands failed sends to the service worker, which keeps them in IndexedDB and sends them
+//! through Background Sync once the network is back, even if every tab has been closed.
+//! See `static/sw.js` for the worker's side.
+
+use gloo::events::EventListener;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{MessageEvent, ServiceWorkerContainer};
+
+use crate::config;
+use crate::state::{Outgoing, SendError};
+
+/// A send waiting on the network, as the worker stores it.
+#[derive(Serialize)]
+struct Pending {
+    id: u64,
+    channel: String,
+    message: String,
+    /// The frame to send, ready-encoded.
+    frame: String,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum ToWorker {
+    /// Replaces what the worker holds.
+    #[serde(rename_all = "camelCase")]
+    Outbox {
+        user: String,
+        server_url: String,
+        frames: Vec<Pending>,
+    },
+    /// Asks which sends the worker has got through since the tab last asked.
+    Delivered,
+}
+
+/// What the service worker tells an open tab.
+#[derive(Deserialize, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum FromWorker {
+    /// These outbox ids were sent while no tab was open.
+    Delivered { ids: Vec<u64> },
+    /// The network is back; the tab should retry its failed sends itself.
+    Flush,
+}
+
+impl FromWorker {
+    pub fn parse(text: &str) -> Option<Self> {
+        serde_json::from_str(text).ok()
+    }
+}
+
+fn container() -> Option<ServiceWorkerContainer> {
+    let navigator = web_sys::window()?.navigator();
+    // `serviceWorker` is missing on insecure origins, so check before touching it.
+    if !js_sys::Reflect::has(&navigator, &"serviceWorker".into()).unwrap_or(false) {
+        return None;
+    }
+    Some(navigator.service_worker())
+}
+
+fn post(message: &ToWorker) {
+    let Some(worker) = container().and_then(|c| c.controller()) else {
+        return;
+    };
+    if let Ok(json) = serde_json::to_string(message) {
+        let _ = worker.post_message(&JsValue::from_str(&json));
+    }
+}
+
+/// Gives the worker `user`'s sends that failed on the way, replacing any it had before.
+/// Refused ones stay behind: the server would only refuse them again.
+pub fn hand_over(user: &str, outbox: &[Outgoing]) {
+    let frames = outbox
+        .iter()
+        .filter(|o| matches!(o.error, Some(SendError::Network(_))))
+        .map(|o| Pending {
+            id: o.id,
+            channel: o.channel.clone(),
+            message: o.message.clone(),
+            frame: o.frame().to_json(),
+        })
+        .collect();
+    post(&ToWorker::Outbox {
+        user: user.to_string(),
+        server_url: config::get().server_url.clone(),
+        frames,
+    });
+}
+
+/// Asks the worker which sends it got through; the answer arrives at [`listen`].
+pub fn ask_delivered() {
+    post(&ToWorker::Delivered);
+}
+
+/// Calls `on_message` with what the worker tells this tab, until dropped.
+pub fn listen(on_message: impl Fn(FromWorker) + 'static) -> Option<EventListener> {
+    let container = container()?;
+    Some(EventListener::new(&container, "message", move |e| {
+        let data = e.unchecked_ref::<MessageEvent>().data();
+        if let Some(message) = data.as_string().as_deref().and_then(FromWorker::parse) {
+            on_message(message);
+        }
+    }))
+}
@@ -0,0 +1,24 @@
+use wasm_bindgen::{JsCast, JsValue};
+
+/// Puts `text` on the system clipboard. Best effort: browsers without the async clipboard API,
+/// or that refuse permission, leave it unchanged.
+pub fn write_text(text: &str) {
+    let clipboard = web_sys::window()
+        .map(|w| w.navigator())
+        .and_then(|n| js_sys::Reflect::get(&n, &JsValue::from_str("clipboard")).ok())
+        .filter(|c| !c.is_undefined());
+    let write = clipboard.as_ref().and_then(|c| {
+        js_sys::Reflect::get(c, &JsValue::from_str("writeText"))
+            .ok()?
+            .dyn_into::<js_sys::Function>()
+            .ok()
+    });
+    match (clipboard, write) {
+        (Some(clipboard), Some(write)) => {
+            if let Err(e) = write.call1(&clipboard, &JsValue::from_str(text)) {
+                log::warn!("failed to copy to the clipboard: {:?}", e);
+            }
+        }
+        _ => log::warn!("clipboard is not available"),
+    }
+}
@@ -0,0 +1,225 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+
+use gloo::timers::callback::Timeout;
+use serde::{Deserialize, Serialize};
+use yew_agent::{Agent, AgentLink, Bridge, Bridged, Dispatched, HandlerId, Public};
+use yewchat_protocol::WebSocketMessage;
+
+use crate::config::{self, Config};
+use crate::services::event_bus::{ChatEvent, ConnectionState, EventBus, Request};
+use crate::services::socket::{ChatSocket, SocketError};
+use crate::services::websocket::WebsocketService;
+
+const RECONNECT_BASE_MS: u32 = 1_000;
+const RECONNECT_MAX_MS: u32 = 30_000;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub enum WorkerInput {
+    /// The page's configuration, sent before anything else. The first one connects.
+    Connect(Config),
+    Send(WebSocketMessage),
+}
+
+pub enum WorkerMsg {
+    Event(ChatEvent),
+    Reconnect,
+}
+
+/// What keeps a worker's connection going as the same user: the registration frames to
+/// replay and the backoff between attempts. Shared by the dedicated and shared workers.
+#[derive(Default)]
+pub struct Session {
+    /// Latest registration frame of each type, replayed in order after a reconnect so the
+    /// server knows who we are again.
+    frames: Vec<WebSocketMessage>,
+    has_connected: bool,
+    /// Another session signed this one out, so it stays disconnected.
+    signed_out: bool,
+    reconnect_attempts: u32,
+}
+
+impl Session {
+    /// Keeps up with `event` from `service`. Returns how many ms to wait before reconnecting
+    /// if it lost the connection, longer after each attempt that never got connected.
+    pub fn observe(&mut self, event: &ChatEvent, service: &impl ChatSocket) -> Option<u32> {
+        match event {
+            ChatEvent::ConnectionChanged(ConnectionState::Connected) => {
+                if self.has_connected {
+                    for frame in self.frames.iter() {
+                        let _ = service.send(frame);
+                    }
+                }
+                self.has_connected = true;
+                self.reconnect_attempts = 0;
+            }
+            ChatEvent::ConnectionChanged(ConnectionState::Disconnected) if !self.signed_out => {
+                let delay = RECONNECT_BASE_MS
+                    .saturating_mul(1 << self.reconnect_attempts.min(5))
+                    .min(RECONNECT_MAX_MS);
+                self.reconnect_attempts += 1;
+                return Some(delay);
+            }
+            ChatEvent::SignedOut => {
+                self.signed_out = true;
+                self.frames.clear();
+            }
+            _ => {}
+        }
+        None
+    }
+
+    /// Sends `message` on `service`, remembering it if it says who we are. A failure comes
+    /// back as the event to hand whoever asked for the send.
+    pub fn send(
+        &mut self,
+        message: WebSocketMessage,
+        service: &impl ChatSocket,
+    ) -> Result<(), ChatEvent> {
+        if message.message_type.is_registration() {
+            match self
+                .frames
+                .iter_mut()
+                .find(|m| m.message_type == message.message_type)
+            {
+                Some(frame) => *frame = message.clone(),
+                None => self.frames.push(message.clone()),
+            }
+        }
+        service
+            .send(&message)
+            .map_err(|error| ChatEvent::SendFailed {
+                error,
+                outgoing_id: message.outgoing_id,
+            })
+    }
+
+    pub fn reconnect_attempts(&self) -> u32 {
+        self.reconnect_attempts
+    }
+}
+
+/// Owns the WebSocket inside a web worker, so frame parsing and reconnects stay off the UI
+/// thread and the connection outlives any single component.
+pub struct ConnectionWorker {
+    link: AgentLink<Self>,
+    subscribers: HashSet<HandlerId>,
+    /// `None` until a page has sent its configuration.
+    service: Option<WebsocketService>,
+    session: Session,
+    _reconnect: Option<Timeout>,
+}
+
+impl Agent for ConnectionWorker {
+    type Reach = Public<Self>;
+    type Message = WorkerMsg;
+    type Input = WorkerInput;
+    type Output = ChatEvent;
+
+    fn create(link: AgentLink<Self>) -> Self {
+        Self {
+            link,
+            subscribers: HashSet::new(),
+            service: None,
+            session: Session::default(),
+            _reconnect: None,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) {
+        match msg {
+            WorkerMsg::Event(event) => {
+                let Some(service) = &self.service else {
+                    return;
+                };
+                if let Some(delay) = self.session.observe(&event, service) {
+                    let reconnect = self.link.callback(|_| WorkerMsg::Reconnect);
+                    self._reconnect = Some(Timeout::new(delay, move || reconnect.emit(())));
+                }
+                for sub in self.subscribers.iter() {
+                    self.link.respond(*sub, event.clone());
+                }
+            }
+            WorkerMsg::Reconnect => {
+                log::debug!(
+                    "reconnecting (attempt {})",
+                    self.session.reconnect_attempts()
+                );
+                for sub in self.subscribers.iter() {
+                    self.link.respond(
+                        *sub,
+                        ChatEvent::ConnectionChanged(ConnectionState::Connecting),
+                    );
+                }
+                self.service = Some(WebsocketService::connect(
+                    self.link.callback(WorkerMsg::Event),
+                ));
+            }
+        }
+    }
+
+    fn handle_input(&mut self, msg: Self::Input, id: HandlerId) {
+        match msg {
+            WorkerInput::Connect(config) => {
+                if self.service.is_none() {
+                    config::set(config);
+                    self.service = Some(WebsocketService::connect(
+                        self.link.callback(WorkerMsg::Event),
+                    ));
+                }
+            }
+            WorkerInput::Send(message) => {
+                let sent = match &self.service {
+                    Some(service) => self.session.send(message, service),
+                    None => Err(ChatEvent::SendFailed {
+                        error: SocketError::Disconnected,
+                        outgoing_id: message.outgoing_id,
+                    }),
+                };
+                if let Err(event) = sent {
+                    self.link.respond(id, event);
+                }
+            }
+        }
+    }
+
+    fn connected(&mut self, id: HandlerId) {
+        self.subscribers.insert(id);
+    }
+
+    fn disconnected(&mut self, id: HandlerId) {
+        self.subscribers.remove(&id);
+    }
+
+    fn name_of_resource() -> &'static str {
+        "worker.js"
+    }
+}
+
+/// Main-thread side of the worker connection; relays the worker's events onto the EventBus.
+pub struct WorkerSocket {
+    bridge: RefCell<Box<dyn Bridge<ConnectionWorker>>>,
+}
+
+impl WorkerSocket {
+    pub fn new() -> Self {
+        let mut event_bus = EventBus::dispatcher();
+        let mut bridge = ConnectionWorker::bridge(yew::Callback::from(move |event| {
+            event_bus.send(Request::EventBusMsg(event))
+        }));
+        bridge.send(WorkerInput::Connect((*config::get()).clone()));
+        Self {
+            bridge: RefCell::new(bridge),
+        }
+    }
+}
+
+impl ChatSocket for WorkerSocket {
+    /// Always `Ok`: the worker sends later, and reports a failure as `ChatEvent::SendFailed`.
+    fn send(&self, message: &WebSocketMessage) -> Result<(), SocketError> {
+        self.bridge
+            .borrow_mut()
+            .send(WorkerInput::Send(message.clone()));
+        Ok(())
+    }
+}
@@ -0,0 +1,62 @@
+//! Bytes moved this session, for users on metered connections. Kept in memory only, and
+//! recorded whether or not telemetry is on.
+
+use std::cell::Cell;
+
+#[derive(Clone, Copy, Debug)]
+pub enum Traffic {
+    /// Frames written to the chat connection.
+    Sent,
+    /// Frames read from the chat connection, attachments included.
+    Received,
+    /// Media fetched outside the connection, such as avatars.
+    Media,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Usage {
+    pub sent: u64,
+    pub received: u64,
+    pub media: u64,
+}
+
+impl Usage {
+    pub fn total(&self) -> u64 {
+        self.sent + self.received + self.media
+    }
+}
+
+thread_local! {
+    static USAGE: Cell<Usage> = Cell::new(Usage::default());
+}
+
+pub fn record(traffic: Traffic, bytes: u64) {
+    USAGE.with(|usage| {
+        let mut u = usage.get();
+        match traffic {
+            Traffic::Sent => u.sent += bytes,
+            Traffic::Received => u.received += bytes,
+            Traffic::Media => u.media += bytes,
+        }
+        usage.set(u);
+    });
+}
+
+pub fn snapshot() -> Usage {
+    USAGE.with(Cell::get)
+}
+
+/// `bytes` in the largest unit that keeps the number at or above one, e.g. "1.5 MB".
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
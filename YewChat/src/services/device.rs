@@ -0,0 +1,42 @@
+//! What this client runs on, described for the user's other sessions.
+
+/// Checked in order: each engine's user agent also names the ones it imitates.
+const BROWSERS: [(&str, &str); 5] = [
+    ("Edg/", "Edge"),
+    ("OPR/", "Opera"),
+    ("Firefox/", "Firefox"),
+    ("Chrome/", "Chrome"),
+    ("Safari/", "Safari"),
+];
+/// Checked in order: Android reports Linux, and iOS reports Mac OS X.
+const SYSTEMS: [(&str, &str); 7] = [
+    ("Android", "Android"),
+    ("iPhone", "iOS"),
+    ("iPad", "iPadOS"),
+    ("Windows", "Windows"),
+    ("CrOS", "ChromeOS"),
+    ("Mac OS X", "macOS"),
+    ("Linux", "Linux"),
+];
+
+/// A short description such as "Firefox on Linux", from a user agent string.
+pub fn describe(user_agent: &str) -> String {
+    let find = |table: &[(&str, &'static str)]| {
+        table
+            .iter()
+            .find(|(marker, _)| user_agent.contains(marker))
+            .map(|(_, name)| *name)
+    };
+    match (find(&BROWSERS), find(&SYSTEMS)) {
+        (Some(browser), Some(system)) => format!("{} on {}", browser, system),
+        (Some(browser), None) => browser.into(),
+        (None, Some(system)) => format!("Browser on {}", system),
+        (None, None) => "Unknown device".into(),
+    }
+}
+
+/// This client, described.
+pub fn current() -> String {
+    let user_agent = gloo::utils::window().navigator().user_agent();
+    describe(&user_agent.unwrap_or_default())
+}
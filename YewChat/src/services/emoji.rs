@@ -0,0 +1,220 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use reqwasm::http::Request;
+use serde::Deserialize;
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+
+/// Fetched on first use rather than compiled in, so it stays out of the initial wasm payload.
+const DATASET_URL: &str = "emoji.json";
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct EmojiEntry {
+    pub emoji: String,
+    pub shortcodes: Vec<String>,
+    pub category: String,
+}
+
+pub type EmojiDataset = Rc<Vec<EmojiEntry>>;
+
+thread_local! {
+    static DATASET: RefCell<Option<EmojiDataset>> = RefCell::new(None);
+}
+
+/// The dataset if it has already been fetched.
+pub fn cached() -> Option<EmojiDataset> {
+    DATASET.with(|d| d.borrow().clone())
+}
+
+/// Returns the dataset, fetching it the first time it is needed.
+pub async fn load() -> Result<EmojiDataset, String> {
+    if let Some(dataset) = cached() {
+        return Ok(dataset);
+    }
+    let entries: Vec<EmojiEntry> = Request::get(DATASET_URL)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?
+        .json()
+        .await
+        .map_err(|e| e.to_string())?;
+    let dataset = Rc::new(entries);
+    DATASET.with(|d| *d.borrow_mut() = Some(dataset.clone()));
+    Ok(dataset)
+}
+
+/// The dataset once loaded, triggering the fetch on first use. `None` until then, or if the
+/// fetch failed.
+pub fn use_dataset() -> Option<EmojiDataset> {
+    let dataset = use_state(cached);
+
+    {
+        let dataset = dataset.clone();
+        use_effect_with_deps(
+            move |_| {
+                if dataset.is_none() {
+                    spawn_local(async move {
+                        match load().await {
+                            Ok(loaded) => dataset.set(Some(loaded)),
+                            Err(e) => log::warn!("failed to load emoji dataset: {}", e),
+                        }
+                    });
+                }
+                || ()
+            },
+            (),
+        );
+    }
+
+    (*dataset).clone()
+}
+
+/// Replaces every `:shortcode:` that names a known emoji. Anything else between colons, such
+/// as a time like `12:30:45`, is left alone.
+pub fn replace_shortcodes(text: &str, dataset: &[EmojiEntry]) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(':') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let found = after.find(':').and_then(|end| {
+            let code = &after[..end];
+            let valid = !code.is_empty() && code.chars().all(is_shortcode_char);
+            valid
+                .then(|| lookup(dataset, code))
+                .flatten()
+                .map(|emoji| (emoji, end))
+        });
+        match found {
+            Some((emoji, end)) => {
+                out.push_str(emoji);
+                rest = &after[end + 1..];
+            }
+            None => {
+                // The closing colon may open the next shortcode, so only skip this one.
+                out.push(':');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// The `:query` being typed at the end of `draft`, with the byte offset of its colon. Needs
+/// at least two characters after the colon so ordinary colons don't open suggestions.
+pub fn shortcode_query(draft: &str) -> Option<(usize, &str)> {
+    let start = draft.rfind(':')?;
+    let query = &draft[start + 1..];
+    let opens_word = start == 0 || draft[..start].ends_with(char::is_whitespace);
+    (opens_word && query.len() >= 2 && query.chars().all(is_shortcode_char))
+        .then_some((start, query))
+}
+
+/// Up to `limit` entries whose shortcodes start with `query`, then ones merely containing it.
+pub fn search<'a>(dataset: &'a [EmojiEntry], query: &str, limit: usize) -> Vec<&'a EmojiEntry> {
+    let matching = |prefix: bool| {
+        dataset.iter().filter(move |entry| {
+            entry
+                .shortcodes
+                .iter()
+                .any(|s| s.starts_with(query) == prefix && s.contains(query))
+        })
+    };
+    let mut found: Vec<&EmojiEntry> = matching(true).take(limit).collect();
+    for entry in matching(false) {
+        if found.len() >= limit {
+            break;
+        }
+        if !found.iter().any(|f| f.emoji == entry.emoji) {
+            found.push(entry);
+        }
+    }
+    found
+}
+
+/// Message text as displayed: shortcodes resolved if the dataset is available.
+pub fn with_emoji(text: &str, dataset: Option<&Vec<EmojiEntry>>) -> String {
+    match dataset {
+        Some(dataset) => replace_shortcodes(text, dataset),
+        None => text.to_string(),
+    }
+}
+
+/// Most emoji a message can hold and still be shown jumbo.
+pub const JUMBO_MAX: usize = 3;
+
+/// How many emoji `text` is, if it is nothing but 1 to [`JUMBO_MAX`] of them and
+/// whitespace. Skin tones, variation selectors, keycap marks and ZWJ sequences count as part
+/// of the emoji before them, and a pair of regional indicators as one flag.
+pub fn jumbo_count(text: &str) -> Option<usize> {
+    let mut count = 0;
+    // The previous char was a zero-width joiner, or the first half of a flag.
+    let (mut joined, mut half_flag) = (false, false);
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        let starts_emoji = match c {
+            c if c.is_whitespace() => {
+                (joined, half_flag) = (false, false);
+                continue;
+            }
+            '\u{200D}' if count > 0 => {
+                joined = true;
+                continue;
+            }
+            '\u{FE0E}' | '\u{FE0F}' | '\u{20E3}' if count > 0 => continue,
+            '\u{1F3FB}'..='\u{1F3FF}' | '\u{E0020}'..='\u{E007F}' if count > 0 => continue,
+            '\u{1F1E6}'..='\u{1F1FF}' => {
+                half_flag = !half_flag;
+                half_flag
+            }
+            // A digit, `#` or `*` is only an emoji as a keycap.
+            '0'..='9' | '#' | '*' => {
+                chars.next_if_eq(&'\u{FE0F}');
+                if chars.peek() != Some(&'\u{20E3}') {
+                    return None;
+                }
+                true
+            }
+            c if is_pictographic(c) => {
+                half_flag = false;
+                !joined
+            }
+            _ => return None,
+        };
+        joined = false;
+        if starts_emoji {
+            count += 1;
+            if count > JUMBO_MAX {
+                return None;
+            }
+        }
+    }
+    (count > 0).then_some(count)
+}
+
+/// Roughly Unicode's `Extended_Pictographic`: the blocks emoji are drawn from.
+fn is_pictographic(c: char) -> bool {
+    matches!(c,
+        '\u{00A9}' | '\u{00AE}' | '\u{203C}' | '\u{2049}' | '\u{2122}' | '\u{2139}'
+        | '\u{2194}'..='\u{21AA}'
+        | '\u{231A}'..='\u{23FF}'
+        | '\u{24C2}'
+        | '\u{25AA}'..='\u{27BF}'
+        | '\u{2934}' | '\u{2935}'
+        | '\u{2B05}'..='\u{2B55}'
+        | '\u{3030}' | '\u{303D}' | '\u{3297}' | '\u{3299}'
+        | '\u{1F000}'..='\u{1FAFF}')
+}
+
+fn lookup<'a>(dataset: &'a [EmojiEntry], code: &str) -> Option<&'a str> {
+    dataset
+        .iter()
+        .find(|entry| entry.shortcodes.iter().any(|s| s == code))
+        .map(|entry| entry.emoji.as_str())
+}
+
+fn is_shortcode_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '+' | '-')
+}
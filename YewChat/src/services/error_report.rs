@@ -0,0 +1,113 @@
+//! Error reports for maintainers. Errors logged at `error` level and panics are sent, with
+//! the log lines leading up to them, to `errorReportEndpoint` once the user has agreed to it
+//! in settings. Nothing is collected otherwise.
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
+
+use serde::Serialize;
+
+use crate::config;
+use crate::logging::{self, LogEntry};
+
+/// Log lines sent along with a report, the latest ones.
+const LOG_LINES: usize = 50;
+/// Reports sent per page load at most, so an error in a loop can't flood the endpoint.
+const MAX_REPORTS: usize = 20;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportKind {
+    /// Logged and recovered from.
+    Error,
+    Panic,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorReport {
+    pub kind: ReportKind,
+    pub message: String,
+    /// Module it was logged from, or the source file and line that panicked.
+    pub module: String,
+    /// Milliseconds since the unix epoch.
+    pub time: f64,
+    pub version: &'static str,
+    pub user_agent: String,
+    /// Most recent log lines, oldest first.
+    pub log: Vec<String>,
+}
+
+impl ErrorReport {
+    pub fn new(kind: ReportKind, module: &str, message: &str, log: &[LogEntry]) -> Self {
+        let skip = log.len().saturating_sub(LOG_LINES);
+        Self {
+            kind,
+            message: message.to_string(),
+            module: module.to_string(),
+            time: js_sys::Date::now(),
+            version: env!("CARGO_PKG_VERSION"),
+            user_agent: web_sys::window()
+                .and_then(|w| w.navigator().user_agent().ok())
+                .unwrap_or_default(),
+            log: log[skip..].iter().map(|e| e.to_string()).collect(),
+        }
+    }
+}
+
+thread_local! {
+    static ENABLED: Cell<bool> = Cell::new(false);
+    // Errors already reported this page load, by module and message.
+    static SENT: RefCell<HashSet<(String, String)>> = RefCell::new(HashSet::new());
+}
+
+/// Follows the user's consent preference.
+pub fn set_enabled(enabled: bool) {
+    ENABLED.with(|e| e.set(enabled));
+}
+
+pub fn endpoint() -> Option<String> {
+    config::get().error_report_endpoint.clone()
+}
+
+/// Reports an error logged from `module`, unless the same one was already reported.
+pub fn capture(module: &str, message: &str) {
+    report(ReportKind::Error, module, message);
+}
+
+/// Reports a panic at `location`. Called from the panic hook in [`crash`](crate::crash).
+pub fn capture_panic(location: &str, message: &str) {
+    report(ReportKind::Panic, location, message);
+}
+
+fn report(kind: ReportKind, module: &str, message: &str) {
+    if !ENABLED.with(Cell::get) {
+        return;
+    }
+    let Some(endpoint) = endpoint() else {
+        return;
+    };
+    let fresh = SENT.with(|sent| {
+        let Ok(mut sent) = sent.try_borrow_mut() else {
+            return false;
+        };
+        sent.len() < MAX_REPORTS && sent.insert((module.to_string(), message.to_string()))
+    });
+    if !fresh {
+        return;
+    }
+    let report = ErrorReport::new(kind, module, message, &logging::recent());
+    let body = serde_json::to_string(&report).unwrap();
+    // A beacon rather than a fetch: it's queued by the browser straight away, so it still
+    // goes out after a panic has stopped the app or while the page unloads.
+    let sent = web_sys::window()
+        .and_then(|w| {
+            w.navigator()
+                .send_beacon_with_opt_str(&endpoint, Some(&body))
+                .ok()
+        })
+        .unwrap_or(false);
+    if !sent {
+        log::debug!("error report to {} was not queued", endpoint);
+    }
+}
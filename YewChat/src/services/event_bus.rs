@@ -1,10 +1,160 @@
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use yew_agent::{Agent, AgentLink, Context, HandlerId};
+use yewchat_protocol::{
+    AuditEntry, ChannelInfo, HistoryPage, InviteInfo, MessageData, MessageReactions, MsgTypes,
+    Role, SessionInfo, Status, Stroke, SystemNotice, TypingInfo, UserInfo, WebSocketMessage,
+    Whiteboard,
+};
+
+use crate::services::socket::SocketError;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ConnectionState {
+    Connecting,
+    Connected,
+    Disconnected,
+}
+
+/// Decoded server traffic and connection changes, as seen by subscribers.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ChatEvent {
+    MessageReceived(MessageData),
+    /// A new version of a message already received, matched by id.
+    MessageEdited(MessageData),
+    UsersUpdated(Vec<UserInfo>),
+    SpectatorsUpdated(Vec<String>),
+    ChannelsUpdated(Vec<ChannelInfo>),
+    ReactionsUpdated(MessageReactions),
+    /// Moderation log entries; only moderators receive these.
+    AuditReceived(Vec<AuditEntry>),
+    MessageDeleted(u64),
+    PinsUpdated(Vec<u64>),
+    /// Answer to our request for an invite link.
+    InviteCreated(InviteInfo),
+    /// Older messages we asked for.
+    HistoryLoaded(HistoryPage),
+    /// Someone drew on a channel's whiteboard.
+    StrokeDrawn(Stroke),
+    /// A whole whiteboard, as asked for or just wiped.
+    WhiteboardLoaded(Whiteboard),
+    ConnectionChanged(ConnectionState),
+    /// The server answered a heartbeat sent at this unix time in ms.
+    Pong(u64),
+    /// Someone else is typing; they count as typing for a few seconds after each of these.
+    Typing(TypingInfo),
+    /// Every session registered under our nick, as asked for.
+    SessionsLoaded(Vec<SessionInfo>),
+    /// Another of our sessions signed this one out; the connection is closing for good.
+    SignedOut,
+    /// Someone joined or left, or a channel was renamed.
+    System(SystemNotice),
+    /// Bytes the connection just wrote or read. Tallied by the store, never applied to state.
+    Traffic {
+        sent: u64,
+        received: u64,
+    },
+    /// The server refused something we sent; `outgoing_id` names the send it refused, if it
+    /// was one.
+    Rejected {
+        reason: String,
+        outgoing_id: Option<u64>,
+    },
+    /// A connection worker couldn't send a frame the page handed it, which the page only
+    /// learns after the fact. `outgoing_id` names the send, if it was one.
+    SendFailed {
+        error: SocketError,
+        outgoing_id: Option<u64>,
+    },
+    Error(String),
+}
+
+impl ChatEvent {
+    /// Decodes a raw frame. Frames that only make sense server-side yield `None`.
+    pub fn from_frame(frame: &str) -> Option<Self> {
+        let msg: WebSocketMessage = match serde_json::from_str(frame) {
+            Ok(msg) => msg,
+            Err(e) => return Some(ChatEvent::Error(format!("malformed frame: {}", e))),
+        };
+        match msg.message_type {
+            MsgTypes::Users => Some(ChatEvent::UsersUpdated(users(msg))),
+            MsgTypes::Message => decode(msg.data, "message", ChatEvent::MessageReceived),
+            MsgTypes::Edited => decode(msg.data, "edit", ChatEvent::MessageEdited),
+            MsgTypes::Spectators => Some(ChatEvent::SpectatorsUpdated(
+                msg.data_array.unwrap_or_default(),
+            )),
+            MsgTypes::Channels => decode(msg.data, "channel list", ChatEvent::ChannelsUpdated),
+            MsgTypes::Reactions => decode(msg.data, "reactions", ChatEvent::ReactionsUpdated),
+            MsgTypes::Audit => decode(msg.data, "audit entries", ChatEvent::AuditReceived),
+            MsgTypes::Deleted => decode(msg.data, "message id", ChatEvent::MessageDeleted),
+            MsgTypes::Pins => decode(msg.data, "pins", ChatEvent::PinsUpdated),
+            MsgTypes::Invite => decode(msg.data, "invite", ChatEvent::InviteCreated),
+            MsgTypes::History => decode(msg.data, "history", ChatEvent::HistoryLoaded),
+            MsgTypes::Stroke => decode(msg.data, "stroke", ChatEvent::StrokeDrawn),
+            MsgTypes::Whiteboard => decode(msg.data, "whiteboard", ChatEvent::WhiteboardLoaded),
+            MsgTypes::Ping => decode(msg.data, "heartbeat", ChatEvent::Pong),
+            MsgTypes::Sessions => decode(msg.data, "session list", ChatEvent::SessionsLoaded),
+            MsgTypes::SignOut => Some(ChatEvent::SignedOut),
+            MsgTypes::Typing => decode(msg.data, "typing notice", ChatEvent::Typing),
+            MsgTypes::System => decode(msg.data, "system notice", ChatEvent::System),
+            MsgTypes::Error => Some(ChatEvent::Rejected {
+                reason: msg.data.unwrap_or_default(),
+                outgoing_id: msg.outgoing_id,
+            }),
+            MsgTypes::Register
+            | MsgTypes::Spectate
+            | MsgTypes::React
+            | MsgTypes::Status
+            | MsgTypes::Edit
+            | MsgTypes::Moderate
+            | MsgTypes::Join
+            | MsgTypes::ToggleTask
+            | MsgTypes::ClearWhiteboard
+            | MsgTypes::Device
+            | MsgTypes::PushSubscribe
+            | MsgTypes::PushUnsubscribe
+            | MsgTypes::Activity
+            | MsgTypes::SuppressMentions => None,
+        }
+    }
+}
+
+/// Parses the JSON in `data` into what `ctor` makes an event of, or an error naming `what`
+/// was malformed.
+fn decode<T: DeserializeOwned>(
+    data: Option<String>,
+    what: &str,
+    ctor: impl FnOnce(T) -> ChatEvent,
+) -> Option<ChatEvent> {
+    Some(match serde_json::from_str(&data.unwrap_or_default()) {
+        Ok(value) => ctor(value),
+        Err(e) => ChatEvent::Error(format!("malformed {}: {}", what, e)),
+    })
+}
+
+/// Prefers the JSON user list in `data`; servers that only send `dataArray` get members.
+fn users(msg: WebSocketMessage) -> Vec<UserInfo> {
+    if let Some(users) = msg.data.and_then(|d| serde_json::from_str(&d).ok()) {
+        return users;
+    }
+    msg.data_array
+        .unwrap_or_default()
+        .into_iter()
+        .map(|name| UserInfo {
+            name,
+            role: Role::Member,
+            status: Status::Online,
+            activity: None,
+            bot: false,
+            verified: false,
+        })
+        .collect()
+}
 
 #[derive(Serialize, Deserialize, Debug)]
 pub enum Request {
-    EventBusMsg(String),
+    EventBusMsg(ChatEvent),
 }
 
 pub struct EventBus {
@@ -16,7 +166,7 @@ impl Agent for EventBus {
     type Reach = Context<Self>;
     type Message = ();
     type Input = Request;
-    type Output = String;
+    type Output = ChatEvent;
 
     fn create(link: AgentLink<Self>) -> Self {
         Self {
@@ -29,9 +179,9 @@ impl Agent for EventBus {
 
     fn handle_input(&mut self, msg: Self::Input, _id: HandlerId) {
         match msg {
-            Request::EventBusMsg(s) => {
+            Request::EventBusMsg(event) => {
                 for sub in self.subscribers.iter() {
-                    self.link.respond(*sub, s.clone())
+                    self.link.respond(*sub, event.clone())
                 }
             }
         }
@@ -44,4 +194,4 @@ impl Agent for EventBus {
     fn disconnected(&mut self, id: HandlerId) {
         self.subscribers.remove(&id);
     }
-}
\ No newline at end of file
+}
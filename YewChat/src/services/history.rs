@@ -0,0 +1,10 @@
+use yewchat_protocol::{HistoryQuery, MsgTypes, WebSocketMessage};
+
+use crate::services::socket::{SocketError, SocketHandle};
+
+/// Asks the server for the page of history described by `query`. The page arrives later as
+/// a `HistoryLoaded` event; an `Err` means the request never left, so none will.
+pub fn request(socket: &SocketHandle, query: &HistoryQuery) -> Result<(), SocketError> {
+    let data = serde_json::to_string(query).unwrap();
+    socket.send(&WebSocketMessage::with_data(MsgTypes::History, data))
+}
@@ -0,0 +1,237 @@
+//! Shrinks images in the browser before they are attached, so frames stay quick to send and
+//! under the server's size limit.
+
+use js_sys::Promise;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    CanvasRenderingContext2d, File, FileReader, HtmlCanvasElement, HtmlImageElement, Url,
+};
+use yewchat_protocol::Attachment;
+
+use crate::config::Config;
+
+/// Small images that need no resizing are sent untouched, keeping transparency and format.
+const PASS_THROUGH_BYTES: f64 = 512.0 * 1024.0;
+/// How far the encoder quality is lowered, step by step, to get under the size limit.
+const MIN_ENCODER_QUALITY: f64 = 0.4;
+const ENCODER_QUALITY_STEP: f64 = 0.15;
+
+/// Trade-off between detail and size for attached images.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ImageQuality {
+    High,
+    #[default]
+    Balanced,
+    /// For slow or metered connections.
+    Low,
+}
+
+impl ImageQuality {
+    pub const ALL: [ImageQuality; 3] = [
+        ImageQuality::High,
+        ImageQuality::Balanced,
+        ImageQuality::Low,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ImageQuality::High => "High",
+            ImageQuality::Balanced => "Balanced",
+            ImageQuality::Low => "Low",
+        }
+    }
+
+    /// Longest side, in pixels, an attached image is scaled down to.
+    pub fn max_dimension(&self) -> u32 {
+        match self {
+            ImageQuality::High => 2560,
+            ImageQuality::Balanced => 1600,
+            ImageQuality::Low => 1024,
+        }
+    }
+
+    /// JPEG encoder quality, from 0 to 1.
+    pub fn encoder_quality(&self) -> f64 {
+        match self {
+            ImageQuality::High => 0.92,
+            ImageQuality::Balanced => 0.8,
+            ImageQuality::Low => 0.6,
+        }
+    }
+}
+
+/// Scales `width`×`height` so the longest side is at most `max`, keeping the aspect ratio.
+/// Never scales up.
+pub fn fit(width: u32, height: u32, max: u32) -> (u32, u32) {
+    let longest = width.max(height);
+    if longest <= max {
+        return (width, height);
+    }
+    let scale = max as f64 / longest as f64;
+    let scaled = |side: u32| ((side as f64 * scale).round() as u32).max(1);
+    (scaled(width), scaled(height))
+}
+
+/// Whether an attached image may be animated. Only GIFs are sent without re-encoding, so
+/// they are the only ones that can be.
+pub fn is_animated(url: &str) -> bool {
+    url.starts_with("data:image/gif")
+}
+
+/// Whether an image travelled inside its message, so showing it fetches nothing more.
+pub fn is_inline(url: &str) -> bool {
+    url.starts_with("data:")
+}
+
+/// Checks a picked file's type and size against the deployment's limits before any work is
+/// done on it. The error is meant for the user.
+pub fn validate(mime: &str, size: u64, config: &Config) -> Result<(), String> {
+    let allowed = &config.allowed_upload_types;
+    if !allowed.iter().any(|pattern| mime_matches(pattern, mime)) {
+        let names: Vec<_> = allowed
+            .iter()
+            .map(|t| t.rsplit('/').next().unwrap_or(t).to_uppercase())
+            .collect();
+        return Err(format!(
+            "That file type can't be attached. Allowed: {}.",
+            names.join(", ")
+        ));
+    }
+    // GIFs are sent as they are, and base64 makes them a third larger on the wire.
+    let limit = if mime == "image/gif" {
+        config.max_upload_bytes / 4 * 3
+    } else {
+        config.max_file_bytes
+    };
+    if size > limit {
+        return Err(format!(
+            "That file is {}; the limit is {}.",
+            megabytes(size),
+            megabytes(limit)
+        ));
+    }
+    Ok(())
+}
+
+/// `image/png` matches itself; `image/*` matches any image.
+fn mime_matches(pattern: &str, mime: &str) -> bool {
+    match pattern.strip_suffix("/*") {
+        Some(family) => mime.split('/').next() == Some(family),
+        None => pattern.eq_ignore_ascii_case(mime),
+    }
+}
+
+fn megabytes(bytes: u64) -> String {
+    format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+}
+
+/// Turns a picked image file into an attachment no larger than `max_bytes` once encoded,
+/// downscaling and recompressing it as needed. The error is meant for the user.
+pub async fn prepare(
+    file: &File,
+    quality: ImageQuality,
+    max_bytes: u64,
+) -> Result<Attachment, String> {
+    let unreadable = || "That image couldn't be read.".to_string();
+    let image = load(file).await.map_err(|_| unreadable())?;
+    let (natural_width, natural_height) = (image.natural_width(), image.natural_height());
+    let (width, height) = fit(natural_width, natural_height, quality.max_dimension());
+
+    // GIFs would lose their animation on a canvas, and small images gain nothing from it.
+    if file.type_() == "image/gif"
+        || ((width, height) == (natural_width, natural_height) && file.size() <= PASS_THROUGH_BYTES)
+    {
+        let url = read_data_url(file).await.map_err(|_| unreadable())?;
+        if url.len() as u64 > max_bytes {
+            return Err(too_large(max_bytes));
+        }
+        return Ok(Attachment::Image {
+            url,
+            width,
+            height,
+            alt: None,
+            spoiler: false,
+        });
+    }
+
+    let canvas: HtmlCanvasElement = gloo::utils::document()
+        .create_element("canvas")
+        .map_err(|_| unreadable())?
+        .unchecked_into();
+    canvas.set_width(width);
+    canvas.set_height(height);
+    let context: CanvasRenderingContext2d = canvas
+        .get_context("2d")
+        .ok()
+        .flatten()
+        .ok_or_else(unreadable)?
+        .unchecked_into();
+    // JPEG has no transparency; without a backdrop it would come out black.
+    context.set_fill_style(&JsValue::from_str("white"));
+    context.fill_rect(0.0, 0.0, width as f64, height as f64);
+    context
+        .draw_image_with_html_image_element_and_dw_and_dh(
+            &image,
+            0.0,
+            0.0,
+            width as f64,
+            height as f64,
+        )
+        .map_err(|_| unreadable())?;
+
+    let mut encoder_quality = quality.encoder_quality();
+    while encoder_quality >= MIN_ENCODER_QUALITY {
+        let url = canvas
+            .to_data_url_with_type_and_encoder_options("image/jpeg", &encoder_quality.into())
+            .map_err(|_| unreadable())?;
+        if url.len() as u64 <= max_bytes {
+            return Ok(Attachment::Image {
+                url,
+                width,
+                height,
+                alt: None,
+                spoiler: false,
+            });
+        }
+        encoder_quality -= ENCODER_QUALITY_STEP;
+    }
+    Err(too_large(max_bytes))
+}
+
+fn too_large(max_bytes: u64) -> String {
+    format!(
+        "That image is too large to send, even compressed (limit {}).",
+        megabytes(max_bytes)
+    )
+}
+
+async fn load(file: &File) -> Result<HtmlImageElement, JsValue> {
+    let url = Url::create_object_url_with_blob(file)?;
+    let image = HtmlImageElement::new()?;
+    let loaded = Promise::new(&mut |resolve, reject| {
+        image.set_onload(Some(&resolve));
+        image.set_onerror(Some(&reject));
+    });
+    image.set_src(&url);
+    let result = JsFuture::from(loaded).await;
+    image.set_onload(None);
+    image.set_onerror(None);
+    let _ = Url::revoke_object_url(&url);
+    result.map(|_| image)
+}
+
+pub(crate) async fn read_data_url(file: &File) -> Result<String, JsValue> {
+    let reader = FileReader::new()?;
+    let read = Promise::new(&mut |resolve, reject| {
+        reader.set_onload(Some(&resolve));
+        reader.set_onerror(Some(&reject));
+    });
+    reader.read_as_data_url(file)?;
+    JsFuture::from(read).await?;
+    reader
+        .result()?
+        .as_string()
+        .ok_or_else(|| JsValue::from_str("not a data URL"))
+}
@@ -0,0 +1,38 @@
+use qrcode::render::svg;
+use qrcode::QrCode;
+use yewchat_protocol::InviteInfo;
+
+use crate::services::query;
+
+/// Shareable link to the join screen for `invite`.
+pub fn link(invite: &InviteInfo) -> String {
+    let origin = web_sys::window()
+        .and_then(|w| w.location().origin().ok())
+        .unwrap_or_default();
+    format!(
+        "{}/join?channel={}&token={}",
+        origin,
+        js_sys::encode_uri_component(&invite.channel),
+        js_sys::encode_uri_component(&invite.token)
+    )
+}
+
+/// The invite in the current URL, as produced by `link`.
+pub fn from_query() -> Option<InviteInfo> {
+    Some(InviteInfo {
+        channel: query::param("channel")?,
+        token: query::param("token")?,
+    })
+}
+
+/// `link` as a QR code, in a data URL for an `<img>`.
+pub fn qr_data_url(link: &str) -> Option<String> {
+    let code = QrCode::new(link.as_bytes())
+        .map_err(|e| log::error!("failed to encode invite QR code: {:?}", e))
+        .ok()?;
+    let svg = code.render::<svg::Color>().min_dimensions(200, 200).build();
+    Some(format!(
+        "data:image/svg+xml;charset=utf-8,{}",
+        js_sys::encode_uri_component(&svg)
+    ))
+}
@@ -0,0 +1,101 @@
+//! The user's current position, and the OpenStreetMap tiles and links that show a shared one.
+
+use std::f64::consts::PI;
+
+use js_sys::{Promise, Reflect};
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::PositionOptions;
+use yewchat_protocol::Attachment;
+
+/// Zoom level of map thumbnails, about street level.
+pub const ZOOM: u32 = 15;
+/// Side of a map tile in pixels.
+pub const TILE_SIZE: f64 = 256.0;
+const TILE_SERVER: &str = "https://tile.openstreetmap.org";
+const POSITION_TIMEOUT_MS: u32 = 15_000;
+/// Web Mercator stops here; the poles would be infinitely far away.
+const MAX_LATITUDE: f64 = 85.051_128;
+
+/// Where a point falls on the tile grid at `zoom`, counted in tiles from the top left. The
+/// whole part names the tile and the fraction is the offset within it.
+pub fn tile_position(latitude: f64, longitude: f64, zoom: u32) -> (f64, f64) {
+    let tiles = f64::from(1u32 << zoom);
+    let latitude = latitude.clamp(-MAX_LATITUDE, MAX_LATITUDE).to_radians();
+    let x = (longitude + 180.0) / 360.0 * tiles;
+    let y = (1.0 - latitude.tan().asinh() / PI) / 2.0 * tiles;
+    (x.rem_euclid(tiles), y)
+}
+
+/// Image of one tile. Columns wrap around the date line; rows past the poles have no tile.
+pub fn tile_url(x: i64, y: i64, zoom: u32) -> Option<String> {
+    let tiles = 1i64 << zoom;
+    (0..tiles)
+        .contains(&y)
+        .then(|| format!("{}/{}/{}/{}.png", TILE_SERVER, zoom, x.rem_euclid(tiles), y))
+}
+
+/// OpenStreetMap page centred on the point, with a marker on it.
+pub fn map_link(latitude: f64, longitude: f64) -> String {
+    format!(
+        "https://www.openstreetmap.org/?mlat={lat:.6}&mlon={lon:.6}#map={zoom}/{lat:.6}/{lon:.6}",
+        lat = latitude,
+        lon = longitude,
+        zoom = ZOOM,
+    )
+}
+
+/// Coordinates as shown under the map, to about a metre.
+pub fn describe(latitude: f64, longitude: f64) -> String {
+    format!("{:.5}, {:.5}", latitude, longitude)
+}
+
+/// Asks the browser where the user is. The browser asks for permission the first time.
+pub async fn current() -> Result<Attachment, String> {
+    let unavailable = || "Your location isn't available right now.".to_string();
+    let geolocation = gloo::utils::window()
+        .navigator()
+        .geolocation()
+        .map_err(|_| unavailable())?;
+    let mut options = PositionOptions::new();
+    options
+        .enable_high_accuracy(true)
+        .timeout(POSITION_TIMEOUT_MS);
+    let promise = Promise::new(&mut |resolve, reject| {
+        if let Err(e) = geolocation.get_current_position_with_error_callback_and_options(
+            &resolve,
+            Some(&reject),
+            &options,
+        ) {
+            let _ = reject.call1(&JsValue::NULL, &e);
+        }
+    });
+    let position = JsFuture::from(promise).await.map_err(|e| refused(&e))?;
+    let coords = Reflect::get(&position, &"coords".into()).map_err(|_| unavailable())?;
+    let number = |key: &str| {
+        Reflect::get(&coords, &key.into())
+            .ok()
+            .and_then(|v| v.as_f64())
+    };
+    match (number("latitude"), number("longitude")) {
+        (Some(latitude), Some(longitude)) => Ok(Attachment::Location {
+            latitude,
+            longitude,
+            accuracy: number("accuracy"),
+        }),
+        _ => Err(unavailable()),
+    }
+}
+
+/// Explains a `GeolocationPositionError`.
+fn refused(error: &JsValue) -> String {
+    let code = Reflect::get(error, &"code".into())
+        .ok()
+        .and_then(|c| c.as_f64());
+    match code.map(|c| c as u32) {
+        Some(1) => "Location access is blocked. Allow it in your browser's site settings to share where you are.",
+        Some(3) => "Finding your location took too long. Try again.",
+        _ => "Your location isn't available right now.",
+    }
+    .into()
+}
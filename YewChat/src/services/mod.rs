@@ -1,2 +1,23 @@
+pub mod avatar;
+pub mod background_sync;
+pub mod clipboard;
+pub mod connection_worker;
+pub mod data_usage;
+pub mod device;
+pub mod emoji;
+pub mod error_report;
+pub mod history;
+pub mod image;
+pub mod invite;
+pub mod location;
+pub mod notifications;
+pub mod push;
+pub mod query;
+pub mod service_worker;
+pub mod shared_worker;
+pub mod sound;
+pub mod tab_sync;
+pub mod telemetry;
 pub mod websocket;
-pub mod event_bus;
\ No newline at end of file
+pub mod event_bus;
+pub mod socket;
\ No newline at end of file
@@ -0,0 +1,20 @@
+//! Single place that decides whether an incoming message may raise a notification or play a
+//! sound, so every output honours the same user settings.
+
+use crate::mentions::Mention;
+use crate::state::Preferences;
+use crate::time;
+
+/// Whether notifications are allowed at all right now: not in Do Not Disturb, and outside
+/// quiet hours by the local clock.
+pub fn allowed(preferences: &Preferences) -> bool {
+    !preferences.dnd && !preferences.quiet_hours.contains(time::local_minute_of_day())
+}
+
+/// Whether `mention` pings the user, or is one of the kinds they suppressed. A suppressed
+/// mention still arrives, but isn't counted or announced as one and is only faintly marked.
+pub fn pings(preferences: &Preferences, mention: Mention) -> bool {
+    !preferences
+        .suppress_mentions
+        .suppresses(mention == Mention::Broadcast)
+}
@@ -0,0 +1,98 @@
+//! Web Push subscriptions, so mentions still reach the user while no tab is open. The
+//! service worker shows what arrives; see `static/sw.js`.
+
+use js_sys::Uint8Array;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Notification, PushManager, PushSubscription, PushSubscriptionOptionsInit};
+
+use crate::config;
+use crate::services::service_worker;
+
+/// Whether pushes can be offered: the deployment has a VAPID key and the browser a push
+/// manager.
+pub fn available() -> bool {
+    config::get().vapid_public_key.is_some()
+        && js_sys::Reflect::has(&gloo::utils::window(), &"PushManager".into()).unwrap_or(false)
+}
+
+/// Decodes unpadded base64url, the encoding VAPID keys are published in.
+pub fn decode_base64url(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(text.len() * 3 / 4);
+    let (mut buffer, mut bits) = (0u32, 0);
+    for c in text.trim_end_matches('=').chars() {
+        let value = match c {
+            'A'..='Z' => c as u32 - 'A' as u32,
+            'a'..='z' => c as u32 - 'a' as u32 + 26,
+            '0'..='9' => c as u32 - '0' as u32 + 52,
+            '-' | '+' => 62,
+            '_' | '/' => 63,
+            _ => return None,
+        };
+        buffer = (buffer << 6) | value;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+/// Asks for permission if need be and subscribes this browser, returning the subscription
+/// as the JSON the server expects.
+pub async fn subscribe() -> Result<String, String> {
+    let key = config::get()
+        .vapid_public_key
+        .as_deref()
+        .and_then(decode_base64url)
+        .ok_or("this server doesn't send push notifications")?;
+    let permission = JsFuture::from(Notification::request_permission().map_err(describe)?)
+        .await
+        .map_err(describe)?;
+    if permission.as_string().as_deref() != Some("granted") {
+        return Err("notifications are blocked for this site".into());
+    }
+    let manager = push_manager().await?;
+    let mut options = PushSubscriptionOptionsInit::new();
+    options
+        .user_visible_only(true)
+        .application_server_key(Some(&Uint8Array::from(key.as_slice())));
+    let subscription = JsFuture::from(manager.subscribe_with_options(&options).map_err(describe)?)
+        .await
+        .map_err(describe)?;
+    // `PushSubscription.toJSON()` gives the endpoint and keys in the shape the server reads.
+    js_sys::JSON::stringify(&subscription)
+        .map(String::from)
+        .map_err(describe)
+}
+
+/// Cancels this browser's subscription, returning its endpoint for the server to forget.
+/// `None` if there was none.
+pub async fn unsubscribe() -> Option<String> {
+    let manager = push_manager().await.ok()?;
+    let subscription = JsFuture::from(manager.get_subscription().ok()?)
+        .await
+        .ok()?;
+    let subscription = subscription.dyn_into::<PushSubscription>().ok()?;
+    let endpoint = subscription.endpoint();
+    if let Ok(promise) = subscription.unsubscribe() {
+        let _ = JsFuture::from(promise).await;
+    }
+    Some(endpoint)
+}
+
+async fn push_manager() -> Result<PushManager, String> {
+    service_worker::registration()
+        .await
+        .ok_or("service workers are unavailable")?
+        .push_manager()
+        .map_err(describe)
+}
+
+fn describe(error: wasm_bindgen::JsValue) -> String {
+    js_sys::Error::from(error)
+        .message()
+        .as_string()
+        .unwrap_or_else(|| "push is unavailable".into())
+}
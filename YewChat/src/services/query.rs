@@ -0,0 +1,24 @@
+/// Decoded value of `name` in the current URL's query string.
+pub fn param(name: &str) -> Option<String> {
+    let search = web_sys::window()?.location().search().ok()?;
+    let prefix = format!("{}=", name);
+    search
+        .trim_start_matches('?')
+        .split('&')
+        .find_map(|pair| pair.strip_prefix(prefix.as_str()))
+        .and_then(|v| js_sys::decode_uri_component(v).ok())
+        .map(String::from)
+}
+
+/// Link that opens `channel` scrolled to message `id`.
+pub fn message_link(channel: &str, id: u64) -> String {
+    let origin = web_sys::window()
+        .and_then(|w| w.location().origin().ok())
+        .unwrap_or_default();
+    format!(
+        "{}/channel/{}?msg={}",
+        origin,
+        js_sys::encode_uri_component(channel),
+        id
+    )
+}
@@ -0,0 +1,33 @@
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::ServiceWorkerRegistration;
+
+const SCRIPT_URL: &str = "sw.js";
+
+/// Registers the offline/installability service worker, if the browser supports one.
+pub fn register() {
+    spawn_local(async {
+        match registration().await {
+            Some(_) => log::debug!("service worker registered"),
+            None => log::debug!("service worker unavailable"),
+        }
+    });
+}
+
+/// Registers (or re-uses) the service worker and returns its registration.
+pub async fn registration() -> Option<ServiceWorkerRegistration> {
+    let window = web_sys::window()?;
+    let navigator = window.navigator();
+    // `serviceWorker` is missing on insecure origins, so check before touching it.
+    if !js_sys::Reflect::has(&navigator, &"serviceWorker".into()).unwrap_or(false) {
+        return None;
+    }
+    let promise = navigator.service_worker().register(SCRIPT_URL);
+    match JsFuture::from(promise).await {
+        Ok(registration) => registration.dyn_into().ok(),
+        Err(e) => {
+            log::error!("service worker registration failed: {:?}", e);
+            None
+        }
+    }
+}
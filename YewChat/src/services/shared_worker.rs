@@ -0,0 +1,229 @@
+//! One connection for every open tab: a shared worker owns the WebSocket and fans what it
+//! hears out to each tab's port, so the user is online once however many tabs they open.
+//! Each user gets a worker of their own, as the socket is registered as one nick.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use gloo::events::EventListener;
+use gloo::timers::callback::Timeout;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{MessageEvent, MessagePort, SharedWorker, WorkerOptions};
+use yew::Callback;
+use yew_agent::Dispatched;
+use yewchat_protocol::WebSocketMessage;
+
+use crate::config::{self, Config};
+use crate::services::connection_worker::Session;
+use crate::services::event_bus::{ChatEvent, ConnectionState, EventBus, Request};
+use crate::services::socket::{ChatSocket, SocketError};
+use crate::services::websocket::WebsocketService;
+
+const SCRIPT: &str = "shared_worker.js";
+
+/// What a tab asks of the shared worker.
+#[derive(Serialize, Deserialize, Debug)]
+enum TabInput {
+    /// The tab's configuration, sent before anything else. The first one connects.
+    Connect(Config),
+    Send(WebSocketMessage),
+    /// The tab is going away; stop posting to its port.
+    Leave,
+}
+
+fn post<T: Serialize>(port: &MessagePort, value: &T) {
+    if let Ok(json) = serde_json::to_string(value) {
+        let _ = port.post_message(&JsValue::from_str(&json));
+    }
+}
+
+fn read<T: DeserializeOwned>(event: &web_sys::Event) -> Option<T> {
+    let data = event.unchecked_ref::<MessageEvent>().data().as_string()?;
+    serde_json::from_str(&data).ok()
+}
+
+/// Tab side of the shared connection; relays the worker's events onto the EventBus.
+pub struct SharedSocket {
+    port: MessagePort,
+    _messages: EventListener,
+    _pagehide: EventListener,
+}
+
+impl SharedSocket {
+    /// Joins the shared worker of the tabs signed in as `username`, starting it if this is
+    /// the first. Spectating under a name doesn't share with being signed in as it. `None`
+    /// where the browser has no shared workers.
+    pub fn new(username: &str, spectator: bool) -> Option<Self> {
+        let role = if spectator { "spectator" } else { "member" };
+        let mut options = WorkerOptions::new();
+        options.name(&format!("{}:{}", role, username));
+        let worker = SharedWorker::new_with_worker_options(SCRIPT, &options).ok()?;
+        let port = worker.port();
+        let mut event_bus = EventBus::dispatcher();
+        let messages = EventListener::new(&port, "message", move |e| {
+            if let Some(event) = read::<ChatEvent>(e) {
+                event_bus.send(Request::EventBusMsg(event));
+            }
+        });
+        let pagehide = {
+            let port = port.clone();
+            EventListener::new(&gloo::utils::window(), "pagehide", move |_| {
+                post(&port, &TabInput::Leave)
+            })
+        };
+        // Listening through `addEventListener` doesn't start the port by itself.
+        port.start();
+        post(&port, &TabInput::Connect((*config::get()).clone()));
+        Some(Self {
+            port,
+            _messages: messages,
+            _pagehide: pagehide,
+        })
+    }
+}
+
+impl Drop for SharedSocket {
+    fn drop(&mut self) {
+        post(&self.port, &TabInput::Leave);
+        self.port.close();
+    }
+}
+
+impl ChatSocket for SharedSocket {
+    /// Always `Ok`: the worker sends later, and reports a failure as `ChatEvent::SendFailed`.
+    fn send(&self, message: &WebSocketMessage) -> Result<(), SocketError> {
+        post(&self.port, &TabInput::Send(message.clone()));
+        Ok(())
+    }
+}
+
+/// The worker's side: the connection and the ports of the tabs using it.
+struct Hub {
+    /// `None` until a tab has sent its configuration.
+    service: Option<WebsocketService>,
+    session: Session,
+    /// Told to each tab as it joins, since it missed the change itself. `None` until the
+    /// first change, so a tab joining the first connection isn't told it's a retry.
+    connection: Option<ConnectionState>,
+    ports: Vec<MessagePort>,
+    /// Kept for the worker's lifetime: a port's listener can't be dropped from inside its
+    /// own callback when the tab leaves.
+    listeners: Vec<EventListener>,
+    reconnect: Option<Timeout>,
+}
+
+impl Hub {
+    fn broadcast(&self, event: &ChatEvent) {
+        for port in self.ports.iter() {
+            post(port, event);
+        }
+    }
+}
+
+fn on_event(hub: &Rc<RefCell<Hub>>) -> Callback<ChatEvent> {
+    let hub = hub.clone();
+    Callback::from(move |event: ChatEvent| {
+        let mut guard = hub.borrow_mut();
+        let state = &mut *guard;
+        let Some(service) = &state.service else {
+            return;
+        };
+        if let ChatEvent::ConnectionChanged(connection) = event {
+            state.connection = Some(connection);
+        }
+        if let Some(delay) = state.session.observe(&event, service) {
+            let hub = hub.clone();
+            state.reconnect = Some(Timeout::new(delay, move || reconnect(&hub)));
+        }
+        state.broadcast(&event);
+    })
+}
+
+/// Connects with the configuration the first tab sent; later tabs' are ignored.
+fn connect(hub: &Rc<RefCell<Hub>>, config: Config) {
+    if hub.borrow().service.is_some() {
+        return;
+    }
+    config::set(config);
+    let service = WebsocketService::connect(on_event(hub));
+    hub.borrow_mut().service = Some(service);
+}
+
+fn reconnect(hub: &Rc<RefCell<Hub>>) {
+    let service = WebsocketService::connect(on_event(hub));
+    let mut state = hub.borrow_mut();
+    log::debug!(
+        "reconnecting (attempt {})",
+        state.session.reconnect_attempts()
+    );
+    state.connection = Some(ConnectionState::Connecting);
+    state.broadcast(&ChatEvent::ConnectionChanged(ConnectionState::Connecting));
+    state.service = Some(service);
+}
+
+/// Starts serving the tabs inside the shared worker: `early` are the ports of tabs that
+/// joined while the module was loading. Nothing connects until a tab sends its configuration.
+pub fn serve(early: js_sys::Array) {
+    let hub = Rc::new(RefCell::new(Hub {
+        service: None,
+        session: Session::default(),
+        connection: None,
+        ports: Vec::new(),
+        listeners: Vec::new(),
+        reconnect: None,
+    }));
+    for port in early.iter().filter_map(|port| port.dyn_into().ok()) {
+        join(&hub, port);
+    }
+
+    let global: web_sys::EventTarget = js_sys::global().unchecked_into();
+    let joins = EventListener::new(&global, "connect", move |e| {
+        if let Ok(port) = e
+            .unchecked_ref::<MessageEvent>()
+            .ports()
+            .get(0)
+            .dyn_into::<MessagePort>()
+        {
+            join(&hub, port);
+        }
+    });
+    // The worker lives as long as any tab does, and so does what it listens to.
+    joins.forget();
+}
+
+/// Starts relaying between the connection and a tab's port.
+fn join(hub: &Rc<RefCell<Hub>>, port: MessagePort) {
+    let listener = {
+        let hub = hub.clone();
+        let from = port.clone();
+        EventListener::new(&port, "message", move |e| match read::<TabInput>(e) {
+            Some(TabInput::Connect(config)) => connect(&hub, config),
+            Some(TabInput::Send(message)) => {
+                let mut guard = hub.borrow_mut();
+                let state = &mut *guard;
+                let sent = match &state.service {
+                    Some(service) => state.session.send(message, service),
+                    None => Err(ChatEvent::SendFailed {
+                        error: SocketError::Disconnected,
+                        outgoing_id: message.outgoing_id,
+                    }),
+                };
+                if let Err(event) = sent {
+                    post(&from, &event);
+                }
+            }
+            Some(TabInput::Leave) => hub.borrow_mut().ports.retain(|p| *p != from),
+            None => {}
+        })
+    };
+    // Whatever the tab posted before now was queued on the port until this.
+    port.start();
+    let mut state = hub.borrow_mut();
+    if let Some(connection) = state.connection {
+        post(&port, &ChatEvent::ConnectionChanged(connection));
+    }
+    state.ports.push(port);
+    state.listeners.push(listener);
+}
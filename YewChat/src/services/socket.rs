@@ -0,0 +1,84 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+use yew_agent::Dispatched;
+use yewchat_protocol::WebSocketMessage;
+
+use crate::services::event_bus::{ChatEvent, EventBus, Request};
+use crate::services::websocket::WebsocketService;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum SocketError {
+    Full,
+    Disconnected,
+}
+
+impl fmt::Display for SocketError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            SocketError::Full => "Too many messages are waiting to go out.",
+            SocketError::Disconnected => "You're offline.",
+        })
+    }
+}
+
+/// Outgoing side of the chat connection. Incoming frames always arrive via the EventBus.
+pub trait ChatSocket {
+    fn send(&self, message: &WebSocketMessage) -> Result<(), SocketError>;
+}
+
+impl ChatSocket for WebsocketService {
+    fn send(&self, message: &WebSocketMessage) -> Result<(), SocketError> {
+        self.tx.clone().try_send(message.to_json()).map_err(|e| {
+            if e.is_full() {
+                SocketError::Full
+            } else {
+                SocketError::Disconnected
+            }
+        })
+    }
+}
+
+/// In-memory socket that records what was sent and can fake incoming frames.
+#[derive(Default)]
+pub struct MockSocket {
+    pub sent: RefCell<Vec<WebSocketMessage>>,
+}
+
+impl MockSocket {
+    /// Delivers `message` to subscribers as if it came from the server.
+    pub fn receive(&self, message: &WebSocketMessage) {
+        if let Some(event) = ChatEvent::from_frame(&message.to_json()) {
+            EventBus::dispatcher().send(Request::EventBusMsg(event));
+        }
+    }
+}
+
+impl ChatSocket for MockSocket {
+    fn send(&self, message: &WebSocketMessage) -> Result<(), SocketError> {
+        self.sent.borrow_mut().push(message.clone());
+        Ok(())
+    }
+}
+
+/// Shared socket handle provided to components through context.
+#[derive(Clone)]
+pub struct SocketHandle(pub Rc<dyn ChatSocket>);
+
+impl SocketHandle {
+    pub fn new(socket: impl ChatSocket + 'static) -> Self {
+        Self(Rc::new(socket))
+    }
+
+    pub fn send(&self, message: &WebSocketMessage) -> Result<(), SocketError> {
+        self.0.send(message)
+    }
+}
+
+impl PartialEq for SocketHandle {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
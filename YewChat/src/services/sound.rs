@@ -0,0 +1,163 @@
+//! Notification sounds. The bundled ones are short tones synthesised with Web Audio, so there
+//! are no audio files to ship; a custom clip is kept in localStorage as a `data:` URL.
+
+use std::cell::RefCell;
+
+use gloo::storage::{LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsValue;
+use web_sys::{AudioContext, File, HtmlAudioElement, OscillatorType};
+
+use crate::services::image;
+
+const CUSTOM_CLIP_KEY: &str = "yewchat.sound";
+/// Largest clip accepted, before encoding. localStorage only holds a few megabytes in all.
+pub const MAX_CLIP_BYTES: u64 = 512 * 1024;
+/// Peak gain of the bundled tones.
+const VOLUME: f32 = 0.2;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum NotificationSound {
+    Off,
+    #[default]
+    Chime,
+    Pop,
+    Bell,
+    /// The clip the user uploaded.
+    Custom,
+}
+
+/// One tone of a bundled sound. Times are in seconds from the start of the sound.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Note {
+    pub frequency: f32,
+    pub start: f64,
+    pub duration: f64,
+}
+
+const fn note(frequency: f32, start: f64, duration: f64) -> Note {
+    Note {
+        frequency,
+        start,
+        duration,
+    }
+}
+
+const CHIME: &[Note] = &[note(880.0, 0.0, 0.15), note(1318.5, 0.12, 0.3)];
+const POP: &[Note] = &[note(620.0, 0.0, 0.08)];
+const BELL: &[Note] = &[note(1046.5, 0.0, 0.8), note(2093.0, 0.0, 0.5)];
+
+impl NotificationSound {
+    pub const ALL: [NotificationSound; 5] = [
+        NotificationSound::Off,
+        NotificationSound::Chime,
+        NotificationSound::Pop,
+        NotificationSound::Bell,
+        NotificationSound::Custom,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            NotificationSound::Off => "None",
+            NotificationSound::Chime => "Chime",
+            NotificationSound::Pop => "Pop",
+            NotificationSound::Bell => "Bell",
+            NotificationSound::Custom => "Custom clip",
+        }
+    }
+
+    /// Tones that make up a bundled sound; empty for `Off` and `Custom`.
+    pub fn notes(&self) -> &'static [Note] {
+        match self {
+            NotificationSound::Chime => CHIME,
+            NotificationSound::Pop => POP,
+            NotificationSound::Bell => BELL,
+            NotificationSound::Off | NotificationSound::Custom => &[],
+        }
+    }
+}
+
+/// Checks a clip the user picked before it is read. The error is meant for the user.
+pub fn validate_clip(mime: &str, size: u64) -> Result<(), String> {
+    if !mime.starts_with("audio/") {
+        return Err("That file isn't an audio clip.".into());
+    }
+    if size > MAX_CLIP_BYTES {
+        return Err(format!(
+            "That clip is {} KB; keep it under {} KB.",
+            size.div_ceil(1024),
+            MAX_CLIP_BYTES / 1024
+        ));
+    }
+    Ok(())
+}
+
+/// Saves `file` as the custom clip, replacing any earlier one.
+pub async fn set_custom_clip(file: &File) -> Result<(), String> {
+    validate_clip(&file.type_(), file.size() as u64)?;
+    let url = image::read_data_url(file)
+        .await
+        .map_err(|_| "That clip couldn't be read.".to_string())?;
+    LocalStorage::raw()
+        .set_item(CUSTOM_CLIP_KEY, &url)
+        .map_err(|_| "There's no room left to save that clip.".to_string())
+}
+
+fn custom_clip() -> Option<String> {
+    LocalStorage::raw().get_item(CUSTOM_CLIP_KEY).ok().flatten()
+}
+
+thread_local! {
+    // Browsers cap how many audio contexts a page may open, so one is shared.
+    static CONTEXT: RefCell<Option<AudioContext>> = const { RefCell::new(None) };
+}
+
+/// Plays `sound`. Browsers stay silent until the user has interacted with the page.
+pub fn play(sound: NotificationSound) {
+    let result = match sound {
+        NotificationSound::Off => Ok(()),
+        NotificationSound::Custom => match custom_clip() {
+            Some(url) => HtmlAudioElement::new_with_src(&url).and_then(|audio| {
+                let _ = audio.play()?;
+                Ok(())
+            }),
+            None => Ok(()),
+        },
+        _ => play_notes(sound.notes()),
+    };
+    if let Err(e) = result {
+        log::debug!("couldn't play notification sound: {:?}", e);
+    }
+}
+
+fn play_notes(notes: &[Note]) -> Result<(), JsValue> {
+    let context = CONTEXT.with(|context| -> Result<AudioContext, JsValue> {
+        let mut context = context.borrow_mut();
+        if let Some(context) = &*context {
+            return Ok(context.clone());
+        }
+        let created = AudioContext::new()?;
+        *context = Some(created.clone());
+        Ok(created)
+    })?;
+    // A context made before the user interacted with the page starts suspended.
+    let _ = context.resume();
+    let now = context.current_time();
+    for note in notes {
+        let (start, end) = (now + note.start, now + note.start + note.duration);
+        let oscillator = context.create_oscillator()?;
+        oscillator.set_type(OscillatorType::Sine);
+        oscillator.frequency().set_value(note.frequency);
+        // A quick fade in and a longer fade out, so the tone doesn't click.
+        let gain = context.create_gain()?;
+        let level = gain.gain();
+        level.set_value_at_time(0.0, start)?;
+        level.linear_ramp_to_value_at_time(VOLUME, start + 0.01)?;
+        level.exponential_ramp_to_value_at_time(0.001, end)?;
+        oscillator.connect_with_audio_node(&gain)?;
+        gain.connect_with_audio_node(&context.destination())?;
+        oscillator.start_with_when(start)?;
+        oscillator.stop_with_when(end)?;
+    }
+    Ok(())
+}
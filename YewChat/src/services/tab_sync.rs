@@ -0,0 +1,65 @@
+//! Keeps other open tabs of the client in step: what has been read, the draft being typed
+//! and preferences such as theme and mutes. Each tab still has its own connection.
+
+use gloo::events::EventListener;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{BroadcastChannel, MessageEvent};
+
+use crate::state::Preferences;
+
+const CHANNEL_NAME: &str = "yewchat.tabs";
+
+/// What one tab tells the others.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum TabMessage {
+    /// The channel was read up to its latest message.
+    Read(String),
+    Draft {
+        channel: String,
+        text: String,
+    },
+    Preferences(Preferences),
+}
+
+/// This tab's end of the channel the tabs share. Listening stops when dropped.
+pub struct TabSync {
+    channel: BroadcastChannel,
+    _listener: EventListener,
+}
+
+impl TabSync {
+    /// Joins the other tabs, calling `on_message` with whatever they post. `None` where the
+    /// browser has no `BroadcastChannel`, in which case tabs simply don't sync.
+    pub fn open(on_message: impl Fn(TabMessage) + 'static) -> Option<Self> {
+        let channel = BroadcastChannel::new(CHANNEL_NAME).ok()?;
+        let listener = EventListener::new(&channel, "message", move |e| {
+            let data = e.unchecked_ref::<MessageEvent>().data();
+            let Some(text) = data.as_string() else {
+                return;
+            };
+            match serde_json::from_str(&text) {
+                Ok(message) => on_message(message),
+                Err(e) => log::warn!("ignoring unreadable message from another tab: {}", e),
+            }
+        });
+        Some(Self {
+            channel,
+            _listener: listener,
+        })
+    }
+
+    /// Tells every other tab; this one doesn't hear its own messages.
+    pub fn post(&self, message: &TabMessage) {
+        let Ok(text) = serde_json::to_string(message) else {
+            return;
+        };
+        if self
+            .channel
+            .post_message(&JsValue::from_str(&text))
+            .is_err()
+        {
+            log::warn!("failed to reach other tabs");
+        }
+    }
+}
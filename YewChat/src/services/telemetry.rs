@@ -0,0 +1,138 @@
+//! Anonymous usage counters. Nothing is recorded until the user opts in from settings, and
+//! nothing leaves the browser unless `telemetryEndpoint` is configured.
+
+use std::cell::RefCell;
+
+use gloo::timers::callback::Interval;
+use reqwasm::http::Request;
+use serde::Serialize;
+use wasm_bindgen_futures::spawn_local;
+
+use crate::config;
+
+const FLUSH_INTERVAL_MS: u32 = 60_000;
+
+#[derive(Clone, Copy, Debug)]
+pub enum Counter {
+    MessagesSent,
+    MessagesReceived,
+    Reconnects,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Metrics {
+    pub messages_sent: u64,
+    pub messages_received: u64,
+    pub reconnects: u64,
+    pub renders: u64,
+    pub render_ms_total: f64,
+    pub render_ms_max: f64,
+}
+
+impl Metrics {
+    pub fn render_ms_avg(&self) -> f64 {
+        if self.renders == 0 {
+            0.0
+        } else {
+            self.render_ms_total / self.renders as f64
+        }
+    }
+}
+
+#[derive(Default)]
+struct Telemetry {
+    enabled: bool,
+    metrics: Metrics,
+    _flush: Option<Interval>,
+}
+
+thread_local! {
+    static TELEMETRY: RefCell<Telemetry> = RefCell::new(Telemetry::default());
+}
+
+/// Follows the user's opt-in preference; disabling also discards what was collected.
+pub fn set_enabled(enabled: bool) {
+    TELEMETRY.with(|t| {
+        let mut t = t.borrow_mut();
+        if t.enabled == enabled {
+            return;
+        }
+        t.enabled = enabled;
+        t.metrics = Metrics::default();
+        t._flush = match (enabled, endpoint()) {
+            (true, Some(_)) => Some(Interval::new(FLUSH_INTERVAL_MS, flush)),
+            _ => None,
+        };
+    });
+}
+
+pub fn record(counter: Counter) {
+    TELEMETRY.with(|t| {
+        let mut t = t.borrow_mut();
+        if !t.enabled {
+            return;
+        }
+        let m = &mut t.metrics;
+        match counter {
+            Counter::MessagesSent => m.messages_sent += 1,
+            Counter::MessagesReceived => m.messages_received += 1,
+            Counter::Reconnects => m.reconnects += 1,
+        }
+    });
+}
+
+pub fn record_render(ms: f64) {
+    TELEMETRY.with(|t| {
+        let mut t = t.borrow_mut();
+        if !t.enabled {
+            return;
+        }
+        let m = &mut t.metrics;
+        m.renders += 1;
+        m.render_ms_total += ms;
+        m.render_ms_max = m.render_ms_max.max(ms);
+    });
+}
+
+/// `performance.now()`, for timing renders.
+pub fn now() -> f64 {
+    web_sys::window()
+        .and_then(|w| w.performance())
+        .map(|p| p.now())
+        .unwrap_or_default()
+}
+
+pub fn snapshot() -> Metrics {
+    TELEMETRY.with(|t| t.borrow().metrics.clone())
+}
+
+pub fn endpoint() -> Option<String> {
+    config::get().telemetry_endpoint.clone()
+}
+
+/// Sends the counters collected since the last flush to the configured endpoint.
+fn flush() {
+    let endpoint = match endpoint() {
+        Some(endpoint) => endpoint,
+        None => return,
+    };
+    let metrics = TELEMETRY.with(|t| {
+        let mut t = t.borrow_mut();
+        std::mem::take(&mut t.metrics)
+    });
+    if metrics == Metrics::default() {
+        return;
+    }
+    let body = serde_json::to_string(&metrics).unwrap();
+    spawn_local(async move {
+        let result = Request::post(&endpoint)
+            .header("Content-Type", "application/json")
+            .body(body)
+            .send()
+            .await;
+        if let Err(e) = result {
+            log::debug!("telemetry upload failed: {}", e);
+        }
+    });
+}
@@ -1,7 +1,11 @@
-use futures::{channel::mpsc::Sender, SinkExt, StreamExt};
+use futures::channel::{mpsc::Sender, oneshot};
+use futures::future::{self, Either};
+use futures::{SinkExt, StreamExt};
 use reqwasm::websocket::{futures::WebSocket, Message};
+use yew::Callback;
 use yew_agent::Dispatched;
-use crate::services::event_bus::{EventBus, Request};
+use crate::config;
+use crate::services::event_bus::{ChatEvent, ConnectionState, EventBus, Request};
 
 use wasm_bindgen_futures::spawn_local;
 
@@ -10,43 +14,81 @@ pub struct WebsocketService {
 }
 
 impl WebsocketService {
+    /// Connects and publishes decoded events on the EventBus.
     pub fn new() -> Self {
-        let ws = WebSocket::open("ws://127.0.0.1:8080").unwrap();
-
-        let (mut write, mut read) = ws.split();
+        let mut event_bus = EventBus::dispatcher();
+        Self::connect(Callback::from(move |event| {
+            event_bus.send(Request::EventBusMsg(event))
+        }))
+    }
 
+    /// Connects and hands every decoded event to `on_event`.
+    pub fn connect(on_event: Callback<ChatEvent>) -> Self {
         let (in_tx, mut in_rx) = futures::channel::mpsc::channel::<String>(1000);
-        let mut event_bus = EventBus::dispatcher();
 
+        let ws = match WebSocket::open(&config::get().server_url) {
+            Ok(ws) => ws,
+            Err(e) => {
+                log::error!("ws: {:?}", e);
+                spawn_local(async move {
+                    on_event.emit(ChatEvent::ConnectionChanged(ConnectionState::Disconnected));
+                });
+                return Self { tx: in_tx };
+            }
+        };
+
+        let (mut write, mut read) = ws.split();
+        // Dropped when reading stops, which is all a socket that never opens ever does.
+        let (closed_tx, closed_rx) = oneshot::channel::<()>();
+
+        let on_sent = on_event.clone();
         spawn_local(async move {
+            // The sink only becomes ready once the socket has opened.
+            let opened = future::poll_fn(|cx| write.poll_ready_unpin(cx));
+            match future::select(opened, closed_rx).await {
+                Either::Left((Ok(()), _)) => {}
+                _ => return,
+            }
+            on_sent.emit(ChatEvent::ConnectionChanged(ConnectionState::Connected));
             while let Some(s) = in_rx.next().await {
                 log::debug!("got event from channel! {}", s);
-                write.send(Message::Text(s)).await.unwrap();
+                let sent = s.len() as u64;
+                if let Err(e) = write.send(Message::Text(s)).await {
+                    log::error!("ws: {:?}", e);
+                    break;
+                }
+                on_sent.emit(ChatEvent::Traffic { sent, received: 0 });
             }
         });
 
         spawn_local(async move {
+            let _closed = closed_tx;
             while let Some(msg) = read.next().await {
-                match msg {
-                    Ok(Message::Text(data)) => {
-                        log::debug!("from websocket: {}", data);
-                        event_bus.send(Request::EventBusMsg(data));
-                    }
-                    Ok(Message::Bytes(b)) => {
-                        let decoded = std::str::from_utf8(&b);
-                        if let Ok(val) = decoded {
-                            log::debug!("from websocket: {}", val);
-                            event_bus.send(Request::EventBusMsg(val.into()));
-                        }
-                    }
+                let frame = match msg {
+                    Ok(Message::Text(data)) => data,
+                    Ok(Message::Bytes(b)) => match std::str::from_utf8(&b) {
+                        Ok(val) => val.into(),
+                        Err(_) => continue,
+                    },
                     Err(e) => {
                         log::error!("ws: {:?}", e);
+                        on_event.emit(ChatEvent::Error(format!("{:?}", e)));
+                        continue;
                     }
+                };
+                log::debug!("from websocket: {}", frame);
+                on_event.emit(ChatEvent::Traffic {
+                    sent: 0,
+                    received: frame.len() as u64,
+                });
+                if let Some(event) = ChatEvent::from_frame(&frame) {
+                    on_event.emit(event);
                 }
             }
             log::debug!("WebSocket Closed");
+            on_event.emit(ChatEvent::ConnectionChanged(ConnectionState::Disconnected));
         });
 
         Self { tx: in_tx }
     }
-}
\ No newline at end of file
+}
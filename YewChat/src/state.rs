@@ -0,0 +1,1280 @@
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+use yew::Reducible;
+use yewchat_protocol::{
+    Attachment, AuditEntry, ChannelInfo, InviteInfo, MentionSuppression, MessageData, MsgTypes,
+    Reaction, Role, SessionInfo, Status, Stroke, SystemEvent, SystemNotice, WebSocketMessage,
+    DEFAULT_CHANNEL,
+};
+
+use crate::links;
+use crate::mentions::mention_of;
+use crate::motion::Motion;
+use crate::services::event_bus::{ChatEvent, ConnectionState};
+use crate::services::image::ImageQuality;
+use crate::services::notifications;
+use crate::services::socket::SocketError;
+use crate::services::sound::NotificationSound;
+use crate::services::tab_sync::TabMessage;
+use crate::theme::Theme;
+use crate::time::TimeFormat;
+use crate::wallpaper::Wallpaper;
+
+/// How long a sent message may go without the server echoing it before it counts as failed.
+pub const ACK_TIMEOUT_MS: u64 = 10_000;
+/// How long sending pauses after the connection refused a send for having too many waiting.
+pub const SEND_HOLD_MS: u64 = 3_000;
+/// Shown as the sender of notes only this client sees, such as `/ping` results.
+pub const LOCAL_SENDER: &str = "YewChat";
+/// How long someone counts as typing after their latest typing notice.
+pub const TYPING_TIMEOUT_MS: u64 = 6_000;
+/// Join, leave and rename lines kept; the oldest go first.
+pub const SYSTEM_LINES_KEPT: usize = 200;
+
+/// Where someone is typing, and until when unless they send another notice.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Typing {
+    pub channel: String,
+    /// Unix time in ms.
+    pub until: u64,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct UserProfile {
+    pub name: String,
+    /// False for placeholders standing in for senders never seen in a user list.
+    pub known: bool,
+    pub role: Role,
+    pub status: Status,
+    /// What they say they're up to, e.g. "In a meeting".
+    pub activity: Option<String>,
+    pub bot: bool,
+    pub verified: bool,
+}
+
+impl UserProfile {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.into(),
+            known: true,
+            role: Role::Member,
+            status: Status::Online,
+            activity: None,
+            bot: false,
+            verified: false,
+        }
+    }
+
+    pub fn placeholder(name: &str) -> Self {
+        Self {
+            name: name.into(),
+            known: false,
+            role: Role::Member,
+            status: Status::Online,
+            activity: None,
+            bot: false,
+            verified: false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Presence {
+    Online,
+    /// Online with notifications silenced.
+    DoNotDisturb,
+    /// Connected read-only; can watch but not post.
+    Spectator,
+    Offline,
+}
+
+impl Presence {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Presence::Online => "Online",
+            Presence::DoNotDisturb => "Do not disturb",
+            Presence::Spectator => "Watching",
+            Presence::Offline => "Offline",
+        }
+    }
+}
+
+/// Messages that arrived in a channel while it wasn't being viewed.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct Unread {
+    pub messages: usize,
+    /// Of those, the ones that mention the current user.
+    pub mentions: usize,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum DisplayMode {
+    /// Avatars and bubbles.
+    Comfortable,
+    /// One line per message, IRC style.
+    Compact,
+}
+
+/// How messages are drawn in the comfortable display mode.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum BubbleStyle {
+    #[default]
+    Classic,
+    /// No bubbles, just rows.
+    Flat,
+    /// Bubbles, with your own on the right.
+    Sides,
+}
+
+impl BubbleStyle {
+    pub const ALL: [BubbleStyle; 3] = [BubbleStyle::Classic, BubbleStyle::Flat, BubbleStyle::Sides];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            BubbleStyle::Classic => "Bubbles",
+            BubbleStyle::Flat => "Flat",
+            BubbleStyle::Sides => "Mine on the right",
+        }
+    }
+}
+
+/// Which incoming messages are read out by screen readers.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum Announcements {
+    #[default]
+    All,
+    /// Only messages that mention you, directly or with `@everyone` or `@here`.
+    Mentions,
+    Off,
+}
+
+impl Announcements {
+    pub const ALL: [Announcements; 3] = [
+        Announcements::All,
+        Announcements::Mentions,
+        Announcements::Off,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Announcements::All => "All messages",
+            Announcements::Mentions => "Mentions only",
+            Announcements::Off => "Off",
+        }
+    }
+}
+
+/// Why sending is held back.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CooldownCause {
+    /// The channel's slow mode.
+    SlowMode,
+    /// The connection had too many sends waiting.
+    RateLimit,
+}
+
+/// A stretch of time, in unix ms, during which sending is held back.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cooldown {
+    pub from: u64,
+    pub until: u64,
+    pub cause: CooldownCause,
+}
+
+impl Cooldown {
+    /// Whole seconds left at `now`, rounded up; 0 once it's over.
+    pub fn seconds_left(&self, now: u64) -> u64 {
+        self.until.saturating_sub(now).div_ceil(1000)
+    }
+
+    /// The share of it still to run at `now`, from 1 at the start down to 0.
+    pub fn remaining(&self, now: u64) -> f64 {
+        let length = self.until.saturating_sub(self.from).max(1);
+        self.until.saturating_sub(now).min(length) as f64 / length as f64
+    }
+}
+
+/// A message we sent that the server hasn't echoed back yet, or that failed to send.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Outgoing {
+    /// Unique on this device, even among sends made in the same millisecond by different
+    /// tabs: random, and below 2^53 so the service worker's JavaScript reads it exactly.
+    pub id: u64,
+    pub channel: String,
+    pub message: String,
+    pub reply_to: Option<u64>,
+    pub attachment: Option<Attachment>,
+    pub tasks_open: bool,
+    /// Unix time in ms of the latest attempt.
+    pub sent_at: u64,
+    /// Why it failed; `None` while waiting for the echo.
+    pub error: Option<SendError>,
+}
+
+/// Why a send failed, which decides whether sending it again unattended is any use.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum SendError {
+    /// It may never have reached the server.
+    Network(String),
+    /// The server turned it down, and would again.
+    Refused(String),
+}
+
+impl std::fmt::Display for SendError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SendError::Network(reason) | SendError::Refused(reason) => f.write_str(reason),
+        }
+    }
+}
+
+impl Outgoing {
+    /// An empty message to `channel`, sent now.
+    pub fn new(channel: String) -> Self {
+        let now = js_sys::Date::now() as u64;
+        Self {
+            id: (js_sys::Math::random() * 2f64.powi(53)) as u64,
+            channel,
+            message: String::new(),
+            reply_to: None,
+            attachment: None,
+            tasks_open: false,
+            sent_at: now,
+            error: None,
+        }
+    }
+
+    pub fn frame(&self) -> WebSocketMessage {
+        let mut frame = WebSocketMessage::with_data(MsgTypes::Message, self.message.clone())
+            .in_channel(self.channel.clone());
+        if let Some(parent) = self.reply_to {
+            frame = frame.replying_to(parent);
+        }
+        if let Some(attachment) = self.attachment.clone() {
+            frame = frame.with_attachment(attachment);
+        }
+        if self.tasks_open {
+            frame = frame.with_open_tasks();
+        }
+        frame.with_outgoing_id(self.id)
+    }
+
+    /// Whether `m` is the server's echo of this message.
+    fn echoed_by(&self, m: &MessageData) -> bool {
+        m.channel == self.channel && m.message == self.message && m.attachment == self.attachment
+    }
+}
+
+/// Destructive or noisy actions that are confirmed first, unless the user has asked not to be.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum Confirmation {
+    DeleteMessage,
+    Kick,
+    Ban,
+    ClearHistory,
+    ClearWhiteboard,
+    /// Sending a message that mentions @everyone or @here.
+    Broadcast,
+}
+
+/// Daily window, in minutes since local midnight, during which notifications are held back.
+/// `start` after `end` wraps past midnight, as in 22:00–08:00.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct QuietHours {
+    pub enabled: bool,
+    pub start: u32,
+    pub end: u32,
+}
+
+impl Default for QuietHours {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            start: 22 * 60,
+            end: 8 * 60,
+        }
+    }
+}
+
+impl QuietHours {
+    pub fn contains(&self, minute_of_day: u32) -> bool {
+        if !self.enabled || self.start == self.end {
+            return false;
+        }
+        if self.start < self.end {
+            (self.start..self.end).contains(&minute_of_day)
+        } else {
+            minute_of_day >= self.start || minute_of_day < self.end
+        }
+    }
+}
+
+/// Saved to localStorage by the store, so everything here survives a reload.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preferences {
+    pub theme: Theme,
+    pub display_mode: DisplayMode,
+    pub bubble_style: BubbleStyle,
+    /// Opt-in for anonymous usage counters.
+    pub telemetry: bool,
+    /// Consent to sending error reports, with recent log lines, to the maintainers.
+    pub error_reports: bool,
+    /// Do not disturb: no sounds or notifications, and others see the status.
+    pub dnd: bool,
+    pub quiet_hours: QuietHours,
+    /// Muted channels, with the unix time in ms the mute lapses, or `None` until unmuted.
+    pub muted: HashMap<String, Option<u64>>,
+    pub time_format: TimeFormat,
+    /// How hard attached images are compressed.
+    pub image_quality: ImageQuality,
+    /// Lets anyone tick the checklists in messages you send, not just you.
+    pub open_checklists: bool,
+    /// Played for messages that arrive while you're looking elsewhere.
+    pub sound: NotificationSound,
+    /// Whether transitions, smooth scrolling and animated images play.
+    pub motion: Motion,
+    /// What screen readers announce as it arrives.
+    pub announcements: Announcements,
+    /// Confirmations the user ticked "Don't ask again" on.
+    pub skip_confirmations: HashSet<Confirmation>,
+    /// Linked GIFs and maps wait for a click, and avatars are fetched smaller.
+    pub data_saver: bool,
+    /// GIFs play as soon as they're shown, rather than waiting paused.
+    pub autoplay_gifs: bool,
+    /// Images and GIFs from anyone not in `media_allowlist` wait behind a warning.
+    pub media_filter: bool,
+    /// Whose media shows straight away with the filter on.
+    pub media_allowlist: HashSet<String>,
+    /// Whose media waits behind a warning, filter or not.
+    pub media_flagged: HashSet<String>,
+    /// Sites whose links open without the warning, subdomains included.
+    pub trusted_domains: HashSet<String>,
+    /// Sites whose links get no preview card, subdomains included.
+    pub unpreviewed_domains: HashSet<String>,
+    /// Mentions are pushed to this browser while no tab is open.
+    pub push: bool,
+    /// Mentions that shouldn't ping, here or by push.
+    pub suppress_mentions: MentionSuppression,
+    /// Shown to others under our name; empty for none.
+    pub activity: String,
+    /// Behind the messages.
+    pub wallpaper: Wallpaper,
+    /// How far, in percent, the wallpaper is faded into the page colour.
+    pub wallpaper_dim: u8,
+}
+
+impl Preferences {
+    /// Whether images and GIFs sent by `from` wait behind a warning for `me`.
+    pub fn hides_media_from(&self, from: &str, me: &str) -> bool {
+        from != me
+            && (self.media_flagged.contains(from)
+                || (self.media_filter && !self.media_allowlist.contains(from)))
+    }
+
+    /// Whether links to `host` open without asking first.
+    pub fn trusts(&self, host: &str) -> bool {
+        self.trusted_domains
+            .iter()
+            .any(|domain| links::is_within(host, domain))
+    }
+
+    /// Whether links to `host` get a preview card.
+    pub fn previews(&self, host: &str) -> bool {
+        !self
+            .unpreviewed_domains
+            .iter()
+            .any(|domain| links::is_within(host, domain))
+    }
+
+    pub fn is_muted(&self, channel: &str) -> bool {
+        self.muted.contains_key(channel)
+    }
+
+    /// When the next timed mute lapses.
+    pub fn next_unmute(&self) -> Option<u64> {
+        self.muted.values().flatten().min().copied()
+    }
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Self {
+            theme: Theme::Light,
+            display_mode: DisplayMode::Comfortable,
+            bubble_style: BubbleStyle::default(),
+            telemetry: false,
+            error_reports: false,
+            dnd: false,
+            quiet_hours: QuietHours::default(),
+            muted: HashMap::new(),
+            time_format: TimeFormat::default(),
+            image_quality: ImageQuality::default(),
+            open_checklists: false,
+            sound: NotificationSound::default(),
+            motion: Motion::default(),
+            announcements: Announcements::default(),
+            skip_confirmations: HashSet::new(),
+            data_saver: false,
+            autoplay_gifs: true,
+            media_filter: false,
+            media_allowlist: HashSet::new(),
+            media_flagged: HashSet::new(),
+            trusted_domains: HashSet::new(),
+            unpreviewed_domains: HashSet::new(),
+            push: false,
+            suppress_mentions: MentionSuppression::default(),
+            activity: String::new(),
+            wallpaper: Wallpaper::default(),
+            wallpaper_dim: 40,
+        }
+    }
+}
+
+pub enum ChatAction {
+    Event(ChatEvent),
+    /// Several events applied in one pass, producing a single render.
+    Events(Vec<ChatEvent>),
+    UpdateDraft(String),
+    ClearDraft,
+    ToggleEmojiPicker,
+    SelectEmoji(String),
+    ToggleTheme,
+    SetPreferences(Preferences),
+    /// Stops pushing mentions to this browser, as when it refused to subscribe, leaving the
+    /// other preferences as they are by now.
+    DisablePush,
+    SwitchChannel(String),
+    /// Mutes a channel until the given unix time in ms, or indefinitely.
+    Mute {
+        channel: String,
+        until: Option<u64>,
+    },
+    Unmute(String),
+    /// Lifts timed mutes that lapsed by the given unix time in ms.
+    ExpireMutes(u64),
+    /// Records who is using the client, so mentions of them can be counted.
+    Identify {
+        name: String,
+        online: bool,
+    },
+    DismissInvite,
+    /// Marks a history request for the channel as in flight.
+    RequestHistory(String),
+    /// Marks the channel's history request as lost, so a retry is offered.
+    HistoryFailed(String),
+    /// Quotes a message above the composer; the next send answers it.
+    ReplyTo(u64),
+    CancelReply,
+    /// Shows a notice above the composer, as if the server had refused the send.
+    ShowNotice(String),
+    /// Adds an `@name` mention to the end of the draft.
+    Mention(String),
+    /// Holds an attachment for the next send.
+    Attach(Attachment),
+    RemoveAttachment,
+    /// Forgets the messages loaded for a channel. They stay on the server.
+    ClearHistory(String),
+    /// Records a message about to be sent, until the server echoes it.
+    Queue(Outgoing),
+    /// Marks a queued message as failed to leave this device, with the reason.
+    SendFailed {
+        id: u64,
+        reason: String,
+    },
+    /// Marks a failed message as being sent again at the given unix time in ms.
+    Retry {
+        id: u64,
+        now: u64,
+    },
+    /// Gives up on a failed message.
+    Discard(u64),
+    /// Pauses sending for `SEND_HOLD_MS` from the given unix time in ms, after the connection
+    /// refused a send.
+    HoldSends(u64),
+    /// Fails sends that have waited longer than `ACK_TIMEOUT_MS` by the given unix time in ms.
+    ExpireSends(u64),
+    /// Records a `/ping` heartbeat sent at this unix time in ms, to report when answered.
+    Ping(u64),
+    /// Adds a note to the current channel that only this client sees.
+    PostLocal(String),
+    /// Applies what another open tab of the client reported.
+    FromTab(TabMessage),
+    /// Stops counting people as typing whose notices ran out by the given unix time in ms.
+    ExpireTyping(u64),
+}
+
+/// Application-wide state, shared through the store context. Kept free of DOM handles so it
+/// can be driven in tests.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChatState {
+    /// Shared so renders hand the list to children without copying it.
+    pub users: Rc<Vec<UserProfile>>,
+    /// A user list has arrived since startup; until then the panels show placeholders.
+    pub users_loaded: bool,
+    pub messages: Rc<Vec<Rc<MessageData>>>,
+    /// Joins, leaves and renames this session, oldest first, shown among the messages.
+    pub system: Rc<Vec<SystemNotice>>,
+    /// Reactions by message id; messages without any are absent.
+    pub reactions: Rc<HashMap<u64, Rc<Vec<Reaction>>>>,
+    pub pinned: Rc<HashSet<u64>>,
+    /// Moderation log, oldest first; stays empty for non-moderators.
+    pub audit: Rc<Vec<AuditEntry>>,
+    /// Everyone seen this session, keyed by name, so message authors resolve in O(1) even
+    /// after they leave.
+    pub profiles: Rc<HashMap<String, UserProfile>>,
+    pub spectators: Rc<Vec<String>>,
+    pub channels: Rc<Vec<ChannelInfo>>,
+    /// Name of the channel being viewed and posted to.
+    pub channel: String,
+    /// First message that was unread when the current channel was opened.
+    pub divider: Option<u64>,
+    /// Unread counts by channel; channels with nothing unread are absent.
+    pub unread: HashMap<String, Unread>,
+    /// Name of the current user, empty before login.
+    pub me: String,
+    /// False while spectating, so `@here` doesn't count as a mention.
+    pub online: bool,
+    /// Channels with a history request in flight.
+    pub loading_history: HashSet<String>,
+    /// Channels whose oldest stored message has been loaded.
+    pub history_exhausted: HashSet<String>,
+    /// Channels whose last history request was never answered.
+    pub history_failed: HashSet<String>,
+    /// Invite we asked for, shown until dismissed.
+    pub invite: Option<InviteInfo>,
+    /// Why the last send was refused, by the server or locally; cleared on the next send.
+    pub notice: Option<String>,
+    /// Last known presence of everyone seen this session; absent users were never online.
+    pub presence: Rc<HashMap<String, Presence>>,
+    /// Sends not yet echoed by the server, and ones that failed, oldest first.
+    pub outbox: Rc<Vec<Outgoing>>,
+    /// Set when the connection refused a send for having too many waiting.
+    pub send_hold: Option<Cooldown>,
+    /// Whiteboard strokes by channel, for boards that have been opened.
+    pub whiteboards: Rc<HashMap<String, Rc<Vec<Stroke>>>>,
+    pub preferences: Preferences,
+    pub connection: ConnectionState,
+    /// Round trip of the latest answered heartbeat in ms; `None` until one is answered on
+    /// the current connection.
+    pub latency: Option<u64>,
+    /// Times the connection was lost and retried this session.
+    pub reconnects: u32,
+    /// When the `/ping` waiting for its answer was sent.
+    pub ping: Option<u64>,
+    /// Sessions under our nick, as last listed by the server.
+    pub sessions: Rc<Vec<SessionInfo>>,
+    /// Another session signed this one out.
+    pub signed_out: bool,
+    /// Who else is typing, by name.
+    pub typing: HashMap<String, Typing>,
+    pub draft: String,
+    /// Message the draft answers, if any.
+    pub reply_to: Option<u64>,
+    /// Sent with the draft.
+    pub attachment: Option<Attachment>,
+    pub show_emoji_picker: bool,
+}
+
+impl Default for ChatState {
+    fn default() -> Self {
+        Self {
+            users: Rc::default(),
+            users_loaded: false,
+            messages: Rc::default(),
+            system: Rc::default(),
+            reactions: Rc::default(),
+            pinned: Rc::default(),
+            audit: Rc::default(),
+            profiles: Rc::default(),
+            spectators: Rc::default(),
+            channels: Rc::new(vec![ChannelInfo {
+                name: DEFAULT_CHANNEL.into(),
+                ..ChannelInfo::default()
+            }]),
+            channel: DEFAULT_CHANNEL.into(),
+            divider: None,
+            unread: HashMap::new(),
+            me: String::new(),
+            online: true,
+            loading_history: HashSet::new(),
+            history_exhausted: HashSet::new(),
+            history_failed: HashSet::new(),
+            invite: None,
+            notice: None,
+            presence: Rc::default(),
+            outbox: Rc::default(),
+            send_hold: None,
+            whiteboards: Rc::default(),
+            preferences: Preferences::default(),
+            connection: ConnectionState::Connecting,
+            latency: None,
+            reconnects: 0,
+            ping: None,
+            sessions: Rc::default(),
+            signed_out: false,
+            typing: HashMap::new(),
+            draft: String::new(),
+            reply_to: None,
+            attachment: None,
+            show_emoji_picker: false,
+        }
+    }
+}
+
+impl ChatState {
+    /// Applies an event from the EventBus. Returns true when a re-render is needed.
+    pub fn handle_event(&mut self, event: ChatEvent) -> bool {
+        match event {
+            ChatEvent::UsersUpdated(users) => {
+                self.users_loaded = true;
+                self.users = Rc::new(
+                    users
+                        .iter()
+                        .map(|u| UserProfile {
+                            role: u.role,
+                            status: u.status,
+                            activity: u.activity.clone(),
+                            bot: u.bot,
+                            verified: u.verified,
+                            ..UserProfile::new(&u.name)
+                        })
+                        .collect(),
+                );
+                let profiles = Rc::make_mut(&mut self.profiles);
+                for user in self.users.iter() {
+                    profiles.insert(user.name.clone(), user.clone());
+                }
+                let present = users
+                    .into_iter()
+                    .map(|u| {
+                        let presence = match u.status {
+                            Status::Online => Presence::Online,
+                            Status::Dnd => Presence::DoNotDisturb,
+                        };
+                        (u.name, presence)
+                    })
+                    .collect();
+                self.replace_presence(&[Presence::Online, Presence::DoNotDisturb], present);
+                true
+            }
+            ChatEvent::SpectatorsUpdated(names) => {
+                self.spectators = Rc::new(names.clone());
+                let present = names
+                    .into_iter()
+                    .map(|n| (n, Presence::Spectator))
+                    .collect();
+                self.replace_presence(&[Presence::Spectator], present);
+                true
+            }
+            ChatEvent::MessageReceived(message_data) => {
+                self.typing.remove(&message_data.from);
+                if message_data.channel != self.channel
+                    && message_data.from != self.me
+                    && !self.preferences.is_muted(&message_data.channel)
+                {
+                    let unread = self.unread.entry(message_data.channel.clone()).or_default();
+                    unread.messages += 1;
+                    let mention = mention_of(&message_data.message, &self.me, self.online);
+                    if mention.is_some_and(|m| notifications::pings(&self.preferences, m)) {
+                        unread.mentions += 1;
+                    }
+                }
+                if message_data.from == self.me {
+                    if let Some(i) = self.outbox.iter().position(|o| o.echoed_by(&message_data)) {
+                        Rc::make_mut(&mut self.outbox).remove(i);
+                    }
+                }
+                Rc::make_mut(&mut self.messages).push(Rc::new(message_data));
+                true
+            }
+            ChatEvent::ChannelsUpdated(channels) => {
+                if !channels.iter().any(|c| c.name == self.channel) {
+                    if let Some(first) = channels.first() {
+                        self.channel = first.name.clone();
+                    }
+                }
+                self.channels = Rc::new(channels);
+                true
+            }
+            ChatEvent::ReactionsUpdated(update) => {
+                let reactions = Rc::make_mut(&mut self.reactions);
+                if update.reactions.is_empty() {
+                    reactions.remove(&update.message_id);
+                } else {
+                    reactions.insert(update.message_id, Rc::new(update.reactions));
+                }
+                true
+            }
+            ChatEvent::AuditReceived(entries) => {
+                // The backlog is resent after a reconnect; keep only what's new.
+                let last = self.audit.last().map_or(0, |e| e.id);
+                let audit = Rc::make_mut(&mut self.audit);
+                audit.extend(entries.into_iter().filter(|e| e.id > last));
+                true
+            }
+            ChatEvent::MessageDeleted(id) => {
+                Rc::make_mut(&mut self.messages).retain(|m| m.id != id);
+                Rc::make_mut(&mut self.reactions).remove(&id);
+                if self.reply_to == Some(id) {
+                    self.reply_to = None;
+                }
+                true
+            }
+            ChatEvent::PinsUpdated(pins) => {
+                self.pinned = Rc::new(pins.into_iter().collect());
+                true
+            }
+            ChatEvent::InviteCreated(invite) => {
+                self.invite = Some(invite);
+                true
+            }
+            ChatEvent::HistoryLoaded(page) => {
+                let mut changed = self.loading_history.remove(&page.channel);
+                changed |= self.history_failed.remove(&page.channel);
+                if !page.more {
+                    changed |= self.history_exhausted.insert(page.channel.clone());
+                }
+                // Pages can overlap what arrived live; keep one copy. The rest is older than
+                // anything the channel has, so it goes in front of the channel's first message.
+                let messages = Rc::make_mut(&mut self.messages);
+                let known: HashSet<u64> = messages.iter().map(|m| m.id).collect();
+                let before = messages.len();
+                let at = messages
+                    .iter()
+                    .position(|m| m.channel == page.channel)
+                    .unwrap_or(before);
+                messages.splice(
+                    at..at,
+                    page.messages
+                        .into_iter()
+                        .filter(|m| !known.contains(&m.id))
+                        .map(Rc::new),
+                );
+                changed || messages.len() != before
+            }
+            ChatEvent::StrokeDrawn(stroke) => {
+                // Strokes on a board we haven't loaded would be drawn without what came before.
+                match Rc::make_mut(&mut self.whiteboards).get_mut(&stroke.channel) {
+                    Some(strokes) => {
+                        Rc::make_mut(strokes).push(stroke);
+                        true
+                    }
+                    None => false,
+                }
+            }
+            ChatEvent::WhiteboardLoaded(board) => {
+                Rc::make_mut(&mut self.whiteboards).insert(board.channel, Rc::new(board.strokes));
+                true
+            }
+            ChatEvent::Rejected {
+                reason,
+                outgoing_id,
+            } => {
+                if let Some(id) = outgoing_id {
+                    self.fail_sends(|o| o.id == id, SendError::Refused(reason.clone()));
+                }
+                self.notice = Some(reason);
+                true
+            }
+            ChatEvent::MessageEdited(message_data) => {
+                let messages = Rc::make_mut(&mut self.messages);
+                match messages.iter_mut().rev().find(|m| m.id == message_data.id) {
+                    Some(message) => {
+                        *message = Rc::new(message_data);
+                        true
+                    }
+                    None => false,
+                }
+            }
+            ChatEvent::ConnectionChanged(connection) => {
+                if connection != ConnectionState::Connected {
+                    self.fail_sends(
+                        |o| o.error.is_none(),
+                        SendError::Network("The connection dropped before this was sent.".into()),
+                    );
+                    self.latency = None;
+                    self.typing.clear();
+                    // Answers to requests in flight are lost with the connection.
+                    self.history_failed.extend(self.loading_history.drain());
+                    if self.ping.take().is_some() {
+                        self.post_local("Ping failed: the connection dropped before an answer.");
+                    }
+                }
+                // The first connection starts out connecting too, and isn't a retry.
+                if connection == ConnectionState::Connecting
+                    && self.connection == ConnectionState::Disconnected
+                {
+                    self.reconnects += 1;
+                }
+                self.connection = connection;
+                true
+            }
+            ChatEvent::Pong(sent_at) => {
+                let latency = (js_sys::Date::now() as u64).saturating_sub(sent_at);
+                self.latency = Some(latency);
+                if self.ping == Some(sent_at) {
+                    self.ping = None;
+                    let reconnects = match self.reconnects {
+                        1 => "1 reconnect".to_string(),
+                        n => format!("{} reconnects", n),
+                    };
+                    self.post_local(&format!(
+                        "🏓 Pong: {} ms round trip, {} this session.",
+                        latency, reconnects
+                    ));
+                }
+                true
+            }
+            ChatEvent::Typing(typing) => {
+                if typing.name == self.me {
+                    return false;
+                }
+                let until = js_sys::Date::now() as u64 + TYPING_TIMEOUT_MS;
+                self.typing.insert(
+                    typing.name,
+                    Typing {
+                        channel: typing.channel,
+                        until,
+                    },
+                );
+                true
+            }
+            ChatEvent::SessionsLoaded(sessions) => {
+                self.sessions = Rc::new(sessions);
+                true
+            }
+            ChatEvent::SignedOut => {
+                self.signed_out = true;
+                true
+            }
+            ChatEvent::System(notice) => {
+                if let SystemEvent::Renamed { from, to } = &notice.event {
+                    self.rename_channel(from, to);
+                }
+                let system = Rc::make_mut(&mut self.system);
+                system.push(notice);
+                let excess = system.len().saturating_sub(SYSTEM_LINES_KEPT);
+                system.drain(..excess);
+                true
+            }
+            ChatEvent::SendFailed { error, outgoing_id } => {
+                let Some(id) = outgoing_id else {
+                    log::debug!("error sending to channel: {:?}", error);
+                    return false;
+                };
+                if error == SocketError::Full {
+                    self.hold_sends(js_sys::Date::now() as u64);
+                }
+                self.fail_sends(|o| o.id == id, SendError::Network(error.to_string()));
+                true
+            }
+            ChatEvent::Traffic { .. } => false,
+            ChatEvent::Error(e) => {
+                log::error!("{}", e);
+                false
+            }
+        }
+    }
+
+    /// Applies `present`, the full list of users in any of `states`; whoever previously had
+    /// one of those states and isn't listed goes offline.
+    fn replace_presence(&mut self, states: &[Presence], present: Vec<(String, Presence)>) {
+        let presence = Rc::make_mut(&mut self.presence);
+        for state in presence.values_mut() {
+            if states.contains(state) {
+                *state = Presence::Offline;
+            }
+        }
+        for (name, state) in present {
+            presence.insert(name, state);
+        }
+    }
+
+    /// Profile for `name`, or a placeholder if they were never in a user list.
+    pub fn profile(&self, name: &str) -> UserProfile {
+        self.profiles
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| UserProfile::placeholder(name))
+    }
+
+    /// A loaded message by id.
+    pub fn message(&self, id: u64) -> Option<&Rc<MessageData>> {
+        self.messages.iter().rev().find(|m| m.id == id)
+    }
+
+    /// Id of the oldest loaded message in `channel`, to page back from.
+    pub fn oldest_message(&self, channel: &str) -> Option<u64> {
+        self.messages
+            .iter()
+            .find(|m| m.channel == channel && m.id != 0)
+            .map(|m| m.id)
+    }
+
+    pub fn current_channel(&self) -> Option<&ChannelInfo> {
+        self.channels.iter().find(|c| c.name == self.channel)
+    }
+
+    /// Whether `name` may post in the current channel. The server enforces the same rule.
+    pub fn can_post(&self, name: &str) -> bool {
+        match self.current_channel() {
+            Some(channel) if channel.announcement => self.profile(name).role.moderates(),
+            _ => true,
+        }
+    }
+
+    /// The wait slow mode puts on our next post in the current channel, counted from our
+    /// latest message there, sent or still on its way. `None` when slow mode is off, we're a
+    /// moderator or we haven't posted; it may already be over.
+    pub fn slow_mode_cooldown(&self) -> Option<Cooldown> {
+        let channel = self.current_channel()?;
+        let seconds = channel.slow_mode?;
+        if self.profile(&self.me).role.moderates() {
+            return None;
+        }
+        let sent = self
+            .messages
+            .iter()
+            .rev()
+            .find(|m| m.from == self.me && m.channel == channel.name)
+            .map(|m| m.time);
+        let queued = self
+            .outbox
+            .iter()
+            .filter(|o| o.channel == channel.name && o.error.is_none())
+            .map(|o| o.sent_at)
+            .max();
+        let from = sent.max(queued)?;
+        Some(Cooldown {
+            from,
+            until: from + u64::from(seconds) * 1000,
+            cause: CooldownCause::SlowMode,
+        })
+    }
+
+    /// The wait before we may send again, whichever of slow mode and the connection's
+    /// pushback ends later. It may already be over.
+    pub fn send_cooldown(&self) -> Option<Cooldown> {
+        [self.slow_mode_cooldown(), self.send_hold]
+            .into_iter()
+            .flatten()
+            .max_by_key(|c| c.until)
+    }
+
+    /// Who is typing in `channel`, in name order.
+    pub fn typing_in(&self, channel: &str) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .typing
+            .iter()
+            .filter(|(_, t)| t.channel == channel)
+            .map(|(name, _)| name.clone())
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// When the next typing notice runs out.
+    pub fn next_typing_expiry(&self) -> Option<u64> {
+        self.typing.values().map(|t| t.until).min()
+    }
+
+    /// Unread totals across every channel.
+    pub fn total_unread(&self) -> Unread {
+        self.unread
+            .values()
+            .fold(Unread::default(), |total, u| Unread {
+                messages: total.messages + u.messages,
+                mentions: total.mentions + u.mentions,
+            })
+    }
+
+    /// Adds a note to the current channel. It has no id, so it can't be replied to, reacted
+    /// to or mistaken for history.
+    pub fn post_local(&mut self, text: &str) {
+        Rc::make_mut(&mut self.messages).push(Rc::new(MessageData {
+            id: 0,
+            from: LOCAL_SENDER.into(),
+            message: text.into(),
+            time: js_sys::Date::now() as u64,
+            channel: self.channel.clone(),
+            edited_at: 0,
+            history: Vec::new(),
+            reply_to: None,
+            attachment: None,
+            tasks_open: false,
+        }));
+    }
+
+    /// Marks the sends picked out by `which` as failed.
+    fn hold_sends(&mut self, now: u64) {
+        self.send_hold = Some(Cooldown {
+            from: now,
+            until: now + SEND_HOLD_MS,
+            cause: CooldownCause::RateLimit,
+        });
+    }
+
+    fn fail_sends(&mut self, which: impl Fn(&Outgoing) -> bool, error: SendError) {
+        for outgoing in Rc::make_mut(&mut self.outbox).iter_mut() {
+            if which(outgoing) {
+                outgoing.error = Some(error.clone());
+            }
+        }
+    }
+
+    /// Drops the channel's messages and what hangs off them. History isn't fetched again
+    /// until the next reload, or the cleared messages would come straight back.
+    pub fn clear_history(&mut self, channel: &str) {
+        let (cleared, kept): (Vec<_>, Vec<_>) = self
+            .messages
+            .iter()
+            .cloned()
+            .partition(|m| m.channel == channel);
+        if cleared.is_empty() {
+            return;
+        }
+        let cleared: HashSet<u64> = cleared.iter().map(|m| m.id).collect();
+        self.messages = Rc::new(kept);
+        let mut reactions = (*self.reactions).clone();
+        reactions.retain(|id, _| !cleared.contains(id));
+        self.reactions = Rc::new(reactions);
+        self.unread.remove(channel);
+        if self.divider.is_some_and(|id| cleared.contains(&id)) {
+            self.divider = None;
+        }
+        if self.reply_to.is_some_and(|id| cleared.contains(&id)) {
+            self.reply_to = None;
+        }
+        self.history_exhausted.insert(channel.to_string());
+    }
+
+    /// Moves everything filed under channel `from` to its new name `to`, the view included.
+    fn rename_channel(&mut self, from: &str, to: &str) {
+        let rename = |name: &mut String| {
+            if name == from {
+                *name = to.to_string();
+            }
+        };
+        for channel in Rc::make_mut(&mut self.channels) {
+            rename(&mut channel.name);
+        }
+        rename(&mut self.channel);
+        for message in Rc::make_mut(&mut self.messages)
+            .iter_mut()
+            .filter(|m| m.channel == from)
+        {
+            rename(&mut Rc::make_mut(message).channel);
+        }
+        for outgoing in Rc::make_mut(&mut self.outbox) {
+            rename(&mut outgoing.channel);
+        }
+        for typing in self.typing.values_mut() {
+            rename(&mut typing.channel);
+        }
+        if let Some(unread) = self.unread.remove(from) {
+            self.unread.insert(to.to_string(), unread);
+        }
+        for set in [
+            &mut self.loading_history,
+            &mut self.history_exhausted,
+            &mut self.history_failed,
+        ] {
+            if set.remove(from) {
+                set.insert(to.to_string());
+            }
+        }
+        if let Some(board) = Rc::make_mut(&mut self.whiteboards).remove(from) {
+            Rc::make_mut(&mut self.whiteboards).insert(to.to_string(), board);
+        }
+        if let Some(until) = self.preferences.muted.remove(from) {
+            self.preferences.muted.insert(to.to_string(), until);
+        }
+    }
+
+    /// Opens `name`, marking where its unread messages start.
+    pub fn switch_channel(&mut self, name: String) {
+        let unread = self.unread.remove(&name).map_or(0, |u| u.messages);
+        self.divider = match unread {
+            0 => None,
+            n => self
+                .messages
+                .iter()
+                .rev()
+                .filter(|m| m.channel == name && m.from != self.me)
+                .nth(n - 1)
+                .map(|m| m.id),
+        };
+        self.channel = name;
+        self.notice = None;
+        self.reply_to = None;
+    }
+
+    pub fn toggle_emoji_picker(&mut self) {
+        self.show_emoji_picker = !self.show_emoji_picker;
+    }
+
+    pub fn select_emoji(&mut self, emoji: &str) {
+        self.draft.push_str(emoji);
+        self.show_emoji_picker = false;
+    }
+
+    pub fn toggle_theme(&mut self) {
+        self.preferences.theme = match self.preferences.theme {
+            Theme::Light => Theme::Dark,
+            Theme::Dark | Theme::HighContrast => Theme::Light,
+        };
+    }
+
+    /// Catches up with another tab. Returns whether anything changed, so that echoes of this
+    /// tab's own changes stop here. Drafts only carry over between tabs on the same channel.
+    pub fn apply_from_tab(&mut self, message: TabMessage) -> bool {
+        match message {
+            TabMessage::Read(channel) => self.unread.remove(&channel).is_some(),
+            TabMessage::Draft { channel, text } => {
+                if channel != self.channel || text == self.draft {
+                    return false;
+                }
+                self.draft = text;
+                true
+            }
+            TabMessage::Preferences(preferences) => {
+                if preferences == self.preferences {
+                    return false;
+                }
+                self.preferences = preferences;
+                true
+            }
+        }
+    }
+
+    /// Empties the composer, returning its contents if there was anything to send.
+    pub fn take_draft(&mut self) -> Option<String> {
+        if self.draft.is_empty() {
+            None
+        } else {
+            Some(std::mem::take(&mut self.draft))
+        }
+    }
+}
+
+impl Reducible for ChatState {
+    type Action = ChatAction;
+
+    fn reduce(self: Rc<Self>, action: Self::Action) -> Rc<Self> {
+        let mut state = (*self).clone();
+        match action {
+            ChatAction::Event(event) => {
+                if !state.handle_event(event) {
+                    return self;
+                }
+            }
+            ChatAction::Events(events) => {
+                let mut changed = false;
+                for event in events {
+                    changed |= state.handle_event(event);
+                }
+                if !changed {
+                    return self;
+                }
+            }
+            ChatAction::UpdateDraft(value) => state.draft = value,
+            ChatAction::ClearDraft => {
+                state.draft.clear();
+                state.reply_to = None;
+                state.attachment = None;
+                state.notice = None;
+            }
+            ChatAction::ToggleEmojiPicker => state.toggle_emoji_picker(),
+            ChatAction::SelectEmoji(emoji) => state.select_emoji(&emoji),
+            ChatAction::ToggleTheme => state.toggle_theme(),
+            ChatAction::SetPreferences(preferences) => state.preferences = preferences,
+            ChatAction::DisablePush => state.preferences.push = false,
+            ChatAction::SwitchChannel(name) => state.switch_channel(name),
+            ChatAction::Mute { channel, until } => {
+                state.unread.remove(&channel);
+                state.preferences.muted.insert(channel, until);
+            }
+            ChatAction::Unmute(channel) => {
+                state.preferences.muted.remove(&channel);
+            }
+            ChatAction::ExpireMutes(now) => {
+                state
+                    .preferences
+                    .muted
+                    .retain(|_, until| !matches!(until, Some(until) if *until <= now));
+            }
+            ChatAction::Identify { name, online } => {
+                state.me = name;
+                state.online = online;
+            }
+            ChatAction::DismissInvite => state.invite = None,
+            ChatAction::RequestHistory(channel) => {
+                state.history_failed.remove(&channel);
+                state.loading_history.insert(channel);
+            }
+            ChatAction::HistoryFailed(channel) => {
+                state.loading_history.remove(&channel);
+                state.history_failed.insert(channel);
+            }
+            ChatAction::ReplyTo(id) => state.reply_to = Some(id),
+            ChatAction::CancelReply => state.reply_to = None,
+            ChatAction::ShowNotice(notice) => state.notice = Some(notice),
+            ChatAction::Mention(name) => {
+                if !state.draft.is_empty() && !state.draft.ends_with(' ') {
+                    state.draft.push(' ');
+                }
+                state.draft.push_str(&format!("@{} ", name));
+            }
+            ChatAction::Attach(attachment) => {
+                state.attachment = Some(attachment);
+                state.notice = None;
+            }
+            ChatAction::RemoveAttachment => state.attachment = None,
+            ChatAction::ClearHistory(channel) => state.clear_history(&channel),
+            ChatAction::Queue(outgoing) => Rc::make_mut(&mut state.outbox).push(outgoing),
+            ChatAction::SendFailed { id, reason } => {
+                let outbox = Rc::make_mut(&mut state.outbox);
+                if let Some(outgoing) = outbox.iter_mut().find(|o| o.id == id) {
+                    outgoing.error = Some(SendError::Network(reason));
+                }
+            }
+            ChatAction::Retry { id, now } => {
+                let outbox = Rc::make_mut(&mut state.outbox);
+                if let Some(outgoing) = outbox.iter_mut().find(|o| o.id == id) {
+                    outgoing.error = None;
+                    outgoing.sent_at = now;
+                }
+            }
+            ChatAction::Discard(id) => Rc::make_mut(&mut state.outbox).retain(|o| o.id != id),
+            ChatAction::HoldSends(now) => state.hold_sends(now),
+            ChatAction::ExpireSends(now) => {
+                let expired = |o: &Outgoing| o.error.is_none() && o.sent_at + ACK_TIMEOUT_MS <= now;
+                if !state.outbox.iter().any(expired) {
+                    return self;
+                }
+                state.fail_sends(
+                    expired,
+                    SendError::Network("The server didn't confirm this message.".into()),
+                );
+            }
+            ChatAction::Ping(sent_at) => state.ping = Some(sent_at),
+            ChatAction::PostLocal(text) => state.post_local(&text),
+            ChatAction::ExpireTyping(now) => {
+                let before = state.typing.len();
+                state.typing.retain(|_, t| t.until > now);
+                if state.typing.len() == before {
+                    return self;
+                }
+            }
+            ChatAction::FromTab(message) => {
+                if !state.apply_from_tab(message) {
+                    return self;
+                }
+            }
+        }
+        Rc::new(state)
+    }
+}
@@ -0,0 +1,291 @@
+use std::rc::Rc;
+
+use gloo::storage::{LocalStorage, Storage};
+use gloo::timers::callback::Timeout;
+use yew::prelude::*;
+use yew_agent::use_bridge;
+
+use crate::services::background_sync;
+use crate::services::data_usage::{self, Traffic};
+use crate::services::error_report;
+use crate::services::event_bus::{ChatEvent, ConnectionState, EventBus};
+use crate::services::tab_sync::{TabMessage, TabSync};
+use crate::services::telemetry::{self, Counter};
+use crate::state::{ChatAction, ChatState, Outgoing, Preferences, SendError, ACK_TIMEOUT_MS};
+
+/// How long to collect events before applying them, so a burst of frames (history replay,
+/// reconnect) costs one render instead of one per frame.
+const BATCH_WINDOW_MS: u32 = 16;
+
+const PREFERENCES_KEY: &str = "yewchat.preferences";
+const OUTBOX_KEY: &str = "yewchat.outbox";
+
+#[derive(Default)]
+struct Batch {
+    events: Vec<ChatEvent>,
+    scheduled: bool,
+    // Kept until the next batch replaces it; dropping it from inside its own callback
+    // would free the running closure.
+    timeout: Option<Timeout>,
+}
+
+/// Handle to the global store. Dispatch `ChatAction`s on it; every consumer re-renders on change.
+pub type Store = UseReducerHandle<ChatState>;
+
+#[derive(Properties, PartialEq)]
+pub struct StoreProviderProps {
+    pub children: Children,
+}
+
+/// Owns the app state and feeds it every event published on the EventBus, in batches.
+#[function_component(StoreProvider)]
+pub fn store_provider(props: &StoreProviderProps) -> Html {
+    let store = use_reducer(|| ChatState {
+        preferences: LocalStorage::get::<Preferences>(PREFERENCES_KEY).unwrap_or_default(),
+        outbox: Rc::new(restore_outbox()),
+        ..ChatState::default()
+    });
+
+    let batch = use_mut_ref(Batch::default);
+
+    {
+        let store = store.clone();
+        use_bridge::<EventBus, _>(move |event| {
+            match &event {
+                ChatEvent::MessageReceived(_) => telemetry::record(Counter::MessagesReceived),
+                ChatEvent::ConnectionChanged(ConnectionState::Connecting) => {
+                    telemetry::record(Counter::Reconnects)
+                }
+                &ChatEvent::Traffic { sent, received } => {
+                    data_usage::record(Traffic::Sent, sent);
+                    data_usage::record(Traffic::Received, received);
+                    return;
+                }
+                _ => {}
+            }
+            let mut pending = batch.borrow_mut();
+            pending.events.push(event);
+            if pending.scheduled {
+                return;
+            }
+            pending.scheduled = true;
+            let store = store.clone();
+            let batch = batch.clone();
+            pending.timeout = Some(Timeout::new(BATCH_WINDOW_MS, move || {
+                let events = {
+                    let mut pending = batch.borrow_mut();
+                    pending.scheduled = false;
+                    std::mem::take(&mut pending.events)
+                };
+                store.dispatch(ChatAction::Events(events));
+            }));
+        });
+    }
+
+    use_effect_with_deps(
+        |enabled| {
+            telemetry::set_enabled(*enabled);
+            || ()
+        },
+        store.preferences.telemetry,
+    );
+    use_effect_with_deps(
+        |enabled| {
+            error_report::set_enabled(*enabled);
+            || ()
+        },
+        store.preferences.error_reports,
+    );
+
+    // Lifts timed channel mutes when they lapse, including ones that lapsed while closed.
+    {
+        let dispatch = store.clone();
+        use_effect_with_deps(
+            move |next| {
+                let timeout = next.map(|until| {
+                    let delay = until.saturating_sub(js_sys::Date::now() as u64);
+                    Timeout::new(delay.min(u32::MAX as u64) as u32, move || {
+                        dispatch.dispatch(ChatAction::ExpireMutes(js_sys::Date::now() as u64))
+                    })
+                });
+                move || drop(timeout)
+            },
+            store.preferences.next_unmute(),
+        );
+    }
+
+    // Drops typing indicators once their notices run out.
+    {
+        let dispatch = store.clone();
+        use_effect_with_deps(
+            move |next| {
+                let timeout = next.map(|until| {
+                    let delay = until.saturating_sub(js_sys::Date::now() as u64);
+                    Timeout::new(delay.min(u32::MAX as u64) as u32, move || {
+                        dispatch.dispatch(ChatAction::ExpireTyping(js_sys::Date::now() as u64))
+                    })
+                });
+                move || drop(timeout)
+            },
+            store.next_typing_expiry(),
+        );
+    }
+
+    // Fails sends the server hasn't echoed in time.
+    {
+        let dispatch = store.clone();
+        let oldest_waiting = store
+            .outbox
+            .iter()
+            .filter(|o| o.error.is_none())
+            .map(|o| o.sent_at)
+            .min();
+        use_effect_with_deps(
+            move |oldest| {
+                let timeout = oldest.map(|sent_at| {
+                    let due = sent_at + ACK_TIMEOUT_MS;
+                    let delay = due.saturating_sub(js_sys::Date::now() as u64);
+                    Timeout::new(delay.min(u32::MAX as u64) as u32, move || {
+                        dispatch.dispatch(ChatAction::ExpireSends(js_sys::Date::now() as u64))
+                    })
+                });
+                move || drop(timeout)
+            },
+            oldest_waiting,
+        );
+    }
+
+    use_effect_with_deps(
+        |(outbox, me)| {
+            if let Err(e) = LocalStorage::set(OUTBOX_KEY, &**outbox) {
+                log::warn!("failed to save unsent messages: {}", e);
+            }
+            if !me.is_empty() {
+                background_sync::hand_over(me, outbox);
+            }
+            || ()
+        },
+        (store.outbox.clone(), store.me.clone()),
+    );
+
+    use_effect_with_deps(
+        |preferences| {
+            if let Err(e) = LocalStorage::set(PREFERENCES_KEY, preferences) {
+                log::warn!("failed to save preferences: {}", e);
+            }
+            || ()
+        },
+        store.preferences.clone(),
+    );
+
+    use_tab_sync(&store);
+
+    html! {
+        <ContextProvider<Store> context={store}>
+            { for props.children.iter() }
+        </ContextProvider<Store>>
+    }
+}
+
+/// Messages that hadn't gone through when the page was last closed. Any still waiting for
+/// the server then can't be matched to its echo any more, so they count as failed.
+fn restore_outbox() -> Vec<Outgoing> {
+    let mut outbox: Vec<Outgoing> = LocalStorage::get(OUTBOX_KEY).unwrap_or_default();
+    for outgoing in outbox.iter_mut().filter(|o| o.error.is_none()) {
+        outgoing.error = Some(SendError::Network(
+            "The page closed before the server confirmed this.".into(),
+        ));
+    }
+    outbox
+}
+
+/// Shares read state, the draft and preferences with other open tabs, and applies theirs.
+fn use_tab_sync(store: &Store) {
+    // The last thing heard from another tab. Applying it changes this tab's state, which
+    // must not be posted back: by the time it arrived the other tab may have moved on.
+    let heard = use_mut_ref(|| None::<TabMessage>);
+    let tabs = {
+        let store = store.clone();
+        let heard = heard.clone();
+        use_state(move || {
+            TabSync::open(move |message| {
+                *heard.borrow_mut() = Some(message.clone());
+                store.dispatch(ChatAction::FromTab(message));
+            })
+        })
+    };
+    let post = move |message: TabMessage| {
+        if heard.borrow().as_ref() == Some(&message) {
+            return;
+        }
+        if let Some(tabs) = &*tabs {
+            tabs.post(&message);
+        }
+    };
+
+    // Whatever arrives in the open channel while the tab is in view has been read.
+    {
+        let post = post.clone();
+        use_effect_with_deps(
+            move |(channel, _)| {
+                if !gloo::utils::document().hidden() {
+                    post(TabMessage::Read(channel.clone()));
+                }
+                || ()
+            },
+            (store.channel.clone(), store.messages.len()),
+        );
+    }
+
+    // Only edits are posted, so a tab opening with an empty composer doesn't wipe the
+    // draft in the others.
+    {
+        let post = post.clone();
+        let previous = use_mut_ref(String::new);
+        let channel = store.channel.clone();
+        use_effect_with_deps(
+            move |draft| {
+                if *previous.borrow() != *draft {
+                    *previous.borrow_mut() = draft.clone();
+                    post(TabMessage::Draft {
+                        channel,
+                        text: draft.clone(),
+                    });
+                }
+                || ()
+            },
+            store.draft.clone(),
+        );
+    }
+
+    use_effect_with_deps(
+        move |preferences| {
+            post(TabMessage::Preferences(preferences.clone()));
+            || ()
+        },
+        store.preferences.clone(),
+    );
+}
+
+pub fn use_store() -> Store {
+    use_context::<Store>().expect("StoreProvider to be mounted")
+}
+
+/// Whether the user asked to hold back media that costs data to fetch. Off without a store,
+/// as in previews.
+pub fn use_data_saver() -> bool {
+    use_context::<Store>().is_some_and(|store| store.preferences.data_saver)
+}
+
+/// Whether images and GIFs from `from` wait behind the media filter's warning. Off without a
+/// store.
+pub fn use_media_hidden(from: &str) -> bool {
+    use_context::<Store>().is_some_and(|store| store.preferences.hides_media_from(from, &store.me))
+}
+
+/// Whether GIFs start playing by themselves; never in data saver mode.
+pub fn use_gif_autoplay() -> bool {
+    use_context::<Store>().map_or(true, |store| {
+        store.preferences.autoplay_gifs && !store.preferences.data_saver
+    })
+}
@@ -0,0 +1,41 @@
+//! JS-callable hooks for end-to-end tests. Only compiled with the `test-hooks` feature.
+
+use wasm_bindgen::prelude::*;
+use yew_agent::Dispatched;
+use yewchat_protocol::{MessageData, MsgTypes, WebSocketMessage, DEFAULT_CHANNEL};
+
+use crate::services::event_bus::{ChatEvent, EventBus, Request};
+
+/// Delivers a raw protocol frame to every mounted component as if the server sent it.
+#[wasm_bindgen(js_name = injectFrame)]
+pub fn inject_frame(frame: String) {
+    if let Some(event) = ChatEvent::from_frame(&frame) {
+        EventBus::dispatcher().send(Request::EventBusMsg(event));
+    }
+}
+
+#[wasm_bindgen(js_name = injectUsers)]
+pub fn inject_users(names: Box<[JsValue]>) {
+    let names = names.iter().filter_map(|n| n.as_string()).collect();
+    inject_frame(WebSocketMessage::with_data_array(MsgTypes::Users, names).to_json());
+}
+
+#[wasm_bindgen(js_name = injectMessage)]
+pub fn inject_message(from: String, message: String, time: f64) {
+    let data = MessageData {
+        id: 0,
+        from,
+        message,
+        time: time as u64,
+        channel: DEFAULT_CHANNEL.into(),
+        edited_at: 0,
+        history: Vec::new(),
+        reply_to: None,
+        attachment: None,
+        tasks_open: false,
+    };
+    inject_frame(
+        WebSocketMessage::with_data(MsgTypes::Message, serde_json::to_string(&data).unwrap())
+            .to_json(),
+    );
+}
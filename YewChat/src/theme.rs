@@ -0,0 +1,176 @@
+use serde::{Deserialize, Serialize};
+use yew::prelude::*;
+
+use crate::store::use_store;
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Theme {
+    Light,
+    Dark,
+    /// Black on white with solid borders, meeting WCAG AAA (7:1) for text.
+    HighContrast,
+}
+
+/// Tailwind classes for every themed surface. Components pick from here instead of matching
+/// on `Theme` themselves.
+#[derive(PartialEq, Debug)]
+pub struct ThemeClasses {
+    pub main_bg: &'static str,
+    pub main_text: &'static str,
+    pub border: &'static str,
+    pub panel_bg: &'static str,
+    pub item_bg: &'static str,
+    pub muted_text: &'static str,
+    pub secondary_text: &'static str,
+    pub bubble_bg: &'static str,
+    pub input_bg: &'static str,
+    pub input_text: &'static str,
+    pub button_bg: &'static str,
+    pub popover_bg: &'static str,
+    pub hover_bg: &'static str,
+    /// Highlighted row in a list or menu.
+    pub selected_bg: &'static str,
+}
+
+const LIGHT: ThemeClasses = ThemeClasses {
+    main_bg: "bg-white",
+    main_text: "text-gray-800",
+    border: "border-gray-300",
+    panel_bg: "bg-gray-100",
+    item_bg: "bg-white",
+    // Gray-400 and -500 fell below 4.5:1 on the gray panels.
+    muted_text: "text-gray-600",
+    secondary_text: "text-gray-600",
+    bubble_bg: "bg-gray-100",
+    input_bg: "bg-gray-100",
+    input_text: "focus:text-gray-700",
+    button_bg: "bg-gray-200",
+    popover_bg: "bg-white border-gray-300",
+    hover_bg: "hover:bg-gray-100",
+    selected_bg: "bg-gray-200",
+};
+
+const DARK: ThemeClasses = ThemeClasses {
+    main_bg: "bg-gray-800",
+    main_text: "text-gray-100",
+    border: "border-gray-600",
+    panel_bg: "bg-gray-700",
+    item_bg: "bg-gray-600",
+    muted_text: "text-gray-300",
+    secondary_text: "text-gray-300",
+    bubble_bg: "bg-gray-700",
+    input_bg: "bg-gray-700",
+    input_text: "text-gray-100 placeholder-gray-400 focus:text-gray-100",
+    button_bg: "bg-gray-600 hover:bg-gray-500",
+    popover_bg: "bg-gray-700 border-gray-600",
+    hover_bg: "hover:bg-gray-600",
+    selected_bg: "bg-gray-600",
+};
+
+const HIGH_CONTRAST: ThemeClasses = ThemeClasses {
+    main_bg: "bg-white",
+    main_text: "text-black",
+    border: "border-black",
+    panel_bg: "bg-white",
+    item_bg: "bg-white border border-black",
+    muted_text: "text-gray-800",
+    secondary_text: "text-gray-800",
+    bubble_bg: "bg-white border border-black",
+    input_bg: "bg-white border border-black",
+    input_text: "text-black placeholder-gray-700 focus:text-black",
+    button_bg: "bg-white border border-black hover:bg-yellow-200",
+    popover_bg: "bg-white border-black",
+    hover_bg: "hover:bg-yellow-200",
+    selected_bg: "bg-yellow-200",
+};
+
+/// Name colors, readable on the light background.
+const LIGHT_NAMES: [&str; 8] = [
+    "text-red-700",
+    "text-orange-700",
+    "text-amber-700",
+    "text-green-700",
+    "text-teal-700",
+    "text-sky-700",
+    "text-indigo-700",
+    "text-fuchsia-700",
+];
+
+/// The same hues, lighter so they hold up on the dark background.
+const DARK_NAMES: [&str; 8] = [
+    "text-red-300",
+    "text-orange-300",
+    "text-amber-300",
+    "text-green-300",
+    "text-teal-300",
+    "text-sky-300",
+    "text-indigo-300",
+    "text-fuchsia-300",
+];
+
+/// The darkest shades, all above 7:1 on white.
+const HIGH_CONTRAST_NAMES: [&str; 8] = [
+    "text-red-900",
+    "text-orange-900",
+    "text-amber-900",
+    "text-green-900",
+    "text-teal-900",
+    "text-sky-900",
+    "text-indigo-900",
+    "text-fuchsia-900",
+];
+
+impl Theme {
+    pub const ALL: [Theme; 3] = [Theme::Light, Theme::Dark, Theme::HighContrast];
+
+    pub fn classes(&self) -> &'static ThemeClasses {
+        match self {
+            Theme::Light => &LIGHT,
+            Theme::Dark => &DARK,
+            Theme::HighContrast => &HIGH_CONTRAST,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Theme::Light => "Light",
+            Theme::Dark => "Dark",
+            Theme::HighContrast => "High contrast",
+        }
+    }
+
+    /// Stable color for a username: the same name always lands on the same hue, in either theme.
+    pub fn name_color(&self, name: &str) -> &'static str {
+        // FNV-1a, so the color doesn't change between builds or sessions.
+        let hash = name.bytes().fold(0x811c_9dc5_u32, |hash, byte| {
+            (hash ^ byte as u32).wrapping_mul(0x0100_0193)
+        });
+        let palette = match self {
+            Theme::Light => &LIGHT_NAMES,
+            Theme::Dark => &DARK_NAMES,
+            Theme::HighContrast => &HIGH_CONTRAST_NAMES,
+        };
+        palette[hash as usize % palette.len()]
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct ThemeProviderProps {
+    pub children: Children,
+}
+
+/// Publishes the theme from the user's preferences to everything below it.
+#[function_component(ThemeProvider)]
+pub fn theme_provider(props: &ThemeProviderProps) -> Html {
+    let theme = use_store().preferences.theme.clone();
+
+    html! {
+        <ContextProvider<Theme> context={theme}>
+            { for props.children.iter() }
+        </ContextProvider<Theme>>
+    }
+}
+
+pub fn use_theme() -> Theme {
+    use_context::<Theme>().expect("ThemeProvider to be mounted")
+}
@@ -0,0 +1,193 @@
+use js_sys::Intl::DateTimeFormat;
+use js_sys::{Array, Date, Function, Object, Reflect};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::{JsCast, JsValue};
+use yew::prelude::*;
+
+use crate::store::use_store;
+
+/// Zones offered when the browser can't list its own.
+const COMMON_TIMEZONES: [&str; 12] = [
+    "UTC",
+    "America/Los_Angeles",
+    "America/Denver",
+    "America/Chicago",
+    "America/New_York",
+    "America/Sao_Paulo",
+    "Europe/London",
+    "Europe/Berlin",
+    "Asia/Kolkata",
+    "Asia/Jakarta",
+    "Asia/Tokyo",
+    "Australia/Sydney",
+];
+
+/// 12- or 24-hour clock for displayed times.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Clock {
+    #[serde(rename = "12h")]
+    TwelveHour,
+    #[serde(rename = "24h")]
+    TwentyFourHour,
+}
+
+impl Clock {
+    /// What the browser's locale uses.
+    pub fn from_locale() -> Self {
+        thread_local! {
+            static LOCALE_CLOCK: Clock = {
+                let options = Object::new();
+                let _ = Reflect::set(&options, &"hour".into(), &"numeric".into());
+                let resolved = DateTimeFormat::new(&Array::new(), &options).resolved_options();
+                let cycle = Reflect::get(&resolved, &"hourCycle".into())
+                    .ok()
+                    .and_then(|c| c.as_string());
+                match cycle.as_deref() {
+                    Some("h11") | Some("h12") => Clock::TwelveHour,
+                    _ => Clock::TwentyFourHour,
+                }
+            };
+        }
+        LOCALE_CLOCK.with(|clock| *clock)
+    }
+
+    /// Locale whose formatting to borrow, JS `hourCycle`, and the style of the hour field.
+    fn style(self) -> (&'static str, &'static str, &'static str) {
+        match self {
+            Clock::TwelveHour => ("en-US", "h12", "numeric"),
+            Clock::TwentyFourHour => ("en-GB", "h23", "2-digit"),
+        }
+    }
+}
+
+/// How timestamps are displayed, from the user's preferences.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TimeFormat {
+    /// IANA zone such as `Europe/Berlin`; `None` follows the browser.
+    pub timezone: Option<String>,
+    /// `None` follows the browser's locale.
+    pub clock: Option<Clock>,
+}
+
+impl TimeFormat {
+    /// Formats a unix timestamp in milliseconds as `HH:MM`, or `h:MM AM` on a 12-hour clock.
+    /// Frames from older servers carry no timestamp (`0`) and format as an empty string.
+    pub fn time(&self, ms: u64) -> String {
+        let (locale, _, hour) = self.clock().style();
+        self.format(ms, locale, &[("hour", hour), ("minute", "2-digit")])
+    }
+
+    /// Like `time`, with seconds.
+    pub fn time_exact(&self, ms: u64) -> String {
+        let (locale, _, hour) = self.clock().style();
+        self.format(
+            ms,
+            locale,
+            &[("hour", hour), ("minute", "2-digit"), ("second", "2-digit")],
+        )
+    }
+
+    /// Date and time in the browser's format, for tooltips.
+    pub fn date_time(&self, ms: u64) -> String {
+        self.format(
+            ms,
+            "default",
+            &[("dateStyle", "medium"), ("timeStyle", "medium")],
+        )
+    }
+
+    pub fn clock(&self) -> Clock {
+        self.clock.unwrap_or_else(Clock::from_locale)
+    }
+
+    fn format(&self, ms: u64, locale: &str, fields: &[(&str, &str)]) -> String {
+        if ms == 0 {
+            return String::new();
+        }
+        let options = Object::new();
+        let set = |key: &str, value: &str| {
+            let _ = Reflect::set(&options, &key.into(), &value.into());
+        };
+        for (key, value) in fields {
+            set(key, value);
+        }
+        set("hourCycle", self.clock().style().1);
+        if let Some(timezone) = &self.timezone {
+            set("timeZone", timezone);
+        }
+        Date::new(&JsValue::from_f64(ms as f64))
+            .to_locale_string(locale, &options)
+            .into()
+    }
+}
+
+/// Zones the user can pick from, as IANA names.
+pub fn timezones() -> Vec<String> {
+    let supported = Reflect::get(&js_sys::global(), &"Intl".into())
+        .and_then(|intl| {
+            let list = Reflect::get(&intl, &"supportedValuesOf".into())?;
+            list.dyn_into::<Function>()?
+                .call1(&intl, &"timeZone".into())
+        })
+        .ok()
+        .filter(|zones| Array::is_array(zones));
+    match supported {
+        Some(zones) => Array::from(&zones)
+            .iter()
+            .filter_map(|zone| zone.as_string())
+            .collect(),
+        None => COMMON_TIMEZONES.iter().map(|z| z.to_string()).collect(),
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct TimeFormatProviderProps {
+    pub children: Children,
+}
+
+/// Publishes the time format from the user's preferences, so timestamps re-render only when
+/// it changes.
+#[function_component(TimeFormatProvider)]
+pub fn time_format_provider(props: &TimeFormatProviderProps) -> Html {
+    let format = use_store().preferences.time_format.clone();
+
+    html! {
+        <ContextProvider<TimeFormat> context={format}>
+            { for props.children.iter() }
+        </ContextProvider<TimeFormat>>
+    }
+}
+
+pub fn use_time_format() -> TimeFormat {
+    use_context::<TimeFormat>().expect("TimeFormatProvider to be mounted")
+}
+
+/// Minutes since local midnight, right now.
+pub fn local_minute_of_day() -> u32 {
+    let date = Date::new_0();
+    date.get_hours() * 60 + date.get_minutes()
+}
+
+/// Parses `HH:MM`, as produced by `<input type="time">`, into minutes since midnight.
+pub fn parse_minute_of_day(value: &str) -> Option<u32> {
+    let (hours, minutes) = value.split_once(':')?;
+    let (hours, minutes): (u32, u32) = (hours.parse().ok()?, minutes.parse().ok()?);
+    (hours < 24 && minutes < 60).then_some(hours * 60 + minutes)
+}
+
+/// Formats minutes since midnight as `HH:MM`.
+pub fn format_minute_of_day(minute: u32) -> String {
+    format!("{:02}:{:02}", minute / 60, minute % 60)
+}
+
+/// How long ago something happened, roughly, e.g. "5 min ago"; under a minute is "just now".
+pub fn ago(elapsed_ms: u64) -> String {
+    let minutes = elapsed_ms / 60_000;
+    match minutes {
+        0 => "just now".into(),
+        1..=59 => format!("{} min ago", minutes),
+        60..=1439 => format!("{} h ago", minutes / 60),
+        _ => format!("{} d ago", minutes / 1440),
+    }
+}
@@ -0,0 +1,7 @@
+use web_sys::TouchEvent;
+
+/// Client coordinates of the first finger still on the screen, if any.
+pub fn point(e: &TouchEvent) -> Option<(i32, i32)> {
+    let touch = e.touches().get(0)?;
+    Some((touch.client_x(), touch.client_y()))
+}
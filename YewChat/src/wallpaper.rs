@@ -0,0 +1,91 @@
+//! Backgrounds for the message pane: a few bundled patterns drawn in CSS, or an image of
+//! the user's choosing.
+
+use serde::{Deserialize, Serialize};
+
+use crate::theme::Theme;
+
+#[derive(Clone, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum Wallpaper {
+    #[default]
+    None,
+    Dots,
+    Grid,
+    Stripes,
+    /// An `http(s)` image URL; see [`is_image_url`].
+    Image(String),
+}
+
+impl Wallpaper {
+    /// The bundled choices, in the order settings offers them; an image comes after.
+    pub const PATTERNS: [Wallpaper; 4] = [
+        Wallpaper::None,
+        Wallpaper::Dots,
+        Wallpaper::Grid,
+        Wallpaper::Stripes,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Wallpaper::None => "None",
+            Wallpaper::Dots => "Dots",
+            Wallpaper::Grid => "Grid",
+            Wallpaper::Stripes => "Stripes",
+            Wallpaper::Image(_) => "Image from a link",
+        }
+    }
+
+    /// The pane's `style` in `theme`, with the wallpaper faded `dim` percent of the way to
+    /// the page colour so text on it stays readable. `None` for no wallpaper, and always in
+    /// high contrast, which keeps a plain background.
+    pub fn style(&self, theme: &Theme, dim: u8) -> Option<String> {
+        let (page, ink) = match theme {
+            Theme::Light => ("255, 255, 255", "0, 0, 0"),
+            Theme::Dark => ("31, 41, 55", "255, 255, 255"),
+            Theme::HighContrast => return None,
+        };
+        let (layer, size) = match self {
+            Wallpaper::None => return None,
+            Wallpaper::Dots => (
+                format!(
+                    "radial-gradient(rgba({}, 0.25) 1px, transparent 1.5px)",
+                    ink
+                ),
+                "16px 16px",
+            ),
+            Wallpaper::Grid => (
+                format!(
+                    "linear-gradient(rgba({0}, 0.12) 1px, transparent 1px), \
+                     linear-gradient(90deg, rgba({0}, 0.12) 1px, transparent 1px)",
+                    ink
+                ),
+                // One size for each of the two layers.
+                "24px 24px, 24px 24px",
+            ),
+            Wallpaper::Stripes => (
+                format!(
+                    "repeating-linear-gradient(45deg, rgba({0}, 0.08) 0 8px, transparent 8px 16px)",
+                    ink
+                ),
+                "auto",
+            ),
+            Wallpaper::Image(url) if is_image_url(url) => (format!("url(\"{}\")", url), "cover"),
+            Wallpaper::Image(_) => return None,
+        };
+        let fade = dim.min(100) as f64 / 100.0;
+        Some(format!(
+            "background: linear-gradient(rgba({0}, {1}), rgba({0}, {1})), {2}; \
+             background-size: auto, {3}; background-position: center",
+            page, fade, layer, size
+        ))
+    }
+}
+
+/// Whether `url` can be used as a wallpaper: `http(s)`, and nothing that could end the CSS
+/// `url("…")` it goes in.
+pub fn is_image_url(url: &str) -> bool {
+    (url.starts_with("https://") || url.starts_with("http://"))
+        && !url
+            .chars()
+            .any(|c| matches!(c, '"' | '\'' | '\\' | '(' | ')' | ';') || c.is_whitespace())
+}
@@ -0,0 +1,222 @@
+// Service worker: caches the app shell so the app can start offline.
+// Also shows Web Push notifications and sends failed messages once the network is back.
+
+const CACHE = 'yewchat-shell-v1';
+const SYNC_TAG = 'yewchat-outbox';
+// How long to wait for the server to echo the messages sent from here.
+const ECHO_TIMEOUT_MS = 15000;
+const SHELL = [
+    '/',
+    '/index.html',
+    '/yewchat.js',
+    '/yewchat_bg.wasm',
+    '/worker.js',
+    '/shared_worker.js',
+    '/emoji.json',
+    '/config.json',
+    '/manifest.webmanifest',
+    '/icon.svg',
+];
+
+self.addEventListener('install', (event) => {
+    event.waitUntil(caches.open(CACHE).then((cache) => cache.addAll(SHELL)));
+    self.skipWaiting();
+});
+
+self.addEventListener('activate', (event) => {
+    event.waitUntil(
+        caches
+            .keys()
+            .then((keys) => Promise.all(keys.filter((k) => k !== CACHE).map((k) => caches.delete(k))))
+            .then(() => self.clients.claim())
+    );
+});
+
+// Network first so deploys are picked up immediately; fall back to the cache when offline.
+self.addEventListener('fetch', (event) => {
+    const { request } = event;
+    if (request.method !== 'GET' || new URL(request.url).origin !== self.location.origin) {
+        return;
+    }
+    event.respondWith(
+        fetch(request)
+            .then((response) => {
+                const copy = response.clone();
+                caches.open(CACHE).then((cache) => cache.put(request, copy));
+                return response;
+            })
+            .catch(() =>
+                caches.match(request).then((cached) => cached || caches.match('/index.html'))
+            )
+    );
+});
+
+// Mentions pushed while no tab is open; the payload is a `PushNotice`.
+self.addEventListener('push', (event) => {
+    if (!event.data) {
+        return;
+    }
+    const notice = event.data.json();
+    const url = `/channel/${encodeURIComponent(notice.channel)}?msg=${notice.messageId}`;
+    event.waitUntil(
+        self.registration.showNotification(notice.title, {
+            body: notice.body,
+            icon: '/icon.svg',
+            tag: `yewchat-${notice.messageId}`,
+            data: { url },
+        })
+    );
+});
+
+// Focuses an open tab if there is one, otherwise opens the mentioned message.
+self.addEventListener('notificationclick', (event) => {
+    event.notification.close();
+    const { url } = event.notification.data;
+    event.waitUntil(
+        self.clients.matchAll({ type: 'window', includeUncontrolled: true }).then((windows) => {
+            const open = windows.find((w) => 'focus' in w);
+            return open ? open.focus() : self.clients.openWindow(url);
+        })
+    );
+});
+
+// Failed sends handed over by the tabs (see `background_sync.rs`), kept in IndexedDB:
+// `pending` is the latest outbox, `delivered` the ids sent from here that no tab has
+// dropped yet.
+function db() {
+    return new Promise((resolve, reject) => {
+        const request = indexedDB.open('yewchat', 1);
+        request.onupgradeneeded = () => request.result.createObjectStore('outbox');
+        request.onsuccess = () => resolve(request.result);
+        request.onerror = () => reject(request.error);
+    });
+}
+
+async function load(key) {
+    const database = await db();
+    return new Promise((resolve, reject) => {
+        const request = database.transaction('outbox').objectStore('outbox').get(key);
+        request.onsuccess = () => resolve(request.result);
+        request.onerror = () => reject(request.error);
+    });
+}
+
+async function save(key, value) {
+    const database = await db();
+    return new Promise((resolve, reject) => {
+        const transaction = database.transaction('outbox', 'readwrite');
+        transaction.objectStore('outbox').put(value, key);
+        transaction.oncomplete = () => resolve();
+        transaction.onerror = () => reject(transaction.error);
+    });
+}
+
+self.addEventListener('message', (event) => {
+    let message;
+    try {
+        message = JSON.parse(event.data);
+    } catch {
+        return;
+    }
+    if (message.type === 'outbox') {
+        event.waitUntil(keep(message));
+    } else if (message.type === 'delivered') {
+        event.waitUntil(
+            load('delivered').then((ids) =>
+                event.source.postMessage(JSON.stringify({ type: 'delivered', ids: ids || [] }))
+            )
+        );
+    }
+});
+
+// A tab restored from localStorage may still list messages sent from here; those are left
+// out until it has dropped them.
+async function keep(outbox) {
+    const delivered = (await load('delivered')) || [];
+    const listed = (id) => outbox.frames.some((f) => f.id === id);
+    await save('delivered', delivered.filter(listed));
+    outbox.frames = outbox.frames.filter((f) => !delivered.includes(f.id));
+    await save('pending', outbox);
+    if (outbox.frames.length && self.registration.sync) {
+        await self.registration.sync.register(SYNC_TAG);
+    }
+}
+
+self.addEventListener('sync', (event) => {
+    if (event.tag === SYNC_TAG) {
+        event.waitUntil(flush());
+    }
+});
+
+async function flush() {
+    const tabs = await self.clients.matchAll({ type: 'window' });
+    if (tabs.length) {
+        tabs.forEach((tab) => tab.postMessage(JSON.stringify({ type: 'flush' })));
+        return;
+    }
+    const pending = await load('pending');
+    if (!pending || !pending.frames.length) {
+        return;
+    }
+    const { sent, refused } = await deliver(pending);
+    await save('delivered', ((await load('delivered')) || []).concat(sent));
+    // Refused frames would be refused again; the tab shows them as failed when it's back.
+    pending.frames = pending.frames.filter((f) => !sent.includes(f.id) && !refused.includes(f.id));
+    await save('pending', pending);
+    if (pending.frames.length) {
+        // Failing the sync makes the browser try again later.
+        throw new Error(`${pending.frames.length} messages still unsent`);
+    }
+}
+
+// Sends the frames as `user` and resolves with the ids the server echoed back, and those it
+// refused.
+function deliver({ user, serverUrl, frames }) {
+    return new Promise((resolve) => {
+        const waiting = frames.slice();
+        const sent = [];
+        const refused = [];
+        const socket = new WebSocket(serverUrl);
+        const done = () => {
+            clearTimeout(timer);
+            socket.close();
+            resolve({ sent, refused });
+        };
+        const timer = setTimeout(done, ECHO_TIMEOUT_MS);
+        socket.onopen = () => {
+            socket.send(JSON.stringify({ messageType: 'register', dataArray: null, data: user }));
+            frames.forEach((f) => socket.send(f.frame));
+        };
+        const settle = (i, into) => {
+            if (i >= 0) {
+                into.push(waiting.splice(i, 1)[0].id);
+                if (!waiting.length) {
+                    done();
+                }
+            }
+        };
+        socket.onmessage = (event) => {
+            let data;
+            try {
+                const frame = JSON.parse(event.data);
+                if (frame.messageType === 'error') {
+                    settle(waiting.findIndex((f) => f.id === frame.outgoingId), refused);
+                    return;
+                }
+                if (frame.messageType !== 'message') {
+                    return;
+                }
+                data = JSON.parse(frame.data);
+            } catch {
+                return;
+            }
+            settle(
+                waiting.findIndex(
+                    (f) => data.from === user && f.channel === data.channel && f.message === data.message
+                ),
+                sent
+            );
+        };
+        socket.onerror = done;
+    });
+}
@@ -0,0 +1,66 @@
+mod common;
+
+use common::message_data;
+use wasm_bindgen_test::*;
+use yewchat::components::announcer::announcement;
+use yewchat_protocol::{Attachment, DEFAULT_CHANNEL};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+fn messages_are_read_as_who_said_what() {
+    let m = message_data("alice", "hello there");
+    assert_eq!(announcement(&m, DEFAULT_CHANNEL), "alice said: hello there");
+}
+
+#[wasm_bindgen_test]
+fn messages_elsewhere_say_which_channel() {
+    let mut m = message_data("alice", "hi");
+    m.channel = "random".into();
+    assert_eq!(
+        announcement(&m, DEFAULT_CHANNEL),
+        "alice in #random said: hi"
+    );
+}
+
+#[wasm_bindgen_test]
+fn attachments_are_described() {
+    let mut m = message_data("bob", "");
+    m.attachment = Some(Attachment::Contact {
+        name: "carol".into(),
+    });
+    assert_eq!(
+        announcement(&m, DEFAULT_CHANNEL),
+        "bob sent carol's contact card"
+    );
+}
+
+#[wasm_bindgen_test]
+fn long_messages_are_cut_short() {
+    let m = message_data("alice", &"a".repeat(500));
+    let spoken = announcement(&m, DEFAULT_CHANNEL);
+    assert!(spoken.ends_with('…'));
+    assert!(spoken.chars().count() < 250);
+}
+
+#[wasm_bindgen_test]
+fn spoiler_images_keep_their_description_to_themselves() {
+    let mut m = message_data("bob", "");
+    let image = |spoiler| Attachment::Image {
+        url: "data:image/png;base64,AAAA".into(),
+        width: 1,
+        height: 1,
+        alt: Some("the twist ending".into()),
+        spoiler,
+    };
+    m.attachment = Some(image(false));
+    assert_eq!(
+        announcement(&m, DEFAULT_CHANNEL),
+        "bob sent an image, the twist ending"
+    );
+    m.attachment = Some(image(true));
+    assert_eq!(
+        announcement(&m, DEFAULT_CHANNEL),
+        "bob sent an image marked as a spoiler"
+    );
+}
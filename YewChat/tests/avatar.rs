@@ -0,0 +1,35 @@
+use wasm_bindgen_test::*;
+use yewchat::services::avatar::{initials, initials_url, remote_url, retry_delay};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+fn initials_come_from_the_first_two_words() {
+    assert_eq!(initials("alice"), "A");
+    assert_eq!(initials("ada lovelace"), "AL");
+    assert_eq!(initials("grace_brewster_hopper"), "GB");
+    assert_eq!(initials("__"), "?");
+}
+
+#[wasm_bindgen_test]
+fn a_name_always_gets_the_same_fallback() {
+    assert_eq!(initials_url("alice"), initials_url("alice"));
+    assert_ne!(initials_url("alice"), initials_url("bob"));
+    assert!(initials_url("alice").starts_with("data:image/svg+xml"));
+}
+
+#[wasm_bindgen_test]
+fn retries_back_off() {
+    assert_eq!(retry_delay(1), 2_000);
+    assert_eq!(retry_delay(2), 4_000);
+    assert!(retry_delay(3) > retry_delay(2));
+    assert_eq!(retry_delay(10), retry_delay(20));
+}
+
+#[wasm_bindgen_test]
+fn names_are_encoded_into_the_avatar_url() {
+    assert_eq!(
+        remote_url("ada lovelace?#1", 48),
+        "https://avatars.dicebear.com/api/adventurer-neutral/ada%20lovelace%3F%231.svg?size=48"
+    );
+}
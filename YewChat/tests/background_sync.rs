@@ -0,0 +1,18 @@
+use wasm_bindgen_test::*;
+use yewchat::services::background_sync::FromWorker;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+fn service_worker_messages_parse() {
+    assert_eq!(
+        FromWorker::parse(r#"{"type":"delivered","ids":[1,2]}"#),
+        Some(FromWorker::Delivered { ids: vec![1, 2] })
+    );
+    assert_eq!(
+        FromWorker::parse(r#"{"type":"flush"}"#),
+        Some(FromWorker::Flush)
+    );
+    assert_eq!(FromWorker::parse(r#"{"type":"push"}"#), None);
+    assert_eq!(FromWorker::parse("not json"), None);
+}
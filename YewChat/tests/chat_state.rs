@@ -0,0 +1,860 @@
+mod common;
+
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use wasm_bindgen_test::*;
+use yew::Reducible;
+use yewchat::services::event_bus::{ChatEvent, ConnectionState};
+use yewchat::services::socket::SocketError;
+use yewchat::services::tab_sync::TabMessage;
+use yewchat::state::{
+    ChatAction, ChatState, CooldownCause, Outgoing, Presence, QuietHours, SendError, Unread,
+    UserProfile, ACK_TIMEOUT_MS, LOCAL_SENDER, SEND_HOLD_MS, SYSTEM_LINES_KEPT,
+};
+use yewchat::theme::Theme;
+use yewchat_protocol::{
+    AuditEntry, ChannelInfo, HistoryPage, InviteInfo, MentionSuppression, MessageData,
+    ModerationAction, MsgTypes, Revision, Role, SessionInfo, Status, Stroke, SystemEvent,
+    TypingInfo, UserInfo, WebSocketMessage, Whiteboard,
+};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+fn users_frame_replaces_user_list() {
+    let mut state = ChatState::default();
+    assert!(common::apply(&mut state, &common::users_frame(&["alice", "bob"])));
+    assert!(common::apply(&mut state, &common::users_frame(&["bob"])));
+
+    let names: Vec<_> = state.users.iter().map(|u| u.name.as_str()).collect();
+    assert_eq!(names, ["bob"]);
+    assert_eq!(state.presence["alice"], Presence::Offline);
+    assert_eq!(state.presence["bob"], Presence::Online);
+}
+
+#[wasm_bindgen_test]
+fn the_panels_stay_loading_until_a_user_list_arrives() {
+    let mut state = ChatState::default();
+    common::apply(&mut state, &common::message_frame("alice", "early"));
+    assert!(!state.users_loaded);
+    common::apply(&mut state, &common::users_frame(&[]));
+    assert!(state.users_loaded);
+}
+
+#[wasm_bindgen_test]
+fn message_frame_is_appended() {
+    let mut state = ChatState::default();
+    common::apply(&mut state, &common::users_frame(&["alice"]));
+    assert!(common::apply(&mut state, &common::message_frame("alice", "hello")));
+    assert!(common::apply(&mut state, &common::message_frame("alice", "again")));
+
+    assert_eq!(state.messages.len(), 2);
+    assert_eq!(*state.messages[0], common::message_data("alice", "hello"));
+}
+
+#[wasm_bindgen_test]
+fn profiles_outlive_user_list_and_unknown_senders_get_placeholders() {
+    let mut state = ChatState::default();
+    common::apply(&mut state, &common::users_frame(&["alice"]));
+    common::apply(&mut state, &common::users_frame(&[]));
+
+    assert!(state.users.is_empty());
+    assert!(state.profile("alice").known);
+    assert_eq!(state.profile("mallory"), UserProfile::placeholder("mallory"));
+}
+
+#[wasm_bindgen_test]
+fn malformed_frames_become_errors_without_changing_state() {
+    let mut state = ChatState::default();
+    assert!(!common::apply(&mut state, "not json"));
+    assert!(!common::apply(&mut state, r#"{"messageType":"message","data":"{}"}"#));
+    assert!(state.messages.is_empty());
+}
+
+#[wasm_bindgen_test]
+fn connection_changes_are_tracked() {
+    let mut state = ChatState::default();
+    assert_eq!(state.connection, ConnectionState::Connecting);
+    state.handle_event(ChatEvent::ConnectionChanged(ConnectionState::Connected));
+    assert_eq!(state.connection, ConnectionState::Connected);
+}
+
+#[wasm_bindgen_test]
+fn heartbeat_answers_time_the_connection_until_it_drops() {
+    let mut state = ChatState::default();
+    state.handle_event(ChatEvent::ConnectionChanged(ConnectionState::Connected));
+    assert_eq!(state.latency, None);
+
+    let sent_at = js_sys::Date::now() as u64 - 120;
+    let pong = WebSocketMessage::with_data(MsgTypes::Ping, sent_at.to_string());
+    common::apply(&mut state, &pong.to_json());
+    assert!(state.latency.is_some_and(|ms| ms >= 120));
+
+    state.handle_event(ChatEvent::ConnectionChanged(ConnectionState::Disconnected));
+    assert_eq!(state.latency, None);
+}
+
+#[wasm_bindgen_test]
+fn typing_notices_last_until_the_message_or_the_timeout() {
+    let mut state = ChatState::default();
+    state.me = "me".into();
+    let typing = |name: &str, channel: &str| {
+        let info = TypingInfo { name: name.into(), channel: channel.into() };
+        ChatEvent::Typing(info)
+    };
+    assert!(!state.handle_event(typing("me", "general")));
+    state.handle_event(typing("bob", "general"));
+    state.handle_event(typing("alice", "general"));
+    state.handle_event(typing("carol", "random"));
+    assert_eq!(state.typing_in("general"), vec!["alice", "bob"]);
+
+    common::apply(&mut state, &common::message_frame("alice", "hi"));
+    assert_eq!(state.typing_in("general"), vec!["bob"]);
+
+    let expiry = state.next_typing_expiry().unwrap();
+    let state = Rc::new(state).reduce(ChatAction::ExpireTyping(expiry));
+    assert!(state.typing.is_empty());
+}
+
+#[wasm_bindgen_test]
+fn session_lists_replace_the_last_and_sign_outs_are_kept() {
+    let mut state = ChatState::default();
+    let sessions = vec![SessionInfo {
+        id: "abc".into(),
+        device: "Firefox on Linux".into(),
+        connected_at: 1,
+        last_active: 2,
+        current: true,
+    }];
+    let data = serde_json::to_string(&sessions).unwrap();
+    let frame = WebSocketMessage::with_data(MsgTypes::Sessions, data);
+    assert!(common::apply(&mut state, &frame.to_json()));
+    assert_eq!(*state.sessions, sessions);
+
+    assert!(!state.signed_out);
+    common::apply(&mut state, &WebSocketMessage::new(MsgTypes::SignOut).to_json());
+    assert!(state.signed_out);
+}
+
+#[wasm_bindgen_test]
+fn ping_reports_its_round_trip_locally() {
+    let mut state = ChatState::default();
+    for connection in [
+        ConnectionState::Connected,
+        ConnectionState::Disconnected,
+        ConnectionState::Connecting,
+        ConnectionState::Connected,
+    ] {
+        state.handle_event(ChatEvent::ConnectionChanged(connection));
+    }
+    let sent_at = js_sys::Date::now() as u64 - 80;
+    let mut state = Rc::new(state).reduce(ChatAction::Ping(sent_at));
+
+    // Heartbeats answered meanwhile aren't the ping's answer.
+    Rc::make_mut(&mut state).handle_event(ChatEvent::Pong(sent_at - 5_000));
+    assert!(state.messages.is_empty());
+
+    Rc::make_mut(&mut state).handle_event(ChatEvent::Pong(sent_at));
+    assert_eq!(state.ping, None);
+    assert_eq!(state.messages.len(), 1);
+    let note = &state.messages[0];
+    assert_eq!((note.id, note.from.as_str()), (0, LOCAL_SENDER));
+    assert_eq!(note.channel, state.channel);
+    assert!(note.message.contains("1 reconnect this session"));
+}
+
+#[wasm_bindgen_test]
+fn the_first_connection_is_not_a_reconnect() {
+    let mut state = ChatState::default();
+    state.handle_event(ChatEvent::ConnectionChanged(ConnectionState::Connecting));
+    state.handle_event(ChatEvent::ConnectionChanged(ConnectionState::Connected));
+    assert_eq!(state.reconnects, 0);
+}
+
+#[wasm_bindgen_test]
+fn selecting_emoji_appends_to_draft_and_closes_picker() {
+    let mut state = ChatState::default();
+    state.draft = "hi ".into();
+    state.toggle_emoji_picker();
+    assert!(state.show_emoji_picker);
+
+    state.select_emoji("👋");
+    assert_eq!(state.draft, "hi 👋");
+    assert!(!state.show_emoji_picker);
+}
+
+#[wasm_bindgen_test]
+fn take_draft_empties_composer() {
+    let mut state = ChatState::default();
+    assert_eq!(state.take_draft(), None);
+
+    state.draft = "hello".into();
+    assert_eq!(state.take_draft().as_deref(), Some("hello"));
+    assert!(state.draft.is_empty());
+}
+
+#[wasm_bindgen_test]
+fn theme_toggles_between_light_and_dark() {
+    let mut state = ChatState::default();
+    assert_eq!(state.preferences.theme, Theme::Light);
+    state.toggle_theme();
+    assert_eq!(state.preferences.theme, Theme::Dark);
+    state.toggle_theme();
+    assert_eq!(state.preferences.theme, Theme::Light);
+
+    state.preferences.theme = Theme::HighContrast;
+    state.toggle_theme();
+    assert_eq!(state.preferences.theme, Theme::Light);
+}
+
+#[wasm_bindgen_test]
+fn only_moderators_can_post_in_announcement_channels() {
+    let mut state = ChatState::default();
+    common::apply(
+        &mut state,
+        &common::users_with_roles_frame(&[
+            UserInfo { name: "alice".into(), role: Role::Moderator, status: Status::Online, activity: None, bot: false, verified: false },
+            UserInfo { name: "bob".into(), role: Role::Member, status: Status::Online, activity: None, bot: false, verified: false },
+        ]),
+    );
+    common::apply(
+        &mut state,
+        &common::channels_frame(&[
+            ChannelInfo { name: "general".into(), announcement: false, ..Default::default() },
+            ChannelInfo { name: "announcements".into(), announcement: true, ..Default::default() },
+        ]),
+    );
+    assert!(state.can_post("bob"));
+
+    state.switch_channel("announcements".into());
+    assert!(state.can_post("alice"));
+    assert!(!state.can_post("bob"));
+}
+
+#[wasm_bindgen_test]
+fn slow_mode_counts_from_our_latest_message_in_the_channel() {
+    let mut state = ChatState::default();
+    state.me = "bob".into();
+    common::apply(
+        &mut state,
+        &common::channels_frame(&[ChannelInfo {
+            name: "general".into(),
+            slow_mode: Some(30),
+            ..Default::default()
+        }]),
+    );
+    assert_eq!(state.slow_mode_cooldown(), None);
+
+    common::apply(&mut state, &common::message_frame("alice", "hi"));
+    assert_eq!(state.slow_mode_cooldown(), None);
+    common::apply(&mut state, &common::message_frame("bob", "hello"));
+    let cooldown = state.slow_mode_cooldown().unwrap();
+    assert_eq!(cooldown.until, 1_700_000_030_000);
+    assert_eq!(cooldown.seconds_left(1_700_000_000_001), 30);
+    assert_eq!(cooldown.seconds_left(1_700_000_029_500), 1);
+    assert_eq!(cooldown.seconds_left(1_700_000_030_000), 0);
+
+    common::apply(
+        &mut state,
+        &common::users_with_roles_frame(&[UserInfo {
+            name: "bob".into(),
+            role: Role::Moderator,
+            status: Status::Online,
+            activity: None,
+            bot: false,
+            verified: false,
+        }]),
+    );
+    assert_eq!(state.slow_mode_cooldown(), None);
+}
+
+#[wasm_bindgen_test]
+fn renamed_channels_take_their_messages_and_viewers_along() {
+    let mut state = ChatState::default();
+    common::apply(
+        &mut state,
+        &common::channels_frame(&[
+            ChannelInfo {
+                name: "general".into(),
+                ..Default::default()
+            },
+            ChannelInfo {
+                name: "dev".into(),
+                ..Default::default()
+            },
+        ]),
+    );
+    common::apply(&mut state, &common::message_in_frame("dev", "bob", "hi"));
+    state.switch_channel("dev".into());
+    state.preferences.muted.insert("dev".into(), None);
+
+    let renamed = SystemEvent::Renamed {
+        from: "dev".into(),
+        to: "backend".into(),
+    };
+    assert!(common::apply(
+        &mut state,
+        &common::system_frame(1_700_000_000_500, renamed.clone())
+    ));
+    assert_eq!(state.channel, "backend");
+    assert_eq!(state.channels[1].name, "backend");
+    assert_eq!(state.messages[0].channel, "backend");
+    assert!(state.preferences.is_muted("backend"));
+    assert_eq!(state.system[0].event, renamed);
+}
+
+#[wasm_bindgen_test]
+fn only_the_latest_system_lines_are_kept() {
+    let mut state = ChatState::default();
+    for i in 0..=SYSTEM_LINES_KEPT as u64 {
+        let user = format!("user{}", i);
+        common::apply(
+            &mut state,
+            &common::system_frame(i, SystemEvent::Joined { user }),
+        );
+    }
+    assert_eq!(state.system.len(), SYSTEM_LINES_KEPT);
+    assert_eq!(state.system[0].time, 1);
+}
+
+#[wasm_bindgen_test]
+fn server_rejections_surface_as_notices() {
+    let mut state = ChatState::default();
+    let frame = r#"{"messageType":"error","data":"only moderators can post in this channel"}"#;
+    assert!(common::apply(&mut state, frame));
+    assert_eq!(state.notice.as_deref(), Some("only moderators can post in this channel"));
+}
+
+#[wasm_bindgen_test]
+fn reaction_updates_replace_the_message_list() {
+    let mut state = ChatState::default();
+    common::apply(&mut state, &common::reactions_frame(1, &[("👍", &["alice", "bob"])]));
+    assert_eq!(state.reactions[&1][0].users, ["alice", "bob"]);
+
+    common::apply(&mut state, &common::reactions_frame(1, &[]));
+    assert!(!state.reactions.contains_key(&1));
+}
+
+#[wasm_bindgen_test]
+fn unread_mentions_are_counted_apart_from_messages() {
+    let mut state = ChatState::default();
+    state.me = "bob".into();
+    common::apply(&mut state, &common::message_in_frame("random", "alice", "hi all"));
+    common::apply(&mut state, &common::message_in_frame("random", "alice", "@bob look"));
+    common::apply(&mut state, &common::message_in_frame("random", "bob", "@bob note to self"));
+    common::apply(&mut state, &common::message_frame("alice", "@bob in view"));
+
+    assert_eq!(state.unread["random"], Unread { messages: 2, mentions: 1 });
+    assert!(!state.unread.contains_key("general"));
+
+    state.switch_channel("random".into());
+    assert_eq!(state.total_unread(), Unread::default());
+}
+
+#[wasm_bindgen_test]
+fn dnd_status_shows_in_presence_and_profiles() {
+    let mut state = ChatState::default();
+    common::apply(
+        &mut state,
+        &common::users_with_roles_frame(&[UserInfo {
+            name: "alice".into(),
+            role: Role::Member,
+            status: Status::Dnd,
+            activity: None,
+            bot: false,
+            verified: false,
+        }]),
+    );
+    assert_eq!(state.presence["alice"], Presence::DoNotDisturb);
+    assert_eq!(state.profile("alice").status, Status::Dnd);
+
+    common::apply(&mut state, &common::users_frame(&[]));
+    assert_eq!(state.presence["alice"], Presence::Offline);
+}
+
+#[wasm_bindgen_test]
+fn activities_follow_the_user_list() {
+    let mut state = ChatState::default();
+    let alice = |activity: Option<&str>| UserInfo {
+        name: "alice".into(),
+        role: Role::Member,
+        status: Status::Online,
+        activity: activity.map(String::from),
+        bot: false,
+        verified: false,
+    };
+    common::apply(
+        &mut state,
+        &common::users_with_roles_frame(&[alice(Some("Working on Tutorial 10"))]),
+    );
+    assert_eq!(
+        state.profile("alice").activity.as_deref(),
+        Some("Working on Tutorial 10")
+    );
+
+    common::apply(&mut state, &common::users_with_roles_frame(&[alice(None)]));
+    assert_eq!(state.profile("alice").activity, None);
+}
+
+#[wasm_bindgen_test]
+fn admins_moderate_and_badge_flags_reach_profiles() {
+    let mut state = ChatState::default();
+    common::apply(
+        &mut state,
+        &common::users_with_roles_frame(&[
+            UserInfo {
+                name: "root".into(),
+                role: Role::Admin,
+                status: Status::Online,
+                activity: None,
+                bot: false,
+                verified: true,
+            },
+            UserInfo {
+                name: "hook".into(),
+                role: Role::Member,
+                status: Status::Online,
+                activity: None,
+                bot: true,
+                verified: false,
+            },
+        ]),
+    );
+    let root = state.profile("root");
+    assert_eq!(root.role, Role::Admin);
+    assert!(root.role.moderates());
+    assert!(root.verified && !root.bot);
+    let hook = state.profile("hook");
+    assert!(hook.bot && !hook.verified);
+    assert!(!hook.role.moderates());
+}
+
+#[wasm_bindgen_test]
+fn quiet_hours_wrap_past_midnight() {
+    let quiet = QuietHours { enabled: true, start: 22 * 60, end: 8 * 60 };
+    assert!(quiet.contains(23 * 60));
+    assert!(quiet.contains(7 * 60 + 59));
+    assert!(!quiet.contains(8 * 60));
+    assert!(!quiet.contains(12 * 60));
+
+    let lunch = QuietHours { enabled: true, start: 12 * 60, end: 13 * 60 };
+    assert!(lunch.contains(12 * 60 + 30));
+    assert!(!lunch.contains(13 * 60));
+    assert!(!QuietHours { enabled: false, ..lunch }.contains(12 * 60 + 30));
+}
+
+#[wasm_bindgen_test]
+fn muted_channels_skip_unread_until_the_mute_lapses() {
+    let mut state = ChatState::default();
+    state.preferences.muted.insert("random".into(), Some(1_000));
+    common::apply(&mut state, &common::message_in_frame("random", "alice", "hi"));
+    assert!(!state.unread.contains_key("random"));
+    assert_eq!(state.preferences.next_unmute(), Some(1_000));
+
+    state.preferences.muted.clear();
+    common::apply(&mut state, &common::message_in_frame("random", "alice", "hi"));
+    assert_eq!(state.unread["random"].messages, 1);
+}
+
+#[wasm_bindgen_test]
+fn other_tabs_clear_unread_and_share_drafts_and_preferences() {
+    let mut state = ChatState::default();
+    common::apply(&mut state, &common::message_in_frame("random", "alice", "hi"));
+    assert!(state.apply_from_tab(TabMessage::Read("random".into())));
+    assert!(!state.unread.contains_key("random"));
+    assert!(!state.apply_from_tab(TabMessage::Read("random".into())));
+
+    let here = state.channel.clone();
+    let draft = |channel: &str| TabMessage::Draft { channel: channel.into(), text: "hel".into() };
+    assert!(!state.apply_from_tab(draft("elsewhere")));
+    assert!(state.apply_from_tab(draft(&here)));
+    assert_eq!(state.draft, "hel");
+    assert!(!state.apply_from_tab(draft(&here)));
+
+    let mut preferences = state.preferences.clone();
+    preferences.theme = Theme::Dark;
+    assert!(state.apply_from_tab(TabMessage::Preferences(preferences.clone())));
+    assert_eq!(state.preferences.theme, Theme::Dark);
+    assert!(!state.apply_from_tab(TabMessage::Preferences(preferences)));
+}
+
+#[wasm_bindgen_test]
+fn edits_replace_the_message_with_the_same_id() {
+    let mut state = ChatState::default();
+    common::apply(&mut state, &common::message_frame("alice", "helo"));
+
+    let edited = MessageData {
+        message: "hello".into(),
+        edited_at: 1_700_000_060_000,
+        history: vec![Revision { message: "helo".into(), time: 1_700_000_000_000 }],
+        ..common::message_data("alice", "helo")
+    };
+    let frame = WebSocketMessage::with_data(MsgTypes::Edited, serde_json::to_string(&edited).unwrap());
+    assert!(common::apply(&mut state, &frame.to_json()));
+    assert_eq!(state.messages.len(), 1);
+    assert_eq!(*state.messages[0], edited);
+}
+
+#[wasm_bindgen_test]
+fn moderation_frames_delete_pin_and_log() {
+    let mut state = ChatState::default();
+    common::apply(&mut state, &common::message_frame("alice", "spam"));
+    common::apply(&mut state, r#"{"messageType":"pins","data":"[1]"}"#);
+    assert!(state.pinned.contains(&1));
+
+    common::apply(&mut state, r#"{"messageType":"deleted","data":"1"}"#);
+    assert!(state.messages.is_empty());
+
+    let entry = AuditEntry {
+        id: 1,
+        time: 1_700_000_000_000,
+        moderator: "mod".into(),
+        action: ModerationAction::Delete { message_id: 1 },
+    };
+    let frame = WebSocketMessage::with_data(MsgTypes::Audit, serde_json::to_string(&[&entry]).unwrap());
+    common::apply(&mut state, &frame.to_json());
+    // A reconnect replays the backlog; it must not duplicate entries.
+    common::apply(&mut state, &frame.to_json());
+    assert_eq!(*state.audit, [entry]);
+}
+
+#[wasm_bindgen_test]
+fn invite_frame_opens_the_invite_panel() {
+    let mut state = ChatState::default();
+    let invite = InviteInfo {
+        channel: "random".into(),
+        token: "abc123".into(),
+    };
+    let frame = WebSocketMessage::with_data(MsgTypes::Invite, serde_json::to_string(&invite).unwrap());
+    assert!(common::apply(&mut state, &frame.to_json()));
+    assert_eq!(state.invite, Some(invite));
+}
+
+#[wasm_bindgen_test]
+fn history_page_merges_older_messages_in_order() {
+    let mut state = ChatState::default();
+    let live = MessageData {
+        id: 5,
+        ..common::message_data("bob", "latest")
+    };
+    let frame = WebSocketMessage::with_data(MsgTypes::Message, serde_json::to_string(&live).unwrap());
+    common::apply(&mut state, &frame.to_json());
+
+    let page = HistoryPage {
+        channel: "general".into(),
+        messages: vec![
+            MessageData {
+                id: 3,
+                ..common::message_data("alice", "older")
+            },
+            live.clone(),
+        ],
+        more: true,
+    };
+    let frame = WebSocketMessage::with_data(MsgTypes::History, serde_json::to_string(&page).unwrap());
+    assert!(common::apply(&mut state, &frame.to_json()));
+
+    let ids: Vec<_> = state.messages.iter().map(|m| m.id).collect();
+    assert_eq!(ids, [3, 5]);
+    // A page with nothing new changes nothing.
+    assert!(!common::apply(&mut state, &frame.to_json()));
+}
+
+#[wasm_bindgen_test]
+fn local_notes_keep_their_place_when_history_loads() {
+    let mut state = ChatState::default();
+    let live = MessageData {
+        id: 5,
+        ..common::message_data("bob", "latest")
+    };
+    let frame = WebSocketMessage::with_data(MsgTypes::Message, serde_json::to_string(&live).unwrap());
+    common::apply(&mut state, &frame.to_json());
+    state.post_local("🏓 Pong");
+
+    let page = HistoryPage {
+        channel: "general".into(),
+        messages: vec![MessageData {
+            id: 3,
+            ..common::message_data("alice", "older")
+        }],
+        more: true,
+    };
+    let frame = WebSocketMessage::with_data(MsgTypes::History, serde_json::to_string(&page).unwrap());
+    assert!(common::apply(&mut state, &frame.to_json()));
+
+    let order: Vec<_> = state.messages.iter().map(|m| (m.id, m.from.as_str())).collect();
+    assert_eq!(order, [(3, "alice"), (5, "bob"), (0, LOCAL_SENDER)]);
+}
+
+#[wasm_bindgen_test]
+fn reply_is_cleared_by_sending_or_deleting_the_parent() {
+    let mut state = ChatState::default();
+    common::apply(&mut state, &common::message_frame("alice", "question?"));
+    let state = Rc::new(state).reduce(ChatAction::ReplyTo(1));
+    assert_eq!(state.message(1).map(|m| m.message.as_str()), Some("question?"));
+
+    let sent = state.clone().reduce(ChatAction::ClearDraft);
+    assert_eq!(sent.reply_to, None);
+
+    let deleted = state.reduce(ChatAction::Event(ChatEvent::MessageDeleted(1)));
+    assert_eq!(deleted.reply_to, None);
+}
+
+#[wasm_bindgen_test]
+fn last_history_page_stops_further_requests() {
+    let mut state = ChatState::default();
+    common::apply(&mut state, &common::message_frame("alice", "hi"));
+    assert_eq!(state.oldest_message("general"), Some(1));
+    let mut state = Rc::new(state).reduce(ChatAction::RequestHistory("general".into()));
+    assert!(state.loading_history.contains("general"));
+
+    let page = HistoryPage {
+        channel: "general".into(),
+        messages: Vec::new(),
+        more: false,
+    };
+    let frame = WebSocketMessage::with_data(MsgTypes::History, serde_json::to_string(&page).unwrap());
+    assert!(common::apply(Rc::make_mut(&mut state), &frame.to_json()));
+    assert!(state.loading_history.is_empty());
+    assert!(state.history_exhausted.contains("general"));
+}
+
+#[wasm_bindgen_test]
+fn lost_history_requests_offer_a_retry() {
+    let state = Rc::new(ChatState::default()).reduce(ChatAction::RequestHistory("general".into()));
+    let state = state.reduce(ChatAction::HistoryFailed("general".into()));
+    assert!(state.loading_history.is_empty());
+    assert!(state.history_failed.contains("general"));
+
+    let mut state = state.reduce(ChatAction::RequestHistory("general".into()));
+    assert!(state.history_failed.is_empty());
+    Rc::make_mut(&mut state).handle_event(ChatEvent::ConnectionChanged(ConnectionState::Disconnected));
+    assert!(state.loading_history.is_empty());
+    assert!(state.history_failed.contains("general"));
+}
+
+#[wasm_bindgen_test]
+fn opening_a_channel_marks_its_first_unread_message() {
+    let mut state = ChatState::default();
+    for (id, text) in [(1, "seen"), (2, "new"), (3, "newer")] {
+        let data = MessageData {
+            id,
+            channel: "random".into(),
+            ..common::message_data("alice", text)
+        };
+        let frame = WebSocketMessage::with_data(MsgTypes::Message, serde_json::to_string(&data).unwrap());
+        common::apply(&mut state, &frame.to_json());
+        if id == 1 {
+            state.switch_channel("random".into());
+            state.switch_channel("general".into());
+        }
+    }
+    state.switch_channel("random".into());
+    assert_eq!(state.divider, Some(2));
+
+    state.switch_channel("general".into());
+    assert_eq!(state.divider, None);
+}
+
+#[wasm_bindgen_test]
+fn strokes_are_kept_only_for_loaded_whiteboards() {
+    let stroke = |channel: &str| Stroke {
+        channel: channel.into(),
+        color: "#111827".into(),
+        width: 0.004,
+        points: vec![[0.1, 0.2], [0.3, 0.4]],
+    };
+    let stroke_frame = |channel: &str| {
+        WebSocketMessage::with_data(MsgTypes::Stroke, serde_json::to_string(&stroke(channel)).unwrap()).to_json()
+    };
+    let board_frame = |strokes: Vec<Stroke>| {
+        let board = Whiteboard {
+            channel: "general".into(),
+            strokes,
+        };
+        WebSocketMessage::with_data(MsgTypes::Whiteboard, serde_json::to_string(&board).unwrap()).to_json()
+    };
+    let mut state = ChatState::default();
+    assert!(!common::apply(&mut state, &stroke_frame("general")));
+    assert!(state.whiteboards.is_empty());
+
+    assert!(common::apply(&mut state, &board_frame(vec![stroke("general")])));
+    assert!(common::apply(&mut state, &stroke_frame("general")));
+    assert!(!common::apply(&mut state, &stroke_frame("random")));
+    assert_eq!(state.whiteboards["general"].len(), 2);
+
+    assert!(common::apply(&mut state, &board_frame(Vec::new())));
+    assert!(state.whiteboards["general"].is_empty());
+}
+
+#[wasm_bindgen_test]
+fn suppressed_mentions_count_as_plain_messages() {
+    let mut state = ChatState::default();
+    state.me = "bob".into();
+    state.preferences.suppress_mentions = MentionSuppression::Broadcasts;
+    common::apply(&mut state, &common::message_in_frame("random", "alice", "@everyone hi"));
+    common::apply(&mut state, &common::message_in_frame("random", "alice", "@bob look"));
+    assert_eq!(state.unread["random"], Unread { messages: 2, mentions: 1 });
+}
+
+#[wasm_bindgen_test]
+fn mentions_are_appended_to_the_draft_with_a_space() {
+    let state = Rc::new(ChatState::default()).reduce(ChatAction::Mention("alice".into()));
+    assert_eq!(state.draft, "@alice ");
+    let state = state
+        .reduce(ChatAction::UpdateDraft("ask".into()))
+        .reduce(ChatAction::Mention("bob".into()));
+    assert_eq!(state.draft, "ask @bob ");
+}
+
+#[wasm_bindgen_test]
+fn clearing_history_forgets_only_that_channel() {
+    let mut state = ChatState::default();
+    for (id, channel) in [(1, "general"), (2, "random"), (3, "general")] {
+        let data = MessageData {
+            id,
+            channel: channel.into(),
+            ..common::message_data("alice", "hi")
+        };
+        let frame = WebSocketMessage::with_data(MsgTypes::Message, serde_json::to_string(&data).unwrap());
+        common::apply(&mut state, &frame.to_json());
+    }
+    common::apply(&mut state, &common::reactions_frame(3, &[("👍", &["bob"])]));
+    let state = Rc::new(state)
+        .reduce(ChatAction::ReplyTo(3))
+        .reduce(ChatAction::ClearHistory("general".into()));
+    assert_eq!(state.messages.iter().map(|m| m.id).collect::<Vec<_>>(), vec![2]);
+    assert!(state.reactions.is_empty());
+    assert_eq!(state.reply_to, None);
+    assert!(state.history_exhausted.contains("general"));
+}
+
+fn queued(text: &str) -> Outgoing {
+    Outgoing {
+        id: 7,
+        message: text.into(),
+        sent_at: 1_000,
+        ..Outgoing::new("general".into())
+    }
+}
+
+#[wasm_bindgen_test]
+fn echoed_sends_leave_the_outbox() {
+    let mut state = ChatState::default();
+    state.me = "alice".into();
+    let mut state = Rc::new(state).reduce(ChatAction::Queue(queued("hello")));
+    assert_eq!(state.outbox.len(), 1);
+    common::apply(Rc::make_mut(&mut state), &common::message_frame("alice", "hello"));
+    assert!(state.outbox.is_empty());
+}
+
+#[wasm_bindgen_test]
+fn unconfirmed_sends_fail_and_can_be_retried_or_discarded() {
+    let state = Rc::new(ChatState::default())
+        .reduce(ChatAction::Queue(queued("hello")))
+        .reduce(ChatAction::ExpireSends(1_000 + ACK_TIMEOUT_MS - 1));
+    assert_eq!(state.outbox[0].error, None);
+
+    let state = state.reduce(ChatAction::ExpireSends(1_000 + ACK_TIMEOUT_MS));
+    assert!(state.outbox[0].error.is_some());
+
+    let state = state.reduce(ChatAction::Retry { id: 7, now: 50_000 });
+    assert_eq!(state.outbox[0].error, None);
+    assert_eq!(state.outbox[0].sent_at, 50_000);
+
+    let state = state.reduce(ChatAction::Discard(7));
+    assert!(state.outbox.is_empty());
+}
+
+#[wasm_bindgen_test]
+fn refusals_fail_only_the_send_they_name() {
+    let mut state = Rc::new(ChatState::default()).reduce(ChatAction::Queue(queued("@everyone hi")));
+    assert_eq!(state.outbox[0].frame().outgoing_id, Some(7));
+
+    // Refusing something else, like a kick, leaves the send waiting.
+    let refused = WebSocketMessage::with_data(MsgTypes::Error, "only moderators can do that");
+    assert!(common::apply(Rc::make_mut(&mut state), &refused.to_json()));
+    assert_eq!(state.outbox[0].error, None);
+
+    let refused = WebSocketMessage::with_data(MsgTypes::Error, "only moderators can mention @everyone or @here")
+        .with_outgoing_id(7);
+    common::apply(Rc::make_mut(&mut state), &refused.to_json());
+    assert_eq!(
+        state.outbox[0].error,
+        Some(SendError::Refused("only moderators can mention @everyone or @here".into()))
+    );
+}
+
+#[wasm_bindgen_test]
+fn sends_a_worker_could_not_make_fail_and_hold_sending_when_full() {
+    let mut state = Rc::new(ChatState::default()).reduce(ChatAction::Queue(queued("hi")));
+    assert!(Rc::make_mut(&mut state).handle_event(ChatEvent::SendFailed {
+        error: SocketError::Full,
+        outgoing_id: Some(7),
+    }));
+    assert_eq!(
+        state.outbox[0].error,
+        Some(SendError::Network(SocketError::Full.to_string()))
+    );
+    assert_eq!(
+        state.send_cooldown().map(|c| c.cause),
+        Some(CooldownCause::RateLimit)
+    );
+}
+
+#[wasm_bindgen_test]
+fn sends_lost_with_the_connection_are_network_failures() {
+    let mut state = Rc::new(ChatState::default()).reduce(ChatAction::Queue(queued("hi")));
+    Rc::make_mut(&mut state).handle_event(ChatEvent::ConnectionChanged(ConnectionState::Disconnected));
+    assert!(matches!(state.outbox[0].error, Some(SendError::Network(_))));
+}
+
+#[wasm_bindgen_test]
+fn the_media_filter_spares_the_allowlist_and_yourself() {
+    let mut preferences = ChatState::default().preferences;
+    assert!(!preferences.hides_media_from("alice", "me"));
+
+    preferences.media_flagged.insert("mallory".into());
+    assert!(preferences.hides_media_from("mallory", "me"));
+    assert!(!preferences.hides_media_from("alice", "me"));
+
+    preferences.media_filter = true;
+    preferences.media_allowlist.insert("alice".into());
+    assert!(!preferences.hides_media_from("alice", "me"));
+    assert!(preferences.hides_media_from("bob", "me"));
+    assert!(!preferences.hides_media_from("me", "me"));
+}
+
+#[wasm_bindgen_test]
+fn a_full_connection_holds_sends_back_for_a_while() {
+    let state = Rc::new(ChatState::default()).reduce(ChatAction::HoldSends(10_000));
+    let cooldown = state.send_cooldown().unwrap();
+    assert_eq!(cooldown.cause, CooldownCause::RateLimit);
+    assert_eq!(cooldown.until, 10_000 + SEND_HOLD_MS);
+    assert_eq!(cooldown.remaining(10_000), 1.0);
+    assert_eq!(cooldown.remaining(10_000 + SEND_HOLD_MS / 2), 0.5);
+    assert_eq!(cooldown.remaining(20_000), 0.0);
+}
+
+#[wasm_bindgen_test]
+fn turning_push_off_keeps_preferences_changed_meanwhile() {
+    let state = Rc::new(ChatState::default());
+    let mut preferences = state.preferences.clone();
+    preferences.push = true;
+    preferences.dnd = true;
+    let state = state
+        .reduce(ChatAction::SetPreferences(preferences))
+        .reduce(ChatAction::DisablePush);
+    assert!(!state.preferences.push);
+    assert!(state.preferences.dnd);
+}
+
+#[wasm_bindgen_test]
+fn sends_made_in_the_same_millisecond_get_different_ids() {
+    let ids: HashSet<u64> = (0..100)
+        .map(|_| Outgoing::new("general".into()).id)
+        .collect();
+    assert_eq!(ids.len(), 100);
+    assert!(ids.iter().all(|&id| id < 1 << 53));
+}
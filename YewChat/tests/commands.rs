@@ -0,0 +1,17 @@
+use wasm_bindgen_test::*;
+use yewchat::commands::Command;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+fn known_commands_are_recognised() {
+    assert_eq!(Command::parse("/ping"), Some(Command::Ping));
+    assert_eq!(Command::parse(" /ping \n"), Some(Command::Ping));
+}
+
+#[wasm_bindgen_test]
+fn anything_else_is_a_message() {
+    assert_eq!(Command::parse("ping"), None);
+    assert_eq!(Command::parse("/ping me later"), None);
+    assert_eq!(Command::parse("/usr/bin is full"), None);
+}
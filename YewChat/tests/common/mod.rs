@@ -0,0 +1,90 @@
+#![allow(dead_code)]
+
+use yewchat::services::event_bus::ChatEvent;
+use yewchat::state::ChatState;
+use yewchat_protocol::{
+    ChannelInfo, MessageData, MessageReactions, MsgTypes, Reaction, SystemEvent, SystemNotice,
+    UserInfo, WebSocketMessage, DEFAULT_CHANNEL,
+};
+
+/// Decodes `frame` the way the websocket service does and applies it to `state`.
+pub fn apply(state: &mut ChatState, frame: &str) -> bool {
+    let event = ChatEvent::from_frame(frame).expect("frame should produce an event");
+    state.handle_event(event)
+}
+
+pub fn users_frame(names: &[&str]) -> String {
+    WebSocketMessage::with_data_array(
+        MsgTypes::Users,
+        names.iter().map(|n| n.to_string()).collect(),
+    )
+    .to_json()
+}
+
+pub fn message_data(from: &str, message: &str) -> MessageData {
+    MessageData {
+        id: 1,
+        from: from.into(),
+        message: message.into(),
+        time: 1_700_000_000_000,
+        channel: DEFAULT_CHANNEL.into(),
+        edited_at: 0,
+        history: Vec::new(),
+        reply_to: None,
+        attachment: None,
+        tasks_open: false,
+    }
+}
+
+pub fn message_in_frame(channel: &str, from: &str, message: &str) -> String {
+    let data = MessageData {
+        channel: channel.into(),
+        ..message_data(from, message)
+    };
+    WebSocketMessage::with_data(MsgTypes::Message, serde_json::to_string(&data).unwrap()).to_json()
+}
+
+pub fn message_frame(from: &str, message: &str) -> String {
+    WebSocketMessage::with_data(
+        MsgTypes::Message,
+        serde_json::to_string(&message_data(from, message)).unwrap(),
+    )
+    .to_json()
+}
+
+/// A `Users` frame carrying roles in `data` alongside the bare names.
+pub fn users_with_roles_frame(users: &[UserInfo]) -> String {
+    WebSocketMessage {
+        data: Some(serde_json::to_string(users).unwrap()),
+        ..WebSocketMessage::with_data_array(
+            MsgTypes::Users,
+            users.iter().map(|u| u.name.clone()).collect(),
+        )
+    }
+    .to_json()
+}
+
+pub fn system_frame(time: u64, event: SystemEvent) -> String {
+    let notice = SystemNotice { time, event };
+    WebSocketMessage::with_data(MsgTypes::System, serde_json::to_string(&notice).unwrap()).to_json()
+}
+
+pub fn channels_frame(channels: &[ChannelInfo]) -> String {
+    WebSocketMessage::with_data(MsgTypes::Channels, serde_json::to_string(channels).unwrap())
+        .to_json()
+}
+
+pub fn reactions_frame(message_id: u64, reactions: &[(&str, &[&str])]) -> String {
+    let update = MessageReactions {
+        message_id,
+        reactions: reactions
+            .iter()
+            .map(|(emoji, users)| Reaction {
+                emoji: emoji.to_string(),
+                users: users.iter().map(|u| u.to_string()).collect(),
+            })
+            .collect(),
+    };
+    WebSocketMessage::with_data(MsgTypes::Reactions, serde_json::to_string(&update).unwrap())
+        .to_json()
+}
@@ -0,0 +1,24 @@
+mod common;
+
+use wasm_bindgen_test::*;
+use yewchat::components::compact_message::continues;
+use yewchat_protocol::MessageData;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+fn at(from: &str, time: u64) -> MessageData {
+    MessageData {
+        time,
+        ..common::message_data(from, "hi")
+    }
+}
+
+#[wasm_bindgen_test]
+fn lines_from_one_author_close_together_are_grouped() {
+    let start = 1_700_000_000_000;
+    assert!(continues(&at("alice", start), &at("alice", start + 60_000)));
+    assert!(!continues(&at("alice", start), &at("bob", start + 60_000)));
+    assert!(!continues(&at("alice", start), &at("alice", start + 10 * 60_000)));
+    // Without timestamps there is nothing to group by.
+    assert!(!continues(&at("alice", 0), &at("alice", 0)));
+}
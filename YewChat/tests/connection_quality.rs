@@ -0,0 +1,13 @@
+use wasm_bindgen_test::*;
+use yewchat::components::connection_quality::Quality;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+fn round_trips_are_rated_by_how_long_they_took() {
+    assert_eq!(Quality::of(40), Quality::Good);
+    assert_eq!(Quality::of(300), Quality::Good);
+    assert_eq!(Quality::of(301), Quality::Ok);
+    assert_eq!(Quality::of(1_000), Quality::Ok);
+    assert_eq!(Quality::of(2_500), Quality::Poor);
+}
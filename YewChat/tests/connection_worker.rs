@@ -0,0 +1,45 @@
+use wasm_bindgen_test::*;
+use yewchat::services::connection_worker::Session;
+use yewchat::services::event_bus::{ChatEvent, ConnectionState};
+use yewchat::services::socket::MockSocket;
+use yewchat_protocol::{MsgTypes, WebSocketMessage};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+fn changed(connection: ConnectionState) -> ChatEvent {
+    ChatEvent::ConnectionChanged(connection)
+}
+
+#[wasm_bindgen_test]
+fn backoff_grows_while_the_server_stays_down() {
+    let socket = MockSocket::default();
+    let mut session = Session::default();
+    // A socket that never opens only ever reports that it closed.
+    let delays: Vec<_> = (0..7)
+        .filter_map(|_| session.observe(&changed(ConnectionState::Disconnected), &socket))
+        .collect();
+    assert_eq!(delays, [1_000, 2_000, 4_000, 8_000, 16_000, 30_000, 30_000]);
+
+    assert_eq!(
+        session.observe(&changed(ConnectionState::Connected), &socket),
+        None
+    );
+    assert_eq!(
+        session.observe(&changed(ConnectionState::Disconnected), &socket),
+        Some(1_000)
+    );
+}
+
+#[wasm_bindgen_test]
+fn registration_is_replayed_once_reconnected() {
+    let socket = MockSocket::default();
+    let mut session = Session::default();
+    session.observe(&changed(ConnectionState::Connected), &socket);
+    let register = WebSocketMessage::with_data(MsgTypes::Register, "alice");
+    session.send(register.clone(), &socket).unwrap();
+    session.observe(&changed(ConnectionState::Disconnected), &socket);
+    assert_eq!(socket.sent.borrow().len(), 1);
+
+    session.observe(&changed(ConnectionState::Connected), &socket);
+    assert_eq!(*socket.sent.borrow(), vec![register.clone(), register]);
+}
@@ -0,0 +1,23 @@
+use log::Level;
+use wasm_bindgen_test::*;
+use yewchat::crash::diagnostics;
+use yewchat::logging::LogEntry;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+fn diagnostics_name_the_panic_and_what_led_up_to_it() {
+    let log = [LogEntry {
+        time: 0.0,
+        level: Level::Warn,
+        module: "services::websocket".into(),
+        message: "reconnecting".into(),
+    }];
+    let text = diagnostics("index out of bounds", "src/state.rs:12", &log);
+    assert!(text.contains("panicked at src/state.rs:12"));
+    assert!(text.contains("index out of bounds"));
+    assert!(text.contains("[services::websocket] reconnecting"));
+
+    let text = diagnostics("oops", "src/state.rs:12", &[]);
+    assert!(!text.contains("Recent log"));
+}
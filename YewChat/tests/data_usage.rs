@@ -0,0 +1,26 @@
+use wasm_bindgen_test::*;
+use yewchat::services::data_usage::{self, format_bytes, Traffic};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+fn sizes_use_the_largest_fitting_unit() {
+    assert_eq!(format_bytes(0), "0 B");
+    assert_eq!(format_bytes(1023), "1023 B");
+    assert_eq!(format_bytes(1536), "1.5 KB");
+    assert_eq!(format_bytes(5 * 1024 * 1024), "5.0 MB");
+    assert_eq!(format_bytes(3 * 1024 * 1024 * 1024), "3.0 GB");
+}
+
+#[wasm_bindgen_test]
+fn traffic_is_tallied_by_kind() {
+    let before = data_usage::snapshot();
+    data_usage::record(Traffic::Sent, 10);
+    data_usage::record(Traffic::Received, 200);
+    data_usage::record(Traffic::Media, 3_000);
+    let after = data_usage::snapshot();
+    assert_eq!(after.sent - before.sent, 10);
+    assert_eq!(after.received - before.received, 200);
+    assert_eq!(after.media - before.media, 3_000);
+    assert_eq!(after.total() - before.total(), 3_210);
+}
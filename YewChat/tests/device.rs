@@ -0,0 +1,32 @@
+use wasm_bindgen_test::*;
+use yewchat::services::device::describe;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+fn browsers_are_told_apart_from_the_ones_they_imitate() {
+    let edge = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) \
+        Chrome/120.0.0.0 Safari/537.36 Edg/120.0.0.0";
+    assert_eq!(describe(edge), "Edge on Windows");
+    let chrome = "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) \
+        Chrome/120.0.0.0 Safari/537.36";
+    assert_eq!(describe(chrome), "Chrome on Linux");
+    let firefox =
+        "Mozilla/5.0 (Macintosh; Intel Mac OS X 14.1; rv:121.0) Gecko/20100101 Firefox/121.0";
+    assert_eq!(describe(firefox), "Firefox on macOS");
+}
+
+#[wasm_bindgen_test]
+fn phones_are_not_mistaken_for_desktops() {
+    let iphone = "Mozilla/5.0 (iPhone; CPU iPhone OS 17_1 like Mac OS X) AppleWebKit/605.1.15 \
+        (KHTML, like Gecko) Version/17.1 Mobile/15E148 Safari/604.1";
+    assert_eq!(describe(iphone), "Safari on iOS");
+    let android = "Mozilla/5.0 (Linux; Android 14) AppleWebKit/537.36 (KHTML, like Gecko) \
+        Chrome/120.0.0.0 Mobile Safari/537.36";
+    assert_eq!(describe(android), "Chrome on Android");
+}
+
+#[wasm_bindgen_test]
+fn unrecognised_agents_still_get_a_label() {
+    assert_eq!(describe("curl/8.4.0"), "Unknown device");
+}
@@ -0,0 +1,70 @@
+use wasm_bindgen_test::*;
+use yewchat::services::emoji::{
+    jumbo_count, replace_shortcodes, search, shortcode_query, EmojiEntry,
+};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+fn dataset() -> Vec<EmojiEntry> {
+    [("😄", "smile"), ("👍", "+1"), ("🎉", "tada")]
+        .iter()
+        .map(|(emoji, code)| EmojiEntry {
+            emoji: emoji.to_string(),
+            shortcodes: vec![code.to_string()],
+            category: "test".into(),
+        })
+        .collect()
+}
+
+#[wasm_bindgen_test]
+fn known_shortcodes_become_emoji() {
+    assert_eq!(
+        replace_shortcodes("hi :smile: :+1::tada:", &dataset()),
+        "hi 😄 👍🎉"
+    );
+}
+
+#[wasm_bindgen_test]
+fn unknown_codes_and_stray_colons_are_kept() {
+    let dataset = dataset();
+    assert_eq!(replace_shortcodes("at 12:30:45", &dataset), "at 12:30:45");
+    assert_eq!(replace_shortcodes("note: :nope: ok", &dataset), "note: :nope: ok");
+    assert_eq!(replace_shortcodes("::smile:", &dataset), ":😄");
+}
+
+#[wasm_bindgen_test]
+fn shortcode_query_needs_two_characters_at_a_word_start() {
+    assert_eq!(shortcode_query("hi :sm"), Some((3, "sm")));
+    assert_eq!(shortcode_query(":ta"), Some((0, "ta")));
+    assert_eq!(shortcode_query("hi :s"), None);
+    assert_eq!(shortcode_query("12:30"), None);
+    assert_eq!(shortcode_query("hi :sm ile"), None);
+}
+
+#[wasm_bindgen_test]
+fn search_matches_prefixes_and_substrings() {
+    let dataset = dataset();
+    let found: Vec<_> = search(&dataset, "a", 8).iter().map(|e| e.emoji.as_str()).collect();
+    assert_eq!(found, ["🎉"]);
+    let found: Vec<_> = search(&dataset, "sm", 8).iter().map(|e| e.emoji.as_str()).collect();
+    assert_eq!(found, ["😄"]);
+}
+
+#[wasm_bindgen_test]
+fn a_few_emoji_alone_are_jumbo() {
+    assert_eq!(jumbo_count("👍"), Some(1));
+    assert_eq!(jumbo_count(" 🎉 😄 "), Some(2));
+    assert_eq!(jumbo_count("👍👍👍👍"), None);
+    assert_eq!(jumbo_count("nice 👍"), None);
+    assert_eq!(jumbo_count("12"), None);
+    assert_eq!(jumbo_count(""), None);
+}
+
+#[wasm_bindgen_test]
+fn sequences_count_as_one_emoji() {
+    assert_eq!(jumbo_count("👨\u{200D}👩\u{200D}👧"), Some(1));
+    assert_eq!(jumbo_count("👍🏽"), Some(1));
+    assert_eq!(jumbo_count("🇬🇧🇫🇷"), Some(2));
+    assert_eq!(jumbo_count("1\u{FE0F}\u{20E3}"), Some(1));
+    assert_eq!(jumbo_count("❤\u{FE0F}❤\u{FE0F}❤\u{FE0F}"), Some(3));
+}
@@ -0,0 +1,36 @@
+use log::Level;
+use wasm_bindgen_test::*;
+use yewchat::logging::LogEntry;
+use yewchat::services::error_report::{ErrorReport, ReportKind};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+fn entry(i: usize) -> LogEntry {
+    LogEntry {
+        time: 0.0,
+        level: Level::Info,
+        module: "services::websocket".into(),
+        message: format!("line {}", i),
+    }
+}
+
+#[wasm_bindgen_test]
+fn reports_carry_the_latest_log_lines() {
+    let log: Vec<LogEntry> = (0..80).map(entry).collect();
+    let report = ErrorReport::new(ReportKind::Error, "services::event_bus", "bad frame", &log);
+    assert_eq!(report.log.len(), 50);
+    assert!(report.log[0].ends_with("line 30"));
+    assert!(report.log[49].ends_with("line 79"));
+
+    let report = ErrorReport::new(ReportKind::Panic, "src/state.rs:12", "oops", &log[..3]);
+    assert_eq!(report.log.len(), 3);
+}
+
+#[wasm_bindgen_test]
+fn reports_serialize_for_the_endpoint() {
+    let report = ErrorReport::new(ReportKind::Panic, "src/state.rs:12", "oops", &[]);
+    let json: serde_json::Value = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["kind"], "panic");
+    assert_eq!(json["module"], "src/state.rs:12");
+    assert!(json.get("userAgent").is_some());
+}
@@ -0,0 +1,47 @@
+use wasm_bindgen_test::*;
+use yewchat::highlight::{highlight, Language, Token};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+fn runs_cover_the_whole_input() {
+    let code = "fn main() {\n    let s = \"a \\\" b\"; // done\n    42\n}";
+    for language in Language::ALL {
+        let joined: String = highlight(code, language).iter().map(|(_, t)| *t).collect();
+        assert_eq!(joined, code);
+    }
+}
+
+#[wasm_bindgen_test]
+fn rust_tokens_are_classified() {
+    let tokens = highlight("let x = \"hi\"; // note\n7", Language::Rust);
+    assert_eq!(
+        tokens,
+        [
+            (Token::Keyword, "let"),
+            (Token::Plain, " x = "),
+            (Token::String, "\"hi\""),
+            (Token::Plain, "; "),
+            (Token::Comment, "// note"),
+            (Token::Plain, "\n"),
+            (Token::Number, "7"),
+        ]
+    );
+}
+
+#[wasm_bindgen_test]
+fn sql_keywords_ignore_case() {
+    let tokens = highlight("SELECT name from users", Language::Sql);
+    let keywords: Vec<_> = tokens
+        .iter()
+        .filter(|(t, _)| *t == Token::Keyword)
+        .map(|(_, text)| *text)
+        .collect();
+    assert_eq!(keywords, ["SELECT", "from"]);
+}
+
+#[wasm_bindgen_test]
+fn unterminated_strings_stop_at_the_line_end() {
+    let tokens = highlight("x = 'open\ny", Language::Python);
+    assert!(tokens.contains(&(Token::String, "'open")));
+}
@@ -0,0 +1,60 @@
+use wasm_bindgen_test::*;
+use yewchat::config::Config;
+use yewchat::services::image::{fit, is_animated, is_inline, validate, ImageQuality};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+fn large_images_shrink_to_the_longest_side() {
+    assert_eq!(fit(4000, 3000, 1600), (1600, 1200));
+    assert_eq!(fit(1000, 5000, 1000), (200, 1000));
+}
+
+#[wasm_bindgen_test]
+fn small_images_are_never_scaled_up() {
+    assert_eq!(fit(640, 480, 1600), (640, 480));
+}
+
+#[wasm_bindgen_test]
+fn lower_quality_means_smaller_images() {
+    let [high, balanced, low] = ImageQuality::ALL;
+    assert!(high.max_dimension() > balanced.max_dimension());
+    assert!(balanced.max_dimension() > low.max_dimension());
+    assert!(high.encoder_quality() > low.encoder_quality());
+}
+
+#[wasm_bindgen_test]
+fn only_allowed_types_can_be_attached() {
+    let config = Config::default();
+    assert!(validate("image/png", 1024, &config).is_ok());
+    let err = validate("application/pdf", 1024, &config).unwrap_err();
+    assert!(err.contains("PNG, JPEG, GIF, WEBP"), "{err}");
+
+    let any_image = Config {
+        allowed_upload_types: vec!["image/*".into()],
+        ..Config::default()
+    };
+    assert!(validate("image/avif", 1024, &any_image).is_ok());
+    assert!(validate("text/plain", 1024, &any_image).is_err());
+}
+
+#[wasm_bindgen_test]
+fn oversized_files_are_refused_before_compressing() {
+    let config = Config::default();
+    assert!(validate("image/jpeg", config.max_file_bytes, &config).is_ok());
+    assert!(validate("image/jpeg", config.max_file_bytes + 1, &config).is_err());
+    // GIFs aren't compressed, so the send limit applies to them directly.
+    assert!(validate("image/gif", config.max_upload_bytes, &config).is_err());
+}
+
+#[wasm_bindgen_test]
+fn only_gifs_count_as_animated() {
+    assert!(is_animated("data:image/gif;base64,R0lGODlh"));
+    assert!(!is_animated("data:image/webp;base64,UklGR"));
+}
+
+#[wasm_bindgen_test]
+fn only_data_urls_are_inline() {
+    assert!(is_inline("data:image/png;base64,iVBORw0KGgo"));
+    assert!(!is_inline("https://example.com/cat.gif"));
+}
@@ -0,0 +1,82 @@
+use wasm_bindgen_test::*;
+use yewchat::links::{
+    first_link, host, is_lookalike, set_collapsed, split_links, Piece, COLLAPSED_KEPT,
+};
+use yewchat::state::Preferences;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+fn links_leave_the_punctuation_around_them_as_text() {
+    assert_eq!(
+        split_links("see (https://example.com/a). ok"),
+        vec![
+            Piece::Text("see ("),
+            Piece::Link("https://example.com/a"),
+            Piece::Text("). ok"),
+        ]
+    );
+    assert_eq!(
+        split_links("http:// alone"),
+        vec![Piece::Text("http:// alone")]
+    );
+    assert_eq!(
+        split_links("nohttps://here"),
+        vec![Piece::Text("nohttps://here")]
+    );
+}
+
+#[wasm_bindgen_test]
+fn hosts_are_shown_as_the_browser_resolves_them() {
+    assert_eq!(
+        host("https://user@Example.COM:8080/x").as_deref(),
+        Some("example.com")
+    );
+    assert_eq!(host("javascript:alert(1)"), None);
+    // The first "а" is Cyrillic.
+    let spoof = host("https://\u{430}pple.com").unwrap();
+    assert!(spoof.starts_with("xn--"), "{spoof}");
+    assert!(is_lookalike(&spoof));
+    assert!(!is_lookalike("apple.com"));
+}
+
+#[wasm_bindgen_test]
+fn trusting_a_domain_covers_its_subdomains_only() {
+    let mut preferences = Preferences::default();
+    preferences.trusted_domains.insert("example.com".into());
+    assert!(preferences.trusts("example.com"));
+    assert!(preferences.trusts("docs.example.com"));
+    assert!(!preferences.trusts("badexample.com"));
+    assert!(!preferences.trusts("example.com.evil.net"));
+}
+
+#[wasm_bindgen_test]
+fn previews_are_for_the_first_link_unless_its_site_is_turned_off() {
+    assert_eq!(
+        first_link("a http://one.example and https://two.example"),
+        Some("http://one.example")
+    );
+    assert_eq!(first_link("no links"), None);
+
+    let mut preferences = Preferences::default();
+    preferences.unpreviewed_domains.insert("example.com".into());
+    assert!(!preferences.previews("www.example.com"));
+    assert!(preferences.previews("example.org"));
+}
+
+#[wasm_bindgen_test]
+fn collapsed_previews_forget_the_oldest_past_the_limit() {
+    let mut collapsed = Vec::new();
+    set_collapsed(&mut collapsed, 1, true);
+    set_collapsed(&mut collapsed, 2, true);
+    set_collapsed(&mut collapsed, 1, true);
+    assert_eq!(collapsed, vec![2, 1]);
+    set_collapsed(&mut collapsed, 2, false);
+    assert_eq!(collapsed, vec![1]);
+
+    for id in 10..10 + COLLAPSED_KEPT as u64 {
+        set_collapsed(&mut collapsed, id, true);
+    }
+    assert_eq!(collapsed.len(), COLLAPSED_KEPT);
+    assert!(!collapsed.contains(&1));
+}
@@ -0,0 +1,36 @@
+use wasm_bindgen_test::*;
+use yewchat::services::location::{describe, map_link, tile_position, tile_url};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+fn assert_close((x, y): (f64, f64), (expected_x, expected_y): (f64, f64)) {
+    assert!((x - expected_x).abs() < 1e-6, "x: {} != {}", x, expected_x);
+    assert!((y - expected_y).abs() < 1e-6, "y: {} != {}", y, expected_y);
+}
+
+#[wasm_bindgen_test]
+fn null_island_is_the_corner_of_the_middle_tiles() {
+    assert_close(tile_position(0.0, 0.0, 1), (1.0, 1.0));
+    assert_close(tile_position(0.0, 0.0, 3), (4.0, 4.0));
+}
+
+#[wasm_bindgen_test]
+fn tiles_wrap_at_the_date_line_and_stop_at_the_poles() {
+    assert_close(tile_position(0.0, 180.0, 2), (0.0, 2.0));
+    assert_close(tile_position(89.9, -180.0, 2), (0.0, 0.0));
+    assert_eq!(
+        tile_url(-1, 0, 1).as_deref(),
+        Some("https://tile.openstreetmap.org/1/1/0.png")
+    );
+    assert_eq!(tile_url(0, 2, 1), None);
+    assert_eq!(tile_url(0, -1, 1), None);
+}
+
+#[wasm_bindgen_test]
+fn links_and_captions_round_the_coordinates() {
+    assert_eq!(
+        map_link(51.5074, -0.1278),
+        "https://www.openstreetmap.org/?mlat=51.507400&mlon=-0.127800#map=15/51.507400/-0.127800"
+    );
+    assert_eq!(describe(51.50735123, -0.12775829), "51.50735, -0.12776");
+}
@@ -0,0 +1,65 @@
+use wasm_bindgen_test::*;
+use yewchat::markdown::{blocks, Align, Block, Table, Task};
+use yewchat_protocol::set_task;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+fn tables_are_split_from_the_surrounding_text() {
+    let text = "Results:\n| name | score |\n|:--|--:|\n| alice | 3 |\n| bob |\nthanks";
+    assert_eq!(
+        blocks(text),
+        [
+            Block::Text("Results:"),
+            Block::Table(Table {
+                header: vec!["name", "score"],
+                align: vec![Align::Left, Align::Right],
+                rows: vec![vec!["alice", "3"], vec!["bob", ""]],
+            }),
+            Block::Text("thanks"),
+        ]
+    );
+}
+
+#[wasm_bindgen_test]
+fn pipes_without_a_delimiter_row_stay_text() {
+    assert_eq!(blocks("a | b\nc | d"), [Block::Text("a | b\nc | d")]);
+}
+
+#[wasm_bindgen_test]
+fn escaped_pipes_stay_in_their_cell() {
+    let Block::Table(table) = &blocks("op | meaning\n:-:|---\n`a \\| b` | or")[0] else {
+        panic!("expected a table");
+    };
+    assert_eq!(table.align, [Align::Center, Align::Left]);
+    assert_eq!(table.rows, [vec!["`a \\| b`", "or"]]);
+}
+
+const CHECKLIST: &str = "todo:\n- [ ] a\n- [x] b\nmore\n* [ ] c";
+
+#[wasm_bindgen_test]
+fn checklist_items_are_numbered_across_the_message() {
+    let task = |index, done, text| Task { index, done, text };
+    assert_eq!(
+        blocks(CHECKLIST),
+        [
+            Block::Text("todo:"),
+            Block::Checklist(vec![task(0, false, "a"), task(1, true, "b")]),
+            Block::Text("more"),
+            Block::Checklist(vec![task(2, false, "c")]),
+        ]
+    );
+}
+
+#[wasm_bindgen_test]
+fn set_task_ticks_the_numbered_item() {
+    assert_eq!(
+        set_task(CHECKLIST, 2, true).as_deref(),
+        Some("todo:\n- [ ] a\n- [x] b\nmore\n* [x] c")
+    );
+    assert_eq!(
+        set_task(CHECKLIST, 1, false).as_deref(),
+        Some("todo:\n- [ ] a\n- [ ] b\nmore\n* [ ] c")
+    );
+    assert_eq!(set_task(CHECKLIST, 3, true), None);
+}
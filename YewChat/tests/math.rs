@@ -0,0 +1,35 @@
+use wasm_bindgen_test::*;
+use yewchat::math::{split_math, Segment};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+fn inline_and_display_math_are_found() {
+    assert_eq!(
+        split_math(r"area is $\pi r^2$, and $$\sum_i x_i$$"),
+        [
+            Segment::Text("area is "),
+            Segment::Math {
+                tex: r"\pi r^2",
+                display: false
+            },
+            Segment::Text(", and "),
+            Segment::Math {
+                tex: r"\sum_i x_i",
+                display: true
+            },
+        ]
+    );
+}
+
+#[wasm_bindgen_test]
+fn prices_stay_text() {
+    let text = "lunch was $5 and dinner $10";
+    assert_eq!(split_math(text), [Segment::Text(text)]);
+}
+
+#[wasm_bindgen_test]
+fn escaped_and_spaced_dollars_stay_text() {
+    assert_eq!(split_math(r"\$x$"), [Segment::Text(r"\$x$")]);
+    assert_eq!(split_math("$ x $"), [Segment::Text("$ x $")]);
+}
@@ -0,0 +1,25 @@
+use wasm_bindgen_test::*;
+use yewchat::mentions::{mention_of, Mention};
+use yewchat_protocol::BroadcastMention;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+fn broadcast_mentions_are_whole_words() {
+    assert_eq!(BroadcastMention::find("@here and @everyone!"), Some(BroadcastMention::Everyone));
+    assert_eq!(BroadcastMention::find("hey @here."), Some(BroadcastMention::Here));
+    assert_eq!(BroadcastMention::find("mail me@everyone.com"), None);
+}
+
+#[wasm_bindgen_test]
+fn here_skips_spectators_but_everyone_does_not() {
+    assert_eq!(mention_of("@here standup", "bob", true), Some(Mention::Broadcast));
+    assert_eq!(mention_of("@here standup", "bob", false), None);
+    assert_eq!(mention_of("@everyone standup", "bob", false), Some(Mention::Broadcast));
+}
+
+#[wasm_bindgen_test]
+fn direct_mentions_match_the_exact_name() {
+    assert_eq!(mention_of("thanks @bob!", "bob", true), Some(Mention::Direct));
+    assert_eq!(mention_of("thanks @bobby", "bob", true), None);
+}
@@ -0,0 +1,32 @@
+use wasm_bindgen_test::*;
+use yewchat::components::message_list::next_cursor;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+const IDS: [u64; 3] = [4, 7, 9];
+
+#[wasm_bindgen_test]
+fn arrows_start_from_the_newest_message() {
+    assert_eq!(next_cursor(&IDS, None, "ArrowUp"), Some(9));
+    assert_eq!(next_cursor(&IDS, None, "ArrowDown"), Some(9));
+}
+
+#[wasm_bindgen_test]
+fn arrows_step_and_stop_at_the_ends() {
+    assert_eq!(next_cursor(&IDS, Some(7), "ArrowUp"), Some(4));
+    assert_eq!(next_cursor(&IDS, Some(7), "ArrowDown"), Some(9));
+    assert_eq!(next_cursor(&IDS, Some(4), "ArrowUp"), Some(4));
+    assert_eq!(next_cursor(&IDS, Some(9), "ArrowDown"), Some(9));
+}
+
+#[wasm_bindgen_test]
+fn home_and_end_jump_to_the_oldest_and_newest() {
+    assert_eq!(next_cursor(&IDS, Some(7), "Home"), Some(4));
+    assert_eq!(next_cursor(&IDS, None, "End"), Some(9));
+}
+
+#[wasm_bindgen_test]
+fn other_keys_and_empty_lists_leave_the_cursor() {
+    assert_eq!(next_cursor(&IDS, Some(7), "a"), None);
+    assert_eq!(next_cursor(&[], None, "ArrowUp"), None);
+}
@@ -0,0 +1,23 @@
+use wasm_bindgen_test::*;
+use yewchat::components::modal::wrap_focus;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+fn tab_wraps_from_the_last_element_to_the_first() {
+    assert_eq!(wrap_focus(3, Some(2), false), Some(0));
+    assert_eq!(wrap_focus(3, Some(0), true), Some(2));
+}
+
+#[wasm_bindgen_test]
+fn tab_between_elements_is_left_to_the_browser() {
+    assert_eq!(wrap_focus(3, Some(0), false), None);
+    assert_eq!(wrap_focus(3, Some(2), true), None);
+}
+
+#[wasm_bindgen_test]
+fn focus_outside_is_pulled_back_in() {
+    assert_eq!(wrap_focus(3, None, false), Some(0));
+    assert_eq!(wrap_focus(3, None, true), Some(2));
+    assert_eq!(wrap_focus(0, None, false), None);
+}
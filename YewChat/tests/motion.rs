@@ -0,0 +1,16 @@
+use wasm_bindgen_test::*;
+use yewchat::motion::Motion;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+fn matching_the_system_follows_its_setting() {
+    assert!(Motion::System.reduced(true));
+    assert!(!Motion::System.reduced(false));
+}
+
+#[wasm_bindgen_test]
+fn an_explicit_choice_overrides_the_system() {
+    assert!(Motion::Reduced.reduced(false));
+    assert!(!Motion::Full.reduced(true));
+}
@@ -0,0 +1,37 @@
+use wasm_bindgen_test::*;
+use yewchat::theme::Theme;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+fn a_name_always_gets_the_same_color() {
+    assert_eq!(
+        Theme::Light.name_color("alice"),
+        Theme::Light.name_color("alice")
+    );
+    assert_eq!(
+        Theme::Dark.name_color("alice"),
+        Theme::Dark.name_color("alice")
+    );
+}
+
+#[wasm_bindgen_test]
+fn each_theme_has_its_own_palette() {
+    assert_ne!(
+        Theme::Light.name_color("alice"),
+        Theme::Dark.name_color("alice")
+    );
+    assert_ne!(
+        Theme::Light.name_color("alice"),
+        Theme::HighContrast.name_color("alice")
+    );
+}
+
+#[wasm_bindgen_test]
+fn names_spread_across_the_palette() {
+    let colors: std::collections::HashSet<_> = ["alice", "bob", "carol", "dave", "erin", "frank"]
+        .iter()
+        .map(|name| Theme::Light.name_color(name))
+        .collect();
+    assert!(colors.len() > 1);
+}
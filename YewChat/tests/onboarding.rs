@@ -0,0 +1,27 @@
+use wasm_bindgen_test::*;
+use yewchat::components::onboarding::{advance, card_position, STEPS};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+fn the_tour_walks_forward_and_back_through_its_steps() {
+    assert_eq!(advance(0, false), Some(1));
+    assert_eq!(advance(0, true), Some(0));
+    assert_eq!(advance(2, true), Some(1));
+    assert_eq!(advance(STEPS.len() - 1, false), None);
+}
+
+#[wasm_bindgen_test]
+fn the_card_sits_below_its_target_unless_that_runs_off_screen() {
+    let viewport = (1024.0, 768.0);
+    // The channel list, near the top left.
+    let (top, left) = card_position(Some((60.0, 0.0, 200.0, 100.0)), viewport);
+    assert!(top > 160.0, "{top}");
+    assert_eq!(left, 12.0);
+    // The composer, along the bottom and right up to the edge.
+    let (top, left) = card_position(Some((720.0, 900.0, 100.0, 40.0)), viewport);
+    assert!(top < 720.0, "{top}");
+    assert!(left + 288.0 <= 1024.0, "{left}");
+    // No target: centered.
+    assert_eq!(card_position(None, viewport), (304.0, 368.0));
+}
@@ -0,0 +1,190 @@
+mod common;
+
+use wasm_bindgen_test::*;
+use yewchat_protocol::{
+    is_accent, mentions, Attachment, ChannelInfo, EmbedField, MentionSuppression, MessageData,
+    ModerationAction, MsgTypes, PushSubscription, Role, Status, UserInfo, WebSocketMessage,
+};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+fn register_uses_camel_case_and_lowercase_type() {
+    let json = WebSocketMessage::with_data(MsgTypes::Register, "alice").to_json();
+    assert_eq!(
+        json,
+        r#"{"messageType":"register","dataArray":null,"data":"alice"}"#
+    );
+}
+
+#[wasm_bindgen_test]
+fn frames_round_trip() {
+    let frames = [
+        WebSocketMessage::with_data(MsgTypes::Register, "alice"),
+        WebSocketMessage::with_data_array(MsgTypes::Users, vec!["alice".into(), "bob".into()]),
+        WebSocketMessage::with_data(MsgTypes::Message, "hi"),
+    ];
+    for frame in frames {
+        let parsed: WebSocketMessage = serde_json::from_str(&frame.to_json()).unwrap();
+        assert_eq!(parsed, frame);
+    }
+}
+
+#[wasm_bindgen_test]
+fn message_data_round_trips_and_tolerates_missing_time() {
+    let data = common::message_data("alice", "hi");
+    let parsed: MessageData = serde_json::from_str(&serde_json::to_string(&data).unwrap()).unwrap();
+    assert_eq!(parsed, data);
+
+    let legacy: MessageData = serde_json::from_str(r#"{"from":"bob","message":"yo"}"#).unwrap();
+    assert_eq!(legacy.time, 0);
+}
+
+#[wasm_bindgen_test]
+fn server_frames_without_optional_fields_parse() {
+    let users: WebSocketMessage =
+        serde_json::from_str(r#"{"messageType":"users","dataArray":["a"]}"#).unwrap();
+    assert_eq!(users.data, None);
+    assert_eq!(users.data_array, Some(vec!["a".to_string()]));
+}
+
+#[wasm_bindgen_test]
+fn attachments_round_trip_with_a_kind_tag() {
+    let attachment = Attachment::Image {
+        url: "data:image/jpeg;base64,AAAA".into(),
+        width: 640,
+        height: 480,
+        alt: Some("A cat on a keyboard".into()),
+        spoiler: true,
+    };
+    let data = MessageData {
+        attachment: Some(attachment.clone()),
+        ..common::message_data("alice", "")
+    };
+    let json = serde_json::to_string(&data).unwrap();
+    assert!(json.contains(r#""kind":"image""#));
+    let parsed: MessageData = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed.attachment, Some(attachment));
+}
+
+#[wasm_bindgen_test]
+fn locations_leave_out_an_unknown_accuracy() {
+    let location = Attachment::Location {
+        latitude: 51.5074,
+        longitude: -0.1278,
+        accuracy: None,
+    };
+    let json = serde_json::to_string(&location).unwrap();
+    assert_eq!(
+        json,
+        r#"{"kind":"location","latitude":51.5074,"longitude":-0.1278}"#
+    );
+    assert_eq!(serde_json::from_str::<Attachment>(&json).unwrap(), location);
+}
+
+#[wasm_bindgen_test]
+fn embeds_count_their_text_against_the_size_limit() {
+    let json = r#"{"kind":"embed","title":"Build","fields":[{"name":"Status","value":"passed","inline":true},{"name":"Log","value":"ok"}]}"#;
+    let embed: Attachment = serde_json::from_str(json).unwrap();
+    let Attachment::Embed { title, fields } = &embed else {
+        panic!("not an embed: {:?}", embed);
+    };
+    assert_eq!(title.as_deref(), Some("Build"));
+    assert_eq!(
+        fields[1],
+        EmbedField {
+            name: "Log".into(),
+            value: "ok".into(),
+            inline: false,
+        }
+    );
+    assert_eq!(
+        embed.size(),
+        "Build".len() + "Statuspassed".len() + "Logok".len()
+    );
+    assert_eq!(serde_json::to_string(&embed).unwrap(), json);
+}
+
+#[wasm_bindgen_test]
+fn push_subscriptions_parse_from_the_browsers_json() {
+    let json = r#"{"endpoint":"https://push.example/abc","expirationTime":null,"keys":{"p256dh":"BN","auth":"xy"}}"#;
+    let subscription: PushSubscription = serde_json::from_str(json).unwrap();
+    assert_eq!(subscription.endpoint, "https://push.example/abc");
+    assert_eq!(subscription.keys.auth, "xy");
+}
+
+#[wasm_bindgen_test]
+fn mentions_need_the_whole_name_after_an_at() {
+    assert!(mentions("hey @alice!", "alice"));
+    assert!(!mentions("hey @alicia", "alice"));
+    assert!(!mentions("alice", "alice"));
+    assert!(!mentions("@", ""));
+}
+
+#[wasm_bindgen_test]
+fn user_lists_leave_out_an_unset_activity() {
+    let user = UserInfo {
+        name: "alice".into(),
+        role: Role::Member,
+        status: Status::Online,
+        activity: None,
+        bot: false,
+        verified: false,
+    };
+    let json = serde_json::to_string(&user).unwrap();
+    assert!(!json.contains("activity"));
+    assert!(!json.contains("bot") && !json.contains("verified"));
+    assert_eq!(serde_json::from_str::<UserInfo>(&json).unwrap(), user);
+
+    let busy: UserInfo =
+        serde_json::from_str(r#"{"name":"bob","activity":"In a meeting"}"#).unwrap();
+    assert_eq!(busy.activity.as_deref(), Some("In a meeting"));
+}
+
+#[wasm_bindgen_test]
+fn accents_are_six_digit_hex_colors() {
+    assert!(is_accent("#3b82f6"));
+    assert!(is_accent("#ABCDEF"));
+    assert!(!is_accent("3b82f6"));
+    assert!(!is_accent("#fff"));
+    assert!(!is_accent("red"));
+    assert!(!is_accent("#3b82f6; display: none"));
+}
+
+#[wasm_bindgen_test]
+fn images_sent_without_a_description_still_parse() {
+    let json = r#"{"kind":"image","url":"data:image/png;base64,AAAA","width":1,"height":1}"#;
+    let attachment: Attachment = serde_json::from_str(json).unwrap();
+    assert!(matches!(attachment, Attachment::Image { alt: None, .. }));
+    assert_eq!(serde_json::to_string(&attachment).unwrap(), json);
+}
+
+#[wasm_bindgen_test]
+fn suppressing_broadcasts_still_lets_names_ping() {
+    assert!(!MentionSuppression::Nothing.suppresses(true));
+    assert!(MentionSuppression::Broadcasts.suppresses(true));
+    assert!(!MentionSuppression::Broadcasts.suppresses(false));
+    assert!(MentionSuppression::All.suppresses(false));
+    assert!(MsgTypes::SuppressMentions.is_registration());
+    assert_eq!(
+        serde_json::to_string(&MentionSuppression::Broadcasts).unwrap(),
+        r#""broadcasts""#
+    );
+}
+
+#[wasm_bindgen_test]
+fn slow_mode_is_a_moderation_action_on_a_channel() {
+    let action: ModerationAction =
+        serde_json::from_str(r#"{"kind":"slowmode","channel":"general","seconds":30}"#).unwrap();
+    assert_eq!(
+        action,
+        ModerationAction::SlowMode {
+            channel: "general".into(),
+            seconds: Some(30)
+        }
+    );
+    assert_eq!(action.kind(), "slowmode");
+
+    let channel: ChannelInfo = serde_json::from_str(r#"{"name":"general"}"#).unwrap();
+    assert_eq!(channel.slow_mode, None);
+}
@@ -0,0 +1,13 @@
+use wasm_bindgen_test::*;
+use yewchat::services::push::decode_base64url;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+fn vapid_keys_decode_from_unpadded_base64url() {
+    assert_eq!(decode_base64url("-_8").unwrap(), vec![0xfb, 0xff]);
+    assert_eq!(decode_base64url("aGk=").unwrap(), b"hi");
+    assert_eq!(decode_base64url("aGk").unwrap(), b"hi");
+    assert_eq!(decode_base64url("").unwrap(), Vec::<u8>::new());
+    assert_eq!(decode_base64url("a b"), None);
+}
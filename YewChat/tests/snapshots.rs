@@ -0,0 +1,218 @@
+//! Rendered markup of the biggest views, compared against `tests/snapshots/*.html`.
+//!
+//! Snapshots are the DOM as rendered, one node per line with attributes and classes sorted,
+//! so only real changes show up in a diff. When a change is intended, the failure message
+//! holds the new snapshot to paste over the old one.
+
+mod common;
+
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use wasm_bindgen_test::*;
+use web_sys::{Element, Node};
+use yew::prelude::*;
+use yewchat::components::composer::Composer;
+use yewchat::components::confirm::ConfirmProvider;
+use yewchat::components::message_bubble::MessageBubble;
+use yewchat::components::user_sidebar::UserSidebar;
+use yewchat::state::{ChatState, UserProfile};
+use yewchat::store::Store;
+use yewchat::theme::Theme;
+use yewchat_protocol::{ChannelInfo, Role};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[derive(Properties, PartialEq)]
+struct HarnessProps {
+    theme: Theme,
+    content: Html,
+}
+
+/// The contexts the views expect from the app around them, with a fresh store.
+#[function_component(Harness)]
+fn harness(props: &HarnessProps) -> Html {
+    let store = use_reducer(ChatState::default);
+    html! {
+        <ContextProvider<Store> context={store}>
+            <ContextProvider<Theme> context={props.theme.clone()}>
+                <ConfirmProvider>
+                    { props.content.clone() }
+                </ConfirmProvider>
+            </ContextProvider<Theme>>
+        </ContextProvider<Store>>
+    }
+}
+
+/// Mounts `content` in `theme`, lets it settle and serializes what it rendered.
+async fn render(theme: Theme, content: Html) -> String {
+    let document = gloo::utils::document();
+    let root = document.create_element("div").unwrap();
+    document.body().unwrap().append_child(&root).unwrap();
+    let app = yew::start_app_with_props_in_element::<Harness>(
+        root.clone(),
+        HarnessProps { theme, content },
+    );
+    // A timer runs after every render and effect queued so far.
+    let tick = js_sys::Promise::new(&mut |resolve, _| {
+        let _ = gloo::utils::window().set_timeout_with_callback(&resolve);
+    });
+    let _ = JsFuture::from(tick).await;
+
+    let mut out = String::new();
+    for node in children(&root) {
+        serialize(&node, 0, &mut out);
+    }
+    app.destroy();
+    root.remove();
+    out
+}
+
+/// Child elements and text that isn't just whitespace.
+fn children(node: &Node) -> Vec<Node> {
+    let nodes = node.child_nodes();
+    (0..nodes.length())
+        .filter_map(|i| nodes.item(i))
+        .filter(|node| match node.node_type() {
+            Node::ELEMENT_NODE => true,
+            Node::TEXT_NODE => !node.text_content().unwrap_or_default().trim().is_empty(),
+            _ => false,
+        })
+        .collect()
+}
+
+fn attributes(element: &Element) -> String {
+    let mut names: Vec<String> = element
+        .get_attribute_names()
+        .iter()
+        .filter_map(|name| name.as_string())
+        .collect();
+    names.sort();
+    names
+        .into_iter()
+        .map(|name| {
+            let value = element.get_attribute(&name).unwrap_or_default();
+            if name == "class" {
+                let mut classes: Vec<&str> = value.split_whitespace().collect();
+                classes.sort_unstable();
+                format!(" class=\"{}\"", classes.join(" "))
+            } else if value.is_empty() || value == name {
+                format!(" {}", name)
+            } else if let Some((kind, _)) =
+                value.split_once(',').filter(|_| value.starts_with("data:"))
+            {
+                // Generated images are long and say nothing a reviewer can check.
+                format!(" {}=\"{},…\"", name, kind)
+            } else {
+                format!(" {}=\"{}\"", name, value)
+            }
+        })
+        .collect()
+}
+
+fn serialize(node: &Node, depth: usize, out: &mut String) {
+    let indent = "  ".repeat(depth);
+    let Some(element) = node.dyn_ref::<Element>() else {
+        let text = node.text_content().unwrap_or_default();
+        out.push_str(&format!("{}{}\n", indent, text.trim()));
+        return;
+    };
+    let tag = element.local_name();
+    let attributes = attributes(element);
+    let children = children(node);
+    match children.as_slice() {
+        [] => out.push_str(&format!("{}<{}{} />\n", indent, tag, attributes)),
+        [only] if only.node_type() == Node::TEXT_NODE => {
+            let text = only.text_content().unwrap_or_default();
+            out.push_str(&format!(
+                "{}<{}{}>{}</{}>\n",
+                indent,
+                tag,
+                attributes,
+                text.trim(),
+                tag
+            ));
+        }
+        _ => {
+            out.push_str(&format!("{}<{}{}>\n", indent, tag, attributes));
+            for child in &children {
+                serialize(child, depth + 1, out);
+            }
+            out.push_str(&format!("{}</{}>\n", indent, tag));
+        }
+    }
+}
+
+fn assert_snapshot(name: &str, expected: &str, actual: &str) {
+    assert!(
+        expected == actual,
+        "snapshot {0} changed; if that's intended, replace tests/snapshots/{0}.html with:\n{1}",
+        name,
+        actual
+    );
+}
+
+fn bubble() -> Html {
+    html! {
+        <MessageBubble message={Rc::new(common::message_data("alice", "Hello there"))} author={UserProfile::new("alice")} />
+    }
+}
+
+#[wasm_bindgen_test]
+async fn message_bubble_matches_its_snapshot() {
+    let light = render(Theme::Light, bubble()).await;
+    assert_snapshot(
+        "message_bubble",
+        include_str!("snapshots/message_bubble.html"),
+        &light,
+    );
+    let dark = render(Theme::Dark, bubble()).await;
+    assert_snapshot(
+        "message_bubble_dark",
+        include_str!("snapshots/message_bubble_dark.html"),
+        &dark,
+    );
+}
+
+#[wasm_bindgen_test]
+async fn user_sidebar_matches_its_snapshot() {
+    let users = vec![
+        UserProfile {
+            activity: Some("In a meeting".into()),
+            ..UserProfile::new("alice")
+        },
+        UserProfile {
+            role: Role::Moderator,
+            verified: true,
+            ..UserProfile::new("bob")
+        },
+    ];
+    let channels = vec![ChannelInfo {
+        name: "general".into(),
+        ..Default::default()
+    }];
+    let sidebar = html! {
+        <UserSidebar users={Rc::new(users)} spectators={Rc::new(Vec::new())} channels={Rc::new(channels)}
+            channel="general" unread={HashMap::new()} on_select_channel={Callback::noop()} on_invite={Callback::noop()}
+            on_moderate={Callback::noop()} on_list_sessions={Callback::noop()} on_sign_out={Callback::noop()} />
+    };
+    let actual = render(Theme::Light, sidebar).await;
+    assert_snapshot(
+        "user_sidebar",
+        include_str!("snapshots/user_sidebar.html"),
+        &actual,
+    );
+}
+
+#[wasm_bindgen_test]
+async fn composer_matches_its_snapshot() {
+    let replying = Rc::new(common::message_data("alice", "Hello there"));
+    let composer = html! {
+        <Composer draft="Hi" show_emoji_picker={false} replying={Some(replying)} on_input={Callback::noop()}
+            on_submit={Callback::noop()} on_toggle_emoji_picker={Callback::noop()} on_select_emoji={Callback::noop()} />
+    };
+    let actual = render(Theme::Light, composer).await;
+    assert_snapshot("composer", include_str!("snapshots/composer.html"), &actual);
+}
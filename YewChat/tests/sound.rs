@@ -0,0 +1,22 @@
+use wasm_bindgen_test::*;
+use yewchat::services::sound::{validate_clip, NotificationSound, MAX_CLIP_BYTES};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+fn only_short_audio_clips_are_accepted() {
+    assert_eq!(validate_clip("audio/mpeg", 40 * 1024), Ok(()));
+    assert!(validate_clip("image/png", 1024).is_err());
+    assert_eq!(
+        validate_clip("audio/ogg", MAX_CLIP_BYTES + 1),
+        Err("That clip is 513 KB; keep it under 512 KB.".to_string())
+    );
+}
+
+#[wasm_bindgen_test]
+fn bundled_sounds_have_notes_and_the_rest_do_not() {
+    for sound in NotificationSound::ALL {
+        let bundled = !matches!(sound, NotificationSound::Off | NotificationSound::Custom);
+        assert_eq!(!sound.notes().is_empty(), bundled, "{:?}", sound);
+    }
+}
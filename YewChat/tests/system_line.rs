@@ -0,0 +1,22 @@
+use wasm_bindgen_test::*;
+use yewchat::components::system_line::{describe, shows_in};
+use yewchat_protocol::SystemEvent;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+fn joins_and_leaves_show_everywhere_and_renames_in_their_channel() {
+    let joined = SystemEvent::Joined {
+        user: "alice".into(),
+    };
+    assert_eq!(describe(&joined), "alice joined");
+    assert!(shows_in(&joined, "random"));
+
+    let renamed = SystemEvent::Renamed {
+        from: "dev".into(),
+        to: "backend".into(),
+    };
+    assert_eq!(describe(&renamed), "#dev was renamed to #backend");
+    assert!(shows_in(&renamed, "backend"));
+    assert!(!shows_in(&renamed, "general"));
+}
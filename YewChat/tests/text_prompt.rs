@@ -0,0 +1,16 @@
+use wasm_bindgen_test::*;
+use yewchat::components::text_prompt::limit_chars;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+fn text_past_the_limit_is_cut_off() {
+    assert_eq!(limit_chars("a cat on a mat", 5), "a cat");
+    assert_eq!(limit_chars("short", 1000), "short");
+}
+
+#[wasm_bindgen_test]
+fn the_limit_counts_chars_not_bytes() {
+    assert_eq!(limit_chars("🐱🐱🐱", 2), "🐱🐱");
+    assert_eq!(limit_chars("héllo", 2), "hé");
+}
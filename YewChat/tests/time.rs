@@ -0,0 +1,56 @@
+use wasm_bindgen_test::*;
+use yewchat::state::Preferences;
+use yewchat::time::{ago, Clock, TimeFormat};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+// 2023-11-14 22:13:20 UTC
+const MS: u64 = 1_700_000_000_000;
+
+#[wasm_bindgen_test]
+fn times_follow_the_chosen_timezone() {
+    let utc = TimeFormat {
+        timezone: Some("UTC".into()),
+        clock: Some(Clock::TwentyFourHour),
+    };
+    assert_eq!(utc.time(MS), "22:13");
+    assert_eq!(utc.time_exact(MS), "22:13:20");
+
+    let tokyo = TimeFormat {
+        timezone: Some("Asia/Tokyo".into()),
+        clock: Some(Clock::TwentyFourHour),
+    };
+    assert_eq!(tokyo.time(MS), "07:13");
+}
+
+#[wasm_bindgen_test]
+fn twelve_hour_clock_shows_a_day_period() {
+    let utc = TimeFormat {
+        timezone: Some("UTC".into()),
+        clock: Some(Clock::TwelveHour),
+    };
+    let time = utc.time(MS);
+    // Newer ICU data separates the day period with a narrow no-break space.
+    assert!(time.starts_with("10:13"), "{time}");
+    assert!(time.ends_with("PM"), "{time}");
+}
+
+#[wasm_bindgen_test]
+fn missing_timestamps_format_as_nothing() {
+    assert_eq!(TimeFormat::default().time(0), "");
+}
+
+#[wasm_bindgen_test]
+fn preferences_saved_before_timezones_still_load() {
+    let preferences: Preferences = serde_json::from_str(r#"{"dnd":true}"#).unwrap();
+    assert!(preferences.dnd);
+    assert_eq!(preferences.time_format, TimeFormat::default());
+}
+
+#[wasm_bindgen_test]
+fn elapsed_time_rounds_down_to_the_largest_unit() {
+    assert_eq!(ago(30_000), "just now");
+    assert_eq!(ago(5 * 60_000 + 59_000), "5 min ago");
+    assert_eq!(ago(3 * 3_600_000), "3 h ago");
+    assert_eq!(ago(50 * 3_600_000), "2 d ago");
+}
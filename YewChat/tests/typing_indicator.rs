@@ -0,0 +1,30 @@
+use wasm_bindgen_test::*;
+use yewchat::components::typing_indicator::describe;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+fn names(names: &[&str]) -> Vec<String> {
+    names.iter().map(|n| n.to_string()).collect()
+}
+
+#[wasm_bindgen_test]
+fn typists_are_listed_by_name() {
+    assert_eq!(describe(&[]), None);
+    assert_eq!(describe(&names(&["alice"])).unwrap(), "alice is typing…");
+    assert_eq!(
+        describe(&names(&["alice", "bob"])).unwrap(),
+        "alice and bob are typing…"
+    );
+    assert_eq!(
+        describe(&names(&["alice", "bob", "carol"])).unwrap(),
+        "alice, bob and carol are typing…"
+    );
+}
+
+#[wasm_bindgen_test]
+fn crowds_collapse_into_several_people() {
+    assert_eq!(
+        describe(&names(&["alice", "bob", "carol", "dave"])).unwrap(),
+        "Several people are typing…"
+    );
+}
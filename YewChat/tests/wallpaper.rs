@@ -0,0 +1,25 @@
+use wasm_bindgen_test::*;
+use yewchat::theme::Theme;
+use yewchat::wallpaper::{is_image_url, Wallpaper};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+fn only_plain_web_links_make_image_wallpapers() {
+    assert!(is_image_url("https://example.com/bg.png"));
+    assert!(!is_image_url("javascript:alert(1)"));
+    assert!(!is_image_url("https://example.com/a.png\"); color: red"));
+    assert!(!is_image_url("https://example.com/a b.png"));
+    assert_eq!(
+        Wallpaper::Image("data:image/png;base64,AAAA".into()).style(&Theme::Light, 0),
+        None
+    );
+}
+
+#[wasm_bindgen_test]
+fn wallpapers_fade_into_the_page_but_not_in_high_contrast() {
+    let style = Wallpaper::Dots.style(&Theme::Dark, 40).unwrap();
+    assert!(style.contains("rgba(31, 41, 55, 0.4)"));
+    assert_eq!(Wallpaper::Dots.style(&Theme::HighContrast, 40), None);
+    assert_eq!(Wallpaper::None.style(&Theme::Light, 40), None);
+}
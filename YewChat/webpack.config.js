@@ -9,10 +9,19 @@ module.exports = {
     devServer: {
         port: 8000,
     },
-    entry: './bootstrap.js',
+    entry: {
+        main: './bootstrap.js',
+        // The connection worker is loaded by yew-agent from `worker.js`.
+        worker: { import: './worker.js', chunkLoading: 'import-scripts' },
+        // The connection shared by every tab, where browsers support shared workers.
+        shared_worker: { import: './shared_worker.js', chunkLoading: 'import-scripts' },
+    },
     output: {
         path: distPath,
-        filename: 'yewchat.js',
+        filename: (pathData) =>
+            ['worker', 'shared_worker'].includes(pathData.chunk.name)
+                ? `${pathData.chunk.name}.js`
+                : 'yewchat.js',
         webassemblyModuleFilename: 'yewchat_bg.wasm',
     },
     plugins: [
@@ -21,7 +30,7 @@ module.exports = {
         }),
         new WasmPackPlugin({
             crateDirectory: '.',
-            extraArgs: '-- --features wee_alloc',
+            extraArgs: `-- --features ${process.env.YEWCHAT_FEATURES || 'wee_alloc'}`,
             outName: 'yewchat',
         }),
     ],
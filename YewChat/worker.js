@@ -0,0 +1,3 @@
+import('./pkg').then((module) => {
+    module.run_worker();
+});